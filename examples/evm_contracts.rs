@@ -0,0 +1,60 @@
+//! Writes a Foundry project with verifier contracts and their tests, which
+//! check the contracts against test vectors of the reference verifier:
+//!
+//! ```text
+//! cargo run --example evm_contracts --features evm -- target/evm
+//! cd target/evm && forge test
+//! ```
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use bulletproofs::evm::{RangeProofVerifier, MAX_NM};
+use bulletproofs::{Bn254Point, BulletproofGens, PedersenGens};
+
+const FOUNDRY_TOML: &str = r#"[profile.default]
+src = "src"
+test = "src"
+out = "out"
+solc_version = "0.8.19"
+optimizer = true
+optimizer_runs = 200
+# The test contracts embed the creation code of the verifiers, and may
+# exceed the limit of EIP-170, which the tests check for the verifiers.
+code_size_limit = 1048576
+"#;
+
+/// The parameters \\((n, m)\\) of the verifiers, the last of which has the
+/// largest contract.
+const PARAMETERS: [(usize, usize); 4] = [(8, 1), (16, 2), (32, 4), (64, MAX_NM / 64)];
+
+fn main() {
+    let dir = env::args()
+        .nth(1)
+        .expect("usage: evm_contracts <output directory>");
+    let src = Path::new(&dir).join("src");
+    fs::create_dir_all(&src).expect("could not create the output directory");
+    fs::write(Path::new(&dir).join("foundry.toml"), FOUNDRY_TOML)
+        .expect("could not write foundry.toml");
+
+    let pc_gens = PedersenGens::<Bn254Point>::standard();
+    let bp_gens = BulletproofGens::<Bn254Point>::generate(b"", 64, 4);
+    let mut rng = ChaChaRng::from_seed([0u8; 32]);
+    for &(n, m) in PARAMETERS.iter() {
+        let verifier = RangeProofVerifier::new(&bp_gens, &pc_gens, b"evm test", n, m)
+            .expect("the parameters are valid");
+        let name = format!("Verifier{}x{}", n, m);
+        let vectors = verifier.test_vectors(&mut rng);
+        fs::write(src.join(format!("{}.sol", name)), verifier.contract(&name))
+            .expect("could not write the contract");
+        fs::write(
+            src.join(format!("{}.t.sol", name)),
+            verifier.test_contract(&name, &vectors),
+        )
+        .expect("could not write the test contract");
+    }
+}
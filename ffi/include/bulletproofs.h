@@ -0,0 +1,308 @@
+/* Generated from src/lib.rs by tests/header.rs.  Do not edit. */
+
+#ifndef BULLETPROOFS_H
+#define BULLETPROOFS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An error code. */
+typedef int32_t bp_error;
+
+/* The call succeeded. */
+#define BP_OK 0
+
+/* A required pointer argument was null. */
+#define BP_ERROR_NULL_POINTER 1
+
+/* An argument was invalid, such as a non-canonical scalar. */
+#define BP_ERROR_INVALID_ARGUMENT 2
+
+/* The output buffer is too small; the required size was written back. */
+#define BP_ERROR_BUFFER_TOO_SMALL 3
+
+/* The library panicked.  This is a bug. */
+#define BP_ERROR_PANIC 4
+
+/* `ProofError::VerificationError`: the proof failed to verify. */
+#define BP_ERROR_VERIFICATION 10
+
+/* `ProofError::FormatError`: the proof encoding is malformed. */
+#define BP_ERROR_FORMAT 11
+
+/* `ProofError::WrongNumBlindingFactors`. */
+#define BP_ERROR_WRONG_NUM_BLINDING_FACTORS 12
+
+/* `ProofError::InvalidBitsize`: the bitsize is not 8, 16, 32 or 64. */
+#define BP_ERROR_INVALID_BITSIZE 13
+
+/*
+ * `ProofError::InvalidAggregation`: the number of values is not a power of 2.
+ */
+#define BP_ERROR_INVALID_AGGREGATION 14
+
+/* `ProofError::InvalidGeneratorsLength`: too few generators for the proof. */
+#define BP_ERROR_INVALID_GENERATORS_LENGTH 15
+
+/* `ProofError::InvalidInputLength`. */
+#define BP_ERROR_INVALID_INPUT_LENGTH 16
+
+/* `MPCError::MaliciousDealer`. */
+#define BP_ERROR_MPC_MALICIOUS_DEALER 20
+
+/* `MPCError::InvalidBitsize`. */
+#define BP_ERROR_MPC_INVALID_BITSIZE 21
+
+/* `MPCError::InvalidAggregation`. */
+#define BP_ERROR_MPC_INVALID_AGGREGATION 22
+
+/* `MPCError::InvalidGeneratorsLength`. */
+#define BP_ERROR_MPC_INVALID_GENERATORS_LENGTH 23
+
+/* `MPCError::WrongNumBitCommitments`. */
+#define BP_ERROR_MPC_WRONG_NUM_BIT_COMMITMENTS 24
+
+/* `MPCError::WrongNumPolyCommitments`. */
+#define BP_ERROR_MPC_WRONG_NUM_POLY_COMMITMENTS 25
+
+/* `MPCError::WrongNumProofShares`. */
+#define BP_ERROR_MPC_WRONG_NUM_PROOF_SHARES 26
+
+/* `MPCError::MalformedProofShares`. */
+#define BP_ERROR_MPC_MALFORMED_PROOF_SHARES 27
+
+/*
+ * The maximum number of distinct transcript labels.  Each label is kept
+ * until the process exits, so labels should come from a small fixed set.
+ */
+#define BP_MAX_TRANSCRIPT_LABELS 256
+
+/* Pedersen commitment generators. */
+typedef struct bp_pedersen_gens bp_pedersen_gens;
+
+/* Bulletproofs generators. */
+typedef struct bp_bulletproof_gens bp_bulletproof_gens;
+
+/* A Merlin transcript. */
+typedef struct bp_transcript bp_transcript;
+
+/* A range proof. */
+typedef struct bp_range_proof bp_range_proof;
+
+/* Returns a static, NUL-terminated description of the error `code`. */
+const char *bp_error_message(bp_error code);
+
+/* Creates the default Pedersen commitment generators. */
+bp_pedersen_gens *bp_pedersen_gens_new(void);
+
+/*
+ * Frees generators created by `bp_pedersen_gens_new`.
+ *
+ * # Safety
+ *
+ * `gens` must be null or a pointer returned by `bp_pedersen_gens_new`,
+ * which has not been freed.
+ */
+void bp_pedersen_gens_free(bp_pedersen_gens *gens);
+
+/*
+ * Writes the compressed Pedersen commitment to `value` with the 32-byte
+ * canonical scalar `blinding` to the 32 bytes at `out_commitment`.
+ *
+ * # Safety
+ *
+ * `gens` must be a valid handle, `blinding` must point to 32 readable
+ * bytes and `out_commitment` to 32 writable bytes.
+ */
+bp_error bp_pedersen_gens_commit(
+    const bp_pedersen_gens *gens,
+    uint64_t value,
+    const uint8_t *blinding,
+    uint8_t *out_commitment);
+
+/*
+ * Creates the generators for proofs of up to `gens_capacity` bits and
+ * `party_capacity` values.  Returns null if the generators could not be
+ * created.
+ */
+bp_bulletproof_gens *bp_bulletproof_gens_new(
+    size_t gens_capacity,
+    size_t party_capacity);
+
+/*
+ * Frees generators created by `bp_bulletproof_gens_new` or
+ * `bp_bulletproof_gens_from_bytes`.
+ *
+ * # Safety
+ *
+ * `gens` must be null or a valid handle, which has not been freed.
+ */
+void bp_bulletproof_gens_free(bp_bulletproof_gens *gens);
+
+/*
+ * Serializes the generators to the buffer `out` of `*out_len` bytes, and
+ * writes the serialized size to `*out_len`.
+ *
+ * Returns `BP_ERROR_BUFFER_TOO_SMALL` if the buffer is too small, in which
+ * case `out` may be null.
+ *
+ * # Safety
+ *
+ * `gens` must be a valid handle, `out_len` must be a valid pointer and
+ * `out` must point to `*out_len` writable bytes.
+ */
+bp_error bp_bulletproof_gens_to_bytes(
+    const bp_bulletproof_gens *gens,
+    uint8_t *out,
+    size_t *out_len);
+
+/*
+ * Deserializes generators serialized by `bp_bulletproof_gens_to_bytes`,
+ * and writes the new handle to `*out_gens`.
+ *
+ * The bytes must come from trusted storage.  Their digest only detects
+ * accidental corruption, and generators with a known discrete log
+ * relation let a prover forge proofs.
+ *
+ * # Safety
+ *
+ * `bytes` must point to `len` readable bytes, and `out_gens` must be a
+ * valid pointer.
+ */
+bp_error bp_bulletproof_gens_from_bytes(
+    const uint8_t *bytes,
+    size_t len,
+    bp_bulletproof_gens **out_gens);
+
+/*
+ * Creates a transcript with the domain separation label of `label_len`
+ * bytes at `label`.  Returns null if `label` is null, or if it would
+ * exceed `BP_MAX_TRANSCRIPT_LABELS` distinct labels.
+ *
+ * # Safety
+ *
+ * `label` must point to `label_len` readable bytes.
+ */
+bp_transcript *bp_transcript_new(const uint8_t *label, size_t label_len);
+
+/*
+ * Appends the message of `message_len` bytes at `message` to the
+ * transcript, with the label of `label_len` bytes at `label`.  Returns
+ * `BP_ERROR_INVALID_ARGUMENT` if the label would exceed
+ * `BP_MAX_TRANSCRIPT_LABELS` distinct labels.
+ *
+ * # Safety
+ *
+ * `transcript` must be a valid handle, `label` must point to `label_len`
+ * readable bytes and `message` to `message_len` readable bytes.
+ */
+bp_error bp_transcript_append_message(
+    bp_transcript *transcript,
+    const uint8_t *label,
+    size_t label_len,
+    const uint8_t *message,
+    size_t message_len);
+
+/*
+ * Frees a transcript created by `bp_transcript_new`.
+ *
+ * # Safety
+ *
+ * `transcript` must be null or a valid handle, which has not been freed.
+ */
+void bp_transcript_free(bp_transcript *transcript);
+
+/*
+ * Creates an aggregated proof that each of the `m` `values` is in the
+ * range [0, 2^n), with the `m` 32-byte canonical scalars `blindings`.
+ *
+ * Writes the new proof handle to `*out_proof` and the `m` 32-byte
+ * compressed commitments to `out_commitments`.
+ *
+ * # Safety
+ *
+ * The handles must be valid, `values` must point to `m` values,
+ * `blindings` to `32 * m` readable bytes, `out_proof` must be a valid
+ * pointer and `out_commitments` must point to `32 * m` writable bytes.
+ */
+bp_error bp_range_proof_prove(
+    const bp_bulletproof_gens *bp_gens,
+    const bp_pedersen_gens *pc_gens,
+    bp_transcript *transcript,
+    const uint64_t *values,
+    const uint8_t *blindings,
+    size_t m,
+    size_t n,
+    bp_range_proof **out_proof,
+    uint8_t *out_commitments);
+
+/*
+ * Verifies the proof that each of the `m` 32-byte compressed
+ * `commitments` opens to a value in the range [0, 2^n).
+ *
+ * Returns `BP_OK` if the proof is valid.
+ *
+ * # Safety
+ *
+ * The handles must be valid, and `commitments` must point to `32 * m`
+ * readable bytes.
+ */
+bp_error bp_range_proof_verify(
+    const bp_range_proof *proof,
+    const bp_bulletproof_gens *bp_gens,
+    const bp_pedersen_gens *pc_gens,
+    bp_transcript *transcript,
+    const uint8_t *commitments,
+    size_t m,
+    size_t n);
+
+/*
+ * Serializes the proof to the buffer `out` of `*out_len` bytes, and
+ * writes the serialized size to `*out_len`.
+ *
+ * Returns `BP_ERROR_BUFFER_TOO_SMALL` if the buffer is too small, in which
+ * case `out` may be null.
+ *
+ * # Safety
+ *
+ * `proof` must be a valid handle, `out_len` must be a valid pointer and
+ * `out` must point to `*out_len` writable bytes.
+ */
+bp_error bp_range_proof_to_bytes(
+    const bp_range_proof *proof,
+    uint8_t *out,
+    size_t *out_len);
+
+/*
+ * Deserializes a proof serialized by `bp_range_proof_to_bytes`, and
+ * writes the new handle to `*out_proof`.
+ *
+ * # Safety
+ *
+ * `bytes` must point to `len` readable bytes, and `out_proof` must be a
+ * valid pointer.
+ */
+bp_error bp_range_proof_from_bytes(
+    const uint8_t *bytes,
+    size_t len,
+    bp_range_proof **out_proof);
+
+/*
+ * Frees a proof created by `bp_range_proof_prove` or
+ * `bp_range_proof_from_bytes`.
+ *
+ * # Safety
+ *
+ * `proof` must be null or a valid handle, which has not been freed.
+ */
+void bp_range_proof_free(bp_range_proof *proof);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BULLETPROOFS_H */
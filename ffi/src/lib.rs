@@ -0,0 +1,543 @@
+//! C bindings for the `bulletproofs` range proofs.
+//!
+//! The generators, transcripts and proofs are exposed to C as opaque
+//! handles, which are created by the `*_new`, `*_prove` and
+//! `*_from_bytes` functions and must be released with the matching
+//! `*_free` function.  Every fallible function returns a `bp_error`,
+//! which is `BP_OK` on success, and writes its results through out
+//! pointers.
+//!
+//! The C declarations are in `include/bulletproofs.h`, which is
+//! generated from this file by the `header` test.  Run
+//!
+//! ```text
+//! BULLETPROOFS_UPDATE_HEADER=1 cargo test -p bulletproofs-ffi --test header
+//! ```
+//!
+//! after changing the API.
+
+#![allow(non_camel_case_types)]
+
+use std::collections::HashSet;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::sync::Mutex;
+
+use bulletproofs::range_proof_mpc::MPCError;
+use bulletproofs::{BulletproofGens, PedersenGens, ProofError, RangeProof};
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+/// An error code.
+pub type bp_error = i32;
+
+/// The call succeeded.
+pub const BP_OK: bp_error = 0;
+/// A required pointer argument was null.
+pub const BP_ERROR_NULL_POINTER: bp_error = 1;
+/// An argument was invalid, such as a non-canonical scalar.
+pub const BP_ERROR_INVALID_ARGUMENT: bp_error = 2;
+/// The output buffer is too small; the required size was written back.
+pub const BP_ERROR_BUFFER_TOO_SMALL: bp_error = 3;
+/// The library panicked.  This is a bug.
+pub const BP_ERROR_PANIC: bp_error = 4;
+
+/// `ProofError::VerificationError`: the proof failed to verify.
+pub const BP_ERROR_VERIFICATION: bp_error = 10;
+/// `ProofError::FormatError`: the proof encoding is malformed.
+pub const BP_ERROR_FORMAT: bp_error = 11;
+/// `ProofError::WrongNumBlindingFactors`.
+pub const BP_ERROR_WRONG_NUM_BLINDING_FACTORS: bp_error = 12;
+/// `ProofError::InvalidBitsize`: the bitsize is not 8, 16, 32 or 64.
+pub const BP_ERROR_INVALID_BITSIZE: bp_error = 13;
+/// `ProofError::InvalidAggregation`: the number of values is not a power of 2.
+pub const BP_ERROR_INVALID_AGGREGATION: bp_error = 14;
+/// `ProofError::InvalidGeneratorsLength`: too few generators for the proof.
+pub const BP_ERROR_INVALID_GENERATORS_LENGTH: bp_error = 15;
+/// `ProofError::InvalidInputLength`.
+pub const BP_ERROR_INVALID_INPUT_LENGTH: bp_error = 16;
+
+/// `MPCError::MaliciousDealer`.
+pub const BP_ERROR_MPC_MALICIOUS_DEALER: bp_error = 20;
+/// `MPCError::InvalidBitsize`.
+pub const BP_ERROR_MPC_INVALID_BITSIZE: bp_error = 21;
+/// `MPCError::InvalidAggregation`.
+pub const BP_ERROR_MPC_INVALID_AGGREGATION: bp_error = 22;
+/// `MPCError::InvalidGeneratorsLength`.
+pub const BP_ERROR_MPC_INVALID_GENERATORS_LENGTH: bp_error = 23;
+/// `MPCError::WrongNumBitCommitments`.
+pub const BP_ERROR_MPC_WRONG_NUM_BIT_COMMITMENTS: bp_error = 24;
+/// `MPCError::WrongNumPolyCommitments`.
+pub const BP_ERROR_MPC_WRONG_NUM_POLY_COMMITMENTS: bp_error = 25;
+/// `MPCError::WrongNumProofShares`.
+pub const BP_ERROR_MPC_WRONG_NUM_PROOF_SHARES: bp_error = 26;
+/// `MPCError::MalformedProofShares`.
+pub const BP_ERROR_MPC_MALFORMED_PROOF_SHARES: bp_error = 27;
+
+/// The maximum number of distinct transcript labels.  Each label is kept
+/// until the process exits, so labels should come from a small fixed set.
+pub const BP_MAX_TRANSCRIPT_LABELS: usize = 256;
+
+/// Pedersen commitment generators.
+pub struct bp_pedersen_gens(PedersenGens);
+
+/// Bulletproofs generators.
+pub struct bp_bulletproof_gens(BulletproofGens);
+
+/// A Merlin transcript.
+pub struct bp_transcript(Transcript);
+
+/// A range proof.
+pub struct bp_range_proof(RangeProof);
+
+fn proof_error_code(e: &ProofError) -> bp_error {
+    match e {
+        ProofError::VerificationError => BP_ERROR_VERIFICATION,
+        ProofError::FormatError => BP_ERROR_FORMAT,
+        ProofError::WrongNumBlindingFactors => BP_ERROR_WRONG_NUM_BLINDING_FACTORS,
+        ProofError::InvalidBitsize => BP_ERROR_INVALID_BITSIZE,
+        ProofError::InvalidAggregation => BP_ERROR_INVALID_AGGREGATION,
+        ProofError::InvalidGeneratorsLength => BP_ERROR_INVALID_GENERATORS_LENGTH,
+        ProofError::InvalidInputLength => BP_ERROR_INVALID_INPUT_LENGTH,
+        ProofError::ProvingError(e) => mpc_error_code(e),
+    }
+}
+
+fn mpc_error_code(e: &MPCError) -> bp_error {
+    match e {
+        MPCError::MaliciousDealer => BP_ERROR_MPC_MALICIOUS_DEALER,
+        MPCError::InvalidBitsize => BP_ERROR_MPC_INVALID_BITSIZE,
+        MPCError::InvalidAggregation => BP_ERROR_MPC_INVALID_AGGREGATION,
+        MPCError::InvalidGeneratorsLength => BP_ERROR_MPC_INVALID_GENERATORS_LENGTH,
+        MPCError::WrongNumBitCommitments => BP_ERROR_MPC_WRONG_NUM_BIT_COMMITMENTS,
+        MPCError::WrongNumPolyCommitments => BP_ERROR_MPC_WRONG_NUM_POLY_COMMITMENTS,
+        MPCError::WrongNumProofShares => BP_ERROR_MPC_WRONG_NUM_PROOF_SHARES,
+        MPCError::MalformedProofShares { .. } => BP_ERROR_MPC_MALFORMED_PROOF_SHARES,
+    }
+}
+
+/// Runs `f`, converting its result and any panic into an error code, so
+/// that no panic unwinds into C.
+fn guard<F: FnOnce() -> Result<(), bp_error>>(f: F) -> bp_error {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => BP_OK,
+        Ok(Err(code)) => code,
+        Err(_) => BP_ERROR_PANIC,
+    }
+}
+
+/// Returns `ptr` as a reference, or `BP_ERROR_NULL_POINTER`.
+unsafe fn reference<'a, T>(ptr: *const T) -> Result<&'a T, bp_error> {
+    ptr.as_ref().ok_or(BP_ERROR_NULL_POINTER)
+}
+
+/// Returns `ptr` as a mutable reference, or `BP_ERROR_NULL_POINTER`.
+unsafe fn reference_mut<'a, T>(ptr: *mut T) -> Result<&'a mut T, bp_error> {
+    ptr.as_mut().ok_or(BP_ERROR_NULL_POINTER)
+}
+
+/// Returns the `len` elements at `ptr`, which may be null if `len` is zero.
+unsafe fn elements<'a, T>(ptr: *const T, len: usize) -> Result<&'a [T], bp_error> {
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(BP_ERROR_NULL_POINTER)
+    } else {
+        Ok(slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Copies `bytes` to the buffer `out` of `*out_len` bytes, writing the
+/// length of `bytes` back to `out_len`.
+unsafe fn write_bytes(bytes: &[u8], out: *mut u8, out_len: *mut usize) -> Result<(), bp_error> {
+    let out_len = reference_mut(out_len)?;
+    let capacity = *out_len;
+    *out_len = bytes.len();
+    if capacity < bytes.len() {
+        return Err(BP_ERROR_BUFFER_TOO_SMALL);
+    }
+    if !bytes.is_empty() {
+        if out.is_null() {
+            return Err(BP_ERROR_NULL_POINTER);
+        }
+        ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+    }
+    Ok(())
+}
+
+/// Returns a `'static` copy of the transcript label `label`.
+///
+/// Merlin requires `'static` labels, so each distinct label is leaked
+/// once and reused afterwards.  Past `BP_MAX_TRANSCRIPT_LABELS` distinct
+/// labels, new labels are rejected, so that labels built at runtime
+/// cannot grow the memory without bound.
+fn intern_label(label: &[u8]) -> Result<&'static [u8], bp_error> {
+    static LABELS: Mutex<Option<HashSet<&'static [u8]>>> = Mutex::new(None);
+
+    let mut labels = LABELS.lock().unwrap_or_else(|e| e.into_inner());
+    let labels = labels.get_or_insert_with(HashSet::new);
+    if let Some(interned) = labels.get(label) {
+        return Ok(interned);
+    }
+    if labels.len() >= BP_MAX_TRANSCRIPT_LABELS {
+        return Err(BP_ERROR_INVALID_ARGUMENT);
+    }
+    let interned: &'static [u8] = Box::leak(label.to_vec().into_boxed_slice());
+    labels.insert(interned);
+    Ok(interned)
+}
+
+fn scalar(bytes: &[u8]) -> Result<Scalar, bp_error> {
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(bytes);
+    Scalar::from_canonical_bytes(buf).ok_or(BP_ERROR_INVALID_ARGUMENT)
+}
+
+/// Returns a static, NUL-terminated description of the error `code`.
+#[no_mangle]
+pub extern "C" fn bp_error_message(code: bp_error) -> *const c_char {
+    let message: &'static [u8] = match code {
+        BP_OK => b"Success.\0",
+        BP_ERROR_NULL_POINTER => b"A required pointer argument was null.\0",
+        BP_ERROR_INVALID_ARGUMENT => b"Invalid argument.\0",
+        BP_ERROR_BUFFER_TOO_SMALL => b"The output buffer is too small.\0",
+        BP_ERROR_PANIC => b"Internal error: the library panicked.\0",
+        BP_ERROR_VERIFICATION => b"Proof verification failed.\0",
+        BP_ERROR_FORMAT => b"Proof data could not be parsed.\0",
+        BP_ERROR_WRONG_NUM_BLINDING_FACTORS => b"Wrong number of blinding factors supplied.\0",
+        BP_ERROR_INVALID_BITSIZE | BP_ERROR_MPC_INVALID_BITSIZE => {
+            b"Invalid bitsize, must have n = 8,16,32,64.\0"
+        }
+        BP_ERROR_INVALID_AGGREGATION | BP_ERROR_MPC_INVALID_AGGREGATION => {
+            b"Invalid aggregation size, m must be a power of 2.\0"
+        }
+        BP_ERROR_INVALID_GENERATORS_LENGTH | BP_ERROR_MPC_INVALID_GENERATORS_LENGTH => {
+            b"Invalid generators size, too few generators for proof.\0"
+        }
+        BP_ERROR_INVALID_INPUT_LENGTH => b"Invalid input size, incorrect input length for proof.\0",
+        BP_ERROR_MPC_MALICIOUS_DEALER => b"Dealer gave a malicious challenge value.\0",
+        BP_ERROR_MPC_WRONG_NUM_BIT_COMMITMENTS => b"Wrong number of bit commitments.\0",
+        BP_ERROR_MPC_WRONG_NUM_POLY_COMMITMENTS => b"Wrong number of polynomial commitments.\0",
+        BP_ERROR_MPC_WRONG_NUM_PROOF_SHARES => b"Wrong number of proof shares.\0",
+        BP_ERROR_MPC_MALFORMED_PROOF_SHARES => b"Malformed proof shares.\0",
+        _ => b"Unknown error code.\0",
+    };
+    message.as_ptr() as *const c_char
+}
+
+/// Creates the default Pedersen commitment generators.
+#[no_mangle]
+pub extern "C" fn bp_pedersen_gens_new() -> *mut bp_pedersen_gens {
+    Box::into_raw(Box::new(bp_pedersen_gens(PedersenGens::default())))
+}
+
+/// Frees generators created by `bp_pedersen_gens_new`.
+///
+/// # Safety
+///
+/// `gens` must be null or a pointer returned by `bp_pedersen_gens_new`,
+/// which has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bp_pedersen_gens_free(gens: *mut bp_pedersen_gens) {
+    if !gens.is_null() {
+        drop(Box::from_raw(gens));
+    }
+}
+
+/// Writes the compressed Pedersen commitment to `value` with the 32-byte
+/// canonical scalar `blinding` to the 32 bytes at `out_commitment`.
+///
+/// # Safety
+///
+/// `gens` must be a valid handle, `blinding` must point to 32 readable
+/// bytes and `out_commitment` to 32 writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bp_pedersen_gens_commit(
+    gens: *const bp_pedersen_gens,
+    value: u64,
+    blinding: *const u8,
+    out_commitment: *mut u8,
+) -> bp_error {
+    guard(|| {
+        let gens = &reference(gens)?.0;
+        let blinding = scalar(elements(blinding, 32)?)?;
+        let out = reference_mut(out_commitment as *mut [u8; 32])?;
+        let commitment = gens.commit(Scalar::from(value), blinding);
+        out.copy_from_slice(commitment.compress().as_bytes());
+        Ok(())
+    })
+}
+
+/// Creates the generators for proofs of up to `gens_capacity` bits and
+/// `party_capacity` values.  Returns null if the generators could not be
+/// created.
+#[no_mangle]
+pub extern "C" fn bp_bulletproof_gens_new(
+    gens_capacity: usize,
+    party_capacity: usize,
+) -> *mut bp_bulletproof_gens {
+    panic::catch_unwind(|| BulletproofGens::new(gens_capacity, party_capacity))
+        .map(|gens| Box::into_raw(Box::new(bp_bulletproof_gens(gens))))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Frees generators created by `bp_bulletproof_gens_new` or
+/// `bp_bulletproof_gens_from_bytes`.
+///
+/// # Safety
+///
+/// `gens` must be null or a valid handle, which has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bp_bulletproof_gens_free(gens: *mut bp_bulletproof_gens) {
+    if !gens.is_null() {
+        drop(Box::from_raw(gens));
+    }
+}
+
+/// Serializes the generators to the buffer `out` of `*out_len` bytes, and
+/// writes the serialized size to `*out_len`.
+///
+/// Returns `BP_ERROR_BUFFER_TOO_SMALL` if the buffer is too small, in which
+/// case `out` may be null.
+///
+/// # Safety
+///
+/// `gens` must be a valid handle, `out_len` must be a valid pointer and
+/// `out` must point to `*out_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bp_bulletproof_gens_to_bytes(
+    gens: *const bp_bulletproof_gens,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> bp_error {
+    guard(|| write_bytes(&reference(gens)?.0.to_bytes(), out, out_len))
+}
+
+/// Deserializes generators serialized by `bp_bulletproof_gens_to_bytes`,
+/// and writes the new handle to `*out_gens`.
+///
+/// The bytes must come from trusted storage.  Their digest only detects
+/// accidental corruption, and generators with a known discrete log
+/// relation let a prover forge proofs.
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes, and `out_gens` must be a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bp_bulletproof_gens_from_bytes(
+    bytes: *const u8,
+    len: usize,
+    out_gens: *mut *mut bp_bulletproof_gens,
+) -> bp_error {
+    guard(|| {
+        let out_gens = reference_mut(out_gens)?;
+        let gens =
+            BulletproofGens::from_bytes(elements(bytes, len)?).map_err(|e| proof_error_code(&e))?;
+        *out_gens = Box::into_raw(Box::new(bp_bulletproof_gens(gens)));
+        Ok(())
+    })
+}
+
+/// Creates a transcript with the domain separation label of `label_len`
+/// bytes at `label`.  Returns null if `label` is null, or if it would
+/// exceed `BP_MAX_TRANSCRIPT_LABELS` distinct labels.
+///
+/// # Safety
+///
+/// `label` must point to `label_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bp_transcript_new(
+    label: *const u8,
+    label_len: usize,
+) -> *mut bp_transcript {
+    match elements(label, label_len).and_then(intern_label) {
+        Ok(label) => Box::into_raw(Box::new(bp_transcript(Transcript::new(label)))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Appends the message of `message_len` bytes at `message` to the
+/// transcript, with the label of `label_len` bytes at `label`.  Returns
+/// `BP_ERROR_INVALID_ARGUMENT` if the label would exceed
+/// `BP_MAX_TRANSCRIPT_LABELS` distinct labels.
+///
+/// # Safety
+///
+/// `transcript` must be a valid handle, `label` must point to `label_len`
+/// readable bytes and `message` to `message_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bp_transcript_append_message(
+    transcript: *mut bp_transcript,
+    label: *const u8,
+    label_len: usize,
+    message: *const u8,
+    message_len: usize,
+) -> bp_error {
+    guard(|| {
+        let transcript = &mut reference_mut(transcript)?.0;
+        let label = intern_label(elements(label, label_len)?)?;
+        transcript.append_message(label, elements(message, message_len)?);
+        Ok(())
+    })
+}
+
+/// Frees a transcript created by `bp_transcript_new`.
+///
+/// # Safety
+///
+/// `transcript` must be null or a valid handle, which has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bp_transcript_free(transcript: *mut bp_transcript) {
+    if !transcript.is_null() {
+        drop(Box::from_raw(transcript));
+    }
+}
+
+/// Creates an aggregated proof that each of the `m` `values` is in the
+/// range \[0, 2^n), with the `m` 32-byte canonical scalars `blindings`.
+///
+/// Writes the new proof handle to `*out_proof` and the `m` 32-byte
+/// compressed commitments to `out_commitments`.
+///
+/// # Safety
+///
+/// The handles must be valid, `values` must point to `m` values,
+/// `blindings` to `32 * m` readable bytes, `out_proof` must be a valid
+/// pointer and `out_commitments` must point to `32 * m` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bp_range_proof_prove(
+    bp_gens: *const bp_bulletproof_gens,
+    pc_gens: *const bp_pedersen_gens,
+    transcript: *mut bp_transcript,
+    values: *const u64,
+    blindings: *const u8,
+    m: usize,
+    n: usize,
+    out_proof: *mut *mut bp_range_proof,
+    out_commitments: *mut u8,
+) -> bp_error {
+    guard(|| {
+        let bp_gens = &reference(bp_gens)?.0;
+        let pc_gens = &reference(pc_gens)?.0;
+        let transcript = &mut reference_mut(transcript)?.0;
+        let values = elements(values, m)?;
+        let blindings = elements(
+            blindings,
+            m.checked_mul(32).ok_or(BP_ERROR_INVALID_ARGUMENT)?,
+        )?
+        .chunks(32)
+        .map(scalar)
+        .collect::<Result<Vec<_>, _>>()?;
+        let out_proof = reference_mut(out_proof)?;
+        if out_commitments.is_null() {
+            return Err(BP_ERROR_NULL_POINTER);
+        }
+
+        let (proof, commitments) =
+            RangeProof::prove_multiple(bp_gens, pc_gens, transcript, values, &blindings, n)
+                .map_err(|e| proof_error_code(&e))?;
+
+        let out_commitments = slice::from_raw_parts_mut(out_commitments, 32 * m);
+        for (out, commitment) in out_commitments.chunks_mut(32).zip(commitments.iter()) {
+            out.copy_from_slice(commitment.as_bytes());
+        }
+        *out_proof = Box::into_raw(Box::new(bp_range_proof(proof)));
+        Ok(())
+    })
+}
+
+/// Verifies the proof that each of the `m` 32-byte compressed
+/// `commitments` opens to a value in the range \[0, 2^n).
+///
+/// Returns `BP_OK` if the proof is valid.
+///
+/// # Safety
+///
+/// The handles must be valid, and `commitments` must point to `32 * m`
+/// readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bp_range_proof_verify(
+    proof: *const bp_range_proof,
+    bp_gens: *const bp_bulletproof_gens,
+    pc_gens: *const bp_pedersen_gens,
+    transcript: *mut bp_transcript,
+    commitments: *const u8,
+    m: usize,
+    n: usize,
+) -> bp_error {
+    guard(|| {
+        let proof = &reference(proof)?.0;
+        let bp_gens = &reference(bp_gens)?.0;
+        let pc_gens = &reference(pc_gens)?.0;
+        let transcript = &mut reference_mut(transcript)?.0;
+        let commitments = elements(
+            commitments,
+            m.checked_mul(32).ok_or(BP_ERROR_INVALID_ARGUMENT)?,
+        )?
+        .chunks(32)
+        .map(CompressedRistretto::from_slice)
+        .collect::<Vec<_>>();
+
+        proof
+            .verify_multiple(bp_gens, pc_gens, transcript, &commitments, n)
+            .map_err(|e| proof_error_code(&e))
+    })
+}
+
+/// Serializes the proof to the buffer `out` of `*out_len` bytes, and
+/// writes the serialized size to `*out_len`.
+///
+/// Returns `BP_ERROR_BUFFER_TOO_SMALL` if the buffer is too small, in which
+/// case `out` may be null.
+///
+/// # Safety
+///
+/// `proof` must be a valid handle, `out_len` must be a valid pointer and
+/// `out` must point to `*out_len` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn bp_range_proof_to_bytes(
+    proof: *const bp_range_proof,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> bp_error {
+    guard(|| write_bytes(&reference(proof)?.0.to_bytes(), out, out_len))
+}
+
+/// Deserializes a proof serialized by `bp_range_proof_to_bytes`, and
+/// writes the new handle to `*out_proof`.
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes, and `out_proof` must be a
+/// valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn bp_range_proof_from_bytes(
+    bytes: *const u8,
+    len: usize,
+    out_proof: *mut *mut bp_range_proof,
+) -> bp_error {
+    guard(|| {
+        let out_proof = reference_mut(out_proof)?;
+        let proof =
+            RangeProof::from_bytes(elements(bytes, len)?).map_err(|e| proof_error_code(&e))?;
+        *out_proof = Box::into_raw(Box::new(bp_range_proof(proof)));
+        Ok(())
+    })
+}
+
+/// Frees a proof created by `bp_range_proof_prove` or
+/// `bp_range_proof_from_bytes`.
+///
+/// # Safety
+///
+/// `proof` must be null or a valid handle, which has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn bp_range_proof_free(proof: *mut bp_range_proof) {
+    if !proof.is_null() {
+        drop(Box::from_raw(proof));
+    }
+}
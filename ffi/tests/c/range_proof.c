@@ -0,0 +1,232 @@
+/*
+ * Exercises the C API: proving, verifying and serializing range proofs
+ * and generators, and the error codes of invalid inputs.
+ *
+ * Compiled and run by tests/c_api.rs.
+ */
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+#include "bulletproofs.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                         \
+    do {                                                                    \
+        if (!(cond)) {                                                      \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,          \
+                    __LINE__, #cond);                                       \
+            failures++;                                                     \
+        }                                                                   \
+    } while (0)
+
+#define CHECK_CODE(expr, expected)                                          \
+    do {                                                                    \
+        bp_error code_ = (expr);                                            \
+        if (code_ != (expected)) {                                          \
+            fprintf(stderr, "%s:%d: %s returned %d (%s), expected %s\n",    \
+                    __FILE__, __LINE__, #expr, (int)code_,                  \
+                    bp_error_message(code_), #expected);                    \
+            failures++;                                                     \
+        }                                                                   \
+    } while (0)
+
+#define LABEL "bulletproofs-ffi test"
+#define M 2
+#define N 32
+
+static bp_transcript *new_transcript(void) {
+    bp_transcript *transcript =
+        bp_transcript_new((const uint8_t *)LABEL, strlen(LABEL));
+    CHECK(transcript != NULL);
+    return transcript;
+}
+
+static void test_prove_and_verify(const bp_bulletproof_gens *bp_gens,
+                                  const bp_pedersen_gens *pc_gens) {
+    const uint64_t values[M] = {1037578891, 0xffffffff};
+    uint8_t blindings[32 * M] = {0};
+    uint8_t commitments[32 * M];
+    uint8_t commitment[32];
+    bp_range_proof *proof = NULL;
+    bp_range_proof *parsed = NULL;
+    bp_transcript *transcript;
+    uint8_t *bytes;
+    size_t len = 0;
+    size_t i;
+
+    blindings[0] = 3;
+    blindings[32] = 5;
+
+    transcript = new_transcript();
+    CHECK_CODE(bp_range_proof_prove(bp_gens, pc_gens, transcript, values,
+                                    blindings, M, N, &proof, commitments),
+               BP_OK);
+    bp_transcript_free(transcript);
+    CHECK(proof != NULL);
+
+    /* The commitments are Pedersen commitments to the values. */
+    for (i = 0; i < M; i++) {
+        CHECK_CODE(bp_pedersen_gens_commit(pc_gens, values[i],
+                                           blindings + 32 * i, commitment),
+                   BP_OK);
+        CHECK(memcmp(commitment, commitments + 32 * i, 32) == 0);
+    }
+
+    transcript = new_transcript();
+    CHECK_CODE(bp_range_proof_verify(proof, bp_gens, pc_gens, transcript,
+                                     commitments, M, N),
+               BP_OK);
+    bp_transcript_free(transcript);
+
+    /* Query the size, then serialize and deserialize the proof. */
+    CHECK_CODE(bp_range_proof_to_bytes(proof, NULL, &len),
+               BP_ERROR_BUFFER_TOO_SMALL);
+    CHECK(len == 32 * (9 + 2 * 6));
+    bytes = malloc(len);
+    CHECK_CODE(bp_range_proof_to_bytes(proof, bytes, &len), BP_OK);
+    CHECK_CODE(bp_range_proof_from_bytes(bytes, len, &parsed), BP_OK);
+
+    transcript = new_transcript();
+    CHECK_CODE(bp_range_proof_verify(parsed, bp_gens, pc_gens, transcript,
+                                     commitments, M, N),
+               BP_OK);
+    bp_transcript_free(transcript);
+    bp_range_proof_free(parsed);
+
+    /* The transcripts must match. */
+    transcript = new_transcript();
+    CHECK_CODE(bp_transcript_append_message(transcript,
+                                            (const uint8_t *)"extra", 5,
+                                            (const uint8_t *)"data", 4),
+               BP_OK);
+    CHECK_CODE(bp_range_proof_verify(proof, bp_gens, pc_gens, transcript,
+                                     commitments, M, N),
+               BP_ERROR_VERIFICATION);
+    bp_transcript_free(transcript);
+
+    /* A tampered proof fails to verify. */
+    bytes[32 * 4] ^= 1;
+    CHECK_CODE(bp_range_proof_from_bytes(bytes, len, &parsed), BP_OK);
+    transcript = new_transcript();
+    CHECK_CODE(bp_range_proof_verify(parsed, bp_gens, pc_gens, transcript,
+                                     commitments, M, N),
+               BP_ERROR_VERIFICATION);
+    bp_transcript_free(transcript);
+    bp_range_proof_free(parsed);
+
+    /* Malformed encodings are rejected. */
+    parsed = NULL;
+    CHECK_CODE(bp_range_proof_from_bytes(bytes, len - 1, &parsed),
+               BP_ERROR_FORMAT);
+    CHECK(parsed == NULL);
+
+    free(bytes);
+    bp_range_proof_free(proof);
+}
+
+static void test_errors(const bp_bulletproof_gens *bp_gens,
+                        const bp_pedersen_gens *pc_gens) {
+    const uint64_t values[3] = {1, 2, 3};
+    uint8_t blindings[32 * 3] = {0};
+    uint8_t commitments[32 * 3];
+    bp_range_proof *proof = NULL;
+    bp_transcript *transcript = new_transcript();
+
+    CHECK_CODE(bp_range_proof_prove(bp_gens, pc_gens, transcript, values,
+                                    blindings, 1, 12, &proof, commitments),
+               BP_ERROR_INVALID_BITSIZE);
+    CHECK_CODE(bp_range_proof_prove(bp_gens, pc_gens, transcript, values,
+                                    blindings, 3, 8, &proof, commitments),
+               BP_ERROR_INVALID_AGGREGATION);
+    CHECK_CODE(bp_range_proof_prove(bp_gens, pc_gens, transcript, values,
+                                    blindings, 1, 64, &proof, commitments),
+               BP_ERROR_INVALID_GENERATORS_LENGTH);
+    CHECK_CODE(bp_range_proof_prove(bp_gens, pc_gens, transcript, values,
+                                    NULL, 1, 8, &proof, commitments),
+               BP_ERROR_NULL_POINTER);
+    CHECK(proof == NULL);
+
+    /* Blinding factors must be canonical scalars. */
+    memset(blindings, 0xff, 32);
+    CHECK_CODE(bp_range_proof_prove(bp_gens, pc_gens, transcript, values,
+                                    blindings, 1, 8, &proof, commitments),
+               BP_ERROR_INVALID_ARGUMENT);
+    CHECK(proof == NULL);
+    bp_transcript_free(transcript);
+
+    CHECK(strcmp(bp_error_message(BP_ERROR_VERIFICATION),
+                 "Proof verification failed.") == 0);
+    CHECK(strcmp(bp_error_message(-1), "Unknown error code.") == 0);
+}
+
+static void test_generators(const bp_bulletproof_gens *bp_gens) {
+    bp_bulletproof_gens *parsed = NULL;
+    uint8_t small[16];
+    uint8_t *bytes;
+    size_t len = sizeof(small);
+
+    CHECK_CODE(bp_bulletproof_gens_to_bytes(bp_gens, small, &len),
+               BP_ERROR_BUFFER_TOO_SMALL);
+    CHECK(len > sizeof(small));
+    bytes = malloc(len);
+    CHECK_CODE(bp_bulletproof_gens_to_bytes(bp_gens, bytes, &len), BP_OK);
+    CHECK_CODE(bp_bulletproof_gens_from_bytes(bytes, len, &parsed), BP_OK);
+    CHECK(parsed != NULL);
+    bp_bulletproof_gens_free(parsed);
+
+    /* The digest detects corruption. */
+    bytes[len / 2] ^= 1;
+    parsed = NULL;
+    CHECK_CODE(bp_bulletproof_gens_from_bytes(bytes, len, &parsed),
+               BP_ERROR_FORMAT);
+    CHECK(parsed == NULL);
+    free(bytes);
+}
+
+/* Exhausts the transcript labels, so it must run last. */
+static void test_labels(void) {
+    bp_transcript *transcript = new_transcript();
+    char label[32];
+    bp_error code = BP_OK;
+    size_t i;
+
+    for (i = 0; i < BP_MAX_TRANSCRIPT_LABELS && code == BP_OK; i++) {
+        snprintf(label, sizeof(label), "label %u", (unsigned)i);
+        code = bp_transcript_append_message(transcript, (const uint8_t *)label,
+                                            strlen(label),
+                                            (const uint8_t *)"data", 4);
+    }
+    CHECK_CODE(code, BP_ERROR_INVALID_ARGUMENT);
+    bp_transcript_free(transcript);
+
+    /* Known labels can still be used, but new ones cannot. */
+    transcript = new_transcript();
+    bp_transcript_free(transcript);
+    CHECK(bp_transcript_new((const uint8_t *)"new", 3) == NULL);
+}
+
+int main(void) {
+    bp_bulletproof_gens *bp_gens = bp_bulletproof_gens_new(N, M);
+    bp_pedersen_gens *pc_gens = bp_pedersen_gens_new();
+
+    CHECK(bp_gens != NULL);
+    CHECK(pc_gens != NULL);
+
+    test_prove_and_verify(bp_gens, pc_gens);
+    test_errors(bp_gens, pc_gens);
+    test_generators(bp_gens);
+    test_labels();
+
+    bp_pedersen_gens_free(pc_gens);
+    bp_bulletproof_gens_free(bp_gens);
+
+    if (failures != 0) {
+        fprintf(stderr, "%d checks failed\n", failures);
+        return EXIT_FAILURE;
+    }
+    printf("ok\n");
+    return EXIT_SUCCESS;
+}
@@ -0,0 +1,57 @@
+//! Compiles the C test program `tests/c/range_proof.c` against the
+//! generated header and the `bulletproofs_ffi` shared library, and runs it.
+//!
+//! The C compiler is taken from the `CC` environment variable, and
+//! defaults to `cc`.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::Command;
+
+fn manifest_path(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+/// The directory of the shared library, which is the `deps` directory
+/// containing this test binary: `cargo test` builds the library there,
+/// but only `cargo build` copies it to its parent.
+fn library_dir() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    exe.parent().unwrap().to_path_buf()
+}
+
+#[test]
+fn c_range_proof() {
+    let library_dir = library_dir();
+    let program = library_dir.join(format!("c_range_proof-{}", std::process::id()));
+    let cc = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+
+    let output = Command::new(&cc)
+        .args(["-std=c99", "-Wall", "-Wextra", "-Werror", "-I"])
+        .arg(manifest_path("include"))
+        .arg(manifest_path("tests/c/range_proof.c"))
+        .arg("-o")
+        .arg(&program)
+        .arg("-L")
+        .arg(&library_dir)
+        .arg("-lbulletproofs_ffi")
+        .arg(format!("-Wl,-rpath,{}", library_dir.display()))
+        .output()
+        .unwrap_or_else(|e| panic!("could not run the C compiler `{}`: {}", cc, e));
+    assert!(
+        output.status.success(),
+        "the C test program failed to compile:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let output = Command::new(&program)
+        .output()
+        .expect("the C test program should run");
+    let _ = std::fs::remove_file(&program);
+    assert!(
+        output.status.success(),
+        "the C test program failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
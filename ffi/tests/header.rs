@@ -0,0 +1,229 @@
+//! Generates the C header `include/bulletproofs.h` from `src/lib.rs`,
+//! and checks that the checked-in header is up to date.
+//!
+//! The generator understands the subset of Rust used by the bindings:
+//!
+//! * `pub struct` declares an opaque handle type,
+//! * `pub type` declares an integer type,
+//! * `pub const` declares an integer constant,
+//! * `pub extern "C" fn` and `pub unsafe extern "C" fn` declare functions,
+//!
+//! and copies the doc comments of these items.  After changing the
+//! bindings, regenerate the header with
+//!
+//! ```text
+//! BULLETPROOFS_UPDATE_HEADER=1 cargo test -p bulletproofs-ffi --test header
+//! ```
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Set to regenerate the header instead of checking it.
+const UPDATE_ENV: &str = "BULLETPROOFS_UPDATE_HEADER";
+
+const PREAMBLE: &str = "\
+/* Generated from src/lib.rs by tests/header.rs.  Do not edit. */
+
+#ifndef BULLETPROOFS_H
+#define BULLETPROOFS_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+";
+
+const POSTAMBLE: &str = "
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* BULLETPROOFS_H */
+";
+
+fn manifest_path(path: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(path)
+}
+
+/// Translates a Rust FFI type to C.
+fn c_type(ty: &str) -> String {
+    let ty = ty.trim();
+    if let Some(pointee) = ty.strip_prefix("*const ") {
+        pointer(&format!("const {}", c_type(pointee)))
+    } else if let Some(pointee) = ty.strip_prefix("*mut ") {
+        pointer(&c_type(pointee))
+    } else {
+        match ty {
+            "u8" => "uint8_t",
+            "u64" => "uint64_t",
+            "usize" => "size_t",
+            "i32" => "int32_t",
+            "c_char" => "char",
+            _ => ty,
+        }
+        .to_string()
+    }
+}
+
+fn pointer(pointee: &str) -> String {
+    if pointee.ends_with('*') {
+        format!("{}*", pointee)
+    } else {
+        format!("{} *", pointee)
+    }
+}
+
+/// Declares `name` with the C type `ty`.
+fn declaration(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{}{}", ty, name)
+    } else {
+        format!("{} {}", ty, name)
+    }
+}
+
+/// Translates a doc comment to a C comment.
+fn comment(doc: &[String]) -> String {
+    let doc: Vec<String> = doc
+        .iter()
+        .map(|line| line.replace("\\[", "[").replace("\\]", "]"))
+        .collect();
+    match doc.as_slice() {
+        [] => String::new(),
+        [line] if line.len() < 74 => format!("/* {} */\n", line),
+        lines => {
+            let mut comment = String::from("/*\n");
+            for line in lines {
+                if line.is_empty() {
+                    comment.push_str(" *\n");
+                } else {
+                    comment.push_str(&format!(" * {}\n", line));
+                }
+            }
+            comment + " */\n"
+        }
+    }
+}
+
+/// Translates the signature of an `extern "C"` function to a prototype.
+fn prototype(signature: &str) -> String {
+    let signature = signature.split("fn ").nth(1).unwrap();
+    let (name, rest) = signature.split_at(signature.find('(').unwrap());
+    let close = rest.rfind(')').unwrap();
+    let params: Vec<String> = rest[1..close]
+        .split(',')
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, ty) = param.split_at(param.find(':').unwrap());
+            declaration(&c_type(&ty[1..]), name)
+        })
+        .collect();
+    let ret = match rest[close + 1..].trim().strip_prefix("->") {
+        Some(ty) => c_type(ty),
+        None => "void".to_string(),
+    };
+
+    let head = declaration(&ret, name.trim());
+    let single = format!(
+        "{}({});\n",
+        head,
+        if params.is_empty() {
+            "void".to_string()
+        } else {
+            params.join(", ")
+        }
+    );
+    if single.len() <= 81 {
+        single
+    } else {
+        format!("{}(\n    {});\n", head, params.join(",\n    "))
+    }
+}
+
+fn generate_header(source: &str) -> String {
+    let mut items = Vec::new();
+    let mut doc: Vec<String> = Vec::new();
+    let mut lines = source.lines().map(str::trim);
+
+    while let Some(line) = lines.next() {
+        if let Some(text) = line.strip_prefix("///") {
+            doc.push(text.strip_prefix(' ').unwrap_or(text).to_string());
+            continue;
+        }
+        if line.starts_with("#[") {
+            continue;
+        }
+
+        let item = if let Some(rest) = line.strip_prefix("pub struct ") {
+            let name = rest.split(|c| c == '(' || c == ' ' || c == ';').next();
+            Some(format!("typedef struct {0} {0};\n", name.unwrap()))
+        } else if let Some(rest) = line.strip_prefix("pub type ") {
+            let (name, ty) = rest.trim_end_matches(';').split_at(rest.find('=').unwrap());
+            Some(format!(
+                "typedef {};\n",
+                declaration(&c_type(&ty[1..]), name.trim())
+            ))
+        } else if let Some(rest) = line.strip_prefix("pub const ") {
+            let name = &rest[..rest.find(':').unwrap()];
+            let value = rest[rest.find('=').unwrap() + 1..].trim_end_matches(';');
+            Some(format!("#define {} {}\n", name, value.trim()))
+        } else if line.starts_with("pub extern \"C\" fn ")
+            || line.starts_with("pub unsafe extern \"C\" fn ")
+        {
+            let mut signature = line.to_string();
+            while !signature.ends_with('{') {
+                signature.push(' ');
+                signature.push_str(lines.next().unwrap());
+            }
+            Some(prototype(signature.trim_end_matches('{')))
+        } else {
+            None
+        };
+
+        if let Some(item) = item {
+            items.push(comment(&doc) + &item);
+        }
+        doc.clear();
+    }
+
+    format!("{}\n{}{}", PREAMBLE, items.join("\n"), POSTAMBLE)
+}
+
+#[test]
+fn header_is_up_to_date() {
+    let source = fs::read_to_string(manifest_path("src/lib.rs")).unwrap();
+    let header = generate_header(&source);
+    let path = manifest_path("include/bulletproofs.h");
+
+    if env::var_os(UPDATE_ENV).is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, header).unwrap();
+        return;
+    }
+
+    let checked_in = fs::read_to_string(&path).unwrap_or_default();
+    if let Some((line, (expected, actual))) = header
+        .lines()
+        .zip(checked_in.lines())
+        .enumerate()
+        .find(|(_, (expected, actual))| expected != actual)
+    {
+        panic!(
+            "include/bulletproofs.h differs from the bindings at line {}: \
+             expected `{}`, found `{}`; set {} to regenerate it",
+            line + 1,
+            expected,
+            actual,
+            UPDATE_ENV
+        );
+    }
+    assert!(
+        header == checked_in,
+        "include/bulletproofs.h is out of date; set {} to regenerate it",
+        UPDATE_ENV
+    );
+}
@@ -0,0 +1,220 @@
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::borrow::Borrow;
+use alloc::vec::Vec;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::curve::{CurvePoint, CurveScalar};
+use crate::errors::ProofError;
+
+/// An accumulator for the verification equations of several proofs,
+/// which are checked together with a single multiscalar multiplication.
+///
+/// Each proof replays its protocol against its own transcript and, instead
+/// of checking its verification equation \\(\sum_i c_i P_i = 0\\), appends
+/// the terms \\((c_i, P_i)\\) to the `BatchVerifier`.  Every equation is
+/// weighted by an independent random scalar, so that [`BatchVerifier::verify`]
+/// succeeds only if all of the appended equations hold (except with
+/// negligible probability).
+///
+/// Errors that do not depend on the final equation, such as malformed
+/// proof points, are returned when a proof is appended.
+///
+/// All of the proofs of a batch must be over the same group `C`.
+pub struct BatchVerifier<T: RngCore + CryptoRng, C: CurvePoint = RistrettoPoint> {
+    rng: T,
+    scalars: Vec<C::Scalar>,
+    points: Vec<Option<C>>,
+}
+
+impl<T: RngCore + CryptoRng, C: CurvePoint> BatchVerifier<T, C> {
+    /// Creates an empty batch, which draws the random weights of the
+    /// verification equations from `rng`.
+    pub fn new(rng: T) -> Self {
+        BatchVerifier {
+            rng,
+            scalars: Vec::new(),
+            points: Vec::new(),
+        }
+    }
+
+    /// Returns the random number generator of the batch, for sampling
+    /// the challenges that proofs use to combine their own checks.
+    pub(crate) fn rng(&mut self) -> &mut T {
+        &mut self.rng
+    }
+
+    /// Appends a verification equation \\(\sum_i c_i P_i = 0\\), given by
+    /// its `scalars` \\(c_i\\) and `points` \\(P_i\\), weighted by a fresh
+    /// random scalar.
+    ///
+    /// Points may be given as `Option<C>`, so that points that
+    /// fail to decompress make the whole batch fail.
+    ///
+    /// Returns an error if the number of scalars and points differ, in which
+    /// case nothing is appended.
+    pub fn append<IS, IP>(&mut self, scalars: IS, points: IP) -> Result<(), ProofError>
+    where
+        IS: IntoIterator,
+        IS::Item: Borrow<C::Scalar>,
+        IP: IntoIterator,
+        IP::Item: Into<Option<C>>,
+    {
+        let weight = C::Scalar::random(&mut self.rng);
+
+        let scalars_len = self.scalars.len();
+        let points_len = self.points.len();
+        self.scalars
+            .extend(scalars.into_iter().map(|c_i| weight * *c_i.borrow()));
+        self.points.extend(points.into_iter().map(Into::into));
+
+        if self.scalars.len() - scalars_len != self.points.len() - points_len {
+            self.scalars.truncate(scalars_len);
+            self.points.truncate(points_len);
+            return Err(ProofError::VerificationError);
+        }
+        Ok(())
+    }
+
+    /// Checks all of the appended verification equations at once.
+    ///
+    /// An empty batch verifies successfully.
+    pub fn verify(self) -> Result<(), ProofError> {
+        let mega_check = C::optional_multiscalar_mul(self.scalars, self.points)
+            .ok_or(ProofError::VerificationError)?;
+
+        if mega_check.is_identity() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use curve25519_dalek::ristretto::CompressedRistretto;
+    use curve25519_dalek::scalar::Scalar;
+    use merlin::Transcript;
+
+    use crate::generators::{BulletproofGens, PedersenGens};
+    use crate::{LinearProof, RangeProof};
+
+    /// Creates a range proof for `value` and returns it with its commitment.
+    fn range_proof(value: u64) -> (RangeProof, CompressedRistretto) {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let mut rng = rand::thread_rng();
+
+        RangeProof::prove_single_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"BatchVerifierTest"),
+            value,
+            &Scalar::random(&mut rng),
+            32,
+            &mut rng,
+        )
+        .unwrap()
+    }
+
+    /// Creates a linear proof for random vectors and returns it with
+    /// its commitment, generators and public vector.
+    fn linear_proof(
+        n: usize,
+    ) -> (
+        LinearProof,
+        CompressedRistretto,
+        Vec<RistrettoPoint>,
+        Vec<Scalar>,
+    ) {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, 1);
+        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+        let mut rng = rand::thread_rng();
+
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r = Scalar::random(&mut rng);
+        let c = crate::inner_product_proof::inner_product(&a, &b);
+        let C = RistrettoPoint::vartime_multiscalar_mul(
+            a.iter().chain(Some(&r)).chain(Some(&c)),
+            G.iter()
+                .chain(Some(&pc_gens.B_blinding))
+                .chain(Some(&pc_gens.B)),
+        )
+        .compress();
+
+        let proof = LinearProof::create(
+            &mut Transcript::new(b"BatchVerifierTest"),
+            &mut rng,
+            &C,
+            r,
+            a,
+            b.clone(),
+            G.clone(),
+            &pc_gens.B,
+            &pc_gens.B_blinding,
+        )
+        .unwrap();
+
+        (proof, C, G, b)
+    }
+
+    #[test]
+    fn batch_range_and_linear_proofs() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+
+        let (rp, V) = range_proof(1037);
+        let (lp, C, G, b) = linear_proof(7);
+
+        let batch_helper = |V: &CompressedRistretto| {
+            let mut batch = BatchVerifier::new(rand::thread_rng());
+            rp.batch_verify_single(
+                &mut batch,
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"BatchVerifierTest"),
+                V,
+                32,
+            )?;
+            lp.batch_verify(
+                &mut batch,
+                &mut Transcript::new(b"BatchVerifierTest"),
+                &C,
+                &G,
+                &pc_gens.B,
+                &pc_gens.B_blinding,
+                b.clone(),
+            )?;
+            batch.verify()
+        };
+
+        assert!(batch_helper(&V).is_ok());
+
+        // A commitment to another value makes the whole batch fail.
+        let (_, other_V) = range_proof(1037);
+        assert!(batch_helper(&other_V).is_err());
+    }
+
+    #[test]
+    fn append_rejects_mismatched_terms() {
+        let B = PedersenGens::default().B;
+        let mut batch = BatchVerifier::new(rand::thread_rng());
+
+        assert!(batch
+            .append(vec![Scalar::one(), -Scalar::one()], vec![B])
+            .is_err());
+        assert!(batch
+            .append(vec![Scalar::one(), -Scalar::one()], vec![B, B])
+            .is_ok());
+        assert!(batch.verify().is_ok());
+    }
+}
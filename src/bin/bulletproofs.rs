@@ -0,0 +1,513 @@
+#![allow(non_snake_case)]
+//! Command-line tool for creating and checking range proofs.
+//!
+//! Run `bulletproofs help` for the list of commands.  The tool is built
+//! with the `cli` feature:
+//!
+//! ```text
+//! cargo run --features cli -- help
+//! ```
+
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand::thread_rng;
+use serde_json::{json, Value};
+
+use bulletproofs::r1cs::R1CSProof;
+use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+
+const USAGE: &str = "\
+Usage: bulletproofs <command> [options]
+
+Commands:
+  commit <value> [--blinding <hex>]
+      Create a Pedersen commitment to <value>, with a random blinding
+      factor unless one is given.
+
+  prove <input> [--bits <n>] [--label <label>]
+      Create a range proof for the openings in the <input> file, which is
+      either JSON, {\"values\": [..], \"blindings\": [..]}, with optional
+      blindings, or hex, with one opening per line: the 8-byte
+      little-endian value followed by the 32-byte blinding.
+
+  verify <proof> [--bits <n>] [--label <label>]
+  verify --proof <hex> --commitment <hex>... --bits <n> [--label <label>]
+      Verify a range proof, given as the JSON output of `prove` or as hex.
+
+  inspect range|r1cs <proof>
+      Decode a RangeProof or an R1CSProof, given as hex or as a file
+      containing hex, and print its fields.
+
+  help
+      Print this message.
+
+The proofs use the default Pedersen generators, and Bulletproofs
+generators created with `BulletproofGens::new`.  The transcript label
+defaults to \"bulletproofs\" and must match the one the proof was created
+with.  The bit size is 8, 16, 32 or 64, the default, and the number of
+values must be a power of two of at most 64.  Use - to read a file from
+standard input.";
+
+/// The default label of the proof transcripts.
+const DEFAULT_LABEL: &str = "bulletproofs";
+
+/// The default bit size of the range proofs.
+const DEFAULT_BITS: usize = 64;
+
+/// The largest number of values of a range proof, which bounds the
+/// generators created for a proof given on the command line.
+const MAX_COUNT: usize = 64;
+
+type CliResult<T> = Result<T, String>;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn run(args: &[String]) -> CliResult<()> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (command.as_str(), args),
+        None => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+    };
+    let args = Args::parse(args)?;
+
+    match command {
+        "commit" => commit(&args),
+        "prove" => prove(&args),
+        "verify" => verify(&args),
+        "inspect" => inspect(&args),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!(
+            "unknown command `{}`, see `bulletproofs help`",
+            command
+        )),
+    }
+}
+
+/// The positional arguments and `--name value` options of a command.
+struct Args {
+    positional: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> CliResult<Args> {
+        let mut positional = Vec::new();
+        let mut options = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `{}`", arg))?;
+                options.push((name.to_string(), value.clone()));
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    fn positional(&self, i: usize, name: &str) -> CliResult<&str> {
+        self.positional
+            .get(i)
+            .map(String::as_str)
+            .ok_or_else(|| format!("missing <{}> argument", name))
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    fn options(&self, name: &str) -> Vec<&str> {
+        self.options
+            .iter()
+            .filter(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    fn bits(&self) -> CliResult<Option<usize>> {
+        self.option("bits")
+            .map(|bits| {
+                bits.parse()
+                    .map_err(|_| format!("invalid bit size `{}`", bits))
+                    .and_then(check_bits)
+            })
+            .transpose()
+    }
+
+    fn transcript(&self) -> Transcript {
+        let label = self.option("label").unwrap_or(DEFAULT_LABEL);
+        // Transcript labels are static; the tool creates a single transcript.
+        Transcript::new(Box::leak(label.as_bytes().to_vec().into_boxed_slice()))
+    }
+}
+
+// Parsing
+
+/// Checks a bit size before generators are created for it, since the
+/// generators of a large bit size would exhaust the memory.
+fn check_bits(bits: u64) -> CliResult<usize> {
+    match bits {
+        8 | 16 | 32 | 64 => Ok(bits as usize),
+        _ => Err(format!(
+            "invalid bit size `{}`, must be 8, 16, 32 or 64",
+            bits
+        )),
+    }
+}
+
+fn check_count(count: usize, name: &str) -> CliResult<()> {
+    if !count.is_power_of_two() {
+        Err(format!(
+            "the number of {} must be a power of two, not {}",
+            name, count
+        ))
+    } else if count > MAX_COUNT {
+        Err(format!(
+            "the number of {} must be at most {}, not {}",
+            name, MAX_COUNT, count
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn read_input(path: &str) -> CliResult<String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("cannot read standard input: {}", e))?;
+        Ok(input)
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("cannot read `{}`: {}", path, e))
+    }
+}
+
+/// Reads `arg` as hex, or as the hex contents of the file `arg` names.
+fn read_hex_arg(arg: &str) -> CliResult<Vec<u8>> {
+    match parse_hex(arg) {
+        Ok(bytes) => Ok(bytes),
+        Err(_) => parse_hex(&read_input(arg)?),
+    }
+}
+
+fn parse_hex(s: &str) -> CliResult<Vec<u8>> {
+    let s = s.trim();
+    let s = s.strip_prefix("0x").unwrap_or(s);
+    hex::decode(s).map_err(|e| format!("invalid hex: {}", e))
+}
+
+fn parse_scalar(s: &str) -> CliResult<Scalar> {
+    let bytes = parse_hex(s)?;
+    if bytes.len() != 32 {
+        return Err(format!("a scalar has 32 bytes, not {}", bytes.len()));
+    }
+    let mut buf = [0u8; 32];
+    buf.copy_from_slice(&bytes);
+    Scalar::from_canonical_bytes(buf).ok_or_else(|| format!("`{}` is not a canonical scalar", s))
+}
+
+fn parse_commitment(s: &str) -> CliResult<CompressedRistretto> {
+    let bytes = parse_hex(s)?;
+    if bytes.len() != 32 {
+        return Err(format!("a commitment has 32 bytes, not {}", bytes.len()));
+    }
+    Ok(CompressedRistretto::from_slice(&bytes))
+}
+
+fn parse_value(s: &str) -> CliResult<u64> {
+    s.parse().map_err(|_| format!("invalid value `{}`", s))
+}
+
+/// The openings of a `prove` input file, with optional blindings.
+fn parse_openings(input: &str) -> CliResult<(Vec<u64>, Vec<Option<Scalar>>)> {
+    if input.trim_start().starts_with('{') {
+        let json: Value =
+            serde_json::from_str(input).map_err(|e| format!("invalid JSON input: {}", e))?;
+        let values = json["values"]
+            .as_array()
+            .ok_or("the input has no `values` array")?
+            .iter()
+            .map(|v| v.as_u64().ok_or_else(|| format!("invalid value `{}`", v)))
+            .collect::<CliResult<Vec<u64>>>()?;
+        let blindings = match json.get("blindings") {
+            None => vec![None; values.len()],
+            Some(blindings) => blindings
+                .as_array()
+                .ok_or("`blindings` is not an array")?
+                .iter()
+                .map(|r| {
+                    let r = r.as_str().ok_or("a blinding is not a hex string")?;
+                    parse_scalar(r).map(Some)
+                })
+                .collect::<CliResult<_>>()?,
+        };
+        if blindings.len() != values.len() {
+            return Err("the input needs one blinding per value".into());
+        }
+        Ok((values, blindings))
+    } else {
+        input
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let bytes = parse_hex(line)?;
+                if bytes.len() != 40 {
+                    return Err(format!(
+                        "an opening has 40 bytes, a value and a blinding, not {}",
+                        bytes.len()
+                    ));
+                }
+                let mut value = [0u8; 8];
+                value.copy_from_slice(&bytes[..8]);
+                let blinding = parse_scalar(&hex::encode(&bytes[8..]))?;
+                Ok((u64::from_le_bytes(value), Some(blinding)))
+            })
+            .collect::<CliResult<Vec<_>>>()
+            .map(|openings| openings.into_iter().unzip())
+    }
+}
+
+// Commands
+
+fn commit(args: &Args) -> CliResult<()> {
+    let value = parse_value(args.positional(0, "value")?)?;
+    let blinding = match args.option("blinding") {
+        Some(r) => parse_scalar(r)?,
+        None => Scalar::random(&mut thread_rng()),
+    };
+    let commitment = PedersenGens::default().commit(Scalar::from(value), blinding);
+
+    print_json(&json!({
+        "value": value,
+        "blinding": hex::encode(blinding.as_bytes()),
+        "commitment": hex::encode(commitment.compress().as_bytes()),
+    }))
+}
+
+fn prove(args: &Args) -> CliResult<()> {
+    let (values, blindings) = parse_openings(&read_input(args.positional(0, "input")?)?)?;
+    if values.is_empty() {
+        return Err("the input has no values".into());
+    }
+    check_count(values.len(), "values")?;
+    let bits = args.bits()?.unwrap_or(DEFAULT_BITS);
+    let blindings: Vec<Scalar> = blindings
+        .into_iter()
+        .map(|r| r.unwrap_or_else(|| Scalar::random(&mut thread_rng())))
+        .collect();
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(bits, values.len());
+    let mut transcript = args.transcript();
+    let (proof, commitments) = RangeProof::prove_multiple(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        &values,
+        &blindings,
+        bits,
+    )
+    .map_err(|e| format!("cannot create the proof: {}", e))?;
+
+    print_json(&json!({
+        "bits": bits,
+        "values": values,
+        "blindings": blindings.iter().map(|r| hex::encode(r.as_bytes())).collect::<Vec<_>>(),
+        "commitments": commitments.iter().map(|V| hex::encode(V.as_bytes())).collect::<Vec<_>>(),
+        "proof": hex::encode(proof.to_bytes()),
+    }))
+}
+
+fn verify(args: &Args) -> CliResult<()> {
+    let (proof, commitments, bits) = match args.option("proof") {
+        Some(proof) => {
+            let commitments = args
+                .options("commitment")
+                .into_iter()
+                .map(parse_commitment)
+                .collect::<CliResult<Vec<_>>>()?;
+            (read_hex_arg(proof)?, commitments, args.bits()?)
+        }
+        None => {
+            let input = read_input(args.positional(0, "proof")?)?;
+            let json: Value =
+                serde_json::from_str(&input).map_err(|e| format!("invalid JSON proof: {}", e))?;
+            let proof = parse_hex(json["proof"].as_str().ok_or("the input has no `proof`")?)?;
+            let commitments = json["commitments"]
+                .as_array()
+                .ok_or("the input has no `commitments` array")?
+                .iter()
+                .map(|V| parse_commitment(V.as_str().ok_or("a commitment is not a hex string")?))
+                .collect::<CliResult<Vec<_>>>()?;
+            let bits = match args.bits()? {
+                Some(bits) => Some(bits),
+                None => match &json["bits"] {
+                    Value::Null => None,
+                    bits => Some(bits.as_u64().ok_or("`bits` is not a number")?),
+                }
+                .map(check_bits)
+                .transpose()?,
+            };
+            (proof, commitments, bits)
+        }
+    };
+    if commitments.is_empty() {
+        return Err("no commitments to verify the proof against".into());
+    }
+    check_count(commitments.len(), "commitments")?;
+    let bits = bits.unwrap_or(DEFAULT_BITS);
+
+    let proof = RangeProof::from_bytes(&proof).map_err(|e| format!("invalid proof: {}", e))?;
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(bits, commitments.len());
+    let mut transcript = args.transcript();
+    proof
+        .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &commitments, bits)
+        .map_err(|e| format!("the proof does not verify: {}", e))?;
+
+    println!("ok");
+    Ok(())
+}
+
+fn inspect(args: &Args) -> CliResult<()> {
+    let kind = args.positional(0, "range|r1cs")?;
+    let bytes = read_hex_arg(args.positional(1, "proof")?)?;
+    match kind {
+        "range" => inspect_range_proof(&bytes),
+        "r1cs" => inspect_r1cs_proof(&bytes),
+        _ => Err(format!(
+            "cannot inspect `{}` proofs, only range or r1cs",
+            kind
+        )),
+    }
+}
+
+// Inspection
+
+/// Prints the fields of an encoding, which has been checked to be valid.
+struct Fields<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, n: usize) -> &'a [u8] {
+        let (field, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        field
+    }
+
+    fn print(&mut self, name: &str) {
+        println!("  {:<14} {}", name, hex::encode(self.take(32)));
+    }
+
+    fn print_inner_product_proof(&mut self) {
+        let k = (self.bytes.len() - 64) / 64;
+        println!("  ipp_proof      {} rounds", k);
+        for i in 0..k {
+            self.print(&format!("  L_{}", i));
+            self.print(&format!("  R_{}", i));
+        }
+        self.print("  a");
+        self.print("  b");
+    }
+}
+
+fn inspect_range_proof(bytes: &[u8]) -> CliResult<()> {
+    let proof = RangeProof::from_bytes(bytes).map_err(|e| format!("invalid RangeProof: {}", e))?;
+    let mut fields = Fields { bytes };
+
+    println!("RangeProof, {} bytes", bytes.len());
+    if bytes.len() % 32 == 0 {
+        let k = (bytes.len() - 4 * 32 - 5 * 32) / 64;
+        println!("  n x m          {}", 1u64 << k);
+        for name in &["A", "S", "T_1", "T_2", "t_x", "t_x_blinding", "e_blinding"] {
+            fields.print(name);
+        }
+    } else {
+        // Proofs with value generators start with a tag and the number of values.
+        fields.take(1);
+        let mut m = [0u8; 8];
+        m.copy_from_slice(fields.take(8));
+        let m = u64::from_le_bytes(m);
+        println!("  value generators, {} values", m);
+        fields.print("A");
+        fields.print("S");
+        for j in 0..m {
+            fields.print(&format!("T_1[{}]", j));
+            fields.print(&format!("T_2[{}]", j));
+        }
+        for j in 0..m {
+            fields.print(&format!("t_x[{}]", j));
+        }
+        fields.print("t_x_blinding");
+        fields.print("e_blinding");
+    }
+    fields.print_inner_product_proof();
+    debug_assert_eq!(proof.to_bytes(), bytes);
+    Ok(())
+}
+
+fn inspect_r1cs_proof(bytes: &[u8]) -> CliResult<()> {
+    let proof = R1CSProof::from_bytes(bytes).map_err(|e| format!("invalid R1CSProof: {}", e))?;
+    let mut fields = Fields { bytes };
+
+    println!("R1CSProof, {} bytes", bytes.len());
+    let two_phase = fields.take(1)[0] == 1;
+    let mut names = vec!["A_I1", "A_O1", "S1"];
+    if two_phase {
+        println!("  two phases");
+        names.extend(&["A_I2", "A_O2", "S2"]);
+    } else {
+        println!("  one phase");
+    }
+    names.extend(&[
+        "T_1",
+        "T_3",
+        "T_4",
+        "T_5",
+        "T_6",
+        "t_x",
+        "t_x_blinding",
+        "e_blinding",
+    ]);
+    for name in names {
+        fields.print(name);
+    }
+    fields.print_inner_product_proof();
+    debug_assert_eq!(proof.to_bytes(), bytes);
+    Ok(())
+}
+
+fn print_json(value: &Value) -> CliResult<()> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| e.to_string())?;
+    println!("{}", json);
+    Ok(())
+}
@@ -0,0 +1,492 @@
+//! Implementations of the curve traits for the G1 group of the BN254
+//! (also known as `alt_bn128`) pairing, whose additions and scalar
+//! multiplications are precompiled contracts on Ethereum-compatible
+//! chains.
+//!
+//! The encodings follow the conventions of the precompiles:
+//!
+//! * scalars are 32-byte big-endian integers modulo the group order \\(r\\);
+//! * points are the 32-byte big-endian affine coordinates \\(x \\| y\\),
+//!   with the identity encoded as 64 zero bytes.
+//!
+//! Points from uniform bytes are found by try-and-increment: the 64
+//! bytes are reduced modulo the field prime \\(p\\) as a big-endian
+//! integer \\(x\\), which is incremented until \\(x^3 + 3\\) is a square, and
+//! the square root \\(y\\) with an even canonical value is chosen.
+//!
+//! # Warning
+//!
+//! The arithmetic of the underlying `substrate-bn` crate is not
+//! constant-time, so this backend is meant for creating proofs of public
+//! test vectors and for verification, and not for proving with secret
+//! values on shared machines.
+
+#![allow(non_snake_case)]
+
+use core::borrow::Borrow;
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use rand_core::{CryptoRng, RngCore};
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use substrate_bn::arith::U256;
+use substrate_bn::{AffineG1, Fq, Fr, Group, G1};
+use subtle::{Choice, ConditionallySelectable};
+
+use super::serde_array::{deserialize_array, serialize_array};
+use super::{CompressedPoint, CurvePoint, CurveScalar};
+
+/// An element of the scalar field of BN254, the integers modulo the
+/// order \\(r\\) of G1.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bn254Scalar(Fr);
+
+/// A point of the G1 group of BN254.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bn254Point(G1);
+
+/// The 64-byte encoding \\(x \\| y\\) of a [`Bn254Point`], as used by the
+/// EVM precompiles.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bn254Encoding(pub [u8; 64]);
+
+fn u256_to_bytes(x: U256) -> [u8; 32] {
+    let mut bytes = [0u8; 32];
+    x.to_big_endian(&mut bytes)
+        .expect("the buffer has the size of a U256");
+    bytes
+}
+
+fn fq_to_bytes(x: Fq) -> [u8; 32] {
+    u256_to_bytes(x.into_u256())
+}
+
+impl Bn254Scalar {
+    /// Returns the scalar of the underlying `substrate-bn` crate.
+    pub fn to_fr(&self) -> Fr {
+        self.0
+    }
+}
+
+impl Bn254Point {
+    /// Returns the point of the underlying `substrate-bn` crate.
+    pub fn to_g1(&self) -> G1 {
+        self.0
+    }
+}
+
+impl From<u64> for Bn254Scalar {
+    fn from(x: u64) -> Self {
+        Bn254Scalar(Fr::new(U256::from(x)).expect("a u64 is smaller than the group order"))
+    }
+}
+
+impl Default for Bn254Scalar {
+    fn default() -> Self {
+        Bn254Scalar(Fr::zero())
+    }
+}
+
+impl Add for Bn254Scalar {
+    type Output = Bn254Scalar;
+
+    fn add(self, other: Bn254Scalar) -> Bn254Scalar {
+        Bn254Scalar(self.0 + other.0)
+    }
+}
+
+impl Sub for Bn254Scalar {
+    type Output = Bn254Scalar;
+
+    fn sub(self, other: Bn254Scalar) -> Bn254Scalar {
+        Bn254Scalar(self.0 - other.0)
+    }
+}
+
+impl Mul for Bn254Scalar {
+    type Output = Bn254Scalar;
+
+    fn mul(self, other: Bn254Scalar) -> Bn254Scalar {
+        Bn254Scalar(self.0 * other.0)
+    }
+}
+
+impl Neg for Bn254Scalar {
+    type Output = Bn254Scalar;
+
+    fn neg(self) -> Bn254Scalar {
+        Bn254Scalar(-self.0)
+    }
+}
+
+impl AddAssign for Bn254Scalar {
+    fn add_assign(&mut self, other: Bn254Scalar) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Bn254Scalar {
+    fn sub_assign(&mut self, other: Bn254Scalar) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for Bn254Scalar {
+    fn mul_assign(&mut self, other: Bn254Scalar) {
+        *self = *self * other;
+    }
+}
+
+impl Sum for Bn254Scalar {
+    fn sum<I: Iterator<Item = Bn254Scalar>>(iter: I) -> Self {
+        iter.fold(Bn254Scalar::zero(), Add::add)
+    }
+}
+
+impl Product for Bn254Scalar {
+    fn product<I: Iterator<Item = Bn254Scalar>>(iter: I) -> Self {
+        iter.fold(Bn254Scalar::one(), Mul::mul)
+    }
+}
+
+impl CurveScalar for Bn254Scalar {
+    fn zero() -> Self {
+        Bn254Scalar(Fr::zero())
+    }
+
+    fn one() -> Self {
+        Bn254Scalar(Fr::one())
+    }
+
+    fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        Bn254Scalar::from_bytes_mod_order_wide(&bytes)
+    }
+
+    fn invert(&self) -> Self {
+        Bn254Scalar(self.0.inverse().unwrap_or_else(Fr::zero))
+    }
+
+    fn to_bytes(&self) -> [u8; 32] {
+        u256_to_bytes(self.0.into_u256())
+    }
+
+    fn from_canonical_bytes(bytes: [u8; 32]) -> Option<Self> {
+        let x = U256::from_slice(&bytes).ok()?;
+        Fr::new(x).map(Bn254Scalar)
+    }
+
+    fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self {
+        Bn254Scalar(Fr::interpret(bytes))
+    }
+}
+
+impl Default for Bn254Point {
+    fn default() -> Self {
+        Bn254Point(G1::zero())
+    }
+}
+
+impl Add for Bn254Point {
+    type Output = Bn254Point;
+
+    fn add(self, other: Bn254Point) -> Bn254Point {
+        Bn254Point(self.0 + other.0)
+    }
+}
+
+impl Sub for Bn254Point {
+    type Output = Bn254Point;
+
+    fn sub(self, other: Bn254Point) -> Bn254Point {
+        Bn254Point(self.0 - other.0)
+    }
+}
+
+impl Neg for Bn254Point {
+    type Output = Bn254Point;
+
+    fn neg(self) -> Bn254Point {
+        Bn254Point(-self.0)
+    }
+}
+
+impl Mul<Bn254Scalar> for Bn254Point {
+    type Output = Bn254Point;
+
+    fn mul(self, scalar: Bn254Scalar) -> Bn254Point {
+        Bn254Point(self.0 * scalar.0)
+    }
+}
+
+impl AddAssign for Bn254Point {
+    fn add_assign(&mut self, other: Bn254Point) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Bn254Point {
+    fn sub_assign(&mut self, other: Bn254Point) {
+        *self = *self - other;
+    }
+}
+
+impl Sum for Bn254Point {
+    fn sum<I: Iterator<Item = Bn254Point>>(iter: I) -> Self {
+        iter.fold(Bn254Point::default(), Add::add)
+    }
+}
+
+impl ConditionallySelectable for Bn254Point {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        // Select the projective coordinates byte by byte.
+        let select = |x: Fq, y: Fq| {
+            let (x, y) = (fq_to_bytes(x), fq_to_bytes(y));
+            let mut bytes = [0u8; 32];
+            for i in 0..32 {
+                bytes[i] = u8::conditional_select(&x[i], &y[i], choice);
+            }
+            Fq::from_slice(&bytes).expect("the bytes encode a field element")
+        };
+        Bn254Point(G1::new(
+            select(a.0.x(), b.0.x()),
+            select(a.0.y(), b.0.y()),
+            select(a.0.z(), b.0.z()),
+        ))
+    }
+}
+
+impl CurvePoint for Bn254Point {
+    type Scalar = Bn254Scalar;
+    type Compressed = Bn254Encoding;
+    type Table = Bn254Point;
+
+    fn identity() -> Self {
+        Bn254Point(G1::zero())
+    }
+
+    fn basepoint() -> Self {
+        Bn254Point(G1::one())
+    }
+
+    fn compress(&self) -> Bn254Encoding {
+        let mut bytes = [0u8; 64];
+        if let Some(affine) = AffineG1::from_jacobian(self.0) {
+            bytes[..32].copy_from_slice(&fq_to_bytes(affine.x()));
+            bytes[32..].copy_from_slice(&fq_to_bytes(affine.y()));
+        }
+        Bn254Encoding(bytes)
+    }
+
+    fn from_uniform_bytes(bytes: &[u8; 64]) -> Self {
+        let mut x = Fq::interpret(bytes);
+        loop {
+            if let Some(mut y) = (x * x * x + G1::b()).sqrt() {
+                if y.into_u256().get_bit(0) == Some(true) {
+                    y = -y;
+                }
+                let point = AffineG1::new(x, y).expect("the point is on the curve");
+                return Bn254Point(point.into());
+            }
+            x = x + Fq::one();
+        }
+    }
+
+    fn multiscalar_mul<I, J>(scalars: I, points: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Bn254Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self>,
+    {
+        scalars
+            .into_iter()
+            .zip(points)
+            .map(|(s, P)| *P.borrow() * *s.borrow())
+            .sum()
+    }
+
+    fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Bn254Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self>,
+    {
+        Bn254Point::multiscalar_mul(scalars, points)
+    }
+
+    fn create_table(&self) -> Bn254Point {
+        *self
+    }
+
+    fn table_mul(table: &Bn254Point, scalar: &Bn254Scalar) -> Self {
+        *table * *scalar
+    }
+
+    fn is_identity(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl CompressedPoint for Bn254Encoding {
+    type Point = Bn254Point;
+
+    const SIZE: usize = 64;
+
+    fn identity() -> Self {
+        Bn254Encoding([0u8; 64])
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 64 {
+            return None;
+        }
+        let mut encoding = [0u8; 64];
+        encoding.copy_from_slice(bytes);
+        Some(Bn254Encoding(encoding))
+    }
+
+    fn decompress(&self) -> Option<Bn254Point> {
+        if self.is_identity() {
+            return Some(Bn254Point::identity());
+        }
+        // Fq::from_slice rejects coordinates that are not reduced.
+        let x = Fq::from_slice(&self.0[..32]).ok()?;
+        let y = Fq::from_slice(&self.0[32..]).ok()?;
+        AffineG1::new(x, y).ok().map(|P| Bn254Point(P.into()))
+    }
+}
+
+impl Serialize for Bn254Scalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_array(&self.to_bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bn254Scalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_array(deserializer, 32, "a canonical BN254 scalar", |bytes| {
+            let mut scalar = [0u8; 32];
+            scalar.copy_from_slice(bytes);
+            Bn254Scalar::from_canonical_bytes(scalar)
+        })
+    }
+}
+
+impl Serialize for Bn254Encoding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_array(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bn254Encoding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_array(
+            deserializer,
+            64,
+            "a BN254 point encoding",
+            Bn254Encoding::from_slice,
+        )
+    }
+}
+
+impl Serialize for Bn254Point {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.compress().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bn254Point {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_array(deserializer, 64, "a valid BN254 point encoding", |bytes| {
+            Bn254Encoding::from_slice(bytes)?.decompress()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalar_encoding_is_big_endian() {
+        let mut bytes = [0u8; 32];
+        bytes[31] = 5;
+        bytes[30] = 1;
+        assert_eq!(Bn254Scalar::from(261u64).to_bytes(), bytes);
+        assert_eq!(
+            Bn254Scalar::from_canonical_bytes(bytes),
+            Some(261u64.into())
+        );
+
+        // The group order r is not canonical, and r - 1 is.
+        let r = hex::decode("30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001")
+            .unwrap();
+        let mut r_bytes = [0u8; 32];
+        r_bytes.copy_from_slice(&r);
+        assert_eq!(Bn254Scalar::from_canonical_bytes(r_bytes), None);
+        r_bytes[31] = 0;
+        assert_eq!(
+            Bn254Scalar::from_canonical_bytes(r_bytes),
+            Some(-Bn254Scalar::one())
+        );
+
+        // Wide reduction treats the 64 bytes as one big-endian integer.
+        let mut wide = [0u8; 64];
+        wide[31] = 1;
+        wide[63] = 7;
+        let expected = (0..8).fold(Bn254Scalar::one(), |acc, _| {
+            acc * Bn254Scalar::from(1u64 << 32)
+        });
+        assert_eq!(
+            Bn254Scalar::from_bytes_mod_order_wide(&wide),
+            expected + Bn254Scalar::from(7u64)
+        );
+    }
+
+    #[test]
+    fn point_encoding_roundtrips() {
+        let B = Bn254Point::basepoint();
+        let mut expected = [0u8; 64];
+        expected[31] = 1;
+        expected[63] = 2;
+        assert_eq!(B.compress(), Bn254Encoding(expected));
+
+        let P = B * Bn254Scalar::from(1037u64);
+        assert_eq!(P.compress().decompress(), Some(P));
+        assert_eq!(
+            Bn254Encoding::identity().decompress(),
+            Some(Bn254Point::identity())
+        );
+        assert_eq!(Bn254Point::identity().compress(), Bn254Encoding::identity());
+
+        // A point off the curve does not decompress.
+        expected[63] = 3;
+        assert_eq!(Bn254Encoding(expected).decompress(), None);
+    }
+
+    #[test]
+    fn points_from_uniform_bytes_are_on_the_curve() {
+        for i in 0..16u8 {
+            let P = Bn254Point::from_uniform_bytes(&[i; 64]);
+            assert!(!P.is_identity());
+            assert_eq!(P.compress().decompress(), Some(P));
+            assert_eq!(P.compress().0[63] & 1, 0);
+        }
+    }
+
+    #[test]
+    fn conditional_select_points() {
+        let B = Bn254Point::basepoint();
+        let P = B * Bn254Scalar::from(5u64);
+        assert_eq!(Bn254Point::conditional_select(&B, &P, Choice::from(0)), B);
+        assert_eq!(Bn254Point::conditional_select(&B, &P, Choice::from(1)), P);
+    }
+}
@@ -0,0 +1,222 @@
+//! The `curve` module defines the traits for the prime-order groups
+//! that the proofs are generic over.
+//!
+//! The proofs only use a group through the [`CurvePoint`] trait, its
+//! scalar field through the [`CurveScalar`] trait, and the encoding of
+//! its points through the [`CompressedPoint`] trait.  The `ristretto255`
+//! group of `curve25519-dalek` is the default implementation.  The
+//! `evm` feature adds the G1 group of the BN254 pairing, which has
+//! precompiled contracts on Ethereum-compatible chains, and the
+//! `secp256k1` feature adds the secp256k1 group of Bitcoin.
+
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::fmt::Debug;
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use rand_core::{CryptoRng, RngCore};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use subtle::ConditionallySelectable;
+
+mod ristretto;
+
+#[cfg(any(feature = "evm", feature = "secp256k1"))]
+mod serde_array;
+
+#[cfg(feature = "evm")]
+pub mod bn254;
+
+#[cfg(feature = "secp256k1")]
+pub mod secp256k1;
+
+/// An element of the scalar field of a prime-order group.
+///
+/// Scalars are encoded as 32 bytes in the byte order of the group's
+/// own encoding, which is little-endian for `ristretto255` and
+/// big-endian for BN254 and secp256k1.
+pub trait CurveScalar:
+    Copy
+    + Eq
+    + Debug
+    + Default
+    + Send
+    + Sync
+    + Serialize
+    + DeserializeOwned
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + Sum
+    + Product
+    + From<u64>
+    + 'static
+{
+    /// Returns the scalar \\(0\\).
+    fn zero() -> Self;
+
+    /// Returns the scalar \\(1\\).
+    fn one() -> Self;
+
+    /// Returns a uniformly random scalar.
+    fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self;
+
+    /// Returns the multiplicative inverse of the scalar, which must be
+    /// nonzero.
+    fn invert(&self) -> Self;
+
+    /// Inverts each of the nonzero `inputs` in place, and returns the
+    /// inverse of their product.
+    fn batch_invert(inputs: &mut [Self]) -> Self {
+        let mut acc = Self::one();
+        let mut partials = Vec::with_capacity(inputs.len());
+        for input in inputs.iter() {
+            partials.push(acc);
+            acc *= *input;
+        }
+        let product_inv = acc.invert();
+        let mut acc = product_inv;
+        for (input, partial) in inputs.iter_mut().zip(partials).rev() {
+            let input_inv = acc * partial;
+            acc *= *input;
+            *input = input_inv;
+        }
+        product_inv
+    }
+
+    /// Returns the canonical 32-byte encoding of the scalar.
+    fn to_bytes(&self) -> [u8; 32];
+
+    /// Decodes a scalar from its canonical encoding, or returns `None`
+    /// if `bytes` is not the canonical encoding of a scalar.
+    fn from_canonical_bytes(bytes: [u8; 32]) -> Option<Self>;
+
+    /// Reduces a 512-bit integer, encoded in the byte order of the
+    /// scalars, modulo the group order.
+    fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self;
+}
+
+/// An element of a prime-order group, written additively.
+pub trait CurvePoint:
+    Copy
+    + Eq
+    + Debug
+    + Default
+    + Send
+    + Sync
+    + Serialize
+    + DeserializeOwned
+    + ConditionallySelectable
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + Mul<<Self as CurvePoint>::Scalar, Output = Self>
+    + Sum
+    + 'static
+{
+    /// The scalar field of the group.
+    type Scalar: CurveScalar;
+
+    /// The encoding of the points of the group in proofs and transcripts.
+    type Compressed: CompressedPoint<Point = Self>;
+
+    /// Precomputed multiples of a point, for fast fixed-base
+    /// multiplication.
+    type Table: Clone + Send + Sync;
+
+    /// Returns the identity of the group.
+    fn identity() -> Self;
+
+    /// Returns the standard generator of the group.
+    fn basepoint() -> Self;
+
+    /// Encodes the point.
+    fn compress(&self) -> Self::Compressed;
+
+    /// Maps 64 uniformly random bytes to a point whose discrete log
+    /// relative to other points is unknown.
+    fn from_uniform_bytes(bytes: &[u8; 64]) -> Self;
+
+    /// Computes the multiscalar multiplication \\(\sum_i s_i P_i\\) in
+    /// constant time.
+    fn multiscalar_mul<I, J>(scalars: I, points: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Self::Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self>;
+
+    /// Computes the multiscalar multiplication \\(\sum_i s_i P_i\\) in
+    /// variable time, for public scalars.
+    fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Self::Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self>;
+
+    /// Computes the multiscalar multiplication \\(\sum_i s_i P_i\\) in
+    /// variable time, or returns `None` if any of the points is `None`.
+    fn optional_multiscalar_mul<I, J>(scalars: I, points: J) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Self::Scalar>,
+        J: IntoIterator<Item = Option<Self>>,
+    {
+        let points = points.into_iter().collect::<Option<Vec<_>>>()?;
+        Some(Self::vartime_multiscalar_mul(scalars, points))
+    }
+
+    /// Precomputes the table of multiples of the point.
+    fn create_table(&self) -> Self::Table;
+
+    /// Multiplies the point of the `table` by `scalar`.
+    fn table_mul(table: &Self::Table, scalar: &Self::Scalar) -> Self;
+
+    /// Returns whether the point is the identity.
+    fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+}
+
+/// The encoding of a point of a group, as it appears in proofs and
+/// transcripts.
+pub trait CompressedPoint:
+    Copy + Eq + Debug + Send + Sync + Serialize + DeserializeOwned + 'static
+{
+    /// The points that this type encodes.
+    type Point: CurvePoint<Compressed = Self>;
+
+    /// The size of the encoding in bytes.
+    const SIZE: usize;
+
+    /// Returns the encoding of the identity.
+    fn identity() -> Self;
+
+    /// Returns the bytes of the encoding.
+    fn as_bytes(&self) -> &[u8];
+
+    /// Reads an encoding from `bytes`, or returns `None` if `bytes` does
+    /// not have the size of an encoding.  The encoding is not checked to
+    /// be valid until it is decompressed.
+    fn from_slice(bytes: &[u8]) -> Option<Self>;
+
+    /// Decodes the point, or returns `None` if the encoding is invalid.
+    fn decompress(&self) -> Option<Self::Point>;
+
+    /// Returns whether this is the encoding of the identity.
+    fn is_identity(&self) -> bool {
+        *self == Self::identity()
+    }
+}
@@ -0,0 +1,130 @@
+//! Implementations of the curve traits for the `ristretto255` group.
+
+use core::borrow::Borrow;
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoBasepointTable, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, MultiscalarMul, VartimeMultiscalarMul};
+use rand_core::{CryptoRng, RngCore};
+
+use super::{CompressedPoint, CurvePoint, CurveScalar};
+
+impl CurveScalar for Scalar {
+    fn zero() -> Self {
+        Scalar::zero()
+    }
+
+    fn one() -> Self {
+        Scalar::one()
+    }
+
+    fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Scalar::random(rng)
+    }
+
+    fn invert(&self) -> Self {
+        Scalar::invert(self)
+    }
+
+    fn batch_invert(inputs: &mut [Self]) -> Self {
+        Scalar::batch_invert(inputs)
+    }
+
+    fn to_bytes(&self) -> [u8; 32] {
+        Scalar::to_bytes(self)
+    }
+
+    fn from_canonical_bytes(bytes: [u8; 32]) -> Option<Self> {
+        Scalar::from_canonical_bytes(bytes)
+    }
+
+    fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self {
+        Scalar::from_bytes_mod_order_wide(bytes)
+    }
+}
+
+impl CurvePoint for RistrettoPoint {
+    type Scalar = Scalar;
+    type Compressed = CompressedRistretto;
+    type Table = RistrettoBasepointTable;
+
+    fn identity() -> Self {
+        <RistrettoPoint as Identity>::identity()
+    }
+
+    fn basepoint() -> Self {
+        RISTRETTO_BASEPOINT_POINT
+    }
+
+    fn compress(&self) -> CompressedRistretto {
+        RistrettoPoint::compress(self)
+    }
+
+    fn from_uniform_bytes(bytes: &[u8; 64]) -> Self {
+        RistrettoPoint::from_uniform_bytes(bytes)
+    }
+
+    fn multiscalar_mul<I, J>(scalars: I, points: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self>,
+    {
+        <RistrettoPoint as MultiscalarMul>::multiscalar_mul(scalars, points)
+    }
+
+    fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self>,
+    {
+        <RistrettoPoint as VartimeMultiscalarMul>::vartime_multiscalar_mul(scalars, points)
+    }
+
+    fn optional_multiscalar_mul<I, J>(scalars: I, points: J) -> Option<Self>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Scalar>,
+        J: IntoIterator<Item = Option<Self>>,
+    {
+        <RistrettoPoint as VartimeMultiscalarMul>::optional_multiscalar_mul(scalars, points)
+    }
+
+    fn create_table(&self) -> RistrettoBasepointTable {
+        RistrettoBasepointTable::create(self)
+    }
+
+    fn table_mul(table: &RistrettoBasepointTable, scalar: &Scalar) -> Self {
+        table * scalar
+    }
+}
+
+impl CompressedPoint for CompressedRistretto {
+    type Point = RistrettoPoint;
+
+    const SIZE: usize = 32;
+
+    fn identity() -> Self {
+        <CompressedRistretto as Identity>::identity()
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        CompressedRistretto::as_bytes(self)
+    }
+
+    fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() == 32 {
+            Some(CompressedRistretto::from_slice(bytes))
+        } else {
+            None
+        }
+    }
+
+    fn decompress(&self) -> Option<RistrettoPoint> {
+        CompressedRistretto::decompress(self)
+    }
+}
@@ -0,0 +1,482 @@
+//! Implementations of the curve traits for the secp256k1 group used by
+//! Bitcoin and Ethereum signatures, on top of the `k256` crate.
+//!
+//! The encodings follow the SEC1 conventions:
+//!
+//! * scalars are 32-byte big-endian integers modulo the group order \\(n\\);
+//! * points are the 33-byte compressed encoding of a tag byte `0x02` or
+//!   `0x03` for the parity of \\(y\\) followed by the big-endian \\(x\\)
+//!   coordinate, with the identity encoded as 33 zero bytes.
+//!
+//! Points from uniform bytes are found by try-and-increment: the 64
+//! bytes are reduced modulo the field prime \\(p\\) as a big-endian
+//! integer \\(x\\), which is incremented until \\(x^3 + 7\\) is a square, and
+//! the square root \\(y\\) with an even canonical value is chosen.
+
+#![allow(non_snake_case)]
+
+use core::borrow::Borrow;
+use core::iter::{Product, Sum};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+use k256::elliptic_curve::bigint::{Encoding, U256, U512};
+use k256::elliptic_curve::group::prime::PrimeCurveAffine;
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::point::{AffineCoordinates, DecompressPoint};
+use k256::elliptic_curve::PrimeField;
+use k256::{AffinePoint, FieldBytes, ProjectivePoint, Scalar};
+use rand_core::{CryptoRng, RngCore};
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use subtle::{Choice, ConditionallySelectable};
+
+use super::serde_array::{deserialize_array, serialize_array};
+use super::{CompressedPoint, CurvePoint, CurveScalar};
+
+/// The prime \\(p = 2^{256} - 2^{32} - 977\\) of the base field.
+const FIELD_MODULUS: U256 =
+    U256::from_be_hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F");
+
+/// An element of the scalar field of secp256k1, the integers modulo the
+/// group order \\(n\\).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Secp256k1Scalar(Scalar);
+
+/// A point of the secp256k1 group.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Secp256k1Point(ProjectivePoint);
+
+/// The 33-byte SEC1 compressed encoding of a [`Secp256k1Point`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Secp256k1Encoding(pub [u8; 33]);
+
+impl Secp256k1Scalar {
+    /// Returns the scalar of the underlying `k256` crate.
+    pub fn to_k256(&self) -> Scalar {
+        self.0
+    }
+}
+
+impl Secp256k1Point {
+    /// Returns the point of the underlying `k256` crate.
+    pub fn to_k256(&self) -> ProjectivePoint {
+        self.0
+    }
+}
+
+impl From<u64> for Secp256k1Scalar {
+    fn from(x: u64) -> Self {
+        Secp256k1Scalar(Scalar::from(x))
+    }
+}
+
+impl Default for Secp256k1Scalar {
+    fn default() -> Self {
+        Secp256k1Scalar(Scalar::ZERO)
+    }
+}
+
+impl Add for Secp256k1Scalar {
+    type Output = Secp256k1Scalar;
+
+    fn add(self, other: Secp256k1Scalar) -> Secp256k1Scalar {
+        Secp256k1Scalar(self.0 + other.0)
+    }
+}
+
+impl Sub for Secp256k1Scalar {
+    type Output = Secp256k1Scalar;
+
+    fn sub(self, other: Secp256k1Scalar) -> Secp256k1Scalar {
+        Secp256k1Scalar(self.0 - other.0)
+    }
+}
+
+impl Mul for Secp256k1Scalar {
+    type Output = Secp256k1Scalar;
+
+    fn mul(self, other: Secp256k1Scalar) -> Secp256k1Scalar {
+        Secp256k1Scalar(self.0 * other.0)
+    }
+}
+
+impl Neg for Secp256k1Scalar {
+    type Output = Secp256k1Scalar;
+
+    fn neg(self) -> Secp256k1Scalar {
+        Secp256k1Scalar(-self.0)
+    }
+}
+
+impl AddAssign for Secp256k1Scalar {
+    fn add_assign(&mut self, other: Secp256k1Scalar) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Secp256k1Scalar {
+    fn sub_assign(&mut self, other: Secp256k1Scalar) {
+        *self = *self - other;
+    }
+}
+
+impl MulAssign for Secp256k1Scalar {
+    fn mul_assign(&mut self, other: Secp256k1Scalar) {
+        *self = *self * other;
+    }
+}
+
+impl Sum for Secp256k1Scalar {
+    fn sum<I: Iterator<Item = Secp256k1Scalar>>(iter: I) -> Self {
+        iter.fold(Secp256k1Scalar::zero(), Add::add)
+    }
+}
+
+impl Product for Secp256k1Scalar {
+    fn product<I: Iterator<Item = Secp256k1Scalar>>(iter: I) -> Self {
+        iter.fold(Secp256k1Scalar::one(), Mul::mul)
+    }
+}
+
+impl CurveScalar for Secp256k1Scalar {
+    fn zero() -> Self {
+        Secp256k1Scalar(Scalar::ZERO)
+    }
+
+    fn one() -> Self {
+        Secp256k1Scalar(Scalar::ONE)
+    }
+
+    fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        Secp256k1Scalar::from_bytes_mod_order_wide(&bytes)
+    }
+
+    fn invert(&self) -> Self {
+        Secp256k1Scalar(Option::from(self.0.invert()).unwrap_or(Scalar::ZERO))
+    }
+
+    fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_bytes().into()
+    }
+
+    fn from_canonical_bytes(bytes: [u8; 32]) -> Option<Self> {
+        Option::from(Scalar::from_repr(bytes.into())).map(Secp256k1Scalar)
+    }
+
+    fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self {
+        Secp256k1Scalar(<Scalar as Reduce<U512>>::reduce(U512::from_be_slice(bytes)))
+    }
+}
+
+impl Default for Secp256k1Point {
+    fn default() -> Self {
+        Secp256k1Point(ProjectivePoint::IDENTITY)
+    }
+}
+
+impl Add for Secp256k1Point {
+    type Output = Secp256k1Point;
+
+    fn add(self, other: Secp256k1Point) -> Secp256k1Point {
+        Secp256k1Point(self.0 + other.0)
+    }
+}
+
+impl Sub for Secp256k1Point {
+    type Output = Secp256k1Point;
+
+    fn sub(self, other: Secp256k1Point) -> Secp256k1Point {
+        Secp256k1Point(self.0 - other.0)
+    }
+}
+
+impl Neg for Secp256k1Point {
+    type Output = Secp256k1Point;
+
+    fn neg(self) -> Secp256k1Point {
+        Secp256k1Point(-self.0)
+    }
+}
+
+impl Mul<Secp256k1Scalar> for Secp256k1Point {
+    type Output = Secp256k1Point;
+
+    fn mul(self, scalar: Secp256k1Scalar) -> Secp256k1Point {
+        Secp256k1Point(self.0 * scalar.0)
+    }
+}
+
+impl AddAssign for Secp256k1Point {
+    fn add_assign(&mut self, other: Secp256k1Point) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Secp256k1Point {
+    fn sub_assign(&mut self, other: Secp256k1Point) {
+        *self = *self - other;
+    }
+}
+
+impl Sum for Secp256k1Point {
+    fn sum<I: Iterator<Item = Secp256k1Point>>(iter: I) -> Self {
+        iter.fold(Secp256k1Point::default(), Add::add)
+    }
+}
+
+impl ConditionallySelectable for Secp256k1Point {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Secp256k1Point(ProjectivePoint::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl CurvePoint for Secp256k1Point {
+    type Scalar = Secp256k1Scalar;
+    type Compressed = Secp256k1Encoding;
+    type Table = Secp256k1Point;
+
+    fn identity() -> Self {
+        Secp256k1Point(ProjectivePoint::IDENTITY)
+    }
+
+    fn basepoint() -> Self {
+        Secp256k1Point(ProjectivePoint::GENERATOR)
+    }
+
+    fn compress(&self) -> Secp256k1Encoding {
+        let mut bytes = [0u8; 33];
+        let affine = self.0.to_affine();
+        if !bool::from(affine.is_identity()) {
+            bytes[0] = 2 + affine.y_is_odd().unwrap_u8();
+            bytes[1..].copy_from_slice(&affine.x());
+        }
+        Secp256k1Encoding(bytes)
+    }
+
+    fn from_uniform_bytes(bytes: &[u8; 64]) -> Self {
+        let mut x: U256 = U512::from_be_slice(bytes)
+            .wrapping_rem(&FIELD_MODULUS.resize())
+            .resize();
+        loop {
+            let x_bytes = FieldBytes::from(x.to_be_bytes());
+            let point = AffinePoint::decompress(&x_bytes, Choice::from(0));
+            if let Some(point) = Option::<AffinePoint>::from(point) {
+                return Secp256k1Point(point.into());
+            }
+            x = x.add_mod(&U256::ONE, &FIELD_MODULUS);
+        }
+    }
+
+    fn multiscalar_mul<I, J>(scalars: I, points: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Secp256k1Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self>,
+    {
+        scalars
+            .into_iter()
+            .zip(points)
+            .map(|(s, P)| *P.borrow() * *s.borrow())
+            .sum()
+    }
+
+    fn vartime_multiscalar_mul<I, J>(scalars: I, points: J) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Secp256k1Scalar>,
+        J: IntoIterator,
+        J::Item: Borrow<Self>,
+    {
+        Secp256k1Point::multiscalar_mul(scalars, points)
+    }
+
+    fn create_table(&self) -> Secp256k1Point {
+        *self
+    }
+
+    fn table_mul(table: &Secp256k1Point, scalar: &Secp256k1Scalar) -> Self {
+        *table * *scalar
+    }
+}
+
+impl CompressedPoint for Secp256k1Encoding {
+    type Point = Secp256k1Point;
+
+    const SIZE: usize = 33;
+
+    fn identity() -> Self {
+        Secp256k1Encoding([0u8; 33])
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn from_slice(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() != 33 {
+            return None;
+        }
+        let mut encoding = [0u8; 33];
+        encoding.copy_from_slice(bytes);
+        Some(Secp256k1Encoding(encoding))
+    }
+
+    fn decompress(&self) -> Option<Secp256k1Point> {
+        if self.is_identity() {
+            return Some(Secp256k1Point::identity());
+        }
+        let y_is_odd = match self.0[0] {
+            2 => Choice::from(0),
+            3 => Choice::from(1),
+            _ => return None,
+        };
+        // The decompression rejects x coordinates that are not reduced.
+        let mut x = [0u8; 32];
+        x.copy_from_slice(&self.0[1..]);
+        Option::<AffinePoint>::from(AffinePoint::decompress(&x.into(), y_is_odd))
+            .map(|P| Secp256k1Point(P.into()))
+    }
+}
+
+impl Serialize for Secp256k1Scalar {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_array(&self.to_bytes(), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secp256k1Scalar {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_array(deserializer, 32, "a canonical secp256k1 scalar", |bytes| {
+            let mut scalar = [0u8; 32];
+            scalar.copy_from_slice(bytes);
+            Secp256k1Scalar::from_canonical_bytes(scalar)
+        })
+    }
+}
+
+impl Serialize for Secp256k1Encoding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_array(&self.0, serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secp256k1Encoding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_array(
+            deserializer,
+            33,
+            "a secp256k1 point encoding",
+            Secp256k1Encoding::from_slice,
+        )
+    }
+}
+
+impl Serialize for Secp256k1Point {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.compress().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secp256k1Point {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserialize_array(
+            deserializer,
+            33,
+            "a valid secp256k1 point encoding",
+            |bytes| Secp256k1Encoding::from_slice(bytes)?.decompress(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalar_encoding_is_big_endian() {
+        let mut bytes = [0u8; 32];
+        bytes[31] = 5;
+        bytes[30] = 1;
+        assert_eq!(Secp256k1Scalar::from(261u64).to_bytes(), bytes);
+        assert_eq!(
+            Secp256k1Scalar::from_canonical_bytes(bytes),
+            Some(261u64.into())
+        );
+
+        // The group order n is not canonical, and n - 1 is.
+        let n = hex::decode("fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141")
+            .unwrap();
+        let mut n_bytes = [0u8; 32];
+        n_bytes.copy_from_slice(&n);
+        assert_eq!(Secp256k1Scalar::from_canonical_bytes(n_bytes), None);
+        n_bytes[31] = 0x40;
+        assert_eq!(
+            Secp256k1Scalar::from_canonical_bytes(n_bytes),
+            Some(-Secp256k1Scalar::one())
+        );
+
+        // Wide reduction treats the 64 bytes as one big-endian integer.
+        let mut wide = [0u8; 64];
+        wide[31] = 1;
+        wide[63] = 7;
+        let expected = (0..8).fold(Secp256k1Scalar::one(), |acc, _| {
+            acc * Secp256k1Scalar::from(1u64 << 32)
+        });
+        assert_eq!(
+            Secp256k1Scalar::from_bytes_mod_order_wide(&wide),
+            expected + Secp256k1Scalar::from(7u64)
+        );
+    }
+
+    #[test]
+    fn point_encoding_roundtrips() {
+        let B = Secp256k1Point::basepoint();
+        let G = hex::decode("0279be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798")
+            .unwrap();
+        assert_eq!(B.compress().as_bytes(), &G[..]);
+
+        let P = B * Secp256k1Scalar::from(1037u64);
+        assert_eq!(P.compress().decompress(), Some(P));
+        assert_eq!((-P).compress().decompress(), Some(-P));
+        assert_eq!(
+            Secp256k1Encoding::identity().decompress(),
+            Some(Secp256k1Point::identity())
+        );
+        assert_eq!(
+            Secp256k1Point::identity().compress(),
+            Secp256k1Encoding::identity()
+        );
+
+        // Encodings with an invalid tag do not decompress.
+        let mut encoding = B.compress();
+        encoding.0[0] = 4;
+        assert_eq!(encoding.decompress(), None);
+    }
+
+    #[test]
+    fn points_from_uniform_bytes_are_on_the_curve() {
+        for i in 0..16u8 {
+            let P = Secp256k1Point::from_uniform_bytes(&[i; 64]);
+            assert!(!P.is_identity());
+            assert_eq!(P.compress().decompress(), Some(P));
+            assert_eq!(P.compress().0[0], 2);
+        }
+    }
+
+    #[test]
+    fn conditional_select_points() {
+        let B = Secp256k1Point::basepoint();
+        let P = B * Secp256k1Scalar::from(5u64);
+        assert_eq!(
+            Secp256k1Point::conditional_select(&B, &P, Choice::from(0)),
+            B
+        );
+        assert_eq!(
+            Secp256k1Point::conditional_select(&B, &P, Choice::from(1)),
+            P
+        );
+    }
+}
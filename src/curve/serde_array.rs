@@ -0,0 +1,67 @@
+//! Serde helpers for the fixed-size byte encodings of the backends
+//! other than `ristretto255`, which serde does not derive for arrays of
+//! more than 32 bytes.
+
+use core::fmt;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::ser::{SerializeTuple, Serializer};
+
+/// Serializes `bytes` as a tuple, as serde does for arrays of up to 32 bytes.
+pub(super) fn serialize_array<S: Serializer>(
+    bytes: &[u8],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut tup = serializer.serialize_tuple(bytes.len())?;
+    for byte in bytes.iter() {
+        tup.serialize_element(byte)?;
+    }
+    tup.end()
+}
+
+/// Deserializes a tuple of `len` bytes serialized by `serialize_array`,
+/// and decodes them with `decode`.
+pub(super) fn deserialize_array<'de, D, T, F>(
+    deserializer: D,
+    len: usize,
+    expecting: &'static str,
+    decode: F,
+) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    F: FnOnce(&[u8]) -> Option<T>,
+{
+    struct ArrayVisitor<F> {
+        len: usize,
+        expecting: &'static str,
+        decode: F,
+    }
+
+    impl<'de, T, F: FnOnce(&[u8]) -> Option<T>> Visitor<'de> for ArrayVisitor<F> {
+        type Value = T;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str(self.expecting)
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<T, A::Error> {
+            let mut bytes = [0u8; 64];
+            for (i, byte) in bytes[..self.len].iter_mut().enumerate() {
+                *byte = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self.expecting))?;
+            }
+            let expecting = self.expecting;
+            (self.decode)(&bytes[..self.len]).ok_or_else(|| de::Error::custom(expecting))
+        }
+    }
+
+    deserializer.deserialize_tuple(
+        len,
+        ArrayVisitor {
+            len,
+            expecting,
+            decode,
+        },
+    )
+}
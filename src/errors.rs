@@ -1,6 +1,8 @@
 //! Errors related to proving and verifying proofs.
 
 extern crate alloc;
+#[cfg(feature = "yoloproofs")]
+use alloc::string::String;
 use alloc::vec::Vec;
 
 #[cfg(feature = "std")]
@@ -0,0 +1,443 @@
+//! Generation of the Solidity source of verifier contracts.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use super::{RangeProofVerifier, TestVector};
+use crate::curve::{CompressedPoint, CurvePoint};
+
+/// The source of a verifier contract, whose `__PARAMETER__`s are
+/// replaced by [`RangeProofVerifier::contract`].
+///
+/// The contract follows [`RangeProofVerifier::verify`]: the challenges
+/// are kept in the memory array `c = [y, z, x, w, a, b, u_0, ...]`, and
+/// the sums of points are accumulated with the `ecMul` (0x07) and `ecAdd`
+/// (0x06) precompiles, whose failure on points that are not on the curve
+/// rejects the proof.  Inverses use the `modexp` (0x05) precompile.
+const CONTRACT: &str = r#"// SPDX-License-Identifier: MIT
+// Generated by bulletproofs::evm::RangeProofVerifier::contract.  Do not edit.
+pragma solidity ^0.8.5;
+
+/// @notice Verifies Bulletproofs range proofs of __M__ values of __N__ bits over
+/// BN254, created with a Keccak-256 transcript labeled "__LABEL__".
+contract __NAME__ {
+    /// The order of the BN254 G1 group.
+    uint256 internal constant R = 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001;
+    /// 2^256 mod R, to reduce 64-byte challenges.
+    uint256 internal constant R_256 = 0x0e0a77c19a07df2f666ea36f7879462e36fc76959f60cd29ac96341c4ffffffb;
+
+    uint256 internal constant N = __N__;
+    uint256 internal constant M = __M__;
+    uint256 internal constant NM = __NM__;
+    uint256 internal constant LG_NM = __LG_NM__;
+    uint256 internal constant HEADER_SIZE = 352;
+    uint256 internal constant PROOF_SIZE = __PROOF_SIZE__;
+    /// 2^N - 1.
+    uint256 internal constant SUM_2 = __SUM_2__;
+
+    /// The transcript state after its creation and the range proof domain separator.
+    bytes32 internal constant TRANSCRIPT_INIT = __TRANSCRIPT_INIT__;
+    /// NM as 8 little-endian bytes, for the inner-product domain separator.
+    bytes internal constant NM_LE64 = hex"__NM_LE64__";
+
+    uint256 internal constant B_X = __B_X__;
+    uint256 internal constant B_Y = __B_Y__;
+    uint256 internal constant B_BLINDING_X = __B_BLINDING_X__;
+    uint256 internal constant B_BLINDING_Y = __B_BLINDING_Y__;
+    /// The generators G_0, ..., G_{NM-1}, H_0, ..., H_{NM-1} as 64-byte x || y.
+    bytes internal constant GENS = hex"__GENS__";
+
+    /// @notice Returns whether `proof` is a valid range proof for the
+    /// `commitments`, which are M points encoded as 64-byte x || y.
+    function verify(bytes calldata proof, bytes calldata commitments) external view returns (bool) {
+        if (proof.length != PROOF_SIZE || commitments.length != 64 * M) {
+            return false;
+        }
+        (bool ok, uint256[] memory c) = challenges(proof, commitments);
+        if (!ok) {
+            return false;
+        }
+        return checkT(proof, commitments, c) && checkIpp(proof, c);
+    }
+
+    function challenges(bytes calldata proof, bytes calldata commitments)
+        internal
+        pure
+        returns (bool, uint256[] memory c)
+    {
+        c = new uint256[](6 + LG_NM);
+        bytes32 s = TRANSCRIPT_INIT;
+        for (uint256 j = 0; j < M; j++) {
+            s = appendMessage(s, "V", commitments[64 * j:64 * j + 64]);
+        }
+
+        if (isIdentity(proof, 0) || isIdentity(proof, 64)) {
+            return (false, c);
+        }
+        s = appendMessage(s, "A", proof[0:64]);
+        s = appendMessage(s, "S", proof[64:128]);
+        (s, c[0]) = challengeScalar(s, "y");
+        (s, c[1]) = challengeScalar(s, "z");
+
+        if (isIdentity(proof, 128) || isIdentity(proof, 192)) {
+            return (false, c);
+        }
+        s = appendMessage(s, "T_1", proof[128:192]);
+        s = appendMessage(s, "T_2", proof[192:256]);
+        (s, c[2]) = challengeScalar(s, "x");
+
+        if (word(proof, 256) >= R || word(proof, 288) >= R || word(proof, 320) >= R) {
+            return (false, c);
+        }
+        s = appendMessage(s, "t_x", proof[256:288]);
+        s = appendMessage(s, "t_x_blinding", proof[288:320]);
+        s = appendMessage(s, "e_blinding", proof[320:352]);
+        (s, c[3]) = challengeScalar(s, "w");
+
+        s = appendMessage(s, "dom-sep", "ipp v1");
+        s = appendMessage(s, "n", NM_LE64);
+        for (uint256 k = 0; k < LG_NM; k++) {
+            uint256 pos = HEADER_SIZE + 128 * k;
+            if (isIdentity(proof, pos) || isIdentity(proof, pos + 64)) {
+                return (false, c);
+            }
+            s = appendMessage(s, "L", proof[pos:pos + 64]);
+            s = appendMessage(s, "R", proof[pos + 64:pos + 128]);
+            (s, c[6 + k]) = challengeScalar(s, "u");
+        }
+
+        c[4] = word(proof, HEADER_SIZE + 128 * LG_NM);
+        c[5] = word(proof, HEADER_SIZE + 128 * LG_NM + 32);
+        if (c[4] >= R || c[5] >= R) {
+            return (false, c);
+        }
+        return (true, c);
+    }
+
+    /// Checks that x T_1 + x^2 T_2 - t_x_blinding B_blinding + (delta(y, z) - t_x) B
+    /// + sum_j z^(2+j) V_j = 0.
+    function checkT(bytes calldata proof, bytes calldata commitments, uint256[] memory c)
+        internal
+        view
+        returns (bool)
+    {
+        uint256[2] memory acc;
+        uint256 x = c[2];
+        if (!mulAdd(acc, word(proof, 128), word(proof, 160), x)) {
+            return false;
+        }
+        if (!mulAdd(acc, word(proof, 192), word(proof, 224), mulmod(x, x, R))) {
+            return false;
+        }
+        if (!mulAdd(acc, B_BLINDING_X, B_BLINDING_Y, R - word(proof, 288))) {
+            return false;
+        }
+        if (!mulAdd(acc, B_X, B_Y, addmod(delta(c[0], c[1]), R - word(proof, 256), R))) {
+            return false;
+        }
+        uint256 zExp = mulmod(c[1], c[1], R);
+        for (uint256 j = 0; j < M; j++) {
+            if (!mulAdd(acc, word(commitments, 64 * j), word(commitments, 64 * j + 32), zExp)) {
+                return false;
+            }
+            zExp = mulmod(zExp, c[1], R);
+        }
+        return acc[0] == 0 && acc[1] == 0;
+    }
+
+    /// Returns (z - z^2) sum_{i<NM} y^i - z^3 (2^N - 1) sum_{j<M} z^j.
+    function delta(uint256 y, uint256 z) internal pure returns (uint256) {
+        uint256 sumY = 0;
+        uint256 yExp = 1;
+        for (uint256 i = 0; i < NM; i++) {
+            sumY = addmod(sumY, yExp, R);
+            yExp = mulmod(yExp, y, R);
+        }
+        uint256 sumZ = 0;
+        uint256 zExp = 1;
+        for (uint256 j = 0; j < M; j++) {
+            sumZ = addmod(sumZ, zExp, R);
+            zExp = mulmod(zExp, z, R);
+        }
+        uint256 zz = mulmod(z, z, R);
+        uint256 minuend = mulmod(addmod(z, R - zz, R), sumY, R);
+        uint256 subtrahend = mulmod(mulmod(mulmod(zz, z, R), SUM_2, R), sumZ, R);
+        return addmod(minuend, R - subtrahend, R);
+    }
+
+    /// Checks that A + x S + sum_k (u_k^2 L_k + u_k^-2 R_k) - e_blinding B_blinding
+    /// + w (t_x - a b) B + sum_i (g_i G_i + h_i H_i) = 0.
+    function checkIpp(bytes calldata proof, uint256[] memory c) internal view returns (bool) {
+        uint256[2] memory acc;
+        if (!proofTerms(acc, proof, c)) {
+            return false;
+        }
+        if (!generatorTerms(acc, c)) {
+            return false;
+        }
+        return acc[0] == 0 && acc[1] == 0;
+    }
+
+    function proofTerms(uint256[2] memory acc, bytes calldata proof, uint256[] memory c)
+        internal
+        view
+        returns (bool)
+    {
+        if (!mulAdd(acc, word(proof, 0), word(proof, 32), 1)) {
+            return false;
+        }
+        if (!mulAdd(acc, word(proof, 64), word(proof, 96), c[2])) {
+            return false;
+        }
+        for (uint256 k = 0; k < LG_NM; k++) {
+            uint256 pos = HEADER_SIZE + 128 * k;
+            uint256 u = c[6 + k];
+            uint256 uInv = inverse(u);
+            if (!mulAdd(acc, word(proof, pos), word(proof, pos + 32), mulmod(u, u, R))) {
+                return false;
+            }
+            if (!mulAdd(acc, word(proof, pos + 64), word(proof, pos + 96), mulmod(uInv, uInv, R))) {
+                return false;
+            }
+        }
+        if (!mulAdd(acc, B_BLINDING_X, B_BLINDING_Y, R - word(proof, 320))) {
+            return false;
+        }
+        uint256 ab = mulmod(c[4], c[5], R);
+        return mulAdd(acc, B_X, B_Y, mulmod(c[3], addmod(word(proof, 256), R - ab, R), R));
+    }
+
+    /// Adds sum_i (g_i G_i + h_i H_i) to `acc`, where g_i = -z - a s_i and
+    /// h_i = z + y^-i (z^(2+j) 2^(i mod N) - b s_(NM-1-i)) for the party j = i / N.
+    function generatorTerms(uint256[2] memory acc, uint256[] memory c) internal view returns (bool) {
+        uint256[] memory s = foldingProducts(c);
+        bytes memory gens = GENS;
+        uint256 yInv = inverse(c[0]);
+        uint256 yInvExp = 1;
+        uint256 zExp = mulmod(c[1], c[1], R);
+        uint256 twoExp = 1;
+        for (uint256 i = 0; i < NM; i++) {
+            uint256 g = addmod(R - c[1], R - mulmod(c[4], s[i], R), R);
+            if (!mulAdd(acc, memWord(gens, 64 * i), memWord(gens, 64 * i + 32), g)) {
+                return false;
+            }
+            uint256 h = addmod(mulmod(zExp, twoExp, R), R - mulmod(c[5], s[NM - 1 - i], R), R);
+            h = addmod(c[1], mulmod(yInvExp, h, R), R);
+            if (!mulAdd(acc, memWord(gens, 64 * (NM + i)), memWord(gens, 64 * (NM + i) + 32), h)) {
+                return false;
+            }
+
+            yInvExp = mulmod(yInvExp, yInv, R);
+            twoExp = addmod(twoExp, twoExp, R);
+            if ((i + 1) % N == 0) {
+                twoExp = 1;
+                zExp = mulmod(zExp, c[1], R);
+            }
+        }
+        return true;
+    }
+
+    /// Returns s_0 = prod_k u_k^-1 and s_i = s_(i - 2^l) u_(LG_NM - 1 - l)^2
+    /// for 2^l <= i < 2^(l+1).
+    function foldingProducts(uint256[] memory c) internal view returns (uint256[] memory s) {
+        s = new uint256[](NM);
+        uint256[] memory uSq = new uint256[](LG_NM);
+        uint256 product = 1;
+        for (uint256 k = 0; k < LG_NM; k++) {
+            product = mulmod(product, c[6 + k], R);
+            uSq[k] = mulmod(c[6 + k], c[6 + k], R);
+        }
+        s[0] = inverse(product);
+        uint256 l = 0;
+        for (uint256 i = 1; i < NM; i++) {
+            if (i == 2 << l) {
+                l++;
+            }
+            s[i] = mulmod(s[i - (1 << l)], uSq[LG_NM - 1 - l], R);
+        }
+    }
+
+    /// Sets s = H(0x00 || s || len(label) || label || len(message) || message).
+    function appendMessage(bytes32 s, bytes memory label, bytes memory message) internal pure returns (bytes32) {
+        return keccak256(abi.encodePacked(bytes1(0x00), s, le32(label.length), label, le32(message.length), message));
+    }
+
+    /// Returns the new state and the challenge, 64 challenge bytes read as a
+    /// big-endian integer modulo R.
+    function challengeScalar(bytes32 s, bytes memory label) internal pure returns (bytes32, uint256) {
+        s = keccak256(abi.encodePacked(bytes1(0x01), s, le32(label.length), label, le32(64)));
+        uint256 hi = uint256(keccak256(abi.encodePacked(bytes1(0x02), s, le32(0))));
+        uint256 lo = uint256(keccak256(abi.encodePacked(bytes1(0x02), s, le32(1))));
+        return (s, addmod(mulmod(hi, R_256, R), lo, R));
+    }
+
+    function le32(uint256 x) internal pure returns (bytes4) {
+        return bytes4(uint32(((x & 0xff) << 24) | ((x & 0xff00) << 8) | ((x >> 8) & 0xff00) | ((x >> 24) & 0xff)));
+    }
+
+    function word(bytes calldata data, uint256 pos) internal pure returns (uint256) {
+        return uint256(bytes32(data[pos:pos + 32]));
+    }
+
+    function memWord(bytes memory data, uint256 pos) internal pure returns (uint256 result) {
+        assembly {
+            result := mload(add(add(data, 0x20), pos))
+        }
+    }
+
+    function isIdentity(bytes calldata data, uint256 pos) internal pure returns (bool) {
+        return word(data, pos) == 0 && word(data, pos + 32) == 0;
+    }
+
+    /// Adds k (x, y) to `acc`, returning false if a precompile fails.
+    function mulAdd(uint256[2] memory acc, uint256 x, uint256 y, uint256 k) internal view returns (bool ok) {
+        uint256[4] memory input;
+        input[0] = x;
+        input[1] = y;
+        input[2] = k;
+        assembly {
+            ok := staticcall(gas(), 0x07, input, 0x60, add(input, 0x40), 0x40)
+        }
+        if (!ok) {
+            return false;
+        }
+        input[0] = acc[0];
+        input[1] = acc[1];
+        assembly {
+            ok := staticcall(gas(), 0x06, input, 0x80, acc, 0x40)
+        }
+    }
+
+    /// Returns a^(R-2) mod R, which is the inverse of a nonzero a.
+    function inverse(uint256 a) internal view returns (uint256 result) {
+        uint256[6] memory input;
+        input[0] = 0x20;
+        input[1] = 0x20;
+        input[2] = 0x20;
+        input[3] = a;
+        input[4] = R - 2;
+        input[5] = R;
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), 0x05, input, 0xc0, input, 0x20)
+        }
+        require(ok, "modexp failed");
+        result = input[0];
+    }
+}
+"#;
+
+/// The source of a Foundry test of a verifier contract against test vectors.
+const TEST_CONTRACT: &str = r#"// SPDX-License-Identifier: MIT
+// Generated by bulletproofs::evm::RangeProofVerifier::test_contract.  Do not edit.
+pragma solidity ^0.8.5;
+
+import "./__NAME__.sol";
+
+contract __NAME__Test {
+    function testVectors() public {
+        __NAME__ verifier = new __NAME__();
+        require(address(verifier).code.length <= 24576, "EIP-170 code size limit");
+__CHECKS__    }
+}
+"#;
+
+impl<'a> RangeProofVerifier<'a> {
+    /// Returns the Solidity source of a contract `name` with a function
+    /// `verify(bytes proof, bytes commitments) returns (bool)`, which
+    /// checks proofs as [`RangeProofVerifier::verify`] does.
+    ///
+    /// The commitments are passed as the concatenation of their 64-byte
+    /// encodings.  The generators are embedded in the contract.
+    pub fn contract(&self, name: &str) -> String {
+        let nm = self.n * self.m;
+        let mut gens = Vec::with_capacity(2 * nm * 64);
+        for G_i in self.bp_gens.G(self.n, self.m) {
+            gens.extend_from_slice(G_i.compress().as_bytes());
+        }
+        for H_i in self.bp_gens.H(self.n, self.m) {
+            gens.extend_from_slice(H_i.compress().as_bytes());
+        }
+        let B = self.pc_gens.B.compress();
+        let B_blinding = self.pc_gens.B_blinding.compress();
+
+        let parameters: [(&str, String); 15] = [
+            ("__N__", self.n.to_string()),
+            ("__M__", self.m.to_string()),
+            ("__NM__", nm.to_string()),
+            ("__LG_NM__", self.lg_nm().to_string()),
+            ("__PROOF_SIZE__", self.proof_size().to_string()),
+            ("__SUM_2__", (u64::max_value() >> (64 - self.n)).to_string()),
+            ("__TRANSCRIPT_INIT__", word(&self.transcript().state())),
+            ("__NM_LE64__", hex(&(nm as u64).to_le_bytes())),
+            ("__B_X__", word(&B.0[..32])),
+            ("__B_Y__", word(&B.0[32..])),
+            ("__B_BLINDING_X__", word(&B_blinding.0[..32])),
+            ("__B_BLINDING_Y__", word(&B_blinding.0[32..])),
+            ("__GENS__", hex(&gens)),
+            // The name and the label last, as they are arbitrary strings.
+            ("__NAME__", name.into()),
+            ("__LABEL__", escape(self.label)),
+        ];
+        substitute(CONTRACT, &parameters)
+    }
+
+    /// Returns the Solidity source of a Foundry test, which checks that
+    /// the contract `name` generated by [`RangeProofVerifier::contract`]
+    /// fits within the code size limit of EIP-170, and accepts exactly the
+    /// valid `vectors`.
+    pub fn test_contract(&self, name: &str, vectors: &[TestVector]) -> String {
+        let mut checks = String::new();
+        for (i, vector) in vectors.iter().enumerate() {
+            let mut commitments = Vec::with_capacity(vector.commitments.len() * 64);
+            for V in vector.commitments.iter() {
+                commitments.extend_from_slice(&V.0);
+            }
+            let _ = writeln!(
+                checks,
+                "        require({}verifier.verify(hex\"{}\", hex\"{}\"), \"vector {}\");",
+                if vector.valid { "" } else { "!" },
+                hex(&vector.proof),
+                hex(&commitments),
+                i
+            );
+        }
+        substitute(
+            TEST_CONTRACT,
+            &[("__NAME__", name.into()), ("__CHECKS__", checks)],
+        )
+    }
+}
+
+fn substitute(template: &str, parameters: &[(&str, String)]) -> String {
+    let mut source = String::from(template);
+    for (key, value) in parameters.iter() {
+        source = source.replace(key, value);
+    }
+    source
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(2 * bytes.len());
+    for byte in bytes.iter() {
+        let _ = write!(s, "{:02x}", byte);
+    }
+    s
+}
+
+/// Formats 32 big-endian bytes as a Solidity hexadecimal literal.
+fn word(bytes: &[u8]) -> String {
+    let mut s = String::from("0x");
+    s.push_str(&hex(bytes));
+    s
+}
+
+/// Escapes a transcript label for the comment of the contract.
+fn escape(label: &[u8]) -> String {
+    let mut s = String::with_capacity(label.len());
+    for &byte in label.iter() {
+        s.extend(core::ascii::escape_default(byte).map(char::from));
+    }
+    s
+}
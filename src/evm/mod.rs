@@ -0,0 +1,616 @@
+//! Range proofs for verification on Ethereum-compatible chains.
+//!
+//! The EVM proof format is a [`RangeProof`] over the G1 group of BN254
+//! ([`Bn254Point`]), created and verified with a [`Keccak256Transcript`],
+//! since the EVM has precompiled contracts for the additions and scalar
+//! multiplications of that group and an opcode for Keccak-256.  The
+//! proofs use the generic protocol of the `range_proof` and
+//! `inner_product_proof` modules, and are serialized by
+//! [`RangeProof::to_bytes`], in which points are 64 bytes and scalars are
+//! 32 bytes, big-endian.
+//!
+//! A [`RangeProofVerifier`] fixes the generators, the transcript label,
+//! the bitsize \\(n\\) and the number of values \\(m\\) of the proofs.  It
+//! generates the source of a self-contained Solidity contract that
+//! verifies such proofs with the precompiles, and is itself a reference
+//! verifier in Rust, which follows the contract step by step.  Both check
+//! the two equations of the aggregated range proof separately, instead of
+//! combining them with a random scalar as [`RangeProof::verify_multiple`]
+//! does.
+//!
+//! ```
+//! use bulletproofs::evm::RangeProofVerifier;
+//! use bulletproofs::{BulletproofGens, Bn254Point, Keccak256Transcript, PedersenGens, RangeProof};
+//!
+//! let pc_gens = PedersenGens::<Bn254Point>::standard();
+//! let bp_gens = BulletproofGens::<Bn254Point>::generate(b"", 32, 1);
+//!
+//! let mut transcript = Keccak256Transcript::new(b"doctest example");
+//! let (proof, commitment) = RangeProof::prove_single(
+//!     &bp_gens,
+//!     &pc_gens,
+//!     &mut transcript,
+//!     1037578891,
+//!     &bulletproofs::CurveScalar::random(&mut rand::thread_rng()),
+//!     32,
+//! )
+//! .expect("A real program could handle errors");
+//!
+//! let verifier = RangeProofVerifier::new(&bp_gens, &pc_gens, b"doctest example", 32, 1)
+//!     .expect("The generators have enough capacity");
+//! assert!(verifier.verify(&proof.to_bytes(), &[commitment]).is_ok());
+//!
+//! // The Solidity source of a contract verifying the same proofs.
+//! let source = verifier.contract("RangeProofVerifier");
+//! assert!(source.contains("contract RangeProofVerifier"));
+//! ```
+//!
+//! [`RangeProofVerifier::test_contract`] generates a Foundry test that
+//! checks a contract against test vectors, whose results the tests of
+//! this crate check against the reference verifier.  The tests of this
+//! crate do not execute the contracts, but check that the encodings of
+//! points and scalars are those of the precompiles.  The contracts are
+//! only executed in CI, where the `evm_contracts` example writes a
+//! Foundry project with such tests, which CI runs with `forge test`:
+//!
+//! ```text
+//! cargo run --example evm_contracts --features evm -- target/evm
+//! cd target/evm && forge test
+//! ```
+
+#![allow(non_snake_case)]
+
+mod contract;
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use rand_core::{CryptoRng, RngCore};
+
+use crate::curve::bn254::{Bn254Encoding, Bn254Point, Bn254Scalar};
+use crate::curve::{CompressedPoint, CurvePoint, CurveScalar};
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, PedersenGens};
+use crate::range_proof::RangeProof;
+use crate::transcript::{Keccak256Transcript, ProofTranscript};
+use crate::util;
+
+/// The size of the points \\(A, S, T_1, T_2\\) and the scalars
+/// \\(t_x, \tilde{t}_x, \tilde{e}\\) at the start of a proof.
+const HEADER_SIZE: usize = 4 * 64 + 3 * 32;
+
+/// The largest \\(nm\\) of the verifiers.  The contracts embed their
+/// \\(2nm\\) generators of 64 bytes in their code, which must stay within
+/// the 24 KiB limit of EIP-170 for the contracts to be deployable: this
+/// bound keeps the generators within 16 KiB, and leaves the rest for the
+/// code of the contract.
+pub const MAX_NM: usize = 128;
+
+/// A verifier of \\(m\\) aggregated \\(n\\)-bit range proofs over BN254,
+/// for fixed generators and transcript label.
+#[derive(Copy, Clone)]
+pub struct RangeProofVerifier<'a> {
+    bp_gens: &'a BulletproofGens<Bn254Point>,
+    pc_gens: &'a PedersenGens<Bn254Point>,
+    label: &'static [u8],
+    n: usize,
+    m: usize,
+}
+
+/// A proof with its value commitments, and whether they should verify.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestVector {
+    /// The encoding of the proof.
+    pub proof: Vec<u8>,
+    /// The value commitments.
+    pub commitments: Vec<Bn254Encoding>,
+    /// Whether the proof is valid for the commitments.
+    pub valid: bool,
+}
+
+/// The challenges of a proof, and its scalars \\(a, b\\).
+struct Challenges {
+    y: Bn254Scalar,
+    z: Bn254Scalar,
+    x: Bn254Scalar,
+    w: Bn254Scalar,
+    a: Bn254Scalar,
+    b: Bn254Scalar,
+    u: Vec<Bn254Scalar>,
+}
+
+impl<'a> RangeProofVerifier<'a> {
+    /// Creates a verifier of proofs created with `bp_gens`, `pc_gens` and a
+    /// [`Keccak256Transcript`] with the given `label`, for `m` values of
+    /// `n` bits.
+    ///
+    /// Returns an error if `n` is not 8, 16, 32 or 64, if `m` is not a
+    /// power of two, if \\(nm\\) exceeds [`MAX_NM`], above which the
+    /// generated contract could not be deployed, or if the generators have
+    /// too small a capacity.
+    pub fn new(
+        bp_gens: &'a BulletproofGens<Bn254Point>,
+        pc_gens: &'a PedersenGens<Bn254Point>,
+        label: &'static [u8],
+        n: usize,
+        m: usize,
+    ) -> Result<Self, ProofError> {
+        if !(n == 8 || n == 16 || n == 32 || n == 64) {
+            return Err(ProofError::InvalidBitsize);
+        }
+        if !m.is_power_of_two() || n * m > MAX_NM {
+            return Err(ProofError::InvalidAggregation);
+        }
+        if bp_gens.gens_capacity < n || bp_gens.party_capacity < m {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+        Ok(RangeProofVerifier {
+            bp_gens,
+            pc_gens,
+            label,
+            n,
+            m,
+        })
+    }
+
+    /// Returns the size in bytes of the proofs.
+    pub fn proof_size(&self) -> usize {
+        HEADER_SIZE + self.lg_nm() * 2 * 64 + 2 * 32
+    }
+
+    fn lg_nm(&self) -> usize {
+        util::folding_rounds(self.n * self.m)
+    }
+
+    /// Returns the transcript after its creation and the domain
+    /// separator of the proofs, which only depend on the parameters.
+    fn transcript(&self) -> Keccak256Transcript {
+        let mut transcript = Keccak256Transcript::new(self.label);
+        transcript.append_message(b"dom-sep", b"rangeproof v1");
+        transcript.append_u64(b"n", self.n as u64);
+        transcript.append_u64(b"m", self.m as u64);
+        transcript
+    }
+
+    /// Verifies the encoding `proof` of a range proof for the
+    /// `commitments`, in the same steps as the generated contract.
+    pub fn verify(&self, proof: &[u8], commitments: &[Bn254Encoding]) -> Result<(), ProofError> {
+        if proof.len() != self.proof_size() {
+            return Err(ProofError::FormatError);
+        }
+        if commitments.len() != self.m {
+            return Err(ProofError::InvalidAggregation);
+        }
+
+        let c = self.challenges(proof, commitments)?;
+        self.check_t(proof, commitments, &c)?;
+        self.check_ipp(proof, &c)
+    }
+
+    /// Replays the transcript of the proof to compute its challenges.
+    fn challenges(
+        &self,
+        proof: &[u8],
+        commitments: &[Bn254Encoding],
+    ) -> Result<Challenges, ProofError> {
+        let mut transcript = self.transcript();
+        for V in commitments.iter() {
+            transcript.append_message(b"V", &V.0);
+        }
+
+        append_nonzero_point(&mut transcript, b"A", &proof[0..64])?;
+        append_nonzero_point(&mut transcript, b"S", &proof[64..128])?;
+        let y = challenge_scalar(&mut transcript, b"y");
+        let z = challenge_scalar(&mut transcript, b"z");
+
+        append_nonzero_point(&mut transcript, b"T_1", &proof[128..192])?;
+        append_nonzero_point(&mut transcript, b"T_2", &proof[192..256])?;
+        let x = challenge_scalar(&mut transcript, b"x");
+
+        read_scalar(proof, 256)?;
+        read_scalar(proof, 288)?;
+        read_scalar(proof, 320)?;
+        transcript.append_message(b"t_x", &proof[256..288]);
+        transcript.append_message(b"t_x_blinding", &proof[288..320]);
+        transcript.append_message(b"e_blinding", &proof[320..352]);
+        let w = challenge_scalar(&mut transcript, b"w");
+
+        transcript.append_message(b"dom-sep", b"ipp v1");
+        transcript.append_u64(b"n", (self.n * self.m) as u64);
+        let mut u = Vec::with_capacity(self.lg_nm());
+        for k in 0..self.lg_nm() {
+            let pos = HEADER_SIZE + 128 * k;
+            append_nonzero_point(&mut transcript, b"L", &proof[pos..pos + 64])?;
+            append_nonzero_point(&mut transcript, b"R", &proof[pos + 64..pos + 128])?;
+            u.push(challenge_scalar(&mut transcript, b"u"));
+        }
+
+        let pos = HEADER_SIZE + 128 * self.lg_nm();
+        let a = read_scalar(proof, pos)?;
+        let b = read_scalar(proof, pos + 32)?;
+
+        Ok(Challenges {
+            y,
+            z,
+            x,
+            w,
+            a,
+            b,
+            u,
+        })
+    }
+
+    /// Checks that
+    /// \\(x T_1 + x^2 T_2 - \tilde{t}_x \tilde{B} + (\delta(y,z) - t_x) B + \sum_j z^{2+j} V_j = 0\\).
+    fn check_t(
+        &self,
+        proof: &[u8],
+        commitments: &[Bn254Encoding],
+        c: &Challenges,
+    ) -> Result<(), ProofError> {
+        let t_x = read_scalar(proof, 256)?;
+        let t_x_blinding = read_scalar(proof, 288)?;
+
+        let mut acc = Bn254Point::identity();
+        acc += read_point(proof, 128)? * c.x;
+        acc += read_point(proof, 192)? * (c.x * c.x);
+        acc += self.pc_gens.B_blinding * -t_x_blinding;
+        acc += self.pc_gens.B * (self.delta(c.y, c.z) - t_x);
+        let mut z_exp = c.z * c.z;
+        for V in commitments.iter() {
+            acc += V.decompress().ok_or(ProofError::VerificationError)? * z_exp;
+            z_exp *= c.z;
+        }
+
+        if acc.is_identity() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Computes \\(\delta(y,z) = (z - z^2) \sum_{i<nm} y^i - z^3 (2^n - 1) \sum_{j<m} z^j\\).
+    fn delta(&self, y: Bn254Scalar, z: Bn254Scalar) -> Bn254Scalar {
+        let sum_y = util::sum_of_powers(&y, self.n * self.m);
+        let sum_z = util::sum_of_powers(&z, self.m);
+        let zz = z * z;
+        (z - zz) * sum_y - zz * z * self.sum_2() * sum_z
+    }
+
+    /// Returns \\(2^n - 1\\).
+    fn sum_2(&self) -> Bn254Scalar {
+        Bn254Scalar::from(u64::max_value() >> (64 - self.n))
+    }
+
+    /// Checks the inner-product argument, that
+    /// \\(A + x S + \sum_k (u_k^2 L_k + u_k^{-2} R_k) - \tilde{e} \tilde{B} + w (t_x - ab) B
+    /// + \sum_i (g_i G_i + h_i H_i) = 0\\).
+    fn check_ipp(&self, proof: &[u8], c: &Challenges) -> Result<(), ProofError> {
+        let t_x = read_scalar(proof, 256)?;
+        let e_blinding = read_scalar(proof, 320)?;
+
+        let mut acc = read_point(proof, 0)?;
+        acc += read_point(proof, 64)? * c.x;
+        for (k, u) in c.u.iter().enumerate() {
+            let u_inv = u.invert();
+            let pos = HEADER_SIZE + 128 * k;
+            acc += read_point(proof, pos)? * (*u * *u);
+            acc += read_point(proof, pos + 64)? * (u_inv * u_inv);
+        }
+        acc += self.pc_gens.B_blinding * -e_blinding;
+        acc += self.pc_gens.B * (c.w * (t_x - c.a * c.b));
+        acc += self.generator_terms(c);
+
+        if acc.is_identity() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Computes \\(\sum_i (g_i G_i + h_i H_i)\\), where
+    /// \\(g_i = -z - a s_i\\) and
+    /// \\(h_i = z + y^{-i} (z^{2+j} 2^{i \bmod n} - b s_{nm-1-i})\\)
+    /// for the party \\(j = \lfloor i/n \rfloor\\).
+    fn generator_terms(&self, c: &Challenges) -> Bn254Point {
+        let nm = self.n * self.m;
+        let s = self.folding_products(c);
+        let G: Vec<Bn254Point> = self.bp_gens.G(self.n, self.m).cloned().collect();
+        let H: Vec<Bn254Point> = self.bp_gens.H(self.n, self.m).cloned().collect();
+
+        let y_inv = c.y.invert();
+        let two = Bn254Scalar::from(2u64);
+        let mut acc = Bn254Point::identity();
+        let mut y_inv_exp = Bn254Scalar::one();
+        let mut z_exp = c.z * c.z;
+        let mut two_exp = Bn254Scalar::one();
+        for i in 0..nm {
+            let g = -c.z - c.a * s[i];
+            let h = c.z + y_inv_exp * (z_exp * two_exp - c.b * s[nm - 1 - i]);
+            acc += G[i] * g;
+            acc += H[i] * h;
+
+            y_inv_exp *= y_inv;
+            two_exp *= two;
+            if (i + 1) % self.n == 0 {
+                two_exp = Bn254Scalar::one();
+                z_exp *= c.z;
+            }
+        }
+        acc
+    }
+
+    /// Computes \\(s_0 = \prod_k u_k^{-1}\\) and
+    /// \\(s_i = s_{i - 2^l} u_{\lg(nm) - 1 - l}^2\\) for \\(2^l \le i < 2^{l+1}\\).
+    fn folding_products(&self, c: &Challenges) -> Vec<Bn254Scalar> {
+        let nm = self.n * self.m;
+        let lg_nm = self.lg_nm();
+        let u_sq: Vec<Bn254Scalar> = c.u.iter().map(|u| *u * *u).collect();
+
+        let mut s = Vec::with_capacity(nm);
+        s.push(c.u.iter().cloned().product::<Bn254Scalar>().invert());
+        let mut l = 0;
+        for i in 1..nm {
+            if i == 2 << l {
+                l += 1;
+            }
+            let s_i = s[i - (1 << l)] * u_sq[lg_nm - 1 - l];
+            s.push(s_i);
+        }
+        s
+    }
+
+    /// Creates test vectors for the parameters of the verifier: proofs of
+    /// random values, and invalid variants of them.
+    pub fn test_vectors<T: RngCore + CryptoRng>(&self, rng: &mut T) -> Vec<TestVector> {
+        let values: Vec<u64> = (0..self.m)
+            .map(|_| rng.next_u64() >> (64 - self.n))
+            .collect();
+        let blindings: Vec<Bn254Scalar> = (0..self.m).map(|_| Bn254Scalar::random(rng)).collect();
+
+        let mut transcript = Keccak256Transcript::new(self.label);
+        let (proof, commitments) = RangeProof::prove_multiple_with_rng(
+            self.bp_gens,
+            self.pc_gens,
+            &mut transcript,
+            &values,
+            &blindings,
+            self.n,
+            rng,
+        )
+        .expect("the parameters are checked by the constructor");
+        let proof = proof.to_bytes();
+
+        let len = proof.len();
+        let valid = TestVector {
+            proof,
+            commitments,
+            valid: true,
+        };
+        let mut vectors = vec![valid.clone()];
+        let mut invalid = |f: &dyn Fn(&mut TestVector)| {
+            let mut vector = valid.clone();
+            f(&mut vector);
+            vector.valid = false;
+            vectors.push(vector);
+        };
+
+        // A wrong value commitment.
+        invalid(&|v| {
+            v.commitments[0] = (v.commitments[0].decompress().unwrap() + self.pc_gens.B).compress()
+        });
+        // Altered points and scalars.
+        invalid(&|v| v.proof[63] ^= 1);
+        invalid(&|v| v.proof[192..256].copy_from_slice(&Bn254Encoding::identity().0));
+        invalid(&|v| v.proof[287] ^= 1);
+        invalid(&|v| v.proof[HEADER_SIZE + 63] ^= 1);
+        invalid(&|v| v.proof[len - 1] ^= 1);
+        // A scalar that is not reduced.
+        invalid(&|v| v.proof[len - 32..].copy_from_slice(&[0xff; 32]));
+        // A truncated proof.
+        invalid(&|v| v.proof.truncate(len - 32));
+        vectors
+    }
+}
+
+/// Appends the encoding of a point, which must not be the identity.
+fn append_nonzero_point(
+    transcript: &mut Keccak256Transcript,
+    label: &'static [u8],
+    encoding: &[u8],
+) -> Result<(), ProofError> {
+    if encoding.iter().all(|&byte| byte == 0) {
+        return Err(ProofError::VerificationError);
+    }
+    transcript.append_message(label, encoding);
+    Ok(())
+}
+
+/// Computes a challenge from 64 challenge bytes, read as a big-endian
+/// integer modulo the group order.
+fn challenge_scalar(transcript: &mut Keccak256Transcript, label: &'static [u8]) -> Bn254Scalar {
+    let mut bytes = [0u8; 64];
+    transcript.challenge_bytes(label, &mut bytes);
+    Bn254Scalar::from_bytes_mod_order_wide(&bytes)
+}
+
+fn read_scalar(proof: &[u8], pos: usize) -> Result<Bn254Scalar, ProofError> {
+    Bn254Scalar::from_canonical_bytes(util::read32(&proof[pos..])).ok_or(ProofError::FormatError)
+}
+
+fn read_point(proof: &[u8], pos: usize) -> Result<Bn254Point, ProofError> {
+    Bn254Encoding::from_slice(&proof[pos..pos + 64])
+        .and_then(|P| P.decompress())
+        .ok_or(ProofError::VerificationError)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand_chacha::ChaChaRng;
+    use rand_core::SeedableRng;
+
+    /// Checks that the reference verifier and `RangeProof::verify_multiple`
+    /// both accept exactly the valid test vectors.
+    fn check_vectors(n: usize, m: usize) {
+        let pc_gens = PedersenGens::<Bn254Point>::standard();
+        let bp_gens = BulletproofGens::<Bn254Point>::generate(b"", n, m);
+        let verifier = RangeProofVerifier::new(&bp_gens, &pc_gens, b"evm test", n, m).unwrap();
+
+        let mut rng = ChaChaRng::from_seed([7u8; 32]);
+        let vectors = verifier.test_vectors(&mut rng);
+        assert_eq!(vectors[0].proof.len(), verifier.proof_size());
+        for vector in vectors.iter() {
+            let reference = verifier.verify(&vector.proof, &vector.commitments);
+            let library = RangeProof::<Bn254Point>::decode(&vector.proof).and_then(|proof| {
+                proof.verify_multiple_with_rng(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut Keccak256Transcript::new(b"evm test"),
+                    &vector.commitments,
+                    n,
+                    &mut rng,
+                )
+            });
+            assert_eq!(reference.is_ok(), vector.valid);
+            assert_eq!(library.is_ok(), vector.valid);
+        }
+    }
+
+    #[test]
+    fn test_vectors_n_8_m_1() {
+        check_vectors(8, 1);
+    }
+
+    #[test]
+    fn test_vectors_n_16_m_2() {
+        check_vectors(16, 2);
+    }
+
+    #[test]
+    fn test_vectors_n_32_m_4() {
+        check_vectors(32, 4);
+    }
+
+    /// Evaluates the `ecMul` precompile on the 96 bytes of its input.
+    fn ec_mul(input: &[u8]) -> Vec<u8> {
+        let P = Bn254Encoding::from_slice(&input[..64])
+            .and_then(|P| P.decompress())
+            .unwrap();
+        let k = Bn254Scalar::from_canonical_bytes(util::read32(&input[64..])).unwrap();
+        (P * k).compress().0.to_vec()
+    }
+
+    /// Evaluates the `ecAdd` precompile on the 128 bytes of its input.
+    fn ec_add(input: &[u8]) -> Vec<u8> {
+        let P = Bn254Encoding::from_slice(&input[..64])
+            .and_then(|P| P.decompress())
+            .unwrap();
+        let Q = Bn254Encoding::from_slice(&input[64..])
+            .and_then(|Q| Q.decompress())
+            .unwrap();
+        (P + Q).compress().0.to_vec()
+    }
+
+    /// Checks that the contract can pass the encodings of points and
+    /// scalars to the precompiles as they are, with the test vectors of
+    /// EIP-196: 32-byte big-endian words, points as \((x, y)\), and the
+    /// identity as \((0, 0)\).
+    #[test]
+    fn precompile_encoding() {
+        let G = Bn254Point::basepoint().compress().0;
+        let two_G = hex::decode(
+            "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3\
+             15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4",
+        )
+        .unwrap();
+
+        let mut input = G.to_vec();
+        input.extend_from_slice(&Bn254Scalar::from(2u64).to_bytes());
+        assert_eq!(ec_mul(&input), two_G);
+
+        let mut input = G.to_vec();
+        input.extend_from_slice(&G);
+        assert_eq!(ec_add(&input), two_G);
+
+        let mut input = G.to_vec();
+        input.extend_from_slice(&[0u8; 32]);
+        assert_eq!(ec_mul(&input), vec![0u8; 64]);
+        assert_eq!(Bn254Encoding::identity().0.to_vec(), vec![0u8; 64]);
+    }
+
+    #[test]
+    fn rejects_other_labels() {
+        let pc_gens = PedersenGens::<Bn254Point>::standard();
+        let bp_gens = BulletproofGens::<Bn254Point>::generate(b"", 8, 1);
+        let verifier = RangeProofVerifier::new(&bp_gens, &pc_gens, b"evm test", 8, 1).unwrap();
+        let other = RangeProofVerifier::new(&bp_gens, &pc_gens, b"other", 8, 1).unwrap();
+
+        let vector = &verifier.test_vectors(&mut ChaChaRng::from_seed([1u8; 32]))[0];
+        assert!(verifier.verify(&vector.proof, &vector.commitments).is_ok());
+        assert!(other.verify(&vector.proof, &vector.commitments).is_err());
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        let pc_gens = PedersenGens::<Bn254Point>::standard();
+        let bp_gens = BulletproofGens::<Bn254Point>::generate(b"", 16, 2);
+        assert!(RangeProofVerifier::new(&bp_gens, &pc_gens, b"", 12, 1).is_err());
+        assert!(RangeProofVerifier::new(&bp_gens, &pc_gens, b"", 16, 3).is_err());
+        assert!(RangeProofVerifier::new(&bp_gens, &pc_gens, b"", 32, 1).is_err());
+        assert!(RangeProofVerifier::new(&bp_gens, &pc_gens, b"", 16, 4).is_err());
+
+        // There must be m commitments.
+        let verifier = RangeProofVerifier::new(&bp_gens, &pc_gens, b"", 16, 2).unwrap();
+        let vector = &verifier.test_vectors(&mut ChaChaRng::from_seed([3u8; 32]))[0];
+        assert_eq!(
+            verifier.verify(&vector.proof, &vector.commitments[..1]),
+            Err(ProofError::InvalidAggregation)
+        );
+
+        // The contracts of larger verifiers would exceed the code size limit.
+        let bp_gens = BulletproofGens::<Bn254Point>::generate(b"", 64, 4);
+        assert!(RangeProofVerifier::new(&bp_gens, &pc_gens, b"", 64, 2).is_ok());
+        assert_eq!(
+            RangeProofVerifier::new(&bp_gens, &pc_gens, b"", 64, 4).err(),
+            Some(ProofError::InvalidAggregation)
+        );
+    }
+
+    #[test]
+    fn contract_embeds_parameters() {
+        let pc_gens = PedersenGens::<Bn254Point>::standard();
+        let bp_gens = BulletproofGens::<Bn254Point>::generate(b"", 16, 2);
+        let verifier = RangeProofVerifier::new(&bp_gens, &pc_gens, b"evm test", 16, 2).unwrap();
+
+        let source = verifier.contract("Verifier16x2");
+        assert!(!source.contains("__"));
+        assert!(source.contains("contract Verifier16x2 {"));
+        assert!(source.contains("uint256 internal constant NM = 32;"));
+        assert!(source.contains("uint256 internal constant LG_NM = 5;"));
+        assert!(source.contains("uint256 internal constant SUM_2 = 65535;"));
+        assert!(source.contains("bytes internal constant NM_LE64 = hex\"2000000000000000\";"));
+        let proof_size = format!("PROOF_SIZE = {};", verifier.proof_size());
+        assert!(source.contains(&proof_size));
+        let init = format!(
+            "TRANSCRIPT_INIT = 0x{};",
+            hex::encode(verifier.transcript().state())
+        );
+        assert!(source.contains(&init));
+        // The generators are embedded in order, G then H.
+        let G_0 = hex::encode(bp_gens.G(16, 2).next().unwrap().compress().0);
+        let H_last = hex::encode(bp_gens.H(16, 2).last().unwrap().compress().0);
+        assert!(source.contains(&format!("GENS = hex\"{}", G_0)));
+        assert!(source.contains(&format!("{}\";", H_last)));
+
+        let vectors = verifier.test_vectors(&mut ChaChaRng::from_seed([2u8; 32]));
+        let test = verifier.test_contract("Verifier16x2", &vectors);
+        assert!(test.contains("import \"./Verifier16x2.sol\";"));
+        assert_eq!(test.matches("require(verifier.verify(").count(), 1);
+        assert_eq!(
+            test.matches("require(!verifier.verify(").count(),
+            vectors.len() - 1
+        );
+    }
+}
@@ -7,13 +7,15 @@
 extern crate alloc;
 
 use alloc::vec::Vec;
-use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
-use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
-use curve25519_dalek::ristretto::RistrettoPoint;
+use core::marker::PhantomData;
+use core::ops::Deref;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::MultiscalarMul;
-use digest::{ExtendableOutput, Input, XofReader};
-use sha3::{Sha3XofReader, Sha3_512, Shake256};
+use digest::{Digest, ExtendableOutput, Input, XofReader};
+use sha3::{Sha3XofReader, Sha3_256, Sha3_512, Shake256};
+
+use crate::curve::{CompressedPoint, CurvePoint};
+use crate::errors::ProofError;
 
 /// Represents a pair of base points for Pedersen commitments.
 ///
@@ -26,54 +28,246 @@ use sha3::{Sha3XofReader, Sha3_512, Shake256};
 /// * `B`: the `ristretto255` basepoint;
 /// * `B_blinding`: the result of `ristretto255` SHA3-512
 /// hash-to-group on input `B_bytes`.
+///
+/// The bases of other groups `C` are given by [`PedersenGens::standard`].
 #[derive(Copy, Clone)]
-pub struct PedersenGens {
+pub struct PedersenGens<C: CurvePoint = RistrettoPoint> {
     /// Base for the committed value
-    pub B: RistrettoPoint,
+    pub B: C,
     /// Base for the blinding factor
-    pub B_blinding: RistrettoPoint,
+    pub B_blinding: C,
 }
 
-impl PedersenGens {
+impl<C: CurvePoint> PedersenGens<C> {
+    /// Returns the standard bases of the group `C`:
+    ///
+    /// * `B`: the standard generator of `C`;
+    /// * `B_blinding`: the point of `C` mapped from the SHA3-512 hash of
+    /// the encoding of `B`.
+    ///
+    /// For `ristretto255` these are the default bases.
+    pub fn standard() -> Self {
+        let B = C::basepoint();
+        let mut uniform_bytes = [0u8; 64];
+        uniform_bytes.copy_from_slice(&Sha3_512::digest(B.compress().as_bytes()));
+        PedersenGens {
+            B,
+            B_blinding: C::from_uniform_bytes(&uniform_bytes),
+        }
+    }
+
     /// Creates a Pedersen commitment using the value scalar and a blinding factor.
-    pub fn commit(&self, value: Scalar, blinding: Scalar) -> RistrettoPoint {
-        RistrettoPoint::multiscalar_mul(&[value, blinding], &[self.B, self.B_blinding])
+    pub fn commit(&self, value: C::Scalar, blinding: C::Scalar) -> C {
+        C::multiscalar_mul(&[value, blinding], &[self.B, self.B_blinding])
+    }
+
+    /// Returns the bases with the value base replaced by `value_generator`,
+    /// such as an asset generator or a blinded asset tag, and the blinding
+    /// base unchanged.
+    pub fn with_value_generator(&self, value_generator: C) -> PedersenGens<C> {
+        PedersenGens {
+            B: value_generator,
+            B_blinding: self.B_blinding,
+        }
+    }
+}
+
+impl PedersenGens {
+    /// Creates a pair of bases determined by the hash of `label`,
+    /// for protocols that need Pedersen commitments independent of
+    /// those with the default bases.
+    ///
+    /// The bases are the first two points of the chain of generators
+    /// produced by SHAKE256 from the label `"PedersenGens" || label`,
+    /// in the same way as the generators of `BulletproofGens`.
+    pub fn from_label(label: &[u8]) -> Self {
+        let mut chain_label = Vec::with_capacity(12 + label.len());
+        chain_label.extend_from_slice(b"PedersenGens");
+        chain_label.extend_from_slice(label);
+
+        let mut chain = GeneratorsChain::new(&chain_label);
+        PedersenGens {
+            B: chain.next().unwrap(),
+            B_blinding: chain.next().unwrap(),
+        }
+    }
+
+    /// Returns the value generator \\(B\_{asset}\\) of the asset
+    /// identified by `asset_id`, for confidential assets.
+    ///
+    /// The generator is the result of `ristretto255` SHA3-512
+    /// hash-to-group on input `"AssetGenerator" || asset_id`, so that
+    /// the generators of different assets are independent of each other
+    /// and of the default bases.
+    pub fn asset_generator(asset_id: &[u8]) -> RistrettoPoint {
+        let mut hash = Sha3_512::default();
+        Input::input(&mut hash, b"AssetGenerator");
+        Input::input(&mut hash, asset_id);
+        RistrettoPoint::from_hash(hash)
+    }
+
+    /// Returns a blinded asset tag \\(B\_{asset} + r \cdot \tilde{B}\\)
+    /// for the asset `asset_id` and the blinding factor `blinding`,
+    /// which hides the asset of the commitments that use it as their
+    /// value generator.
+    pub fn blinded_asset_tag(&self, asset_id: &[u8], blinding: Scalar) -> RistrettoPoint {
+        PedersenGens::asset_generator(asset_id) + blinding * self.B_blinding
+    }
+
+    /// Returns the bases for committing to amounts of the asset
+    /// `asset_id`: the value base is the asset's generator and the
+    /// blinding base is unchanged.
+    pub fn for_asset(&self, asset_id: &[u8]) -> PedersenGens {
+        self.with_value_generator(PedersenGens::asset_generator(asset_id))
     }
 }
 
 impl Default for PedersenGens {
     fn default() -> Self {
-        PedersenGens {
-            B: RISTRETTO_BASEPOINT_POINT,
-            B_blinding: RistrettoPoint::hash_from_bytes::<Sha3_512>(
-                RISTRETTO_BASEPOINT_COMPRESSED.as_bytes(),
-            ),
+        PedersenGens::standard()
+    }
+}
+
+/// Represents a pair of base points for Pedersen commitments, together
+/// with precomputed tables of multiples of the bases.
+///
+/// Committing with the tables is several times faster than with
+/// [`PedersenGens::commit`], while creating the tables costs about as
+/// much as a few dozen commitments, so provers that commit many values with
+/// the same bases should create a `PedersenGensTable` once and reuse it,
+/// for instance with [`RangeProof::prove_multiple_with_table`](crate::RangeProof::prove_multiple_with_table).
+#[derive(Clone)]
+pub struct PedersenGensTable<C: CurvePoint = RistrettoPoint> {
+    /// The bases of the tables.
+    gens: PedersenGens<C>,
+    /// Precomputed multiples of the base for the committed value.
+    B_table: C::Table,
+    /// Precomputed multiples of the base for the blinding factor.
+    B_blinding_table: C::Table,
+}
+
+impl<C: CurvePoint> PedersenGensTable<C> {
+    /// Precomputes the tables for the bases `gens`.
+    pub fn new(gens: &PedersenGens<C>) -> Self {
+        PedersenGensTable {
+            gens: *gens,
+            B_table: gens.B.create_table(),
+            B_blinding_table: gens.B_blinding.create_table(),
         }
     }
+
+    /// Returns the bases of the tables.
+    pub fn gens(&self) -> &PedersenGens<C> {
+        &self.gens
+    }
+
+    /// Creates a Pedersen commitment using the value scalar and a blinding
+    /// factor, which equals the commitment of [`PedersenGens::commit`].
+    pub fn commit(&self, value: C::Scalar, blinding: C::Scalar) -> C {
+        C::table_mul(&self.B_table, &value) + C::table_mul(&self.B_blinding_table, &blinding)
+    }
+
+    /// Creates a Pedersen commitment for each pair of a value scalar and
+    /// a blinding factor in `openings`.
+    pub fn commit_batch(&self, openings: &[(C::Scalar, C::Scalar)]) -> Vec<C> {
+        openings
+            .iter()
+            .map(|&(value, blinding)| self.commit(value, blinding))
+            .collect()
+    }
+}
+
+/// The Pedersen bases of a prover, which commits with precomputed tables
+/// if it was given a [`PedersenGensTable`].
+///
+/// Dereferences to the bases themselves.
+pub(crate) enum PedersenBases<'a, C: CurvePoint = RistrettoPoint> {
+    Gens(&'a PedersenGens<C>),
+    Table(&'a PedersenGensTable<C>),
+    /// The bases `gens`, committing with the table `B_table` of the value
+    /// base `gens.B` and the blinding table of `blinding_table`, which
+    /// parties with different value bases share.
+    ValueTable {
+        gens: PedersenGens<C>,
+        B_table: &'a C::Table,
+        blinding_table: &'a PedersenGensTable<C>,
+    },
+}
+
+impl<'a, C: CurvePoint> Clone for PedersenBases<'a, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, C: CurvePoint> Copy for PedersenBases<'a, C> {}
+
+impl<'a, C: CurvePoint> PedersenBases<'a, C> {
+    /// Creates a Pedersen commitment using the value scalar and a blinding factor.
+    pub(crate) fn commit(&self, value: C::Scalar, blinding: C::Scalar) -> C {
+        match self {
+            PedersenBases::Gens(gens) => gens.commit(value, blinding),
+            PedersenBases::Table(table) => table.commit(value, blinding),
+            PedersenBases::ValueTable {
+                B_table,
+                blinding_table,
+                ..
+            } => {
+                C::table_mul(B_table, &value)
+                    + C::table_mul(&blinding_table.B_blinding_table, &blinding)
+            }
+        }
+    }
+}
+
+impl<'a, C: CurvePoint> Deref for PedersenBases<'a, C> {
+    type Target = PedersenGens<C>;
+
+    fn deref(&self) -> &PedersenGens<C> {
+        match self {
+            PedersenBases::Gens(gens) => gens,
+            PedersenBases::Table(table) => table.gens(),
+            PedersenBases::ValueTable { gens, .. } => gens,
+        }
+    }
+}
+
+impl<'a, C: CurvePoint> From<&'a PedersenGens<C>> for PedersenBases<'a, C> {
+    fn from(gens: &'a PedersenGens<C>) -> Self {
+        PedersenBases::Gens(gens)
+    }
+}
+
+impl<'a, C: CurvePoint> From<&'a PedersenGensTable<C>> for PedersenBases<'a, C> {
+    fn from(table: &'a PedersenGensTable<C>) -> Self {
+        PedersenBases::Table(table)
+    }
 }
 
 /// The `GeneratorsChain` creates an arbitrary-long sequence of
 /// orthogonal generators.  The sequence can be deterministically
 /// produced starting with an arbitrary point.
-struct GeneratorsChain {
+pub(crate) struct GeneratorsChain<C: CurvePoint = RistrettoPoint> {
     reader: Sha3XofReader,
+    _curve: PhantomData<C>,
 }
 
-impl GeneratorsChain {
+impl<C: CurvePoint> GeneratorsChain<C> {
     /// Creates a chain of generators, determined by the hash of `label`.
-    fn new(label: &[u8]) -> Self {
+    pub(crate) fn new(label: &[u8]) -> Self {
         let mut shake = Shake256::default();
         shake.input(b"GeneratorsChain");
         shake.input(label);
 
         GeneratorsChain {
             reader: shake.xof_result(),
+            _curve: PhantomData,
         }
     }
 
     /// Advances the reader n times, squeezing and discarding
     /// the result.
-    fn fast_forward(mut self, n: usize) -> Self {
+    pub(crate) fn fast_forward(mut self, n: usize) -> Self {
         for _ in 0..n {
             let mut buf = [0u8; 64];
             self.reader.read(&mut buf);
@@ -82,20 +276,20 @@ impl GeneratorsChain {
     }
 }
 
-impl Default for GeneratorsChain {
+impl<C: CurvePoint> Default for GeneratorsChain<C> {
     fn default() -> Self {
         Self::new(&[])
     }
 }
 
-impl Iterator for GeneratorsChain {
-    type Item = RistrettoPoint;
+impl<C: CurvePoint> Iterator for GeneratorsChain<C> {
+    type Item = C;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut uniform_bytes = [0u8; 64];
         self.reader.read(&mut uniform_bytes);
 
-        Some(RistrettoPoint::from_uniform_bytes(&uniform_bytes))
+        Some(C::from_uniform_bytes(&uniform_bytes))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -103,6 +297,19 @@ impl Iterator for GeneratorsChain {
     }
 }
 
+/// Magic bytes at the start of serialized `BulletproofGens`.
+const GENS_FORMAT_MAGIC: &[u8] = b"BPGENS";
+/// Version of the serialization format of `BulletproofGens`.
+///
+/// Version 1 had no domain label, so its header ended after the two
+/// capacities.  Version 2 added the domain label.
+const GENS_FORMAT_VERSION: u8 = 2;
+/// Size of the magic bytes, the version, the two capacities and the
+/// length of the domain, which is followed by the domain itself.
+const GENS_HEADER_SIZE: usize = 6 + 1 + 8 + 8 + 8;
+/// Size of the SHA3-256 digest at the end of serialized `BulletproofGens`.
+const GENS_DIGEST_SIZE: usize = 32;
+
 /// The `BulletproofGens` struct contains all the generators needed
 /// for aggregating up to `m` range proofs of up to `n` bits each.
 ///
@@ -114,7 +321,8 @@ impl Iterator for GeneratorsChain {
 ///
 /// To construct an arbitrary-length chain of generators, we apply
 /// SHAKE256 to a domain separator label, and feed each 64 bytes of
-/// XOF output into the `ristretto255` hash-to-group function.
+/// XOF output into the hash-to-group function of the group, which is
+/// the `ristretto255` one by default.
 /// Each of the `m` parties' generators are constructed using a
 /// different domain separation label, and proving and verification
 /// uses the first `n` elements of the arbitrary-length chain.
@@ -129,35 +337,44 @@ impl Iterator for GeneratorsChain {
 /// chain, and even forward-compatible to multiparty aggregation of
 /// constraint system proofs, since the generators are namespaced by
 /// their party index.
+///
+/// # Domain Separation
+///
+/// Generators created with [`BulletproofGens::with_domain`] append the
+/// domain label to each party's label, so that protocols with different
+/// domains use independent generators.  The generators of
+/// [`BulletproofGens::new`] are those of the empty domain.
+///
+/// # Other Groups
+///
+/// The generators of groups other than `ristretto255` are created with
+/// [`BulletproofGens::generate`].
 #[derive(Clone)]
-pub struct BulletproofGens {
+pub struct BulletproofGens<C: CurvePoint = RistrettoPoint> {
     /// The maximum number of usable generators for each party.
     pub gens_capacity: usize,
     /// Number of values or parties
     pub party_capacity: usize,
+    /// Domain separation label, appended to the label of each party.
+    domain: Vec<u8>,
     /// Precomputed \\(\mathbf G\\) generators for each party.
-    G_vec: Vec<Vec<RistrettoPoint>>,
+    G_vec: Vec<Vec<C>>,
     /// Precomputed \\(\mathbf H\\) generators for each party.
-    H_vec: Vec<Vec<RistrettoPoint>>,
+    H_vec: Vec<Vec<C>>,
 }
 
-impl BulletproofGens {
-    /// Create a new `BulletproofGens` object.
-    ///
-    /// # Inputs
-    ///
-    /// * `gens_capacity` is the number of generators to precompute
-    ///    for each party.  For rangeproofs, it is sufficient to pass
-    ///    `64`, the maximum bitsize of the rangeproofs.  For circuit
-    ///    proofs, the capacity must be greater than the number of
-    ///    multipliers, rounded up to the next power of two.
+impl<C: CurvePoint> BulletproofGens<C> {
+    /// Create a new `BulletproofGens` object for the group `C`, whose
+    /// generators are determined by the domain separation label `domain`,
+    /// as well as by the party index.
     ///
-    /// * `party_capacity` is the maximum number of parties that can
-    ///    produce an aggregated proof.
-    pub fn new(gens_capacity: usize, party_capacity: usize) -> Self {
+    /// The inputs are as for [`BulletproofGens::with_domain`], which is
+    /// the same as `generate` for `ristretto255`.
+    pub fn generate(domain: &[u8], gens_capacity: usize, party_capacity: usize) -> Self {
         let mut gens = BulletproofGens {
             gens_capacity: 0,
             party_capacity,
+            domain: domain.to_vec(),
             G_vec: (0..party_capacity).map(|_| Vec::new()).collect(),
             H_vec: (0..party_capacity).map(|_| Vec::new()).collect(),
         };
@@ -167,7 +384,7 @@ impl BulletproofGens {
 
     /// Returns j-th share of generators, with an appropriate
     /// slice of vectors G and H for the j-th range proof.
-    pub fn share(&self, j: usize) -> BulletproofGensShare<'_> {
+    pub fn share(&self, j: usize) -> BulletproofGensShare<'_, C> {
         BulletproofGensShare {
             gens: &self,
             share: j,
@@ -185,17 +402,18 @@ impl BulletproofGens {
 
         for i in 0..self.party_capacity {
             let party_index = i as u32;
-            let mut label = [b'G', 0, 0, 0, 0];
+            let mut label = [b'G', 0, 0, 0, 0].to_vec();
             LittleEndian::write_u32(&mut label[1..5], party_index);
+            label.extend_from_slice(&self.domain);
             self.G_vec[i].extend(
-                &mut GeneratorsChain::new(&label)
+                &mut GeneratorsChain::<C>::new(&label)
                     .fast_forward(self.gens_capacity)
                     .take(new_capacity - self.gens_capacity),
             );
 
             label[0] = b'H';
             self.H_vec[i].extend(
-                &mut GeneratorsChain::new(&label)
+                &mut GeneratorsChain::<C>::new(&label)
                     .fast_forward(self.gens_capacity)
                     .take(new_capacity - self.gens_capacity),
             );
@@ -204,7 +422,7 @@ impl BulletproofGens {
     }
 
     /// Return an iterator over the aggregation of the parties' G generators with given size `n`.
-    pub(crate) fn G(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
+    pub(crate) fn G(&self, n: usize, m: usize) -> impl Iterator<Item = &C> {
         AggregatedGensIter {
             n,
             m,
@@ -215,7 +433,7 @@ impl BulletproofGens {
     }
 
     /// Return an iterator over the aggregation of the parties' H generators with given size `n`.
-    pub(crate) fn H(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
+    pub(crate) fn H(&self, n: usize, m: usize) -> impl Iterator<Item = &C> {
         AggregatedGensIter {
             n,
             m,
@@ -226,16 +444,209 @@ impl BulletproofGens {
     }
 }
 
-struct AggregatedGensIter<'a> {
-    array: &'a Vec<Vec<RistrettoPoint>>,
+impl BulletproofGens {
+    /// Create a new `BulletproofGens` object.
+    ///
+    /// # Inputs
+    ///
+    /// * `gens_capacity` is the number of generators to precompute
+    ///    for each party.  For rangeproofs, it is sufficient to pass
+    ///    `64`, the maximum bitsize of the rangeproofs.  For circuit
+    ///    proofs, the capacity must be greater than the number of
+    ///    multipliers, rounded up to the next power of two.
+    ///
+    /// * `party_capacity` is the maximum number of parties that can
+    ///    produce an aggregated proof.
+    pub fn new(gens_capacity: usize, party_capacity: usize) -> Self {
+        BulletproofGens::with_domain(&[], gens_capacity, party_capacity)
+    }
+
+    /// Create a new `BulletproofGens` object whose generators are
+    /// determined by the domain separation label `domain`, as well as by
+    /// the party index.
+    ///
+    /// The inputs `gens_capacity` and `party_capacity` are as for
+    /// [`BulletproofGens::new`], which is the same as `with_domain` with
+    /// an empty `domain`.
+    pub fn with_domain(domain: &[u8], gens_capacity: usize, party_capacity: usize) -> Self {
+        BulletproofGens::generate(domain, gens_capacity, party_capacity)
+    }
+
+    /// Returns the size in bytes of the serialized generators.
+    pub fn serialized_size(&self) -> usize {
+        GENS_HEADER_SIZE
+            + self.domain.len()
+            + 2 * self.party_capacity * self.gens_capacity * 32
+            + GENS_DIGEST_SIZE
+    }
+
+    /// Serializes the precomputed generators, so that they can be cached
+    /// and loaded with [`BulletproofGens::from_bytes`] instead of being
+    /// recomputed.
+    ///
+    /// # Layout
+    ///
+    /// * the magic bytes `BPGENS` and a version byte, currently `2`,
+    /// * `gens_capacity` and `party_capacity` as little-endian `u64`s,
+    /// * the length of the domain label as a little-endian `u64`,
+    ///   followed by the domain label,
+    /// * for each party, its `gens_capacity` compressed \\(\mathbf G\\)
+    ///   generators followed by its `gens_capacity` compressed
+    ///   \\(\mathbf H\\) generators,
+    /// * the SHA3-256 digest of all of the preceding bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        use byteorder::{ByteOrder, LittleEndian};
+
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        buf.extend_from_slice(GENS_FORMAT_MAGIC);
+        buf.push(GENS_FORMAT_VERSION);
+        let mut capacity = [0u8; 8];
+        LittleEndian::write_u64(&mut capacity, self.gens_capacity as u64);
+        buf.extend_from_slice(&capacity);
+        LittleEndian::write_u64(&mut capacity, self.party_capacity as u64);
+        buf.extend_from_slice(&capacity);
+        LittleEndian::write_u64(&mut capacity, self.domain.len() as u64);
+        buf.extend_from_slice(&capacity);
+        buf.extend_from_slice(&self.domain);
+
+        for (G_j, H_j) in self.G_vec.iter().zip(self.H_vec.iter()) {
+            for P in G_j.iter().chain(H_j.iter()) {
+                buf.extend_from_slice(P.compress().as_bytes());
+            }
+        }
+
+        let digest = Sha3_256::digest(&buf);
+        buf.extend_from_slice(&digest);
+        buf
+    }
+
+    /// Deserializes generators serialized by [`BulletproofGens::to_bytes`].
+    ///
+    /// Returns an error if the version is unknown, the digest does not
+    /// match, or any of the points is not a valid compressed Ristretto point.
+    ///
+    /// The digest only detects accidental corruption.  Generators with a
+    /// known discrete log relation break the soundness of proofs, so the
+    /// bytes must come from trusted storage.
+    ///
+    /// The loaded generators can be extended with
+    /// [`BulletproofGens::increase_capacity`] as usual.
+    pub fn from_bytes(slice: &[u8]) -> Result<BulletproofGens, ProofError> {
+        use byteorder::{ByteOrder, LittleEndian};
+
+        if slice.len() < GENS_HEADER_SIZE + GENS_DIGEST_SIZE {
+            return Err(ProofError::FormatError);
+        }
+        let (data, digest) = slice.split_at(slice.len() - GENS_DIGEST_SIZE);
+        if &data[..GENS_FORMAT_MAGIC.len()] != GENS_FORMAT_MAGIC
+            || data[GENS_FORMAT_MAGIC.len()] != GENS_FORMAT_VERSION
+        {
+            return Err(ProofError::FormatError);
+        }
+        if Sha3_256::digest(data).as_slice() != digest {
+            return Err(ProofError::FormatError);
+        }
+
+        let pos = GENS_FORMAT_MAGIC.len() + 1;
+        let gens_capacity = LittleEndian::read_u64(&data[pos..]) as usize;
+        let party_capacity = LittleEndian::read_u64(&data[pos + 8..]) as usize;
+        let domain_len = LittleEndian::read_u64(&data[pos + 16..]) as usize;
+        if domain_len > data.len() - GENS_HEADER_SIZE {
+            return Err(ProofError::FormatError);
+        }
+        let (domain, points) = data[GENS_HEADER_SIZE..].split_at(domain_len);
+        let num_points = gens_capacity
+            .checked_mul(party_capacity)
+            .and_then(|n| n.checked_mul(2))
+            .ok_or(ProofError::FormatError)?;
+        if points.len() / 32 != num_points || points.len() % 32 != 0 {
+            return Err(ProofError::FormatError);
+        }
+
+        let mut chunks = points.chunks(32).map(|bytes| {
+            CompressedRistretto::from_slice(bytes)
+                .decompress()
+                .ok_or(ProofError::FormatError)
+        });
+        let mut read_gens = || -> Result<Vec<RistrettoPoint>, ProofError> {
+            chunks.by_ref().take(gens_capacity).collect()
+        };
+        let mut G_vec = Vec::with_capacity(party_capacity);
+        let mut H_vec = Vec::with_capacity(party_capacity);
+        for _ in 0..party_capacity {
+            G_vec.push(read_gens()?);
+            H_vec.push(read_gens()?);
+        }
+
+        Ok(BulletproofGens {
+            gens_capacity,
+            party_capacity,
+            domain: domain.to_vec(),
+            G_vec,
+            H_vec,
+        })
+    }
+
+    /// Loads the generators cached at `path`, or creates them if the file
+    /// does not exist or cannot be parsed.
+    ///
+    /// This is [`BulletproofGens::load_or_create_with_domain`] with an
+    /// empty domain, which gives the generators of [`BulletproofGens::new`].
+    #[cfg(feature = "std")]
+    pub fn load_or_create<P: AsRef<std::path::Path>>(
+        path: P,
+        gens_capacity: usize,
+        party_capacity: usize,
+    ) -> std::io::Result<BulletproofGens> {
+        BulletproofGens::load_or_create_with_domain(path, &[], gens_capacity, party_capacity)
+    }
+
+    /// Loads the generators of the domain `domain` cached at `path`, or
+    /// creates them if the file does not exist, cannot be parsed, or
+    /// contains generators of another domain.
+    ///
+    /// If the cached generators have fewer than `gens_capacity` generators
+    /// per party, only the missing generators are computed, as by
+    /// [`BulletproofGens::increase_capacity`].  If they have fewer than
+    /// `party_capacity` parties, all of the generators are recomputed.
+    /// In both cases the file is rewritten with the result, so that
+    /// subsequent calls load the larger set of generators.
+    #[cfg(feature = "std")]
+    pub fn load_or_create_with_domain<P: AsRef<std::path::Path>>(
+        path: P,
+        domain: &[u8],
+        gens_capacity: usize,
+        party_capacity: usize,
+    ) -> std::io::Result<BulletproofGens> {
+        let cached = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| BulletproofGens::from_bytes(&bytes).ok())
+            .filter(|gens| gens.domain == domain && gens.party_capacity >= party_capacity);
+
+        let gens = match cached {
+            Some(gens) if gens.gens_capacity >= gens_capacity => return Ok(gens),
+            Some(mut gens) => {
+                gens.increase_capacity(gens_capacity);
+                gens
+            }
+            None => BulletproofGens::with_domain(domain, gens_capacity, party_capacity),
+        };
+
+        std::fs::write(&path, gens.to_bytes())?;
+        Ok(gens)
+    }
+}
+
+struct AggregatedGensIter<'a, C> {
+    array: &'a Vec<Vec<C>>,
     n: usize,
     m: usize,
     party_idx: usize,
     gen_idx: usize,
 }
 
-impl<'a> Iterator for AggregatedGensIter<'a> {
-    type Item = &'a RistrettoPoint;
+impl<'a, C> Iterator for AggregatedGensIter<'a, C> {
+    type Item = &'a C;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.gen_idx >= self.n {
@@ -266,22 +677,29 @@ impl<'a> Iterator for AggregatedGensIter<'a> {
 /// provides a view of the generators for one of the `m` parties' shares.
 ///
 /// The `BulletproofGensShare` is produced by [`BulletproofGens::share()`].
-#[derive(Copy, Clone)]
-pub struct BulletproofGensShare<'a> {
+pub struct BulletproofGensShare<'a, C: CurvePoint = RistrettoPoint> {
     /// The parent object that this is a view into
-    gens: &'a BulletproofGens,
+    gens: &'a BulletproofGens<C>,
     /// Which share we are
     share: usize,
 }
 
-impl<'a> BulletproofGensShare<'a> {
+impl<'a, C: CurvePoint> Clone for BulletproofGensShare<'a, C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, C: CurvePoint> Copy for BulletproofGensShare<'a, C> {}
+
+impl<'a, C: CurvePoint> BulletproofGensShare<'a, C> {
     /// Return an iterator over this party's G generators with given size `n`.
-    pub fn G(&self, n: usize) -> impl Iterator<Item = &'a RistrettoPoint> {
+    pub fn G(&self, n: usize) -> impl Iterator<Item = &'a C> {
         self.gens.G_vec[self.share].iter().take(n)
     }
 
     /// Return an iterator over this party's H generators with given size `n`.
-    pub(crate) fn H(&self, n: usize) -> impl Iterator<Item = &'a RistrettoPoint> {
+    pub fn H(&self, n: usize) -> impl Iterator<Item = &'a C> {
         self.gens.H_vec[self.share].iter().take(n)
     }
 }
@@ -290,6 +708,56 @@ impl<'a> BulletproofGensShare<'a> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn asset_generators() {
+        let pc_gens = PedersenGens::default();
+        let gold = PedersenGens::asset_generator(b"gold");
+
+        assert_eq!(gold, PedersenGens::asset_generator(b"gold"));
+        assert_ne!(gold, PedersenGens::asset_generator(b"silver"));
+        assert_ne!(gold, pc_gens.B);
+        assert_ne!(gold, pc_gens.B_blinding);
+
+        let gold_gens = pc_gens.for_asset(b"gold");
+        assert_eq!(gold_gens.B, gold);
+        assert_eq!(gold_gens.B_blinding, pc_gens.B_blinding);
+
+        // A commitment with a blinded asset tag is a commitment with the
+        // asset generator, whose blinding absorbs the tag's blinding.
+        let (v, r, r_tag) = (
+            Scalar::from(1037u64),
+            Scalar::from(5u64),
+            Scalar::from(7u64),
+        );
+        let tag = pc_gens.blinded_asset_tag(b"gold", r_tag);
+        assert_eq!(
+            pc_gens.with_value_generator(tag).commit(v, r),
+            gold_gens.commit(v, r + v * r_tag)
+        );
+    }
+
+    #[test]
+    fn table_commitments_match_gens_commitments() {
+        let pc_gens = PedersenGens::from_label(b"table test");
+        let pc_table = PedersenGensTable::new(&pc_gens);
+        let mut rng = rand::thread_rng();
+
+        let openings: Vec<_> = (0..4)
+            .map(|_| (Scalar::random(&mut rng), Scalar::random(&mut rng)))
+            .collect();
+        let commitments: Vec<_> = openings
+            .iter()
+            .map(|&(value, blinding)| pc_gens.commit(value, blinding))
+            .collect();
+
+        assert_eq!(pc_table.commit_batch(&openings), commitments);
+        assert_eq!(
+            pc_table.commit(openings[0].0, openings[0].1),
+            commitments[0]
+        );
+        assert!(pc_table.commit_batch(&[]).is_empty());
+    }
+
     #[test]
     fn aggregated_gens_iter_matches_flat_map() {
         let gens = BulletproofGens::new(64, 8);
@@ -353,4 +821,189 @@ mod tests {
         helper(32, 8);
         helper(16, 8);
     }
+
+    fn hex_points<'a, I: IntoIterator<Item = &'a RistrettoPoint>>(points: I) -> Vec<String> {
+        points
+            .into_iter()
+            .map(|P| hex::encode(P.compress().as_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn pedersen_gens_from_label_test_vectors() {
+        let gens = PedersenGens::from_label(b"test domain");
+        assert_eq!(
+            hex_points(&[gens.B, gens.B_blinding]),
+            vec![
+                "322a01beb2510fddbc55331576506634a1f4a0d37a7c8444e50b5491c2eebb38",
+                "8ca3195321f2ef1b3ce4b6b36d56ccf1941ec82c9ef4eecc9241f9bf6acd5e19",
+            ]
+        );
+
+        let default_gens = PedersenGens::default();
+        let other_gens = PedersenGens::from_label(b"other domain");
+        assert_ne!(gens.B, default_gens.B);
+        assert_ne!(gens.B_blinding, default_gens.B_blinding);
+        assert_ne!(gens.B, other_gens.B);
+        assert_ne!(gens.B_blinding, other_gens.B_blinding);
+    }
+
+    #[test]
+    fn bulletproof_gens_with_domain_test_vectors() {
+        // The empty domain gives the generators of `BulletproofGens::new`.
+        let gens = BulletproofGens::new(2, 2);
+        assert_eq!(
+            hex_points(gens.G(2, 2)),
+            vec![
+                "fc3b25801422672a6a8d3adb5d8457d4301fe92324b4fc56ae934c8713ddfe2d",
+                "ae817fdef62f713dd169dc8a26406f68be0bd3cd53652614636b0801567c4264",
+                "0eeebec183d151ded1e24320cf43c987617b36e77114788e5ae8ace41570b74b",
+                "4a9c15ba1bb7f231abb71ccd50192d2de742cfff28b971a3fd9a4c239b53f109",
+            ]
+        );
+        assert_eq!(
+            hex_points(BulletproofGens::with_domain(b"", 2, 2).G(2, 2)),
+            hex_points(gens.G(2, 2))
+        );
+
+        let gens = BulletproofGens::with_domain(b"test domain", 2, 2);
+        assert_eq!(
+            hex_points(gens.G(2, 2)),
+            vec![
+                "5c855e8dfbf5dd6e6ea7d0ce9ba326ef285a7388a1643bab1f0c0a8e3fe50f16",
+                "92ca98a2be402d037f942eb306736320b5776dbd4b2b5299d6fa3c61b659d94d",
+                "ba6631129785c08e893de88b4752940e1895974ef784c1e2d2011d045d5de41f",
+                "38a79a3d466e818610f31d2e7dae8f36ba2628b76f62d53a7b30f5a0ef5f4556",
+            ]
+        );
+        assert_eq!(
+            hex_points(gens.H(2, 2)),
+            vec![
+                "048acacef97daa417c990f7aa93a97937844f276d12e79b2907257a7254c4e16",
+                "58504dcaf98978174136a31d1b5984fab8df7b7e1232d714735252691c3c9045",
+                "ecb2b0962cffdeb9c814075b9f04ea7982343f8335bd0e11d35d9503e7218929",
+                "3ae5b1fb9b790d6dfadbd3106095433ae6e4fb52886a0db049003de91acd264c",
+            ]
+        );
+
+        // The domain is kept when the generators are extended or serialized.
+        let mut resized = BulletproofGens::with_domain(b"test domain", 1, 2);
+        resized.increase_capacity(2);
+        assert_eq!(resized.G_vec, gens.G_vec);
+        assert_eq!(resized.H_vec, gens.H_vec);
+
+        let mut loaded = BulletproofGens::from_bytes(
+            &BulletproofGens::with_domain(b"test domain", 1, 2).to_bytes(),
+        )
+        .unwrap();
+        loaded.increase_capacity(2);
+        assert_eq!(loaded.G_vec, gens.G_vec);
+        assert_eq!(loaded.H_vec, gens.H_vec);
+    }
+
+    #[test]
+    fn serialized_gens_match_created_gens() {
+        let gens = BulletproofGens::new(16, 2);
+        let bytes = gens.to_bytes();
+        assert_eq!(bytes.len(), gens.serialized_size());
+
+        let mut loaded = BulletproofGens::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.gens_capacity, 16);
+        assert_eq!(loaded.party_capacity, 2);
+        assert_eq!(loaded.G_vec, gens.G_vec);
+        assert_eq!(loaded.H_vec, gens.H_vec);
+
+        // Loaded generators extend in the same way as created ones.
+        loaded.increase_capacity(32);
+        let bigger_gens = BulletproofGens::new(32, 2);
+        assert_eq!(loaded.G_vec, bigger_gens.G_vec);
+        assert_eq!(loaded.H_vec, bigger_gens.H_vec);
+    }
+
+    #[test]
+    fn from_bytes_rejects_corrupted_gens() {
+        let bytes = BulletproofGens::new(4, 1).to_bytes();
+
+        // A flipped bit in a point or in the digest.
+        for i in &[40, bytes.len() - 1] {
+            let mut corrupted = bytes.clone();
+            corrupted[*i] ^= 1;
+            assert!(BulletproofGens::from_bytes(&corrupted).is_err());
+        }
+
+        // An unknown version.
+        let mut corrupted = bytes.clone();
+        corrupted[6] = 3;
+        assert!(BulletproofGens::from_bytes(&corrupted).is_err());
+
+        // A truncated file.
+        assert!(BulletproofGens::from_bytes(&bytes[..bytes.len() - 33]).is_err());
+    }
+
+    /// Serializes `gens` in the version 1 format, which has no domain label.
+    fn to_v1_bytes(gens: &BulletproofGens) -> Vec<u8> {
+        let mut buf = Vec::new();
+        buf.extend_from_slice(GENS_FORMAT_MAGIC);
+        buf.push(1);
+        buf.extend_from_slice(&(gens.gens_capacity as u64).to_le_bytes());
+        buf.extend_from_slice(&(gens.party_capacity as u64).to_le_bytes());
+        for (G_j, H_j) in gens.G_vec.iter().zip(gens.H_vec.iter()) {
+            for P in G_j.iter().chain(H_j.iter()) {
+                buf.extend_from_slice(P.compress().as_bytes());
+            }
+        }
+        let digest = Sha3_256::digest(&buf);
+        buf.extend_from_slice(&digest);
+        buf
+    }
+
+    #[test]
+    fn from_bytes_rejects_version_1_gens() {
+        let gens = BulletproofGens::new(4, 1);
+        assert_eq!(
+            BulletproofGens::from_bytes(&to_v1_bytes(&gens)).err(),
+            Some(ProofError::FormatError)
+        );
+
+        // A cached version 1 file is replaced.
+        let path = std::env::temp_dir().join(format!(
+            "bulletproofs-gens-v1-test-{}.bin",
+            std::process::id()
+        ));
+        std::fs::write(&path, to_v1_bytes(&gens)).unwrap();
+        let loaded = BulletproofGens::load_or_create(&path, 4, 1).unwrap();
+        assert_eq!(loaded.G_vec, gens.G_vec);
+        assert_eq!(std::fs::read(&path).unwrap(), gens.to_bytes());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn load_or_create_extends_cached_gens() {
+        let path =
+            std::env::temp_dir().join(format!("bulletproofs-gens-test-{}.bin", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let gens = BulletproofGens::load_or_create(&path, 8, 2).unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), gens.to_bytes());
+
+        // A smaller request is served from the cache as is.
+        let cached = BulletproofGens::load_or_create(&path, 4, 1).unwrap();
+        assert_eq!(cached.G_vec, gens.G_vec);
+
+        // A bigger request extends the cache and rewrites it.
+        let extended = BulletproofGens::load_or_create(&path, 16, 2).unwrap();
+        let bigger_gens = BulletproofGens::new(16, 2);
+        assert_eq!(extended.G_vec, bigger_gens.G_vec);
+        assert_eq!(extended.H_vec, bigger_gens.H_vec);
+        assert_eq!(std::fs::read(&path).unwrap(), bigger_gens.to_bytes());
+
+        // Generators of another domain replace the cached ones.
+        let domain_gens =
+            BulletproofGens::load_or_create_with_domain(&path, b"test domain", 4, 1).unwrap();
+        let expected_gens = BulletproofGens::with_domain(b"test domain", 4, 1);
+        assert_eq!(domain_gens.G_vec, expected_gens.G_vec);
+        assert_eq!(std::fs::read(&path).unwrap(), expected_gens.to_bytes());
+
+        std::fs::remove_file(&path).unwrap();
+    }
 }
@@ -5,26 +5,44 @@
 extern crate alloc;
 
 use alloc::borrow::Borrow;
+use alloc::vec;
 use alloc::vec::Vec;
 
 use core::iter;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use core::marker::PhantomData;
+use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::VartimeMultiscalarMul;
-use merlin::Transcript;
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::curve::{CompressedPoint, CurvePoint, CurveScalar};
 use crate::errors::ProofError;
-use crate::transcript::TranscriptProtocol;
-
+use crate::transcript::{ProofTranscript, TranscriptProtocol};
+use crate::util;
+use crate::vector_commitment::{VectorCommitment, VectorPedersenGens};
+
+/// Three vectors of scalars of an inner-product proof: the challenges
+/// \\([u\_{i}]\\) or their squares, their inverses, and the scalars \\([s\_{i}]\\).
+type VerificationScalars<S> = (Vec<S>, Vec<S>, Vec<S>);
+
+/// An inner-product proof, which proves knowledge of vectors
+/// \\(\mathbf{a}, \mathbf{b}\\) such that
+/// \\(P = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{b}, \mathbf{H'} \rangle
+/// + \langle \mathbf{a}, \mathbf{b} \rangle \cdot Q\\).
+///
+/// See the [notes](../notes/index.html#inner-product-proof) for details.
+///
+/// The proof is generic over the group `C`, which defaults to
+/// `ristretto255`.
 #[derive(Clone, Debug)]
-pub struct InnerProductProof {
-    pub(crate) L_vec: Vec<CompressedRistretto>,
-    pub(crate) R_vec: Vec<CompressedRistretto>,
-    pub(crate) a: Scalar,
-    pub(crate) b: Scalar,
+pub struct InnerProductProof<C: CurvePoint = RistrettoPoint> {
+    pub(crate) L_vec: Vec<C::Compressed>,
+    pub(crate) R_vec: Vec<C::Compressed>,
+    pub(crate) a: C::Scalar,
+    pub(crate) b: C::Scalar,
 }
 
-impl InnerProductProof {
+impl<C: CurvePoint> InnerProductProof<C> {
     /// Create an inner-product proof.
     ///
     /// The proof is created with respect to the bases \\(G\\), \\(H'\\),
@@ -34,79 +52,77 @@ impl InnerProductProof {
     /// challenges depend on the *entire* transcript (including parent
     /// protocols).
     ///
-    /// The lengths of the vectors must all be the same, and must all be
-    /// either 0 or a power of 2.
+    /// The lengths of the vectors must all be the same, and must not be 0.
+    /// Lengths that are not a power of 2 are folded with unbalanced rounds,
+    /// which carry the unpaired last element into the next round.
     pub fn create(
-        transcript: &mut Transcript,
-        Q: &RistrettoPoint,
-        G_factors: &[Scalar],
-        H_factors: &[Scalar],
-        mut G_vec: Vec<RistrettoPoint>,
-        mut H_vec: Vec<RistrettoPoint>,
-        mut a_vec: Vec<Scalar>,
-        mut b_vec: Vec<Scalar>,
-    ) -> InnerProductProof {
-        // Create slices G, H, a, b backed by their respective
-        // vectors.  This lets us reslice as we compress the lengths
-        // of the vectors in the main loop below.
-        let mut G = &mut G_vec[..];
-        let mut H = &mut H_vec[..];
-        let mut a = &mut a_vec[..];
-        let mut b = &mut b_vec[..];
-
-        let mut n = G.len();
+        transcript: &mut impl ProofTranscript,
+        Q: &C,
+        G_factors: &[C::Scalar],
+        H_factors: &[C::Scalar],
+        mut G_vec: Vec<C>,
+        mut H_vec: Vec<C>,
+        mut a_vec: Vec<C::Scalar>,
+        mut b_vec: Vec<C::Scalar>,
+    ) -> InnerProductProof<C> {
+        let mut n = G_vec.len();
 
         // All of the input vectors must have the same length.
-        assert_eq!(G.len(), n);
-        assert_eq!(H.len(), n);
-        assert_eq!(a.len(), n);
-        assert_eq!(b.len(), n);
+        assert_eq!(G_vec.len(), n);
+        assert_eq!(H_vec.len(), n);
+        assert_eq!(a_vec.len(), n);
+        assert_eq!(b_vec.len(), n);
         assert_eq!(G_factors.len(), n);
         assert_eq!(H_factors.len(), n);
 
-        // All of the input vectors must have a length that is a power of two.
-        assert!(n.is_power_of_two());
+        // The input vectors must not be empty.
+        assert!(n > 0);
 
         transcript.innerproduct_domain_sep(n as u64);
 
-        let lg_n = n.next_power_of_two().trailing_zeros() as usize;
+        let lg_n = util::folding_rounds(n);
         let mut L_vec = Vec::with_capacity(lg_n);
         let mut R_vec = Vec::with_capacity(lg_n);
 
+        // Each round folds the first 2k elements of the vectors into the
+        // first k, where k = n/2.  If n is odd, the last element is not
+        // paired and is moved to position k unchanged, so the vectors of
+        // the next round have length n - k.
+
         // If it's the first iteration, unroll the Hprime = H*y_inv scalar mults
         // into multiscalar muls, for performance.
         if n != 1 {
-            n = n / 2;
-            let (a_L, a_R) = a.split_at_mut(n);
-            let (b_L, b_R) = b.split_at_mut(n);
-            let (G_L, G_R) = G.split_at_mut(n);
-            let (H_L, H_R) = H.split_at_mut(n);
+            let k = n / 2;
+            let (a_L, a_R) = a_vec[..2 * k].split_at_mut(k);
+            let (b_L, b_R) = b_vec[..2 * k].split_at_mut(k);
+            let (G_L, G_R) = G_vec[..2 * k].split_at_mut(k);
+            let (H_L, H_R) = H_vec[..2 * k].split_at_mut(k);
 
             let c_L = inner_product(&a_L, &b_R);
             let c_R = inner_product(&a_R, &b_L);
 
-            let L = RistrettoPoint::vartime_multiscalar_mul(
+            let L = C::vartime_multiscalar_mul(
                 a_L.iter()
-                    .zip(G_factors[n..2 * n].into_iter())
-                    .map(|(a_L_i, g)| a_L_i * g)
+                    .zip(G_factors[k..2 * k].into_iter())
+                    .map(|(a_L_i, g)| *a_L_i * *g)
                     .chain(
                         b_R.iter()
-                            .zip(H_factors[0..n].into_iter())
-                            .map(|(b_R_i, h)| b_R_i * h),
+                            .zip(H_factors[0..k].into_iter())
+                            .map(|(b_R_i, h)| *b_R_i * *h),
                     )
                     .chain(iter::once(c_L)),
                 G_R.iter().chain(H_L.iter()).chain(iter::once(Q)),
             )
             .compress();
 
-            let R = RistrettoPoint::vartime_multiscalar_mul(
+            let R = C::vartime_multiscalar_mul(
                 a_R.iter()
-                    .zip(G_factors[0..n].into_iter())
-                    .map(|(a_R_i, g)| a_R_i * g)
+                    .zip(G_factors[0..k].into_iter())
+                    .map(|(a_R_i, g)| *a_R_i * *g)
                     .chain(
                         b_L.iter()
-                            .zip(H_factors[n..2 * n].into_iter())
-                            .map(|(b_L_i, h)| b_L_i * h),
+                            .zip(H_factors[k..2 * k].into_iter())
+                            .map(|(b_L_i, h)| *b_L_i * *h),
                     )
                     .chain(iter::once(c_R)),
                 G_L.iter().chain(H_R.iter()).chain(iter::once(Q)),
@@ -119,45 +135,49 @@ impl InnerProductProof {
             transcript.append_point(b"L", &L);
             transcript.append_point(b"R", &R);
 
-            let u = transcript.challenge_scalar(b"u");
+            let u: C::Scalar = transcript.challenge_scalar(b"u");
             let u_inv = u.invert();
 
-            for i in 0..n {
+            for i in 0..k {
                 a_L[i] = a_L[i] * u + u_inv * a_R[i];
                 b_L[i] = b_L[i] * u_inv + u * b_R[i];
-                G_L[i] = RistrettoPoint::vartime_multiscalar_mul(
-                    &[u_inv * G_factors[i], u * G_factors[n + i]],
+                G_L[i] = C::vartime_multiscalar_mul(
+                    &[u_inv * G_factors[i], u * G_factors[k + i]],
                     &[G_L[i], G_R[i]],
                 );
-                H_L[i] = RistrettoPoint::vartime_multiscalar_mul(
-                    &[u * H_factors[i], u_inv * H_factors[n + i]],
+                H_L[i] = C::vartime_multiscalar_mul(
+                    &[u * H_factors[i], u_inv * H_factors[k + i]],
                     &[H_L[i], H_R[i]],
                 )
             }
 
-            a = a_L;
-            b = b_L;
-            G = G_L;
-            H = H_L;
+            if n % 2 == 1 {
+                a_vec[k] = a_vec[2 * k];
+                b_vec[k] = b_vec[2 * k];
+                G_vec[k] = G_vec[2 * k] * G_factors[2 * k];
+                H_vec[k] = H_vec[2 * k] * H_factors[2 * k];
+            }
+
+            n -= k;
         }
 
         while n != 1 {
-            n = n / 2;
-            let (a_L, a_R) = a.split_at_mut(n);
-            let (b_L, b_R) = b.split_at_mut(n);
-            let (G_L, G_R) = G.split_at_mut(n);
-            let (H_L, H_R) = H.split_at_mut(n);
+            let k = n / 2;
+            let (a_L, a_R) = a_vec[..2 * k].split_at_mut(k);
+            let (b_L, b_R) = b_vec[..2 * k].split_at_mut(k);
+            let (G_L, G_R) = G_vec[..2 * k].split_at_mut(k);
+            let (H_L, H_R) = H_vec[..2 * k].split_at_mut(k);
 
             let c_L = inner_product(&a_L, &b_R);
             let c_R = inner_product(&a_R, &b_L);
 
-            let L = RistrettoPoint::vartime_multiscalar_mul(
+            let L = C::vartime_multiscalar_mul(
                 a_L.iter().chain(b_R.iter()).chain(iter::once(&c_L)),
                 G_R.iter().chain(H_L.iter()).chain(iter::once(Q)),
             )
             .compress();
 
-            let R = RistrettoPoint::vartime_multiscalar_mul(
+            let R = C::vartime_multiscalar_mul(
                 a_R.iter().chain(b_L.iter()).chain(iter::once(&c_R)),
                 G_L.iter().chain(H_R.iter()).chain(iter::once(Q)),
             )
@@ -169,45 +189,56 @@ impl InnerProductProof {
             transcript.append_point(b"L", &L);
             transcript.append_point(b"R", &R);
 
-            let u = transcript.challenge_scalar(b"u");
+            let u: C::Scalar = transcript.challenge_scalar(b"u");
             let u_inv = u.invert();
 
-            for i in 0..n {
+            for i in 0..k {
                 a_L[i] = a_L[i] * u + u_inv * a_R[i];
                 b_L[i] = b_L[i] * u_inv + u * b_R[i];
-                G_L[i] = RistrettoPoint::vartime_multiscalar_mul(&[u_inv, u], &[G_L[i], G_R[i]]);
-                H_L[i] = RistrettoPoint::vartime_multiscalar_mul(&[u, u_inv], &[H_L[i], H_R[i]]);
+                G_L[i] = C::vartime_multiscalar_mul(&[u_inv, u], &[G_L[i], G_R[i]]);
+                H_L[i] = C::vartime_multiscalar_mul(&[u, u_inv], &[H_L[i], H_R[i]]);
             }
 
-            a = a_L;
-            b = b_L;
-            G = G_L;
-            H = H_L;
+            if n % 2 == 1 {
+                a_vec[k] = a_vec[2 * k];
+                b_vec[k] = b_vec[2 * k];
+                G_vec[k] = G_vec[2 * k];
+                H_vec[k] = H_vec[2 * k];
+            }
+
+            n -= k;
         }
 
         InnerProductProof {
             L_vec: L_vec,
             R_vec: R_vec,
-            a: a[0],
-            b: b[0],
+            a: a_vec[0],
+            b: b_vec[0],
         }
     }
 
-    /// Computes three vectors of verification scalars \\([u\_{i}^{2}]\\), \\([u\_{i}^{-2}]\\) and \\([s\_{i}]\\) for combined multiscalar multiplication
-    /// in a parent protocol. See [inner product protocol notes](index.html#verification-equation) for details.
+    /// Computes the challenges \\([u\_{i}]\\) of the folding rounds, their
+    /// inverses \\([u\_{i}^{-1}]\\) and the vector \\([s\_{i}]\\) of the
+    /// products of the challenges that fold the generators \\(G\\), for
+    /// custom verifiers of protocols embedding an inner-product proof.
+    /// See [inner product protocol notes](index.html#verification-equation) for details.
     /// The verifier must provide the input length \\(n\\) explicitly to avoid unbounded allocation within the inner product proof.
-    pub(crate) fn verification_scalars(
+    /// The scalars for \\(H\\) are \\([s\_{i}^{-1}]\\), which is \\([s\_{n-1-i}]\\) if \\(n\\) is a power of 2.
+    ///
+    /// The challenges are returned in the order in which they were created,
+    /// which is the order of the \\(L\\) and \\(R\\) points of the proof.
+    pub fn verification_challenges(
         &self,
         n: usize,
-        transcript: &mut Transcript,
-    ) -> Result<(Vec<Scalar>, Vec<Scalar>, Vec<Scalar>), ProofError> {
+        transcript: &mut impl ProofTranscript,
+    ) -> Result<VerificationScalars<C::Scalar>, ProofError> {
         let lg_n = self.L_vec.len();
         if lg_n >= 32 {
             // 4 billion multiplications should be enough for anyone
-            // and this check prevents overflow in 1<<lg_n below.
+            // and this check bounds the length n below.
             return Err(ProofError::VerificationError);
         }
-        if n != (1 << lg_n) {
+        if n == 0 || lg_n != util::folding_rounds(n) {
             return Err(ProofError::VerificationError);
         }
 
@@ -215,21 +246,43 @@ impl InnerProductProof {
 
         // 1. Recompute x_k,...,x_1 based on the proof transcript
 
-        let mut challenges = Vec::with_capacity(lg_n);
+        let mut challenges: Vec<C::Scalar> = Vec::with_capacity(lg_n);
         for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
             transcript.validate_and_append_point(b"L", L)?;
             transcript.validate_and_append_point(b"R", R)?;
             challenges.push(transcript.challenge_scalar(b"u"));
         }
 
-        // 2. Compute 1/(u_k...u_1) and 1/u_k, ..., 1/u_1
+        // 2. Compute 1/u_k, ..., 1/u_1
 
         let mut challenges_inv = challenges.clone();
-        let allinv = Scalar::batch_invert(&mut challenges_inv);
+        C::Scalar::batch_invert(&mut challenges_inv);
 
-        // 3. Compute u_i^2 and (1/u_i)^2
+        // 3. Compute s values: the folding rounds multiply the left halves
+        // of G by 1/u_i and the right halves by u_i.
+        // The challenges are stored in "creation order" as [u_k,...,u_1].
 
-        for i in 0..lg_n {
+        let s = util::folding_products(n, &challenges_inv, &challenges);
+
+        Ok((challenges, challenges_inv, s))
+    }
+
+    /// Computes three vectors of verification scalars \\([u\_{i}^{2}]\\), \\([u\_{i}^{-2}]\\) and \\([s\_{i}]\\) for combined multiscalar multiplication
+    /// in a parent protocol, which give the weights of the points \\(L\_i\\),
+    /// \\(R\_i\\) and \\(G\_i\\) in the verification equation.
+    ///
+    /// The challenges are those of [`InnerProductProof::verification_challenges`], squared.
+    pub fn verification_scalars(
+        &self,
+        n: usize,
+        transcript: &mut impl ProofTranscript,
+    ) -> Result<VerificationScalars<C::Scalar>, ProofError> {
+        let (mut challenges, mut challenges_inv, s) =
+            self.verification_challenges(n, transcript)?;
+
+        // Compute u_i^2 and (1/u_i)^2
+
+        for i in 0..challenges.len() {
             // XXX missing square fn upstream
             challenges[i] = challenges[i] * challenges[i];
             challenges_inv[i] = challenges_inv[i] * challenges_inv[i];
@@ -237,89 +290,126 @@ impl InnerProductProof {
         let challenges_sq = challenges;
         let challenges_inv_sq = challenges_inv;
 
-        // 4. Compute s values inductively.
-
-        let mut s = Vec::with_capacity(n);
-        s.push(allinv);
-        for i in 1..n {
-            let lg_i = (32 - 1 - (i as u32).leading_zeros()) as usize;
-            let k = 1 << lg_i;
-            // The challenges are stored in "creation order" as [u_k,...,u_1],
-            // so u_{lg(i)+1} = is indexed by (lg_n-1) - lg_i
-            let u_lg_i_sq = challenges_sq[(lg_n - 1) - lg_i];
-            s.push(s[i - k] * u_lg_i_sq);
-        }
-
         Ok((challenges_sq, challenges_inv_sq, s))
     }
 
-    /// This method is for testing that proof generation work,
-    /// but for efficiency the actual protocols would use `verification_scalars`
-    /// method to combine inner product verification with other checks
-    /// in a single multiscalar multiplication.
-    #[allow(dead_code)]
-    pub fn verify<IG, IH>(
+    /// Computes the terms of the verification equation of the proof,
+    /// as a list of scalars and a list of points, such that the proof is
+    /// valid if and only if their multiscalar multiplication is the identity.
+    ///
+    /// A parent protocol can append the terms to its own, weighted by a
+    /// random scalar, to check the proof in a single multiscalar
+    /// multiplication with its other checks.
+    ///
+    /// The equation is
+    /// \\[
+    ///    (a b) \cdot Q + \sum\_i (a s\_i g\_i) \cdot G\_i + \sum\_i (b s\_i^{-1} h\_i) \cdot H\_i
+    ///    - \sum\_j (u\_j^2 \cdot L\_j + u\_j^{-2} \cdot R\_j) - P = 0,
+    /// \\]
+    /// where \\(g\_i\\) and \\(h\_i\\) are the `G_factors` and `H_factors`,
+    /// and the challenges \\(u\_j\\) of [`InnerProductProof::verification_challenges`]
+    /// are squared, as in [`InnerProductProof::verification_scalars`].
+    pub fn verification_terms<IG, IH>(
         &self,
         n: usize,
-        transcript: &mut Transcript,
+        transcript: &mut impl ProofTranscript,
         G_factors: IG,
         H_factors: IH,
-        P: &RistrettoPoint,
-        Q: &RistrettoPoint,
-        G: &[RistrettoPoint],
-        H: &[RistrettoPoint],
-    ) -> Result<(), ProofError>
+        P: &C,
+        Q: &C,
+        G: &[C],
+        H: &[C],
+    ) -> Result<(Vec<C::Scalar>, Vec<C>), ProofError>
     where
         IG: IntoIterator,
-        IG::Item: Borrow<Scalar>,
+        IG::Item: Borrow<C::Scalar>,
         IH: IntoIterator,
-        IH::Item: Borrow<Scalar>,
+        IH::Item: Borrow<C::Scalar>,
     {
+        if G.len() != n || H.len() != n {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
         let (u_sq, u_inv_sq, s) = self.verification_scalars(n, transcript)?;
 
         let g_times_a_times_s = G_factors
             .into_iter()
             .zip(s.iter())
-            .map(|(g_i, s_i)| (self.a * s_i) * g_i.borrow())
-            .take(G.len());
+            .map(|(g_i, s_i)| (self.a * *s_i) * *g_i.borrow());
 
-        // 1/s[i] is s[!i], and !i runs from n-1 to 0 as i runs from 0 to n-1
-        let inv_s = s.iter().rev();
+        // The folding rounds multiply H by the inverse factors of G.
+        // If n is a power of two, 1/s[i] is s[n-1-i].
+        let mut inv_s = s.clone();
+        C::Scalar::batch_invert(&mut inv_s);
 
         let h_times_b_div_s = H_factors
             .into_iter()
-            .zip(inv_s)
-            .map(|(h_i, s_i_inv)| (self.b * s_i_inv) * h_i.borrow());
+            .zip(inv_s.iter())
+            .map(|(h_i, s_i_inv)| (self.b * *s_i_inv) * *h_i.borrow());
 
-        let neg_u_sq = u_sq.iter().map(|ui| -ui);
-        let neg_u_inv_sq = u_inv_sq.iter().map(|ui| -ui);
+        let neg_u_sq = u_sq.iter().map(|ui| -*ui);
+        let neg_u_inv_sq = u_inv_sq.iter().map(|ui| -*ui);
 
         let Ls = self
             .L_vec
             .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
-            .collect::<Result<Vec<_>, _>>()?;
+            .map(|p| p.decompress().ok_or(ProofError::VerificationError));
 
         let Rs = self
             .R_vec
             .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
+            .map(|p| p.decompress().ok_or(ProofError::VerificationError));
+
+        // a * b * Q + <a * s, G> + <b / s, H> - sum(u_i^2 L_i + u_i^-2 R_i) - P == 0
+        let scalars = iter::once(self.a * self.b)
+            .chain(g_times_a_times_s)
+            .chain(h_times_b_div_s)
+            .chain(neg_u_sq)
+            .chain(neg_u_inv_sq)
+            .chain(iter::once(-C::Scalar::one()))
+            .collect::<Vec<_>>();
+
+        let points = iter::once(Ok(*Q))
+            .chain(G.iter().cloned().map(Ok))
+            .chain(H.iter().cloned().map(Ok))
+            .chain(Ls)
+            .chain(Rs)
+            .chain(iter::once(Ok(*P)))
             .collect::<Result<Vec<_>, _>>()?;
 
-        let expect_P = RistrettoPoint::vartime_multiscalar_mul(
-            iter::once(self.a * self.b)
-                .chain(g_times_a_times_s)
-                .chain(h_times_b_div_s)
-                .chain(neg_u_sq)
-                .chain(neg_u_inv_sq),
-            iter::once(Q)
-                .chain(G.iter())
-                .chain(H.iter())
-                .chain(Ls.iter())
-                .chain(Rs.iter()),
-        );
+        if scalars.len() != points.len() {
+            // Fewer factors than generators were provided.
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
 
-        if expect_P == *P {
+        Ok((scalars, points))
+    }
+
+    /// This method is for testing that proof generation work,
+    /// but for efficiency the actual protocols would use `verification_scalars`
+    /// or `verification_terms` to combine inner product verification with
+    /// other checks in a single multiscalar multiplication.
+    pub fn verify<IG, IH>(
+        &self,
+        n: usize,
+        transcript: &mut impl ProofTranscript,
+        G_factors: IG,
+        H_factors: IH,
+        P: &C,
+        Q: &C,
+        G: &[C],
+        H: &[C],
+    ) -> Result<(), ProofError>
+    where
+        IG: IntoIterator,
+        IG::Item: Borrow<C::Scalar>,
+        IH: IntoIterator,
+        IH::Item: Borrow<C::Scalar>,
+    {
+        let (scalars, points) =
+            self.verification_terms(n, transcript, G_factors, H_factors, P, Q, G, H)?;
+
+        if C::vartime_multiscalar_mul(scalars, points).is_identity() {
             Ok(())
         } else {
             Err(ProofError::VerificationError)
@@ -330,14 +420,16 @@ impl InnerProductProof {
     /// product proof.
     ///
     /// For vectors of length `n` the proof size is
-    /// \\(32 \cdot (2\lg n+2)\\) bytes.
+    /// \\(2\lg n\\) encoded points and two 32-byte scalars, which is
+    /// \\(32 \cdot (2\lg n+2)\\) bytes for `ristretto255`.
     pub fn serialized_size(&self) -> usize {
-        (self.L_vec.len() * 2 + 2) * 32
+        self.L_vec.len() * 2 * C::Compressed::SIZE + 2 * 32
     }
 
-    /// Serializes the proof into a byte array of \\(2n+2\\) 32-byte elements.
+    /// Serializes the proof into a byte array of \\(2n\\) encoded points
+    /// followed by two 32-byte scalars.
     /// The layout of the inner product proof is:
-    /// * \\(n\\) pairs of compressed Ristretto points \\(L_0, R_0 \dots, L_{n-1}, R_{n-1}\\),
+    /// * \\(n\\) pairs of encoded points \\(L_0, R_0 \dots, L_{n-1}, R_{n-1}\\),
     /// * two scalars \\(a, b\\).
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(self.serialized_size());
@@ -345,14 +437,14 @@ impl InnerProductProof {
             buf.extend_from_slice(l.as_bytes());
             buf.extend_from_slice(r.as_bytes());
         }
-        buf.extend_from_slice(self.a.as_bytes());
-        buf.extend_from_slice(self.b.as_bytes());
+        buf.extend_from_slice(&self.a.to_bytes());
+        buf.extend_from_slice(&self.b.to_bytes());
         buf
     }
 
     /// Converts the proof into a byte iterator over serialized view of the proof.
     /// The layout of the inner product proof is:
-    /// * \\(n\\) pairs of compressed Ristretto points \\(L_0, R_0 \dots, L_{n-1}, R_{n-1}\\),
+    /// * \\(n\\) pairs of encoded points \\(L_0, R_0 \dots, L_{n-1}, R_{n-1}\\),
     /// * two scalars \\(a, b\\).
     #[inline]
     pub(crate) fn to_bytes_iter(&self) -> impl Iterator<Item = u8> + '_ {
@@ -360,61 +452,186 @@ impl InnerProductProof {
             .iter()
             .zip(self.R_vec.iter())
             .flat_map(|(l, r)| l.as_bytes().iter().chain(r.as_bytes()))
-            .chain(self.a.as_bytes())
-            .chain(self.b.as_bytes())
             .copied()
+            .chain(self.a.to_bytes().to_vec())
+            .chain(self.b.to_bytes().to_vec())
     }
 
-    /// Deserializes the proof from a byte slice.
+    /// Deserializes a proof over the group `C` from a byte slice.
     /// Returns an error in the following cases:
-    /// * the slice does not have \\(2n+2\\) 32-byte elements,
+    /// * the slice does not have \\(2n\\) encoded points and 2 scalars,
     /// * \\(n\\) is larger or equal to 32 (proof is too big),
-    /// * any of \\(2n\\) points are not valid compressed Ristretto points,
-    /// * any of 2 scalars are not canonical scalars modulo Ristretto group order.
-    pub fn from_bytes(slice: &[u8]) -> Result<InnerProductProof, ProofError> {
-        let b = slice.len();
-        if b % 32 != 0 {
-            return Err(ProofError::FormatError);
-        }
-        let num_elements = b / 32;
-        if num_elements < 2 {
-            return Err(ProofError::FormatError);
-        }
-        if (num_elements - 2) % 2 != 0 {
+    /// * any of the \\(L\\) and \\(R\\) points is the identity,
+    /// * any of 2 scalars are not canonical scalars modulo the group order.
+    ///
+    /// The points are checked to be valid encodings when the proof is
+    /// verified.
+    pub fn decode(slice: &[u8]) -> Result<InnerProductProof<C>, ProofError> {
+        let point_size = C::Compressed::SIZE;
+        if slice.len() < 2 * 32 || (slice.len() - 2 * 32) % (2 * point_size) != 0 {
             return Err(ProofError::FormatError);
         }
-        let lg_n = (num_elements - 2) / 2;
+        let lg_n = (slice.len() - 2 * 32) / (2 * point_size);
         if lg_n >= 32 {
             return Err(ProofError::FormatError);
         }
 
         use crate::util::read32;
 
-        let mut L_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
-        let mut R_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
-        for i in 0..lg_n {
-            let pos = 2 * i * 32;
-            L_vec.push(CompressedRistretto(read32(&slice[pos..])));
-            R_vec.push(CompressedRistretto(read32(&slice[pos + 32..])));
+        let mut L_vec: Vec<C::Compressed> = Vec::with_capacity(lg_n);
+        let mut R_vec: Vec<C::Compressed> = Vec::with_capacity(lg_n);
+        for chunk in slice[..2 * lg_n * point_size].chunks(2 * point_size) {
+            let L =
+                C::Compressed::from_slice(&chunk[..point_size]).ok_or(ProofError::FormatError)?;
+            let R =
+                C::Compressed::from_slice(&chunk[point_size..]).ok_or(ProofError::FormatError)?;
+            if L.is_identity() || R.is_identity() {
+                return Err(ProofError::FormatError);
+            }
+            L_vec.push(L);
+            R_vec.push(R);
         }
 
-        let pos = 2 * lg_n * 32;
-        let a =
-            Scalar::from_canonical_bytes(read32(&slice[pos..])).ok_or(ProofError::FormatError)?;
-        let b = Scalar::from_canonical_bytes(read32(&slice[pos + 32..]))
+        let pos = 2 * lg_n * point_size;
+        let a = C::Scalar::from_canonical_bytes(read32(&slice[pos..]))
+            .ok_or(ProofError::FormatError)?;
+        let b = C::Scalar::from_canonical_bytes(read32(&slice[pos + 32..]))
             .ok_or(ProofError::FormatError)?;
 
         Ok(InnerProductProof { L_vec, R_vec, a, b })
     }
 }
 
+impl InnerProductProof {
+    /// Create an inner-product proof for the vectors `a_vec` and `b_vec`,
+    /// which are committed to with the generators `gens` as
+    /// \\(P = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{b}, \mathbf{H} \rangle
+    /// + \langle \mathbf{a}, \mathbf{b} \rangle \cdot B\\).
+    ///
+    /// Unlike [`InnerProductProof::create`], the commitment \\(P\\) is appended
+    /// to the `transcript`, so that the proof can be used on its own.
+    /// Returns the proof together with the commitment \\(P\\).
+    pub fn prove_committed(
+        transcript: &mut impl ProofTranscript,
+        gens: &VectorPedersenGens,
+        a_vec: Vec<Scalar>,
+        b_vec: Vec<Scalar>,
+    ) -> Result<(InnerProductProof, VectorCommitment), ProofError> {
+        let n = a_vec.len();
+        if n == 0 || b_vec.len() != n {
+            return Err(ProofError::InvalidInputLength);
+        }
+
+        let c = inner_product(&a_vec, &b_vec);
+        let P = gens.commit_pair(&a_vec, &b_vec, c, Scalar::zero())?;
+        transcript.append_point(b"P", &P.compress());
+
+        let ones = vec![Scalar::one(); n];
+        let proof = InnerProductProof::create(
+            transcript,
+            &gens.pc_gens.B,
+            &ones,
+            &ones,
+            gens.G(n).cloned().collect(),
+            gens.H(n).cloned().collect(),
+            a_vec,
+            b_vec,
+        );
+        Ok((proof, P))
+    }
+
+    /// Verifies an inner-product proof created by
+    /// [`InnerProductProof::prove_committed`] for vectors of length `n`,
+    /// committed to as `P`.
+    pub fn verify_committed(
+        &self,
+        n: usize,
+        transcript: &mut impl ProofTranscript,
+        gens: &VectorPedersenGens,
+        P: &VectorCommitment,
+    ) -> Result<(), ProofError> {
+        if n > gens.gens_capacity {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
+        transcript.append_point(b"P", &P.compress());
+
+        let G: Vec<RistrettoPoint> = gens.G(n).cloned().collect();
+        let H: Vec<RistrettoPoint> = gens.H(n).cloned().collect();
+        self.verify(
+            n,
+            transcript,
+            iter::repeat(Scalar::one()).take(n),
+            iter::repeat(Scalar::one()).take(n),
+            P.as_point(),
+            &gens.pc_gens.B,
+            &G,
+            &H,
+        )
+    }
+
+    /// Deserializes the proof from a byte slice.
+    /// Returns an error in the following cases:
+    /// * the slice does not have \\(2n+2\\) 32-byte elements,
+    /// * \\(n\\) is larger or equal to 32 (proof is too big),
+    /// * any of the \\(L\\) and \\(R\\) points is the identity,
+    /// * any of 2 scalars are not canonical scalars modulo Ristretto group order.
+    ///
+    /// Use [`InnerProductProof::decode`] for proofs over other groups.
+    pub fn from_bytes(slice: &[u8]) -> Result<InnerProductProof, ProofError> {
+        InnerProductProof::decode(slice)
+    }
+}
+
+impl<C: CurvePoint> Serialize for InnerProductProof<C> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de, C: CurvePoint> Deserialize<'de> for InnerProductProof<C> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct InnerProductProofVisitor<C>(PhantomData<C>);
+
+        impl<'de, C: CurvePoint> Visitor<'de> for InnerProductProofVisitor<C> {
+            type Value = InnerProductProof<C>;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid InnerProductProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<InnerProductProof<C>, E>
+            where
+                E: serde::de::Error,
+            {
+                // Using Error::custom requires T: Display, which our error
+                // type only implements when it implements std::error::Error.
+                #[cfg(feature = "std")]
+                return InnerProductProof::decode(v).map_err(serde::de::Error::custom);
+                // In no-std contexts, drop the error message.
+                #[cfg(not(feature = "std"))]
+                return InnerProductProof::decode(v)
+                    .map_err(|_| serde::de::Error::custom("deserialization error"));
+            }
+        }
+
+        deserializer.deserialize_bytes(InnerProductProofVisitor(PhantomData))
+    }
+}
+
 /// Computes an inner product of two vectors
 /// \\[
 ///    {\langle {\mathbf{a}}, {\mathbf{b}} \rangle} = \sum\_{i=0}^{n-1} a\_i \cdot b\_i.
 /// \\]
 /// Panics if the lengths of \\(\mathbf{a}\\) and \\(\mathbf{b}\\) are not equal.
-pub fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
-    let mut out = Scalar::zero();
+pub fn inner_product<S: CurveScalar>(a: &[S], b: &[S]) -> S {
+    let mut out = S::zero();
     if a.len() != b.len() {
         panic!("inner_product(a,b): lengths of vectors do not match");
     }
@@ -428,6 +645,8 @@ pub fn inner_product(a: &[Scalar], b: &[Scalar]) -> Scalar {
 mod tests {
     use super::*;
 
+    use merlin::Transcript;
+
     use crate::util;
     use sha3::Sha3_512;
 
@@ -519,6 +738,11 @@ mod tests {
         test_helper_create(2);
     }
 
+    #[test]
+    fn make_ipp_3() {
+        test_helper_create(3);
+    }
+
     #[test]
     fn make_ipp_4() {
         test_helper_create(4);
@@ -534,6 +758,184 @@ mod tests {
         test_helper_create(64);
     }
 
+    #[test]
+    fn make_ipp_100() {
+        test_helper_create(100);
+    }
+
+    #[test]
+    fn batch_verification_terms() {
+        let mut rng = rand::thread_rng();
+
+        use crate::generators::BulletproofGens;
+        let bp_gens = BulletproofGens::new(8, 1);
+        let Q = RistrettoPoint::hash_from_bytes::<Sha3_512>(b"test point");
+
+        let mut scalars = Vec::new();
+        let mut points = Vec::new();
+        for &n in &[5, 8] {
+            let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+            let H: Vec<RistrettoPoint> = bp_gens.share(0).H(n).cloned().collect();
+            let ones = vec![Scalar::one(); n];
+
+            let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let P = RistrettoPoint::vartime_multiscalar_mul(
+                a.iter()
+                    .chain(b.iter())
+                    .chain(iter::once(&inner_product(&a, &b))),
+                G.iter().chain(H.iter()).chain(iter::once(&Q)),
+            );
+
+            let proof = InnerProductProof::create(
+                &mut Transcript::new(b"innerproducttest"),
+                &Q,
+                &ones,
+                &ones,
+                G.clone(),
+                H.clone(),
+                a,
+                b,
+            );
+
+            let (proof_scalars, proof_points) = proof
+                .verification_terms(
+                    n,
+                    &mut Transcript::new(b"innerproducttest"),
+                    &ones,
+                    &ones,
+                    &P,
+                    &Q,
+                    &G,
+                    &H,
+                )
+                .unwrap();
+
+            // Weight each proof's equation by a random scalar.
+            let w = Scalar::random(&mut rng);
+            scalars.extend(proof_scalars.into_iter().map(|s| w * s));
+            points.extend(proof_points);
+        }
+
+        assert!(RistrettoPoint::vartime_multiscalar_mul(&scalars, &points).is_identity());
+
+        scalars[0] += Scalar::one();
+        assert!(!RistrettoPoint::vartime_multiscalar_mul(&scalars, &points).is_identity());
+    }
+
+    #[test]
+    fn verification_challenges_match_scalars() {
+        let mut rng = rand::thread_rng();
+
+        use crate::generators::BulletproofGens;
+        let n = 5;
+        let bp_gens = BulletproofGens::new(n, 1);
+        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+        let H: Vec<RistrettoPoint> = bp_gens.share(0).H(n).cloned().collect();
+        let Q = RistrettoPoint::hash_from_bytes::<Sha3_512>(b"test point");
+        let ones = vec![Scalar::one(); n];
+
+        let proof = InnerProductProof::create(
+            &mut Transcript::new(b"innerproducttest"),
+            &Q,
+            &ones,
+            &ones,
+            G,
+            H,
+            (0..n).map(|_| Scalar::random(&mut rng)).collect(),
+            (0..n).map(|_| Scalar::random(&mut rng)).collect(),
+        );
+
+        let (u, u_inv, s) = proof
+            .verification_challenges(n, &mut Transcript::new(b"innerproducttest"))
+            .unwrap();
+        let (u_sq, u_inv_sq, s_sq) = proof
+            .verification_scalars(n, &mut Transcript::new(b"innerproducttest"))
+            .unwrap();
+
+        assert_eq!(u.len(), proof.L_vec.len());
+        for i in 0..u.len() {
+            assert_eq!(u[i] * u_inv[i], Scalar::one());
+            assert_eq!(u[i] * u[i], u_sq[i]);
+            assert_eq!(u_inv[i] * u_inv[i], u_inv_sq[i]);
+        }
+        assert_eq!(s, s_sq);
+    }
+
+    #[test]
+    fn make_ipp_committed() {
+        let mut rng = rand::thread_rng();
+        let n = 6;
+        let gens = VectorPedersenGens::new(8);
+
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+
+        let (proof, P) = InnerProductProof::prove_committed(
+            &mut Transcript::new(b"innerproducttest"),
+            &gens,
+            a.clone(),
+            b.clone(),
+        )
+        .unwrap();
+
+        assert!(proof
+            .verify_committed(n, &mut Transcript::new(b"innerproducttest"), &gens, &P)
+            .is_ok());
+
+        // The commitment is bound to the transcript.
+        let other_P = gens
+            .commit_pair(&b, &a, inner_product(&a, &b), Scalar::zero())
+            .unwrap();
+        assert!(proof
+            .verify_committed(
+                n,
+                &mut Transcript::new(b"innerproducttest"),
+                &gens,
+                &other_P
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn from_bytes_rejects_malformed_proofs() {
+        use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
+
+        let point = RISTRETTO_BASEPOINT_COMPRESSED.to_bytes();
+        let scalar = Scalar::one().to_bytes();
+        let encode = |elements: &[[u8; 32]]| elements.concat();
+
+        let bytes = encode(&[point, point, scalar, scalar]);
+        let proof = InnerProductProof::from_bytes(&bytes).unwrap();
+        assert_eq!(proof.to_bytes(), bytes);
+
+        // Serde uses the same canonical encoding.
+        let serde_proof: InnerProductProof =
+            bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+        assert_eq!(serde_proof.to_bytes(), bytes);
+
+        // Identity L and R points are rejected.
+        let identity = [0u8; 32];
+        assert_eq!(
+            InnerProductProof::from_bytes(&encode(&[identity, point, scalar, scalar])).unwrap_err(),
+            ProofError::FormatError
+        );
+        assert_eq!(
+            InnerProductProof::from_bytes(&encode(&[point, identity, scalar, scalar])).unwrap_err(),
+            ProofError::FormatError
+        );
+
+        // Non-canonical scalars are rejected.
+        let non_canonical = [0xffu8; 32];
+        assert_eq!(
+            InnerProductProof::from_bytes(&encode(&[point, point, non_canonical, scalar]))
+                .unwrap_err(),
+            ProofError::FormatError
+        );
+        let serialized = bincode::serialize(&encode(&[point, point, scalar, non_canonical]));
+        assert!(bincode::deserialize::<InnerProductProof>(&serialized.unwrap()).is_err());
+    }
+
     #[test]
     fn test_inner_product() {
         let a = vec![
@@ -21,17 +21,41 @@ mod notes {
     mod r1cs_proof {}
 }
 
+mod batch_verifier;
+mod curve;
 mod errors;
 mod generators;
 mod inner_product_proof;
 mod linear_proof;
 mod range_proof;
 mod transcript;
+mod vector_commitment;
+mod weighted_inner_product_proof;
 
+pub use crate::batch_verifier::BatchVerifier;
+pub use crate::curve::{CompressedPoint, CurvePoint, CurveScalar};
 pub use crate::errors::ProofError;
-pub use crate::generators::{BulletproofGens, BulletproofGensShare, PedersenGens};
+pub use crate::generators::{
+    BulletproofGens, BulletproofGensShare, PedersenGens, PedersenGensTable,
+};
+pub use crate::inner_product_proof::InnerProductProof;
 pub use crate::linear_proof::LinearProof;
-pub use crate::range_proof::RangeProof;
+pub use crate::range_proof::{RangeProof, ValueGens};
+pub use crate::transcript::{
+    first_divergence, Keccak256Transcript, Keccak256TranscriptRng, ProofTranscript,
+    RecordingTranscript, TranscriptDivergence, TranscriptOp,
+};
+pub use crate::vector_commitment::{VectorCommitment, VectorPedersenGens};
+pub use crate::weighted_inner_product_proof::{WeightedInnerProductProof, WippGens};
+
+#[cfg(feature = "evm")]
+pub use crate::curve::bn254::{Bn254Encoding, Bn254Point, Bn254Scalar};
+
+#[cfg(feature = "secp256k1")]
+pub use crate::curve::secp256k1::{Secp256k1Encoding, Secp256k1Point, Secp256k1Scalar};
+
+#[cfg(feature = "evm")]
+pub mod evm;
 
 #[doc(include = "../docs/aggregation-api.md")]
 pub mod range_proof_mpc {
@@ -42,5 +66,4 @@ pub mod range_proof_mpc {
 }
 
 #[cfg(feature = "yoloproofs")]
-#[cfg(feature = "std")]
 pub mod r1cs;
@@ -2,18 +2,27 @@
 
 extern crate alloc;
 
+use alloc::vec;
 use alloc::vec::Vec;
 
 use core::iter;
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::VartimeMultiscalarMul;
-use merlin::Transcript;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
 use rand_core::{CryptoRng, RngCore};
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
 
+use crate::batch_verifier::BatchVerifier;
 use crate::errors::ProofError;
 use crate::inner_product_proof::inner_product;
-use crate::transcript::TranscriptProtocol;
+use crate::transcript::{ProofTranscript, TranscriptProtocol};
+use crate::util;
+use crate::vector_commitment::{VectorCommitment, VectorPedersenGens};
+
+/// The challenges \\([x\_{i}]\\) and their inverses, the subset-products \\([s\_{i}]\\),
+/// the base case \\(b\_0\\) and the final challenge \\(x^{*}\\) of a linear proof.
+type VerificationScalars = (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Scalar, Scalar);
 
 /// A linear proof, which is an "lightweight" version of a Bulletproofs inner-product proof
 /// Protocol: Section E.3 of [GHL'21](https://eprint.iacr.org/2021/1397.pdf)
@@ -35,10 +44,12 @@ impl LinearProof {
     /// Create a linear proof, a lightweight variant of a Bulletproofs inner-product proof.
     /// This proves that <a, b> = c where a is secret and b is public.
     ///
-    /// The lengths of the vectors must all be the same, and must all be either 0 or a power of 2.
+    /// The lengths of the vectors must all be the same, and must not be 0.
+    /// Lengths that are not a power of 2 are folded with unbalanced rounds,
+    /// which carry the unpaired last element into the next round.
     /// The proof is created with respect to the bases \\(G\\).
     pub fn create<T: RngCore + CryptoRng>(
-        transcript: &mut Transcript,
+        transcript: &mut impl ProofTranscript,
         rng: &mut T,
         // Commitment to witness
         C: &CompressedRistretto,
@@ -63,14 +74,14 @@ impl LinearProof {
         if a_vec.len() != n {
             return Err(ProofError::InvalidInputLength);
         }
-        // All of the input vectors must have a length that is a power of two.
-        if !n.is_power_of_two() {
+        // The input vectors must not be empty.
+        if n == 0 {
             return Err(ProofError::InvalidInputLength);
         }
 
         // Append all public data to the transcript
         transcript.innerproduct_domain_sep(n as u64);
-        transcript.append_point(b"C", &C);
+        transcript.append_point(b"C", C);
         for b_i in &b_vec {
             transcript.append_scalar(b"b_i", b_i);
         }
@@ -80,22 +91,19 @@ impl LinearProof {
         transcript.append_point(b"F", &F.compress());
         transcript.append_point(b"B", &B.compress());
 
-        // Create slices G, H, a, b backed by their respective
-        // vectors. This lets us reslice as we compress the lengths
-        // of the vectors in the main loop below.
-        let mut G = &mut G_vec[..];
-        let mut a = &mut a_vec[..];
-        let mut b = &mut b_vec[..];
-
-        let lg_n = n.next_power_of_two().trailing_zeros() as usize;
+        let lg_n = util::folding_rounds(n);
         let mut L_vec = Vec::with_capacity(lg_n);
         let mut R_vec = Vec::with_capacity(lg_n);
 
+        // Each round folds the first 2k elements of the vectors into the
+        // first k, where k = n/2.  If n is odd, the last element is not
+        // paired and is moved to position k unchanged, so the vectors of
+        // the next round have length n - k.
         while n != 1 {
-            n = n / 2;
-            let (a_L, a_R) = a.split_at_mut(n);
-            let (b_L, b_R) = b.split_at_mut(n);
-            let (G_L, G_R) = G.split_at_mut(n);
+            let k = n / 2;
+            let (a_L, a_R) = a_vec[..2 * k].split_at_mut(k);
+            let (b_L, b_R) = b_vec[..2 * k].split_at_mut(k);
+            let (G_L, G_R) = G_vec[..2 * k].split_at_mut(k);
 
             let c_L = inner_product(&a_L, &b_R);
             let c_R = inner_product(&a_R, &b_L);
@@ -123,10 +131,10 @@ impl LinearProof {
             transcript.append_point(b"L", &L);
             transcript.append_point(b"R", &R);
 
-            let x_j = transcript.challenge_scalar(b"x_j");
+            let x_j: Scalar = transcript.challenge_scalar(b"x_j");
             let x_j_inv = x_j.invert();
 
-            for i in 0..n {
+            for i in 0..k {
                 // a_L = a_L + x_j^{-1} * a_R
                 a_L[i] = a_L[i] + x_j_inv * a_R[i];
                 // b_L = b_L + x_j * b_R
@@ -137,19 +145,24 @@ impl LinearProof {
                     &[G_L[i], G_R[i]],
                 );
             }
-            a = a_L;
-            b = b_L;
-            G = G_L;
+
+            if n % 2 == 1 {
+                a_vec[k] = a_vec[2 * k];
+                b_vec[k] = b_vec[2 * k];
+                G_vec[k] = G_vec[2 * k];
+            }
+
+            n -= k;
             r = r + x_j * s_j + x_j_inv * t_j;
         }
 
         let s_star = Scalar::random(rng);
         let t_star = Scalar::random(rng);
-        let S = (t_star * B + s_star * b[0] * F + s_star * G[0]).compress();
+        let S = (t_star * B + s_star * b_vec[0] * F + s_star * G_vec[0]).compress();
         transcript.append_point(b"S", &S);
 
-        let x_star = transcript.challenge_scalar(b"x_star");
-        let a_star = s_star + x_star * a[0];
+        let x_star: Scalar = transcript.challenge_scalar(b"x_star");
+        let a_star = s_star + x_star * a_vec[0];
         let r_star = t_star + x_star * r;
 
         Ok(LinearProof {
@@ -163,7 +176,7 @@ impl LinearProof {
 
     pub fn verify(
         &self,
-        transcript: &mut Transcript,
+        transcript: &mut impl ProofTranscript,
         // Commitment to witness
         C: &CompressedRistretto,
         // Generator vector
@@ -175,142 +188,450 @@ impl LinearProof {
         // Public scalar vector b
         b_vec: Vec<Scalar>,
     ) -> Result<(), ProofError> {
-        let n = b_vec.len();
-        if G.len() != n {
-            return Err(ProofError::InvalidGeneratorsLength);
+        let (scalars, points) = self.verification_terms(transcript, C, G, F, B, b_vec)?;
+
+        if RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
         }
+    }
 
-        // Append all public data to the transcript
-        transcript.innerproduct_domain_sep(n as u64);
-        transcript.append_point(b"C", &C);
-        for b_i in &b_vec {
-            transcript.append_scalar(b"b_i", b_i);
+    /// Create a linear proof that \\(\langle a, b \rangle = c\\) for the secret
+    /// vector `a_vec` and the public vector `b_vec`, where `a_vec` and \\(c\\)
+    /// are committed to with the generators `gens` as
+    /// \\(C = \langle a, G \rangle + c \cdot B + r \cdot \tilde{B}\\).
+    ///
+    /// Returns the proof together with the commitment \\(C\\).
+    pub fn prove_committed<T: RngCore + CryptoRng>(
+        transcript: &mut impl ProofTranscript,
+        rng: &mut T,
+        gens: &VectorPedersenGens,
+        // Secret scalar vector a
+        a_vec: Vec<Scalar>,
+        // Blinding factor for C
+        blinding: Scalar,
+        // Public scalar vector b
+        b_vec: Vec<Scalar>,
+    ) -> Result<(LinearProof, VectorCommitment), ProofError> {
+        let n = b_vec.len();
+        if a_vec.len() != n {
+            return Err(ProofError::InvalidInputLength);
         }
-        for G_i in G {
-            transcript.append_point(b"G_i", &G_i.compress());
+
+        let c = inner_product(&a_vec, &b_vec);
+        let C = gens.commit(&a_vec, c, blinding)?;
+        let G_vec: Vec<RistrettoPoint> = gens.G(n).cloned().collect();
+
+        let proof = LinearProof::create(
+            transcript,
+            rng,
+            &C.compress(),
+            blinding,
+            a_vec,
+            b_vec,
+            G_vec,
+            &gens.pc_gens.B,
+            &gens.pc_gens.B_blinding,
+        )?;
+        Ok((proof, C))
+    }
+
+    /// Verifies a linear proof created by [`LinearProof::prove_committed`]
+    /// for the commitment `C` and the public vector `b_vec`.
+    pub fn verify_committed(
+        &self,
+        transcript: &mut impl ProofTranscript,
+        gens: &VectorPedersenGens,
+        C: &VectorCommitment,
+        b_vec: Vec<Scalar>,
+    ) -> Result<(), ProofError> {
+        let G: Vec<RistrettoPoint> = gens.G(b_vec.len()).cloned().collect();
+        self.verify(
+            transcript,
+            &C.compress(),
+            &G,
+            &gens.pc_gens.B,
+            &gens.pc_gens.B_blinding,
+            b_vec,
+        )
+    }
+
+    /// Appends the verification equation of the proof to the `batch`,
+    /// which checks it together with the equations of other proofs in
+    /// [`BatchVerifier::verify`].
+    pub fn batch_verify<T: RngCore + CryptoRng>(
+        &self,
+        batch: &mut BatchVerifier<T>,
+        transcript: &mut impl ProofTranscript,
+        // Commitment to witness
+        C: &CompressedRistretto,
+        // Generator vector
+        G: &[RistrettoPoint],
+        // Pedersen generator F, for committing to the secret value
+        F: &RistrettoPoint,
+        // Pedersen generator B, for committing to the blinding value
+        B: &RistrettoPoint,
+        // Public scalar vector b
+        b_vec: Vec<Scalar>,
+    ) -> Result<(), ProofError> {
+        let (scalars, points) = self.verification_terms(transcript, C, G, F, B, b_vec)?;
+        batch.append(scalars, points)
+    }
+
+    /// Appends the verification equations of several independent linear
+    /// proofs, which share the generator vector \\(G\\), to the `batch`.
+    ///
+    /// The equations are combined with random weights into one, in which
+    /// the scalars of each \\(G\_i\\), \\(F\\) and \\(B\\) are summed over
+    /// all of the proofs, so that \\(G\\) contributes only \\(n\\) terms to
+    /// the multiscalar multiplication regardless of the number of proofs.
+    ///
+    /// The `proofs`, `transcripts`, `commitments` and `b_vecs` must all have
+    /// the same length, and the i-th element of each belongs to the i-th proof.
+    pub fn batch_verify_shared<T: RngCore + CryptoRng>(
+        batch: &mut BatchVerifier<T>,
+        proofs: &[LinearProof],
+        transcripts: &mut [impl ProofTranscript],
+        // Commitments to the witnesses
+        commitments: &[CompressedRistretto],
+        // Generator vector, shared by all of the proofs
+        G: &[RistrettoPoint],
+        // Pedersen generator F, for committing to the secret value
+        F: &RistrettoPoint,
+        // Pedersen generator B, for committing to the blinding value
+        B: &RistrettoPoint,
+        // Public scalar vectors b
+        b_vecs: Vec<Vec<Scalar>>,
+    ) -> Result<(), ProofError> {
+        let m = proofs.len();
+        if transcripts.len() != m || commitments.len() != m || b_vecs.len() != m {
+            return Err(ProofError::VerificationError);
         }
-        transcript.append_point(b"F", &F.compress());
-        transcript.append_point(b"B", &B.compress());
 
-        let (x_vec, x_inv_vec, b_0) = self.verification_scalars(n, transcript, b_vec)?;
-        transcript.append_point(b"S", &self.S);
-        let x_star = transcript.challenge_scalar(b"x_star");
+        let mut B_scalar = Scalar::zero();
+        let mut F_scalar = Scalar::zero();
+        let mut G_scalars = vec![Scalar::zero(); G.len()];
+        let mut proof_scalars = Vec::new();
+        let mut proof_points = Vec::new();
 
-        // Decompress the compressed L values
-        let Ls = self
-            .L_vec
+        for (((proof, transcript), C), b_vec) in proofs
             .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
-            .collect::<Result<Vec<_>, _>>()?;
+            .zip(transcripts.iter_mut())
+            .zip(commitments.iter())
+            .zip(b_vecs)
+        {
+            let (x_vec, x_inv_vec, s, b_0, x_star) =
+                proof.verification_scalars(transcript, C, G, F, B, b_vec)?;
+
+            // Weight each proof's equation, as in `verification_terms`,
+            // by an independent random scalar.
+            let w = Scalar::random(batch.rng());
+            let w_a = w * proof.a;
+            let w_x_star = w * x_star;
+
+            B_scalar += w * proof.r;
+            F_scalar += w_a * b_0;
+            for (G_scalar, s_i) in G_scalars.iter_mut().zip(s.iter()) {
+                *G_scalar += w_a * s_i;
+            }
+
+            proof_scalars.extend(
+                iter::once(-w_x_star)
+                    .chain(x_vec.iter().map(|x_j| -w_x_star * x_j))
+                    .chain(x_inv_vec.iter().map(|x_j_inv| -w_x_star * x_j_inv))
+                    .chain(iter::once(-w)),
+            );
+            for P in iter::once(C)
+                .chain(proof.L_vec.iter())
+                .chain(proof.R_vec.iter())
+                .chain(iter::once(&proof.S))
+            {
+                proof_points.push(P.decompress().ok_or(ProofError::VerificationError)?);
+            }
+        }
+
+        batch.append(
+            iter::once(B_scalar)
+                .chain(iter::once(F_scalar))
+                .chain(G_scalars)
+                .chain(proof_scalars),
+            iter::once(*B)
+                .chain(iter::once(*F))
+                .chain(G.iter().cloned())
+                .chain(proof_points),
+        )
+    }
 
-        // Decompress the compressed R values
-        let Rs = self
-            .R_vec
+    /// Create an aggregated linear proof, which proves that
+    /// \\(\langle a, b\_j \rangle = c\_j\\) for several public vectors
+    /// \\(b\_j\\) and a single secret vector \\(a\\), committed to as
+    /// \\(C = \langle a, G \rangle + r B\\).
+    ///
+    /// The relations are combined with the powers of a challenge \\(\rho\\)
+    /// into \\(\langle a, \sum\_j \rho^j b\_j \rangle = \sum\_j \rho^j c\_j\\),
+    /// which is proven by a single linear proof for the commitment
+    /// \\(C + (\sum\_j \rho^j c\_j) F\\).  The size of the proof does not
+    /// depend on the number of relations.
+    ///
+    /// The values \\(c\_j\\) are public: they are returned with the proof and
+    /// must be passed to [`LinearProof::verify_aggregated`].
+    pub fn create_aggregated<T: RngCore + CryptoRng>(
+        transcript: &mut impl ProofTranscript,
+        rng: &mut T,
+        // Commitment to the secret vector
+        C: &CompressedRistretto,
+        // Blinding factor for C
+        r: Scalar,
+        // Secret scalar vector a
+        a_vec: Vec<Scalar>,
+        // Public scalar vectors b_j
+        b_vecs: Vec<Vec<Scalar>>,
+        // Generator vector
+        G_vec: Vec<RistrettoPoint>,
+        // Pedersen generator F, for committing to the secret value
+        F: &RistrettoPoint,
+        // Pedersen generator B, for committing to the blinding value
+        B: &RistrettoPoint,
+    ) -> Result<(LinearProof, Vec<Scalar>), ProofError> {
+        let n = a_vec.len();
+        if b_vecs.is_empty() || b_vecs.iter().any(|b_vec| b_vec.len() != n) {
+            return Err(ProofError::InvalidInputLength);
+        }
+
+        let c_vec: Vec<Scalar> = b_vecs
             .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
-            .collect::<Result<Vec<_>, _>>()?;
+            .map(|b_vec| inner_product(&a_vec, b_vec))
+            .collect();
 
-        // L_R_factors = sum_{j=0}^{l-1} (x_j * L_j + x_j^{-1} * R_j)
-        //
-        // Note: in GHL'21 the verification equation is incorrect (as of 05/03/22), with x_j and x_j^{-1} reversed.
-        // (Incorrect paper equation: sum_{j=0}^{l-1} (x_j^{-1} * L_j + x_j * R_j) )
-        let L_R_factors: RistrettoPoint = RistrettoPoint::vartime_multiscalar_mul(
-            x_vec.iter().chain(x_inv_vec.iter()),
-            Ls.iter().chain(Rs.iter()),
-        );
+        let (b_vec, c) = LinearProof::aggregate_relations(transcript, C, &b_vecs, &c_vec);
+        let C = (C.decompress().ok_or(ProofError::FormatError)? + c * F).compress();
+
+        let proof = LinearProof::create(transcript, rng, &C, r, a_vec, b_vec, G_vec, F, B)?;
+        Ok((proof, c_vec))
+    }
+
+    /// Verifies an aggregated linear proof created by
+    /// [`LinearProof::create_aggregated`], for the public vectors `b_vecs`
+    /// and the values `c_vec` returned with the proof.
+    pub fn verify_aggregated(
+        &self,
+        transcript: &mut impl ProofTranscript,
+        // Commitment to the secret vector
+        C: &CompressedRistretto,
+        // Generator vector
+        G: &[RistrettoPoint],
+        // Pedersen generator F, for committing to the secret value
+        F: &RistrettoPoint,
+        // Pedersen generator B, for committing to the blinding value
+        B: &RistrettoPoint,
+        // Public scalar vectors b_j
+        b_vecs: Vec<Vec<Scalar>>,
+        // Public values c_j
+        c_vec: &[Scalar],
+    ) -> Result<(), ProofError> {
+        let n = G.len();
+        if b_vecs.is_empty()
+            || b_vecs.len() != c_vec.len()
+            || b_vecs.iter().any(|b_vec| b_vec.len() != n)
+        {
+            return Err(ProofError::VerificationError);
+        }
+
+        let (b_vec, c) = LinearProof::aggregate_relations(transcript, C, &b_vecs, c_vec);
+        let C = (C.decompress().ok_or(ProofError::VerificationError)? + c * F).compress();
+
+        self.verify(transcript, &C, G, F, B, b_vec)
+    }
+
+    /// Appends the relations \\(\langle a, b\_j \rangle = c\_j\\) to the
+    /// transcript and combines them with the powers of a challenge \\(\rho\\),
+    /// returning \\(\sum\_j \rho^j b\_j\\) and \\(\sum\_j \rho^j c\_j\\).
+    fn aggregate_relations(
+        transcript: &mut impl ProofTranscript,
+        C: &CompressedRistretto,
+        b_vecs: &[Vec<Scalar>],
+        c_vec: &[Scalar],
+    ) -> (Vec<Scalar>, Scalar) {
+        let n = b_vecs[0].len();
+        transcript.aggregated_linearproof_domain_sep(n as u64, b_vecs.len() as u64);
+        transcript.append_point(b"C", C);
+        for (b_vec, c) in b_vecs.iter().zip(c_vec.iter()) {
+            for b_i in b_vec {
+                transcript.append_scalar(b"b_i", b_i);
+            }
+            transcript.append_scalar(b"c", c);
+        }
 
-        // This is an optimized way to compute the base case G (G_0 in the paper):
-        // G_0 = sum_{i=0}^{2^{l-1}} (x<i> * G_i)
-        let s = self.subset_product(n, x_vec);
-        let G_0: RistrettoPoint = RistrettoPoint::vartime_multiscalar_mul(s.iter(), G.iter());
+        let rho: Scalar = transcript.challenge_scalar(b"rho");
+
+        let mut b_vec = vec![Scalar::zero(); n];
+        let mut c = Scalar::zero();
+        for (rho_j, (b_j, c_j)) in util::exp_iter(rho).zip(b_vecs.iter().zip(c_vec.iter())) {
+            for (b_i, b_ji) in b_vec.iter_mut().zip(b_j.iter()) {
+                *b_i += rho_j * b_ji;
+            }
+            c += rho_j * c_j;
+        }
+        (b_vec, c)
+    }
 
-        let S = self.S.decompress().ok_or(ProofError::VerificationError)?;
-        let C = C.decompress().ok_or(ProofError::VerificationError)?;
+    /// Computes the terms of the verification equation of the proof,
+    /// as a list of scalars and a list of points, such that the proof is
+    /// valid if and only if their multiscalar multiplication is the identity.
+    ///
+    /// A parent protocol can append the terms to its own, weighted by a
+    /// random scalar, to check the proof in a single multiscalar
+    /// multiplication with its other checks.
+    pub fn verification_terms(
+        &self,
+        transcript: &mut impl ProofTranscript,
+        // Commitment to witness
+        C: &CompressedRistretto,
+        // Generator vector
+        G: &[RistrettoPoint],
+        // Pedersen generator F, for committing to the secret value
+        F: &RistrettoPoint,
+        // Pedersen generator B, for committing to the blinding value
+        B: &RistrettoPoint,
+        // Public scalar vector b
+        b_vec: Vec<Scalar>,
+    ) -> Result<(Vec<Scalar>, Vec<RistrettoPoint>), ProofError> {
+        let (x_vec, x_inv_vec, s, b_0, x_star) =
+            self.verification_scalars(transcript, C, G, F, B, b_vec)?;
 
         // This matches the verification equation:
         // S == r_star * B + a_star * b_0 * F
         //      - x_star * (C + sum_{j=0}^{l-1} (x_j * L_j + x_j^{-1} * R_j))
         //      + a_star * sum_{i=0}^{2^{l-1}} (x<i> * G_i)
         //
-        // Where L_R_factors = sum_{j=0}^{l-1} (x_j * L_j + x_j^{-1} * R_j)
-        // and G_0 = sum_{i=0}^{2^{l-1}} (x<i> * G_i)
-        let expect_S = self.r * B + self.a * b_0 * F - x_star * (C + L_R_factors) + self.a * G_0;
+        // Note: in GHL'21 the verification equation is incorrect (as of 05/03/22), with x_j and x_j^{-1} reversed.
+        // (Incorrect paper equation: sum_{j=0}^{l-1} (x_j^{-1} * L_j + x_j * R_j) )
+        let scalars = iter::once(self.r)
+            .chain(iter::once(self.a * b_0))
+            .chain(iter::once(-x_star))
+            .chain(x_vec.iter().map(|x_j| -x_star * x_j))
+            .chain(x_inv_vec.iter().map(|x_j_inv| -x_star * x_j_inv))
+            .chain(s.iter().map(|s_i| self.a * s_i))
+            .chain(iter::once(-Scalar::one()))
+            .collect();
 
-        if expect_S == S {
-            Ok(())
-        } else {
-            Err(ProofError::VerificationError)
-        }
+        let points = iter::once(*B)
+            .chain(iter::once(*F))
+            .map(Ok)
+            .chain(iter::once(
+                C.decompress().ok_or(ProofError::VerificationError),
+            ))
+            .chain(
+                self.L_vec
+                    .iter()
+                    .chain(self.R_vec.iter())
+                    .map(|p| p.decompress().ok_or(ProofError::VerificationError)),
+            )
+            .chain(G.iter().cloned().map(Ok))
+            .chain(iter::once(
+                self.S.decompress().ok_or(ProofError::VerificationError),
+            ))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((scalars, points))
     }
 
-    /// Computes the vector of challenge scalars \\([x\_{i}]\\), and its inverse \\([x\_{i}^{-1}]\\)
+    /// Computes the vector of challenge scalars \\([x\_{i}]\\), its inverse \\([x\_{i}^{-1}]\\),
+    /// and the subset-products \\([s\_{i}]\\) that fold the generators \\(G\\)
     /// for combined multiscalar multiplication in a parent protocol.
-    /// Also computes \\(b_0\\) which is the base case for public vector \\(b\\).
+    /// Also computes \\(b_0\\) which is the base case for public vector \\(b\\),
+    /// and the final challenge \\(x^{*}\\).
     ///
-    /// The verifier must provide the input length \\(n\\) explicitly to avoid unbounded allocation.
-    pub(crate) fn verification_scalars(
+    /// The public inputs are appended to the `transcript` in the same way as
+    /// [`LinearProof::verify`] does, and the challenges are returned in the
+    /// order in which they were created, which is the order of the
+    /// \\(L\\) and \\(R\\) points of the proof.
+    pub fn verification_scalars(
         &self,
-        n: usize,
-        transcript: &mut Transcript,
+        transcript: &mut impl ProofTranscript,
+        // Commitment to witness
+        C: &CompressedRistretto,
+        // Generator vector
+        G: &[RistrettoPoint],
+        // Pedersen generator F, for committing to the secret value
+        F: &RistrettoPoint,
+        // Pedersen generator B, for committing to the blinding value
+        B: &RistrettoPoint,
+        // Public scalar vector b
         mut b_vec: Vec<Scalar>,
-    ) -> Result<(Vec<Scalar>, Vec<Scalar>, Scalar), ProofError> {
+    ) -> Result<VerificationScalars, ProofError> {
+        let n = b_vec.len();
+        if G.len() != n {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
         let lg_n = self.L_vec.len();
         if lg_n >= 32 {
             // 4 billion multiplications should be enough for anyone
-            // and this check prevents overflow in 1<<lg_n below.
+            // and this check bounds the length n below.
             return Err(ProofError::VerificationError);
         }
-        if n != (1 << lg_n) {
+        if n == 0 || lg_n != util::folding_rounds(n) {
             return Err(ProofError::VerificationError);
         }
 
+        // Append all public data to the transcript
+        transcript.innerproduct_domain_sep(n as u64);
+        transcript.append_point(b"C", C);
+        for b_i in &b_vec {
+            transcript.append_scalar(b"b_i", b_i);
+        }
+        for G_i in G {
+            transcript.append_point(b"G_i", &G_i.compress());
+        }
+        transcript.append_point(b"F", &F.compress());
+        transcript.append_point(b"B", &B.compress());
+
         // 1. Recompute x_k,...,x_1 based on the proof transcript
         // 2. Generate b_0 from the public vector b
         let mut n_mut = n;
-        let mut b = &mut b_vec[..];
         let mut challenges = Vec::with_capacity(lg_n);
         for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
             transcript.validate_and_append_point(b"L", L)?;
             transcript.validate_and_append_point(b"R", R)?;
-            let x_j = transcript.challenge_scalar(b"x_j");
+            let x_j: Scalar = transcript.challenge_scalar(b"x_j");
             challenges.push(x_j);
-            n_mut = n_mut / 2;
-            let (b_L, b_R) = b.split_at_mut(n_mut);
-            for i in 0..n_mut {
+            let k = n_mut / 2;
+            let (b_L, b_R) = b_vec[..2 * k].split_at_mut(k);
+            for i in 0..k {
                 b_L[i] = b_L[i] + x_j * b_R[i];
             }
-            b = b_L;
+            if n_mut % 2 == 1 {
+                b_vec[k] = b_vec[2 * k];
+            }
+            n_mut -= k;
         }
 
         // 3. Compute the challenge inverses: 1/x_k, ..., 1/x_1
         let mut challenges_inv = challenges.clone();
         Scalar::batch_invert(&mut challenges_inv);
 
-        Ok((challenges, challenges_inv, b[0]))
+        // 4. Compute the subset-products that give the base case of G:
+        // G_0 = sum_{i=0}^{n-1} (x<i> * G_i)
+        let s = self.subset_product(n, &challenges);
+
+        transcript.append_point(b"S", &self.S);
+        let x_star: Scalar = transcript.challenge_scalar(b"x_star");
+
+        Ok((challenges, challenges_inv, s, b_vec[0], x_star))
     }
 
-    /// Compute the subset-products of \\(x_j\\) inductively:
+    /// Compute the subset-products of \\(x_j\\):
     /// for i = 1..n, \\(s_i = product_(j=1^{log_2(n)}) x_j ^ b(i,j)\\)
-    /// where \\(b(i,j)\\) = 1 if the jth bit of (i-1) is 1, and 0 otherwise.
+    /// where \\(b(i,j)\\) = 1 if element (i-1) is in the right half of round j, and 0 otherwise.
     /// In GHL'21 this is referred to as the subset-product \\(x<i>\\).
     ///
     /// Note that this is different from the Bulletproofs \\(s_i\\) generation,
-    /// where \\(b(i, j)\\) = 1 if the jth bit of (i-1) is 1, and -1 otherwise.
-    fn subset_product(&self, n: usize, challenges: Vec<Scalar>) -> Vec<Scalar> {
-        let lg_n = self.L_vec.len();
-
-        let mut s = Vec::with_capacity(n);
-        s.push(Scalar::one());
-        for i in 1..n {
-            let lg_i = (32 - 1 - (i as u32).leading_zeros()) as usize;
-            let k = 1 << lg_i;
-            // The challenges are stored in "creation order" as [x_k,...,x_1],
-            // so x_{lg(i)+1} = is indexed by (lg_n-1) - lg_i
-            let x_lg_i = challenges[(lg_n - 1) - lg_i];
-            s.push(s[i - k] * x_lg_i);
-        }
-
-        s
+    /// where the left halves are multiplied by \\(x_j^{-1}\\) instead of 1.
+    fn subset_product(&self, n: usize, challenges: &[Scalar]) -> Vec<Scalar> {
+        let ones = vec![Scalar::one(); challenges.len()];
+        util::folding_products(n, &ones, challenges)
     }
 
     /// Returns the size in bytes required to serialize the linear proof.
@@ -361,6 +682,7 @@ impl LinearProof {
     /// * the slice does not have \\(2n+3\\) 32-byte elements,
     /// * \\(n\\) is larger or equal to 32 (proof is too big),
     /// * any of \\(2n + 1\\) points are not valid compressed Ristretto points,
+    /// * any of the \\(L\\) and \\(R\\) points is the identity,
     /// * any of 2 scalars are not canonical scalars modulo Ristretto group order.
     pub fn from_bytes(slice: &[u8]) -> Result<LinearProof, ProofError> {
         let b = slice.len();
@@ -385,8 +707,13 @@ impl LinearProof {
         let mut R_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
         for i in 0..lg_n {
             let pos = 2 * i * 32;
-            L_vec.push(CompressedRistretto(read32(&slice[pos..])));
-            R_vec.push(CompressedRistretto(read32(&slice[pos + 32..])));
+            let L = CompressedRistretto(read32(&slice[pos..]));
+            let R = CompressedRistretto(read32(&slice[pos + 32..]));
+            if L.is_identity() || R.is_identity() {
+                return Err(ProofError::FormatError);
+            }
+            L_vec.push(L);
+            R_vec.push(R);
         }
 
         let pos = 2 * lg_n * 32;
@@ -406,10 +733,54 @@ impl LinearProof {
     }
 }
 
+impl Serialize for LinearProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for LinearProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct LinearProofVisitor;
+
+        impl<'de> Visitor<'de> for LinearProofVisitor {
+            type Value = LinearProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid LinearProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<LinearProof, E>
+            where
+                E: serde::de::Error,
+            {
+                // Using Error::custom requires T: Display, which our error
+                // type only implements when it implements std::error::Error.
+                #[cfg(feature = "std")]
+                return LinearProof::from_bytes(v).map_err(serde::de::Error::custom);
+                // In no-std contexts, drop the error message.
+                #[cfg(not(feature = "std"))]
+                return LinearProof::from_bytes(v)
+                    .map_err(|_| serde::de::Error::custom("deserialization error"));
+            }
+        }
+
+        deserializer.deserialize_bytes(LinearProofVisitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use merlin::Transcript;
+
     fn test_helper(n: usize) {
         let mut rng = rand::thread_rng();
 
@@ -462,15 +833,63 @@ mod tests {
         let deserialized_proof = LinearProof::from_bytes(&serialized_proof).unwrap();
         let mut serde_verifier_transcript = Transcript::new(b"linearprooftest");
         assert!(deserialized_proof
+            .verify(&mut serde_verifier_transcript, &C, &G, &F, &B, b.clone())
+            .is_ok());
+
+        // Test serde with the same encoding
+        let serde_proof: LinearProof =
+            bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+        assert_eq!(serde_proof.to_bytes(), serialized_proof);
+        let mut serde_verifier_transcript = Transcript::new(b"linearprooftest");
+        assert!(serde_proof
             .verify(&mut serde_verifier_transcript, &C, &G, &F, &B, b)
             .is_ok());
     }
 
+    #[test]
+    fn from_bytes_rejects_malformed_proofs() {
+        use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
+
+        let point = RISTRETTO_BASEPOINT_COMPRESSED.to_bytes();
+        let scalar = Scalar::one().to_bytes();
+        let encode = |elements: &[[u8; 32]]| elements.concat();
+
+        assert!(LinearProof::from_bytes(&encode(&[point, point, point, scalar, scalar])).is_ok());
+
+        // Identity L and R points are rejected.
+        let identity = [0u8; 32];
+        assert_eq!(
+            LinearProof::from_bytes(&encode(&[identity, point, point, scalar, scalar]))
+                .unwrap_err(),
+            ProofError::FormatError
+        );
+        assert_eq!(
+            LinearProof::from_bytes(&encode(&[point, identity, point, scalar, scalar]))
+                .unwrap_err(),
+            ProofError::FormatError
+        );
+
+        // Non-canonical scalars are rejected.
+        let non_canonical = [0xffu8; 32];
+        assert_eq!(
+            LinearProof::from_bytes(&encode(&[point, point, point, non_canonical, scalar]))
+                .unwrap_err(),
+            ProofError::FormatError
+        );
+        let serialized = bincode::serialize(&encode(&[point, point, point, scalar, non_canonical]));
+        assert!(bincode::deserialize::<LinearProof>(&serialized.unwrap()).is_err());
+    }
+
     #[test]
     fn test_linear_proof_base() {
         test_helper(1);
     }
 
+    #[test]
+    fn test_linear_proof_3() {
+        test_helper(3);
+    }
+
     #[test]
     fn test_linear_proof_16() {
         test_helper(16);
@@ -485,4 +904,207 @@ mod tests {
     fn test_linear_proof_64() {
         test_helper(64);
     }
+
+    #[test]
+    fn test_linear_proof_100() {
+        test_helper(100);
+    }
+
+    #[test]
+    fn test_linear_proof_committed() {
+        let mut rng = rand::thread_rng();
+        let n = 10;
+        let gens = VectorPedersenGens::new(n);
+
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let r = Scalar::random(&mut rng);
+
+        let (proof, C) = LinearProof::prove_committed(
+            &mut Transcript::new(b"linearprooftest"),
+            &mut rng,
+            &gens,
+            a.clone(),
+            r,
+            b.clone(),
+        )
+        .unwrap();
+
+        assert!(gens
+            .verify_opening(&C, &a, inner_product(&a, &b), r)
+            .is_ok());
+        assert!(proof
+            .verify_committed(
+                &mut Transcript::new(b"linearprooftest"),
+                &gens,
+                &C,
+                b.clone()
+            )
+            .is_ok());
+
+        // A commitment to another value does not verify.
+        let other_C = C + gens.commit(&[], Scalar::one(), Scalar::zero()).unwrap();
+        assert!(proof
+            .verify_committed(&mut Transcript::new(b"linearprooftest"), &gens, &other_C, b)
+            .is_err());
+    }
+
+    fn aggregated_helper(n: usize, k: usize) {
+        let mut rng = rand::thread_rng();
+
+        use crate::generators::{BulletproofGens, PedersenGens};
+        let bp_gens = BulletproofGens::new(n, 1);
+        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+
+        let pedersen_gens = PedersenGens::default();
+        let F = pedersen_gens.B;
+        let B = pedersen_gens.B_blinding;
+
+        // a is a private vector, b_1, ..., b_k are public vectors
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b_vecs: Vec<Vec<_>> = (0..k)
+            .map(|_| (0..n).map(|_| Scalar::random(&mut rng)).collect())
+            .collect();
+
+        // C = <a, G> + r * B
+        let r = Scalar::random(&mut rng);
+        let C = RistrettoPoint::vartime_multiscalar_mul(
+            a.iter().chain(iter::once(&r)),
+            G.iter().chain(iter::once(&B)),
+        )
+        .compress();
+
+        let (proof, c_vec) = LinearProof::create_aggregated(
+            &mut Transcript::new(b"linearprooftest"),
+            &mut rng,
+            &C,
+            r,
+            a.clone(),
+            b_vecs.clone(),
+            G.clone(),
+            &F,
+            &B,
+        )
+        .unwrap();
+
+        assert_eq!(c_vec.len(), k);
+        for (b_vec, c) in b_vecs.iter().zip(c_vec.iter()) {
+            assert_eq!(inner_product(&a, b_vec), *c);
+        }
+
+        assert!(proof
+            .verify_aggregated(
+                &mut Transcript::new(b"linearprooftest"),
+                &C,
+                &G,
+                &F,
+                &B,
+                b_vecs.clone(),
+                &c_vec,
+            )
+            .is_ok());
+
+        // Claiming a different value for any of the relations fails.
+        let mut wrong_c_vec = c_vec.clone();
+        wrong_c_vec[k - 1] += Scalar::one();
+        assert!(proof
+            .verify_aggregated(
+                &mut Transcript::new(b"linearprooftest"),
+                &C,
+                &G,
+                &F,
+                &B,
+                b_vecs,
+                &wrong_c_vec,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_aggregated_linear_proof_1() {
+        aggregated_helper(16, 1);
+    }
+
+    #[test]
+    fn test_aggregated_linear_proof_3() {
+        aggregated_helper(16, 3);
+    }
+
+    #[test]
+    fn test_aggregated_linear_proof_5_of_7() {
+        aggregated_helper(7, 5);
+    }
+
+    #[test]
+    fn test_batch_verify_shared() {
+        let mut rng = rand::thread_rng();
+        let n = 12;
+        let m = 4;
+
+        use crate::generators::{BulletproofGens, PedersenGens};
+        let bp_gens = BulletproofGens::new(n, 1);
+        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+
+        let pedersen_gens = PedersenGens::default();
+        let F = pedersen_gens.B;
+        let B = pedersen_gens.B_blinding;
+
+        let mut proofs = Vec::new();
+        let mut commitments = Vec::new();
+        let mut b_vecs = Vec::new();
+        for _ in 0..m {
+            let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+            let r = Scalar::random(&mut rng);
+            let c = inner_product(&a, &b);
+            let C = RistrettoPoint::vartime_multiscalar_mul(
+                a.iter().chain(iter::once(&r)).chain(iter::once(&c)),
+                G.iter().chain(iter::once(&B)).chain(iter::once(&F)),
+            )
+            .compress();
+
+            let proof = LinearProof::create(
+                &mut Transcript::new(b"linearprooftest"),
+                &mut rng,
+                &C,
+                r,
+                a,
+                b.clone(),
+                G.clone(),
+                &F,
+                &B,
+            )
+            .unwrap();
+
+            proofs.push(proof);
+            commitments.push(C);
+            b_vecs.push(b);
+        }
+
+        let batch_helper = |commitments: &[CompressedRistretto]| {
+            let mut transcripts = vec![Transcript::new(b"linearprooftest"); m];
+            let mut batch = BatchVerifier::new(rand::thread_rng());
+            LinearProof::batch_verify_shared(
+                &mut batch,
+                &proofs,
+                &mut transcripts,
+                commitments,
+                &G,
+                &F,
+                &B,
+                b_vecs.clone(),
+            )?;
+            batch.verify()
+        };
+
+        assert!(batch_helper(&commitments).is_ok());
+
+        // Swapping the commitments of two proofs makes the batch fail.
+        let mut swapped = commitments.clone();
+        swapped.swap(0, 1);
+        assert!(batch_helper(&swapped).is_err());
+
+        // So does a missing commitment.
+        assert!(batch_helper(&commitments[1..]).is_err());
+    }
 }
@@ -0,0 +1,487 @@
+//! Confidential multi-asset transfers in the style of the
+//! [spacesuit](https://github.com/interstellar/spacesuit) cloak.
+//!
+//! A [`Value`] is a pair of a quantity `q` and a flavor (asset type) `f`.
+//! The [`cloak`] gadget proves that a list of committed input values and
+//! a list of committed output values hold the same total quantity of
+//! every flavor, and that all quantities are 64-bit integers.  Inputs
+//! and outputs can have different lengths, and the flavors stay hidden.
+//!
+//! The gadget is a pipeline of smaller gadgets:
+//!
+//! ```text
+//! inputs --value_shuffle--> merge_in --k_mix--> merge_out
+//!                                                   |
+//!                                             padded_shuffle
+//!                                                   |
+//! outputs <-value_shuffle-- split_in --k_mix--> split_out
+//! ```
+//!
+//! The prover groups the inputs by flavor in the first shuffle, so that
+//! the [`k_mix`] chain merges every flavor into a single value, padded
+//! with zero values.  The outputs are reduced the same way, and the
+//! padded shuffle proves that both reductions agree.
+
+#![allow(non_snake_case)]
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::max;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use rand_core::{CryptoRng, RngCore};
+
+use super::{
+    ConstraintSystem, LinearCombination, PermProof, Prover, R1CSError,
+    RandomizableConstraintSystem, RandomizedConstraintSystem, Variable, Verifier,
+};
+
+/// Bit width of the quantities.
+pub const QUANTITY_BITS: usize = 64;
+
+/// A quantity of some flavor of asset.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Value {
+    /// Quantity
+    pub q: u64,
+    /// Flavor
+    pub f: Scalar,
+}
+
+/// Commitments to the quantity and the flavor of a [`Value`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommittedValue {
+    /// Commitment to the quantity
+    pub q: CompressedRistretto,
+    /// Commitment to the flavor
+    pub f: CompressedRistretto,
+}
+
+/// A [`Value`] whose quantity and flavor are variables of a constraint system.
+#[derive(Copy, Clone, Debug)]
+pub struct AllocatedValue {
+    /// Variable for the quantity
+    pub q: Variable,
+    /// Variable for the flavor
+    pub f: Variable,
+    /// Assignment of the value, known only to the prover
+    pub assignment: Option<Value>,
+}
+
+impl Value {
+    /// Returns the zero value, with zero quantity and zero flavor.
+    pub fn zero() -> Value {
+        Value {
+            q: 0,
+            f: Scalar::zero(),
+        }
+    }
+
+    /// Commits to the quantity and the flavor of the value with random
+    /// blinding factors, and allocates them as high-level variables.
+    pub fn commit<T: RngCore + CryptoRng>(
+        &self,
+        prover: &mut Prover,
+        rng: &mut T,
+    ) -> (CommittedValue, AllocatedValue) {
+        let (q_commit, q) = prover.commit(self.q.into(), Scalar::random(rng));
+        let (f_commit, f) = prover.commit(self.f, Scalar::random(rng));
+
+        (
+            CommittedValue {
+                q: q_commit,
+                f: f_commit,
+            },
+            AllocatedValue {
+                q,
+                f,
+                assignment: Some(*self),
+            },
+        )
+    }
+}
+
+impl CommittedValue {
+    /// Allocates the committed quantity and flavor as high-level variables.
+    pub fn commit(&self, verifier: &mut Verifier) -> AllocatedValue {
+        AllocatedValue {
+            q: verifier.commit(self.q),
+            f: verifier.commit(self.f),
+            assignment: None,
+        }
+    }
+}
+
+impl AllocatedValue {
+    /// Allocates an unconstrained value as the two inputs of a multiplier.
+    pub fn allocate<CS: ConstraintSystem>(
+        cs: &mut CS,
+        assignment: Option<Value>,
+    ) -> Result<AllocatedValue, R1CSError> {
+        let (q, f, _) = cs.allocate_multiplier(assignment.map(|v| (v.q.into(), v.f)))?;
+
+        Ok(AllocatedValue { q, f, assignment })
+    }
+
+    /// Allocates a value that is constrained to be [`Value::zero`].
+    pub fn zero<CS: ConstraintSystem>(cs: &mut CS) -> Result<AllocatedValue, R1CSError> {
+        let value = AllocatedValue::allocate(cs, Some(Value::zero()))?;
+        cs.constrain(value.q.into());
+        cs.constrain(value.f.into());
+
+        Ok(value)
+    }
+}
+
+/// Enforces that the outputs are either a merge or a move of the inputs:
+///
+/// * merge: `A.f = B.f`, `C.q = 0` and `D = (A.q + B.q, A.f)`,
+/// * move: `C = A` and `D = B`.
+///
+/// Both cases are checked with a single multiplier, whose inputs are
+/// compressed with the powers of a challenge from the second phase.
+pub fn mix<CS: RandomizableConstraintSystem>(
+    cs: &mut CS,
+    A: AllocatedValue,
+    B: AllocatedValue,
+    C: AllocatedValue,
+    D: AllocatedValue,
+) -> Result<(), R1CSError> {
+    cs.specify_randomized_constraints(move |cs| {
+        let w = cs.challenge_scalar(b"mix challenge");
+        let w2 = w * w;
+        let w3 = w2 * w;
+
+        let (_, _, mul_out) = cs.multiply(
+            (A.q - C.q) + (A.f - C.f) * w + (B.q - D.q) * w2 + (B.f - D.f) * w3,
+            C.q + (A.f - B.f) * w + (D.q - A.q - B.q) * w2 + (D.f - A.f) * w3,
+        );
+
+        // One of the two factors must be zero.
+        cs.constrain(mul_out.into());
+
+        Ok(())
+    })
+}
+
+/// Enforces a chain of [`mix`] gadgets over `k` inputs:
+/// the first mix takes the first two inputs, and every next mix takes
+/// the second output of the previous one and the next input.
+///
+/// The `intermediates` are the second outputs of all but the last mix,
+/// so there are `k - 2` of them (none when `k < 2`).  The `outputs` are
+/// the first outputs of all mixes, followed by the second output of the
+/// last one.  With a single input, the output is the input.
+pub fn k_mix<CS: RandomizableConstraintSystem>(
+    cs: &mut CS,
+    inputs: Vec<AllocatedValue>,
+    intermediates: Vec<AllocatedValue>,
+    outputs: Vec<AllocatedValue>,
+) -> Result<(), R1CSError> {
+    let k = inputs.len();
+    if k == 0 || outputs.len() != k || intermediates.len() != max(k, 2) - 2 {
+        return Err(R1CSError::GadgetError {
+            description: "k_mix needs k outputs and k - 2 intermediate values for k inputs".into(),
+        });
+    }
+
+    if k == 1 {
+        cs.constrain(inputs[0].q - outputs[0].q);
+        cs.constrain(inputs[0].f - outputs[0].f);
+        return Ok(());
+    }
+
+    for i in 0..k - 1 {
+        let A = if i == 0 {
+            inputs[0]
+        } else {
+            intermediates[i - 1]
+        };
+        let D = if i == k - 2 {
+            outputs[k - 1]
+        } else {
+            intermediates[i]
+        };
+        mix(cs, A, inputs[i + 1], outputs[i], D)?;
+    }
+
+    Ok(())
+}
+
+/// Enforces that the values `y` are a permutation of the values `x`,
+/// moving quantities and flavors together.
+pub fn value_shuffle<CS: RandomizableConstraintSystem>(
+    cs: &mut CS,
+    x: Vec<AllocatedValue>,
+    y: Vec<AllocatedValue>,
+) -> Result<(), R1CSError> {
+    PermProof::create_randomized_tuple_constraints(
+        cs,
+        x.iter().map(|v| vec![v.q, v.f]).collect(),
+        y.iter().map(|v| vec![v.q, v.f]).collect(),
+    )
+}
+
+/// Enforces that the values `y` are a permutation of the values `x`,
+/// after padding the shorter side with zero values.
+pub fn padded_shuffle<CS: RandomizableConstraintSystem>(
+    cs: &mut CS,
+    mut x: Vec<AllocatedValue>,
+    mut y: Vec<AllocatedValue>,
+) -> Result<(), R1CSError> {
+    while x.len() < y.len() {
+        x.push(AllocatedValue::zero(cs)?);
+    }
+    while y.len() < x.len() {
+        y.push(AllocatedValue::zero(cs)?);
+    }
+
+    value_shuffle(cs, x, y)
+}
+
+/// Enforces that the quantity of v is in the range [0, 2^n).
+pub fn range_proof<CS: ConstraintSystem>(
+    cs: &mut CS,
+    mut v: LinearCombination,
+    v_assignment: Option<u64>,
+    n: usize,
+) -> Result<(), R1CSError> {
+    let mut exp_2 = Scalar::one();
+    for i in 0..n {
+        // Create low-level variables and add them to constraints
+        let (a, b, o) = cs.allocate_multiplier(v_assignment.map(|q| {
+            let bit: u64 = (q >> i) & 1;
+            ((1 - bit).into(), bit.into())
+        }))?;
+
+        // Enforce a * b = 0, so one of (a,b) is zero
+        cs.constrain(o.into());
+
+        // Enforce that a = 1 - b, so they both are 1 or 0.
+        cs.constrain(a + (b - 1u64));
+
+        // Add `-b_i*2^i` to the linear combination
+        // in order to form the following constraint by the end of the loop:
+        // v = Sum(b_i * 2^i, i = 0..n-1)
+        v = v - b * exp_2;
+
+        exp_2 = exp_2 + exp_2;
+    }
+
+    // Enforce that v = Sum(b_i * 2^i, i = 0..n-1)
+    cs.constrain(v);
+
+    Ok(())
+}
+
+/// Computes the assignments of a [`k_mix`] chain that merges adjacent
+/// values of the same flavor and moves the others.
+///
+/// Returns the intermediate and the output assignments.
+fn k_mix_assignments(inputs: &[Value]) -> Result<(Vec<Value>, Vec<Value>), R1CSError> {
+    let k = inputs.len();
+    let mut intermediates = Vec::with_capacity(max(k, 2) - 2);
+    let mut outputs = Vec::with_capacity(k);
+
+    let mut A = inputs[0];
+    for B in inputs.iter().skip(1) {
+        let (C, D) = if A.f == B.f {
+            let q = A.q.checked_add(B.q).ok_or(R1CSError::GadgetError {
+                description: "the total quantity of a flavor overflows".into(),
+            })?;
+            (Value::zero(), Value { q, f: A.f })
+        } else {
+            (A, *B)
+        };
+        outputs.push(C);
+        intermediates.push(D);
+        A = D;
+    }
+    // The last second output is the last output, not an intermediate.
+    intermediates.pop();
+    outputs.push(A);
+
+    Ok((intermediates, outputs))
+}
+
+/// The inputs, intermediates and outputs of a [`k_mix`] chain.
+type KMixValues = (
+    Vec<AllocatedValue>,
+    Vec<AllocatedValue>,
+    Vec<AllocatedValue>,
+);
+
+/// Allocates the values of a [`k_mix`] chain over `values`, which are
+/// grouped by flavor by the prover.
+///
+/// Returns the allocated inputs, intermediates and outputs.
+fn allocate_k_mix<CS: ConstraintSystem>(
+    cs: &mut CS,
+    values: Option<Vec<Value>>,
+    k: usize,
+) -> Result<KMixValues, R1CSError> {
+    let (inputs, intermediates, outputs) = match values {
+        Some(mut values) => {
+            // Stable sort, so that values of the same flavor become adjacent.
+            values.sort_by(|a, b| a.f.as_bytes().cmp(b.f.as_bytes()));
+            let (intermediates, outputs) = k_mix_assignments(&values)?;
+            (
+                values.into_iter().map(Some).collect(),
+                intermediates.into_iter().map(Some).collect(),
+                outputs.into_iter().map(Some).collect(),
+            )
+        }
+        None => (vec![None; k], vec![None; max(k, 2) - 2], vec![None; k]),
+    };
+
+    let mut allocate = |assignments: Vec<Option<Value>>| {
+        assignments
+            .into_iter()
+            .map(|v| AllocatedValue::allocate(cs, v))
+            .collect::<Result<Vec<_>, _>>()
+    };
+
+    Ok((
+        allocate(inputs)?,
+        allocate(intermediates)?,
+        allocate(outputs)?,
+    ))
+}
+
+/// Enforces that the `outputs` hold the same total quantity of every
+/// flavor as the `inputs`, and that all input and output quantities are
+/// in the range [0, 2^64).
+///
+/// The prover must provide the assignments of all inputs and outputs,
+/// the verifier none of them.  Values of zero quantity keep their
+/// flavor, so a flavor that has only zero quantities on one side must
+/// also appear on the other side for the prover to succeed.
+pub fn cloak<CS: RandomizableConstraintSystem>(
+    cs: &mut CS,
+    inputs: Vec<AllocatedValue>,
+    outputs: Vec<AllocatedValue>,
+) -> Result<(), R1CSError> {
+    let m = inputs.len();
+    let n = outputs.len();
+    if m == 0 || n == 0 {
+        return Err(R1CSError::GadgetError {
+            description: "cloak needs at least one input and one output".into(),
+        });
+    }
+
+    let input_assignments: Option<Vec<Value>> = inputs.iter().map(|v| v.assignment).collect();
+    let output_assignments: Option<Vec<Value>> = outputs.iter().map(|v| v.assignment).collect();
+
+    // Allocate all the values in the first phase, the shuffles and mixes
+    // add their constraints in the second phase.
+    let (merge_in, merge_mid, merge_out) = allocate_k_mix(cs, input_assignments, m)?;
+    let (split_in, split_mid, split_out) = allocate_k_mix(cs, output_assignments, n)?;
+
+    for value in inputs.iter().chain(outputs.iter()) {
+        range_proof(
+            cs,
+            value.q.into(),
+            value.assignment.map(|v| v.q),
+            QUANTITY_BITS,
+        )?;
+    }
+
+    value_shuffle(cs, inputs, merge_in.clone())?;
+    k_mix(cs, merge_in, merge_mid, merge_out.clone())?;
+    padded_shuffle(cs, merge_out, split_out.clone())?;
+    k_mix(cs, split_in.clone(), split_mid, split_out)?;
+    value_shuffle(cs, split_in, outputs)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulletproofGens, PedersenGens};
+    use merlin::Transcript;
+
+    fn value(q: u64, f: u64) -> Value {
+        Value { q, f: f.into() }
+    }
+
+    fn cloak_helper(inputs: &[Value], outputs: &[Value]) -> Result<(), R1CSError> {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(2048, 1);
+        let mut rng = rand::thread_rng();
+
+        let (proof, input_commits, output_commits) = {
+            let mut transcript = Transcript::new(b"CloakTest");
+            let mut prover = Prover::new(&pc_gens, &mut transcript);
+
+            let (input_commits, input_vars): (Vec<_>, Vec<_>) = inputs
+                .iter()
+                .map(|v| v.commit(&mut prover, &mut rng))
+                .unzip();
+            let (output_commits, output_vars): (Vec<_>, Vec<_>) = outputs
+                .iter()
+                .map(|v| v.commit(&mut prover, &mut rng))
+                .unzip();
+
+            cloak(&mut prover, input_vars, output_vars)?;
+            let proof = prover.prove(&bp_gens)?;
+
+            (proof, input_commits, output_commits)
+        };
+
+        let mut transcript = Transcript::new(b"CloakTest");
+        let mut verifier = Verifier::new(&mut transcript);
+
+        let input_vars = input_commits
+            .iter()
+            .map(|c| c.commit(&mut verifier))
+            .collect();
+        let output_vars = output_commits
+            .iter()
+            .map(|c| c.commit(&mut verifier))
+            .collect();
+
+        cloak(&mut verifier, input_vars, output_vars)?;
+        verifier.verify(&proof, &pc_gens, &bp_gens)
+    }
+
+    #[test]
+    fn cloak_single_asset() {
+        assert!(cloak_helper(&[value(10, 1)], &[value(10, 1)]).is_ok());
+        assert!(cloak_helper(&[value(10, 1)], &[value(3, 1), value(7, 1)]).is_ok());
+        assert!(cloak_helper(&[value(3, 1), value(7, 1)], &[value(10, 1)]).is_ok());
+        assert!(cloak_helper(&[value(10, 1)], &[value(11, 1)]).is_err());
+        assert!(cloak_helper(&[value(10, 1)], &[value(10, 2)]).is_err());
+    }
+
+    #[test]
+    fn cloak_multi_asset() {
+        let inputs = [value(5, 1), value(8, 2), value(2, 1)];
+        let outputs = [value(8, 2), value(4, 1), value(3, 1)];
+        assert!(cloak_helper(&inputs, &outputs).is_ok());
+
+        let outputs = [value(1, 1), value(8, 2), value(6, 1), value(0, 1)];
+        assert!(cloak_helper(&inputs, &outputs).is_ok());
+
+        // Zero quantities keep their flavor on both sides
+        let inputs = [value(5, 1), value(8, 2), value(2, 1), value(0, 3)];
+        let outputs = [
+            value(8, 2),
+            value(4, 1),
+            value(3, 1),
+            value(0, 3),
+            value(0, 3),
+        ];
+        assert!(cloak_helper(&inputs, &outputs).is_ok());
+
+        // Moving a unit of quantity from one flavor to another
+        let outputs = [value(7, 2), value(4, 1), value(4, 1)];
+        assert!(cloak_helper(&inputs, &outputs).is_err());
+    }
+
+    #[test]
+    fn cloak_empty_is_rejected() {
+        assert!(cloak_helper(&[], &[value(1, 1)]).is_err());
+        assert!(cloak_helper(&[value(1, 1)], &[]).is_err());
+    }
+}
@@ -1,8 +1,9 @@
 //! Definition of the constraint system trait.
 
 use super::{LinearCombination, R1CSError, Variable};
+use crate::curve::CurveScalar;
+use crate::transcript::ProofTranscript;
 use curve25519_dalek::scalar::Scalar;
-use merlin::Transcript;
 
 /// The interface for a constraint system, abstracting over the prover
 /// and verifier's roles.
@@ -16,11 +17,18 @@ use merlin::Transcript;
 /// verifier, gadgets for the constraint system should be written
 /// using the `ConstraintSystem` trait, so that the prover and
 /// verifier share the logic for specifying constraints.
-pub trait ConstraintSystem {
+///
+/// The constraints are over the scalars `S` of the group of the proof,
+/// which are `ristretto255` scalars by default.  Gadgets for other
+/// groups take a bound such as `CS: ConstraintSystem<S>`.
+pub trait ConstraintSystem<S: CurveScalar = Scalar> {
+    /// The transcript the proof is bound to.
+    type Transcript: ProofTranscript;
+
     /// Leases the proof transcript to the user, so they can
     /// add extra data to which the proof must be bound, but which
     /// is not available before creation of the constraint system.
-    fn transcript(&mut self) -> &mut Transcript;
+    fn transcript(&mut self) -> &mut Self::Transcript;
 
     /// Allocate and constrain multiplication variables.
     ///
@@ -38,8 +46,8 @@ pub trait ConstraintSystem {
     /// Returns `(left, right, out)` for use in further constraints.
     fn multiply(
         &mut self,
-        left: LinearCombination,
-        right: LinearCombination,
+        left: LinearCombination<S>,
+        right: LinearCombination<S>,
     ) -> (Variable, Variable, Variable);
 
     /// Allocate a single variable.
@@ -52,7 +60,7 @@ pub trait ConstraintSystem {
     /// has the `right` assigned to zero and all its variables committed.
     ///
     /// Returns unconstrained `Variable` for use in further constraints.
-    fn allocate(&mut self, assignment: Option<Scalar>) -> Result<Variable, R1CSError>;
+    fn allocate(&mut self, assignment: Option<S>) -> Result<Variable, R1CSError>;
 
     /// Allocate variables `left`, `right`, and `out`
     /// with the implicit constraint that
@@ -63,7 +71,7 @@ pub trait ConstraintSystem {
     /// Returns `(left, right, out)` for use in further constraints.
     fn allocate_multiplier(
         &mut self,
-        input_assignments: Option<(Scalar, Scalar)>,
+        input_assignments: Option<(S, S)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError>;
 
     /// Counts the amount of allocated multipliers.
@@ -73,7 +81,7 @@ pub trait ConstraintSystem {
     /// ```text
     /// lc = 0
     /// ```
-    fn constrain(&mut self, lc: LinearCombination);
+    fn constrain(&mut self, lc: LinearCombination<S>);
 }
 
 /// An extension to the constraint system trait that permits randomized constraints.
@@ -81,9 +89,9 @@ pub trait ConstraintSystem {
 /// while gadgets that need randomization should use trait bound `CS: RandomizedConstraintSystem`.
 /// Gadgets generally _should not_ use this trait as a bound on the CS argument: it should be used
 /// by the higher-order protocol that composes gadgets together.
-pub trait RandomizableConstraintSystem: ConstraintSystem {
+pub trait RandomizableConstraintSystem<S: CurveScalar = Scalar>: ConstraintSystem<S> {
     /// Represents a concrete type for the CS in a randomization phase.
-    type RandomizedCS: RandomizedConstraintSystem;
+    type RandomizedCS: RandomizedConstraintSystem<S>;
 
     /// Specify additional variables and constraints randomized using a challenge scalar
     /// bound to the assignments of the non-randomized variables.
@@ -114,7 +122,7 @@ pub trait RandomizableConstraintSystem: ConstraintSystem {
 ///
 /// Note: this trait also includes `ConstraintSystem` trait
 /// in order to allow composition of gadgets: e.g. a shuffle gadget can be used in both phases.
-pub trait RandomizedConstraintSystem: ConstraintSystem {
+pub trait RandomizedConstraintSystem<S: CurveScalar = Scalar>: ConstraintSystem<S> {
     /// Generates a challenge scalar.
     ///
     /// ### Usage
@@ -131,5 +139,5 @@ pub trait RandomizedConstraintSystem: ConstraintSystem {
     ///     // ...
     /// })
     /// ```
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> S;
 }
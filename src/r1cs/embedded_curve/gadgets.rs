@@ -0,0 +1,471 @@
+//! Constraint system gadgets for the embedded curve.
+
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+use curve25519_dalek::scalar::Scalar;
+
+use super::point::{EmbeddedPoint, EMBEDDED_D};
+use super::scalar::EmbeddedScalar;
+use crate::r1cs::{ConstraintSystem, LinearCombination, R1CSError, Variable};
+
+/// A point of the embedded curve whose coordinates are linear
+/// combinations of the variables of a constraint system.
+///
+/// Every `AllocatedPoint` built by this module is constrained to be on
+/// the curve, which is what makes the complete addition law sound.
+#[derive(Clone, Debug)]
+pub struct AllocatedPoint {
+    /// The \\(x\\) coordinate
+    pub x: LinearCombination,
+    /// The \\(y\\) coordinate
+    pub y: LinearCombination,
+    /// Assignment of the point, known only to the prover
+    pub assignment: Option<EmbeddedPoint>,
+}
+
+/// A scalar of the embedded curve as a vector of bits, least
+/// significant first.
+#[derive(Clone, Debug)]
+pub struct AllocatedScalar {
+    /// The bits of the scalar, each constrained to 0 or 1
+    pub bits: Vec<LinearCombination>,
+    /// Assignment of the scalar, known only to the prover
+    pub assignment: Option<EmbeddedScalar>,
+}
+
+impl AllocatedPoint {
+    /// Creates a point with constant coordinates, without allocating variables.
+    pub fn constant(point: EmbeddedPoint) -> Self {
+        AllocatedPoint {
+            x: point.x().into(),
+            y: point.y().into(),
+            assignment: Some(point),
+        }
+    }
+
+    /// Allocates a point as the two inputs of a multiplier,
+    /// and constrains it to be on the curve.
+    pub fn allocate<CS: ConstraintSystem>(
+        cs: &mut CS,
+        assignment: Option<EmbeddedPoint>,
+    ) -> Result<Self, R1CSError> {
+        let (x, y, _) = cs.allocate_multiplier(assignment.map(|p| (p.x(), p.y())))?;
+
+        AllocatedPoint::from_variables(cs, x, y, assignment)
+    }
+
+    /// Creates a point from variables for its coordinates, such as
+    /// committed high-level variables, and constrains it to be on the curve.
+    pub fn from_variables<CS: ConstraintSystem>(
+        cs: &mut CS,
+        x: Variable,
+        y: Variable,
+        assignment: Option<EmbeddedPoint>,
+    ) -> Result<Self, R1CSError> {
+        // -x^2 + y^2 = 1 + d x^2 y^2
+        let (_, _, xx) = cs.multiply(x.into(), x.into());
+        let (_, _, yy) = cs.multiply(y.into(), y.into());
+        let (_, _, xxyy) = cs.multiply(xx.into(), yy.into());
+        cs.constrain(yy - xx - Scalar::one() - xxyy * EMBEDDED_D);
+
+        Ok(AllocatedPoint {
+            x: x.into(),
+            y: y.into(),
+            assignment,
+        })
+    }
+}
+
+impl AllocatedScalar {
+    /// Creates a scalar with constant bits, without allocating variables.
+    ///
+    /// Only the `n` least significant bits of the scalar are kept.
+    pub fn constant(scalar: EmbeddedScalar, n: usize) -> Self {
+        AllocatedScalar {
+            bits: (0..n)
+                .map(|i| Scalar::from(scalar.bit(i) as u64).into())
+                .collect(),
+            assignment: Some(scalar),
+        }
+    }
+
+    /// Allocates the `n` least significant bits of a scalar,
+    /// and constrains each of them to be 0 or 1.
+    pub fn allocate<CS: ConstraintSystem>(
+        cs: &mut CS,
+        assignment: Option<EmbeddedScalar>,
+        n: usize,
+    ) -> Result<Self, R1CSError> {
+        if let Some(scalar) = assignment {
+            if (n..256).any(|i| scalar.bit(i)) {
+                return Err(R1CSError::GadgetError {
+                    description: "scalar does not fit in the number of bits".into(),
+                });
+            }
+        }
+
+        let bits = (0..n)
+            .map(|i| {
+                let (a, b, o) = cs.allocate_multiplier(assignment.map(|scalar| {
+                    let bit = scalar.bit(i) as u64;
+                    ((1 - bit).into(), bit.into())
+                }))?;
+
+                // Enforce a * b = 0 and a = 1 - b, so that b is a bit
+                cs.constrain(o.into());
+                cs.constrain(a + (b - 1u64));
+
+                Ok(b.into())
+            })
+            .collect::<Result<Vec<_>, R1CSError>>()?;
+
+        Ok(AllocatedScalar { bits, assignment })
+    }
+
+    /// Returns the integer value \\(\sum_i b_i 2^i\\) of the bits, for
+    /// example to constrain it to a committed variable.
+    pub fn value(&self) -> LinearCombination {
+        let mut exp_2 = Scalar::one();
+        let mut value = LinearCombination::default();
+        for bit in self.bits.iter() {
+            value = value + bit.clone() * exp_2;
+            exp_2 = exp_2 + exp_2;
+        }
+        value
+    }
+
+    fn bit_assignment(&self, i: usize) -> Option<bool> {
+        self.assignment.map(|scalar| scalar.bit(i))
+    }
+}
+
+/// Allocates the quotient `numerator / denominator` given its assignment
+/// and the assignment of the denominator.
+fn divide<CS: ConstraintSystem>(
+    cs: &mut CS,
+    numerator: LinearCombination,
+    denominator: LinearCombination,
+    assignment: Option<(Scalar, Scalar)>,
+) -> Result<LinearCombination, R1CSError> {
+    let (quotient, d, n) = cs.allocate_multiplier(assignment)?;
+    cs.constrain(d - denominator);
+    cs.constrain(n - numerator);
+
+    Ok(quotient.into())
+}
+
+/// Enforces that `p` and `q` are the same point.
+pub fn constrain_equal<CS: ConstraintSystem>(cs: &mut CS, p: &AllocatedPoint, q: &AllocatedPoint) {
+    cs.constrain(p.x.clone() - q.x.clone());
+    cs.constrain(p.y.clone() - q.y.clone());
+}
+
+/// Computes the sum of two points with the complete addition law,
+/// using 7 multipliers.
+pub fn add<CS: ConstraintSystem>(
+    cs: &mut CS,
+    p: &AllocatedPoint,
+    q: &AllocatedPoint,
+) -> Result<AllocatedPoint, R1CSError> {
+    let (_, _, x1y2) = cs.multiply(p.x.clone(), q.y.clone());
+    let (_, _, y1x2) = cs.multiply(p.y.clone(), q.x.clone());
+    let (_, _, x1x2) = cs.multiply(p.x.clone(), q.x.clone());
+    let (_, _, y1y2) = cs.multiply(p.y.clone(), q.y.clone());
+    let (_, _, t) = cs.multiply(x1x2.into(), y1y2.into());
+
+    let assignment = p.assignment.and_then(|p| q.assignment.map(|q| (p, q)));
+    let dt = assignment.map(|(p, q)| EMBEDDED_D * p.x() * q.x() * p.y() * q.y());
+    let sum = assignment.map(|(p, q)| p + q);
+
+    // x3 (1 + d t) = x1 y2 + y1 x2
+    let x = divide(
+        cs,
+        x1y2 + y1x2,
+        t * EMBEDDED_D + Scalar::one(),
+        sum.and_then(|sum| dt.map(|dt| (sum.x(), Scalar::one() + dt))),
+    )?;
+    // y3 (1 - d t) = y1 y2 + x1 x2
+    let y = divide(
+        cs,
+        y1y2 + x1x2,
+        LinearCombination::from(Scalar::one()) - t * EMBEDDED_D,
+        sum.and_then(|sum| dt.map(|dt| (sum.y(), Scalar::one() - dt))),
+    )?;
+
+    Ok(AllocatedPoint {
+        x,
+        y,
+        assignment: sum,
+    })
+}
+
+/// Computes the double of a point, using 5 multipliers.
+pub fn double<CS: ConstraintSystem>(
+    cs: &mut CS,
+    p: &AllocatedPoint,
+) -> Result<AllocatedPoint, R1CSError> {
+    // On the curve, y^2 - x^2 = 1 + d x^2 y^2, so the denominators
+    // of the addition law only need the squares of the coordinates.
+    let (_, _, xx) = cs.multiply(p.x.clone(), p.x.clone());
+    let (_, _, yy) = cs.multiply(p.y.clone(), p.y.clone());
+    let (_, _, xy) = cs.multiply(p.x.clone(), p.y.clone());
+
+    let double = p.assignment.map(|p| p.double());
+    let squares = p.assignment.map(|p| (p.x() * p.x(), p.y() * p.y()));
+
+    // x3 (y^2 - x^2) = 2 x y
+    let x = divide(
+        cs,
+        xy * Scalar::from(2u64),
+        yy - xx,
+        double.and_then(|double| squares.map(|(xx, yy)| (double.x(), yy - xx))),
+    )?;
+    // y3 (2 - y^2 + x^2) = y^2 + x^2
+    let y = divide(
+        cs,
+        yy + xx,
+        Scalar::from(2u64) - yy + xx,
+        double
+            .and_then(|double| squares.map(|(xx, yy)| (double.y(), Scalar::from(2u64) - yy + xx))),
+    )?;
+
+    Ok(AllocatedPoint {
+        x,
+        y,
+        assignment: double,
+    })
+}
+
+/// Returns `a` if the `bit` is 1 and `b` if it is 0, using 2 multipliers.
+fn select<CS: ConstraintSystem>(
+    cs: &mut CS,
+    bit: LinearCombination,
+    bit_assignment: Option<bool>,
+    a: &AllocatedPoint,
+    b: &AllocatedPoint,
+) -> AllocatedPoint {
+    let (_, _, dx) = cs.multiply(bit.clone(), a.x.clone() - b.x.clone());
+    let (_, _, dy) = cs.multiply(bit, a.y.clone() - b.y.clone());
+
+    AllocatedPoint {
+        x: b.x.clone() + dx,
+        y: b.y.clone() + dy,
+        assignment: bit_assignment.and_then(|bit| if bit { a.assignment } else { b.assignment }),
+    }
+}
+
+/// Computes the product of a constant `base` point and an allocated scalar,
+/// using 8 multipliers for every 2 bits of the scalar.
+///
+/// The bits are processed in windows of 2, and the multiples of the
+/// base for each window are looked up with linear combinations of the
+/// bits and their product.
+pub fn fixed_base_mul<CS: ConstraintSystem>(
+    cs: &mut CS,
+    base: EmbeddedPoint,
+    scalar: &AllocatedScalar,
+) -> Result<AllocatedPoint, R1CSError> {
+    let mut acc: Option<AllocatedPoint> = None;
+    let mut window_base = base;
+
+    for (w, bits) in scalar.bits.chunks(2).enumerate() {
+        let T1 = window_base;
+        let T2 = T1.double();
+        let T3 = T2 + T1;
+        let identity = EmbeddedPoint::identity();
+
+        let b0 = bits[0].clone();
+        let index = (0..bits.len()).try_fold(0usize, |index, i| {
+            scalar
+                .bit_assignment(2 * w + i)
+                .map(|bit| index + ((bit as usize) << i))
+        });
+
+        let lookup = if bits.len() == 2 {
+            let b1 = bits[1].clone();
+            let (_, _, b01) = cs.multiply(b0.clone(), b1.clone());
+            let coordinate = |c: fn(&EmbeddedPoint) -> Scalar| {
+                b0.clone() * (c(&T1) - c(&identity))
+                    + b1.clone() * (c(&T2) - c(&identity))
+                    + b01 * (c(&T3) - c(&T2) - c(&T1) + c(&identity))
+                    + c(&identity)
+            };
+            AllocatedPoint {
+                x: coordinate(EmbeddedPoint::x),
+                y: coordinate(EmbeddedPoint::y),
+                assignment: index.map(|index| [identity, T1, T2, T3][index]),
+            }
+        } else {
+            AllocatedPoint {
+                x: b0.clone() * T1.x(),
+                y: b0 * (T1.y() - Scalar::one()) + Scalar::one(),
+                assignment: index.map(|index| [identity, T1][index]),
+            }
+        };
+
+        acc = Some(match acc {
+            None => lookup,
+            Some(acc) => add(cs, &acc, &lookup)?,
+        });
+        window_base = T2.double();
+    }
+
+    Ok(acc.unwrap_or_else(|| AllocatedPoint::constant(EmbeddedPoint::identity())))
+}
+
+/// Computes the product of an allocated `base` point and an allocated
+/// scalar with double-and-add, using 14 multipliers per bit of the scalar.
+pub fn variable_base_mul<CS: ConstraintSystem>(
+    cs: &mut CS,
+    base: &AllocatedPoint,
+    scalar: &AllocatedScalar,
+) -> Result<AllocatedPoint, R1CSError> {
+    let identity = AllocatedPoint::constant(EmbeddedPoint::identity());
+    let n = scalar.bits.len();
+    if n == 0 {
+        return Ok(identity);
+    }
+
+    // Start with the most significant bit, to skip doubling the identity.
+    let mut acc = select(
+        cs,
+        scalar.bits[n - 1].clone(),
+        scalar.bit_assignment(n - 1),
+        base,
+        &identity,
+    );
+    for i in (0..n - 1).rev() {
+        acc = double(cs, &acc)?;
+        let sum = add(cs, &acc, base)?;
+        acc = select(
+            cs,
+            scalar.bits[i].clone(),
+            scalar.bit_assignment(i),
+            &sum,
+            &acc,
+        );
+    }
+
+    Ok(acc)
+}
+
+/// Enforces that \\((R, s)\\) is a valid Schnorr signature under the
+/// verification key `A` for the challenge `e`, that is
+/// \\( s G = R + e A \\) where \\(G\\) is the generator of the embedded curve.
+///
+/// The challenge must be computed outside of the constraint system,
+/// see [`Signature::challenge`](super::Signature::challenge).
+/// Both `A` and `R` should be checked to be in the prime-order subgroup
+/// when they are public.
+pub fn verify_signature<CS: ConstraintSystem>(
+    cs: &mut CS,
+    A: &AllocatedPoint,
+    R: &AllocatedPoint,
+    s: &AllocatedScalar,
+    e: &AllocatedScalar,
+) -> Result<(), R1CSError> {
+    let sG = fixed_base_mul(cs, EmbeddedPoint::generator(), s)?;
+    let eA = variable_base_mul(cs, A, e)?;
+    let R_plus_eA = add(cs, R, &eA)?;
+
+    constrain_equal(cs, &sG, &R_plus_eA);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::r1cs::{Prover, Verifier};
+    use crate::{BulletproofGens, PedersenGens};
+    use merlin::Transcript;
+
+    #[derive(Copy, Clone)]
+    enum Op {
+        Add,
+        Double,
+        FixedBaseMul,
+        VariableBaseMul,
+    }
+
+    /// Applies `op` to the allocated point `p` (and the generator) or the
+    /// `n`-bit scalar `k`, and constrains the result to be `expected`.
+    fn op_gadget<CS: ConstraintSystem>(
+        cs: &mut CS,
+        op: Op,
+        p: Option<EmbeddedPoint>,
+        k: Option<EmbeddedScalar>,
+        n: usize,
+        expected: EmbeddedPoint,
+    ) -> Result<(), R1CSError> {
+        let p = AllocatedPoint::allocate(cs, p)?;
+        let k = AllocatedScalar::allocate(cs, k, n)?;
+        let g = AllocatedPoint::constant(EmbeddedPoint::generator());
+
+        let result = match op {
+            Op::Add => add(cs, &p, &g)?,
+            Op::Double => double(cs, &p)?,
+            Op::FixedBaseMul => fixed_base_mul(cs, EmbeddedPoint::generator(), &k)?,
+            Op::VariableBaseMul => variable_base_mul(cs, &p, &k)?,
+        };
+        constrain_equal(cs, &result, &AllocatedPoint::constant(expected));
+
+        Ok(())
+    }
+
+    fn op_helper(
+        op: Op,
+        p: EmbeddedPoint,
+        k: u64,
+        n: usize,
+        expected: EmbeddedPoint,
+    ) -> Result<(), R1CSError> {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(512, 1);
+
+        let proof = {
+            let mut transcript = Transcript::new(b"EmbeddedCurveTest");
+            let mut prover = Prover::new(&pc_gens, &mut transcript);
+            op_gadget(&mut prover, op, Some(p), Some(k.into()), n, expected)?;
+            prover.prove(&bp_gens)?
+        };
+
+        let mut transcript = Transcript::new(b"EmbeddedCurveTest");
+        let mut verifier = Verifier::new(&mut transcript);
+        op_gadget(&mut verifier, op, None, None, n, expected)?;
+        verifier.verify(&proof, &pc_gens, &bp_gens)
+    }
+
+    #[test]
+    fn add_and_double() {
+        let g = EmbeddedPoint::generator();
+        let p = g * EmbeddedScalar::random(&mut rand::thread_rng());
+        let identity = EmbeddedPoint::identity();
+
+        assert!(op_helper(Op::Add, p, 0, 0, p + g).is_ok());
+        assert!(op_helper(Op::Add, identity, 0, 0, g).is_ok());
+        assert!(op_helper(Op::Add, -g, 0, 0, identity).is_ok());
+        assert!(op_helper(Op::Add, p, 0, 0, p + p).is_err());
+
+        assert!(op_helper(Op::Double, p, 0, 0, p.double()).is_ok());
+        assert!(op_helper(Op::Double, identity, 0, 0, identity).is_ok());
+        assert!(op_helper(Op::Double, p, 0, 0, p).is_err());
+    }
+
+    #[test]
+    fn scalar_mul() {
+        let g = EmbeddedPoint::generator();
+        let p = g * EmbeddedScalar::random(&mut rand::thread_rng());
+
+        for &(k, n) in &[(0u64, 8), (1, 1), (0b1011_0110_1101, 12), (0x5a5a, 15)] {
+            let k_scalar = EmbeddedScalar::from(k);
+            assert!(op_helper(Op::FixedBaseMul, p, k, n, g * k_scalar).is_ok());
+            assert!(op_helper(Op::VariableBaseMul, p, k, n, p * k_scalar).is_ok());
+            assert!(op_helper(Op::VariableBaseMul, p, k, n, g * k_scalar + g).is_err());
+        }
+
+        // The scalar does not fit in the bits
+        assert!(op_helper(Op::FixedBaseMul, p, 0x100, 8, g).is_err());
+    }
+}
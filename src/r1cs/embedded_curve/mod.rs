@@ -0,0 +1,42 @@
+//! An elliptic curve embedded in the constraint system.
+//!
+//! The coordinates of the points of the embedded curve are elements of
+//! the scalar field of `ristretto255`, the field over which the
+//! constraint system is defined.  Curve operations are therefore cheap
+//! to express as constraints, which allows proving statements about
+//! signatures, commitments or hashes over this curve in an
+//! [`R1CSProof`](super::R1CSProof).
+//!
+//! The curve is the complete twisted Edwards curve
+//! \\[
+//! -x^2 + y^2 = 1 + d x^2 y^2
+//! \\]
+//! where \\(d\\) is the non-square [`EMBEDDED_D`].  Its order is
+//! \\(4q\\), where \\(q\\) is the 251-bit prime [`ORDER`], and its
+//! 2-torsion subgroup is cyclic, which is required for a complete
+//! Edwards model.
+//!
+//! The curve was found with the complex multiplication method: the
+//! Hilbert class polynomial of discriminant \\(-3355\\) has roots
+//! modulo the field order that give curves of order \\(4q\\), and
+//! descending the 2-isogeny volcano by two levels gives a curve with a
+//! cyclic 2-torsion subgroup.  The generator is \\(4P\\), where \\(P\\)
+//! is the point with \\(y = 2\\) and an even \\(x\\).
+//!
+//! The gadgets of this module allocate points and scalars in a
+//! [`ConstraintSystem`](super::ConstraintSystem), and provide point
+//! addition, doubling, fixed-base and variable-base scalar
+//! multiplication, and the verification of Schnorr [`Signature`]s.
+
+mod gadgets;
+mod point;
+mod scalar;
+mod schnorr;
+
+pub use self::gadgets::{
+    add, constrain_equal, double, fixed_base_mul, variable_base_mul, verify_signature,
+    AllocatedPoint, AllocatedScalar,
+};
+pub use self::point::{EmbeddedPoint, EMBEDDED_D};
+pub use self::scalar::{EmbeddedScalar, ORDER, SCALAR_BITS};
+pub use self::schnorr::{Signature, SigningKey, VerificationKey, CHALLENGE_BITS};
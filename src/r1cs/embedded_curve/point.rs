@@ -0,0 +1,247 @@
+//! Native arithmetic on the points of the embedded curve.
+
+use core::convert::TryFrom;
+use core::ops::{Add, Mul, Neg, Sub};
+use curve25519_dalek::scalar::Scalar;
+use subtle::{Choice, ConditionallySelectable};
+
+use super::scalar::{EmbeddedScalar, ORDER, SCALAR_BITS};
+
+/// The curve constant \\(d\\) of the embedded curve
+/// \\( -x^2 + y^2 = 1 + d x^2 y^2 \\).
+pub const EMBEDDED_D: Scalar = Scalar::from_bits([
+    183, 160, 128, 200, 133, 187, 254, 163, 57, 125, 89, 185, 52, 131, 97, 137, 242, 17, 164, 140,
+    97, 108, 191, 82, 29, 141, 206, 172, 12, 199, 176, 4,
+]);
+
+/// The \\(x\\) coordinate of the generator of the prime-order subgroup.
+const GENERATOR_X: Scalar = Scalar::from_bits([
+    116, 120, 45, 55, 44, 33, 236, 90, 53, 62, 155, 175, 138, 63, 161, 210, 171, 137, 28, 7, 78,
+    137, 133, 254, 79, 141, 149, 181, 17, 6, 233, 11,
+]);
+
+/// The \\(y\\) coordinate of the generator of the prime-order subgroup.
+const GENERATOR_Y: Scalar = Scalar::from_bits([
+    25, 208, 198, 76, 167, 235, 19, 145, 21, 104, 152, 29, 178, 30, 254, 98, 95, 236, 199, 36, 59,
+    79, 109, 252, 7, 173, 191, 145, 40, 212, 213, 4,
+]);
+
+/// A point of the embedded curve in affine coordinates.
+///
+/// The addition law is complete, so it has no exceptional cases and
+/// handles doubling and the identity \\((0, 1)\\) as well.
+///
+/// Deserialization checks that the point is on the curve, as
+/// [`EmbeddedPoint::from_affine`] does.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "AffineCoordinates")]
+pub struct EmbeddedPoint {
+    x: Scalar,
+    y: Scalar,
+}
+
+/// The serialized coordinates of an [`EmbeddedPoint`], which may not be
+/// on the curve.
+#[derive(Deserialize)]
+#[serde(rename = "EmbeddedPoint")]
+struct AffineCoordinates {
+    x: Scalar,
+    y: Scalar,
+}
+
+impl TryFrom<AffineCoordinates> for EmbeddedPoint {
+    type Error = &'static str;
+
+    fn try_from(coordinates: AffineCoordinates) -> Result<Self, Self::Error> {
+        EmbeddedPoint::from_affine(coordinates.x, coordinates.y)
+            .ok_or("the point is not on the embedded curve")
+    }
+}
+
+impl EmbeddedPoint {
+    /// Returns the identity point \\((0, 1)\\).
+    pub fn identity() -> Self {
+        EmbeddedPoint {
+            x: Scalar::zero(),
+            y: Scalar::one(),
+        }
+    }
+
+    /// Returns the generator of the prime-order subgroup.
+    pub fn generator() -> Self {
+        EmbeddedPoint {
+            x: GENERATOR_X,
+            y: GENERATOR_Y,
+        }
+    }
+
+    /// Creates a point from its affine coordinates.
+    ///
+    /// Returns `None` if the coordinates are not on the curve.
+    pub fn from_affine(x: Scalar, y: Scalar) -> Option<Self> {
+        let point = EmbeddedPoint { x, y };
+        if point.is_on_curve() {
+            Some(point)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the \\(x\\) coordinate.
+    pub fn x(&self) -> Scalar {
+        self.x
+    }
+
+    /// Returns the \\(y\\) coordinate.
+    pub fn y(&self) -> Scalar {
+        self.y
+    }
+
+    fn is_on_curve(&self) -> bool {
+        let xx = self.x * self.x;
+        let yy = self.y * self.y;
+        yy - xx == Scalar::one() + EMBEDDED_D * xx * yy
+    }
+
+    /// Checks that the point is in the prime-order subgroup,
+    /// that is, it has no component of order dividing the cofactor 4.
+    pub fn is_torsion_free(&self) -> bool {
+        let mut acc = EmbeddedPoint::identity();
+        for i in (0..SCALAR_BITS).rev() {
+            acc = acc.double();
+            if (ORDER >> i as u32) & ethnum::U256::ONE == ethnum::U256::ONE {
+                acc = acc + *self;
+            }
+        }
+        acc == EmbeddedPoint::identity()
+    }
+
+    /// Computes \\(2P\\).
+    pub fn double(&self) -> Self {
+        *self + *self
+    }
+
+    /// Multiplies the point by a scalar with a double-and-always-add
+    /// ladder, so that the sequence of operations does not depend on
+    /// the bits of the scalar.
+    fn ladder(&self, scalar: &EmbeddedScalar) -> Self {
+        let mut acc = EmbeddedPoint::identity();
+        for i in (0..SCALAR_BITS).rev() {
+            acc = acc.double();
+            let sum = acc + *self;
+            acc = EmbeddedPoint::conditional_select(&acc, &sum, Choice::from(scalar.bit(i) as u8));
+        }
+        acc
+    }
+}
+
+impl Add for EmbeddedPoint {
+    type Output = EmbeddedPoint;
+
+    fn add(self, rhs: EmbeddedPoint) -> EmbeddedPoint {
+        // Unified addition for a = -1:
+        //   x3 = (x1 y2 + y1 x2) / (1 + d x1 x2 y1 y2)
+        //   y3 = (y1 y2 + x1 x2) / (1 - d x1 x2 y1 y2)
+        // The denominators never vanish, since d is not a square.
+        let x1x2 = self.x * rhs.x;
+        let y1y2 = self.y * rhs.y;
+        let t = EMBEDDED_D * x1x2 * y1y2;
+
+        EmbeddedPoint {
+            x: (self.x * rhs.y + self.y * rhs.x) * (Scalar::one() + t).invert(),
+            y: (y1y2 + x1x2) * (Scalar::one() - t).invert(),
+        }
+    }
+}
+
+impl Neg for EmbeddedPoint {
+    type Output = EmbeddedPoint;
+
+    fn neg(self) -> EmbeddedPoint {
+        EmbeddedPoint {
+            x: -self.x,
+            y: self.y,
+        }
+    }
+}
+
+impl Sub for EmbeddedPoint {
+    type Output = EmbeddedPoint;
+
+    fn sub(self, rhs: EmbeddedPoint) -> EmbeddedPoint {
+        self + (-rhs)
+    }
+}
+
+impl ConditionallySelectable for EmbeddedPoint {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        EmbeddedPoint {
+            x: Scalar::conditional_select(&a.x, &b.x, choice),
+            y: Scalar::conditional_select(&a.y, &b.y, choice),
+        }
+    }
+}
+
+impl Mul<EmbeddedScalar> for EmbeddedPoint {
+    type Output = EmbeddedPoint;
+
+    fn mul(self, scalar: EmbeddedScalar) -> EmbeddedPoint {
+        self.ladder(&scalar)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generator() {
+        let g = EmbeddedPoint::generator();
+        assert!(g.is_on_curve());
+        assert_ne!(g, EmbeddedPoint::identity());
+
+        // q * G = 0, so the order of G is the prime q
+        assert!(g.is_torsion_free());
+    }
+
+    #[test]
+    fn group_law() {
+        let g = EmbeddedPoint::generator();
+        let identity = EmbeddedPoint::identity();
+
+        assert_eq!(g + identity, g);
+        assert_eq!(g - g, identity);
+        assert_eq!(g.double(), g * EmbeddedScalar::from(2u64));
+        assert_eq!(g * EmbeddedScalar::zero(), identity);
+        assert_eq!(g * -EmbeddedScalar::one(), -g);
+
+        let mut rng = rand::thread_rng();
+        let a = EmbeddedScalar::random(&mut rng);
+        let b = EmbeddedScalar::random(&mut rng);
+        assert_eq!(g * a + g * b, g * (a + b));
+        assert_eq!((g * a) * b, g * (a * b));
+        assert!((g * a).is_on_curve());
+    }
+
+    #[test]
+    fn torsion() {
+        // (0, -1) has order 2
+        let t = EmbeddedPoint::from_affine(Scalar::zero(), -Scalar::one()).unwrap();
+        assert_eq!(t.double(), EmbeddedPoint::identity());
+        assert!(!t.is_torsion_free());
+        assert!(!(EmbeddedPoint::generator() + t).is_torsion_free());
+
+        assert!(EmbeddedPoint::from_affine(Scalar::one(), Scalar::one()).is_none());
+    }
+
+    #[test]
+    fn deserialize_checks_the_curve() {
+        let g = EmbeddedPoint::generator();
+        let bytes = bincode::serialize(&g).unwrap();
+        assert_eq!(bincode::deserialize::<EmbeddedPoint>(&bytes).unwrap(), g);
+
+        // (1, 1) is not on the curve.
+        let bytes = bincode::serialize(&(Scalar::one(), Scalar::one())).unwrap();
+        assert!(bincode::deserialize::<EmbeddedPoint>(&bytes).is_err());
+    }
+}
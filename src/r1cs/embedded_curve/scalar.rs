@@ -0,0 +1,177 @@
+//! Arithmetic modulo the order of the prime-order subgroup of the embedded curve.
+
+use core::ops::{Add, Mul, Neg, Sub};
+use ethnum::U256;
+use rand_core::{CryptoRng, RngCore};
+
+/// The order \\( q = 2^{250} + 6334867285664809656969020453784863155 \\)
+/// of the prime-order subgroup of the embedded curve.
+pub const ORDER: U256 = U256::from_words(
+    0x0400_0000_0000_0000_0000_0000_0000_0000,
+    0x04c4_0d12_4346_5a5e_812b_878c_0fcf_41b3,
+);
+
+/// Bit length of the canonical encoding of an [`EmbeddedScalar`].
+pub const SCALAR_BITS: usize = 251;
+
+/// An integer modulo the order [`ORDER`] of the embedded curve subgroup.
+///
+/// These scalars multiply points of the embedded curve.  They are not
+/// the `Scalar`s of `curve25519-dalek`, which are the coordinates of
+/// the embedded curve.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct EmbeddedScalar(U256);
+
+impl EmbeddedScalar {
+    /// Returns the scalar \\(0\\).
+    pub fn zero() -> Self {
+        EmbeddedScalar(U256::ZERO)
+    }
+
+    /// Returns the scalar \\(1\\).
+    pub fn one() -> Self {
+        EmbeddedScalar(U256::ONE)
+    }
+
+    /// Samples a uniformly random scalar.
+    pub fn random<T: RngCore + CryptoRng>(rng: &mut T) -> Self {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        EmbeddedScalar::from_bytes_mod_order(&bytes)
+    }
+
+    /// Interprets the little-endian `bytes` as an integer of any length,
+    /// and reduces it modulo the group order.
+    pub fn from_bytes_mod_order(bytes: &[u8]) -> Self {
+        let mut acc = EmbeddedScalar::zero();
+        for byte in bytes.iter().rev() {
+            for i in (0..8).rev() {
+                acc = acc + acc;
+                if (byte >> i) & 1 == 1 {
+                    acc = acc + EmbeddedScalar::one();
+                }
+            }
+        }
+        acc
+    }
+
+    /// Decodes a scalar from its canonical 32-byte little-endian encoding.
+    ///
+    /// Returns `None` if the encoding is not reduced modulo the group order.
+    pub fn from_canonical_bytes(bytes: [u8; 32]) -> Option<Self> {
+        let value = U256::from_le_bytes(bytes);
+        if value < ORDER {
+            Some(EmbeddedScalar(value))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the canonical 32-byte little-endian encoding of the scalar.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0.to_le_bytes()
+    }
+
+    /// Returns the bit of the scalar at position `i`, counting from the
+    /// least significant bit.
+    pub fn bit(&self, i: usize) -> bool {
+        i < 256 && (self.0 >> i as u32) & U256::ONE == U256::ONE
+    }
+}
+
+impl From<u64> for EmbeddedScalar {
+    fn from(x: u64) -> EmbeddedScalar {
+        // 2^64 is smaller than the group order, so no reduction is needed.
+        EmbeddedScalar(U256::from(x))
+    }
+}
+
+impl Add for EmbeddedScalar {
+    type Output = EmbeddedScalar;
+
+    fn add(self, rhs: EmbeddedScalar) -> EmbeddedScalar {
+        // Both operands are below 2^251, so the sum cannot overflow.
+        let sum = self.0 + rhs.0;
+        if sum >= ORDER {
+            EmbeddedScalar(sum - ORDER)
+        } else {
+            EmbeddedScalar(sum)
+        }
+    }
+}
+
+impl Neg for EmbeddedScalar {
+    type Output = EmbeddedScalar;
+
+    fn neg(self) -> EmbeddedScalar {
+        if self.0 == U256::ZERO {
+            self
+        } else {
+            EmbeddedScalar(ORDER - self.0)
+        }
+    }
+}
+
+impl Sub for EmbeddedScalar {
+    type Output = EmbeddedScalar;
+
+    fn sub(self, rhs: EmbeddedScalar) -> EmbeddedScalar {
+        self + (-rhs)
+    }
+}
+
+impl Mul for EmbeddedScalar {
+    type Output = EmbeddedScalar;
+
+    fn mul(self, rhs: EmbeddedScalar) -> EmbeddedScalar {
+        // Double-and-add over the bits of rhs, most significant first.
+        let mut acc = EmbeddedScalar::zero();
+        for i in (0..SCALAR_BITS).rev() {
+            acc = acc + acc;
+            if rhs.bit(i) {
+                acc = acc + self;
+            }
+        }
+        acc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let a = EmbeddedScalar::from(1234567u64);
+        let b = EmbeddedScalar::from(7654321u64);
+        assert_eq!(a * b, EmbeddedScalar::from(1234567u64 * 7654321));
+        assert_eq!(a + b - b, a);
+        assert_eq!(a - b + b, a);
+        assert_eq!(-a + a, EmbeddedScalar::zero());
+
+        let minus_one = -EmbeddedScalar::one();
+        assert_eq!(minus_one * minus_one, EmbeddedScalar::one());
+    }
+
+    #[test]
+    fn reduction() {
+        let order = ORDER.to_le_bytes();
+        assert_eq!(
+            EmbeddedScalar::from_bytes_mod_order(&order),
+            EmbeddedScalar::zero()
+        );
+        assert_eq!(EmbeddedScalar::from_canonical_bytes(order), None);
+
+        // 2^256 mod q, computed by repeated doubling
+        let mut wide = [0u8; 33];
+        wide[32] = 1;
+        let mut two_256 = EmbeddedScalar::one();
+        for _ in 0..256 {
+            two_256 = two_256 + two_256;
+        }
+        assert_eq!(EmbeddedScalar::from_bytes_mod_order(&wide), two_256);
+
+        let x = EmbeddedScalar::random(&mut rand::thread_rng());
+        assert_eq!(EmbeddedScalar::from_canonical_bytes(x.to_bytes()), Some(x));
+    }
+}
@@ -0,0 +1,261 @@
+//! Schnorr signatures over the embedded curve.
+
+#![allow(non_snake_case)]
+
+use core::convert::TryFrom;
+
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+use super::point::EmbeddedPoint;
+use super::scalar::EmbeddedScalar;
+use crate::r1cs::R1CSError;
+use crate::transcript::TranscriptProtocol;
+
+/// Bit length of the signature challenges.
+///
+/// Short challenges keep the variable-base multiplication in
+/// [`verify_signature`](super::verify_signature) cheap, and 128 bits
+/// are enough for the 125-bit security level of the curve.
+pub const CHALLENGE_BITS: usize = 128;
+
+/// A secret key for signing messages.
+pub struct SigningKey(EmbeddedScalar);
+
+/// A public key for verifying signatures, which is a point of the
+/// prime-order subgroup of the embedded curve.
+///
+/// Deserialization checks that the point is in the subgroup, as
+/// [`VerificationKey::from_point`] does.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "EmbeddedPoint")]
+pub struct VerificationKey(EmbeddedPoint);
+
+impl TryFrom<EmbeddedPoint> for VerificationKey {
+    type Error = &'static str;
+
+    fn try_from(point: EmbeddedPoint) -> Result<Self, Self::Error> {
+        VerificationKey::from_point(point).ok_or("the point is not in the prime-order subgroup")
+    }
+}
+
+/// A Schnorr signature \\((R, s)\\) with \\(s G = R + e A\\).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    /// The commitment to the nonce
+    pub R: EmbeddedPoint,
+    /// The response
+    pub s: EmbeddedScalar,
+}
+
+impl SigningKey {
+    /// Samples a random signing key.
+    pub fn random<T: RngCore + CryptoRng>(rng: &mut T) -> Self {
+        SigningKey(EmbeddedScalar::random(rng))
+    }
+
+    /// Returns the secret scalar of the key.
+    pub fn as_scalar(&self) -> EmbeddedScalar {
+        self.0
+    }
+
+    /// Returns the verification key \\(A = a G\\) of this signing key \\(a\\).
+    pub fn verification_key(&self) -> VerificationKey {
+        VerificationKey(EmbeddedPoint::generator() * self.0)
+    }
+
+    /// Signs the message that the caller has appended to the `transcript`.
+    ///
+    /// The nonce is derived from the transcript and the secret key,
+    /// with randomness from `rng` mixed in.
+    pub fn sign<T: RngCore + CryptoRng>(
+        &self,
+        transcript: &mut Transcript,
+        rng: &mut T,
+    ) -> Signature {
+        let mut nonce_rng = transcript
+            .build_rng()
+            .rekey_with_witness_bytes(b"signing_key", &self.0.to_bytes())
+            .finalize(rng);
+        let k = EmbeddedScalar::random(&mut nonce_rng);
+
+        let R = EmbeddedPoint::generator() * k;
+        let e = Signature::challenge(transcript, &self.verification_key(), &R);
+
+        Signature {
+            R,
+            s: k + e * self.0,
+        }
+    }
+}
+
+impl VerificationKey {
+    /// Creates a verification key from a point.
+    ///
+    /// Returns `None` if the point is not in the prime-order subgroup.
+    pub fn from_point(point: EmbeddedPoint) -> Option<Self> {
+        if point.is_torsion_free() {
+            Some(VerificationKey(point))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the point of the key.
+    pub fn as_point(&self) -> EmbeddedPoint {
+        self.0
+    }
+
+    /// Verifies a signature on the message that the caller has appended
+    /// to the `transcript`.
+    pub fn verify(
+        &self,
+        transcript: &mut Transcript,
+        signature: &Signature,
+    ) -> Result<(), R1CSError> {
+        if !signature.R.is_torsion_free() {
+            return Err(R1CSError::VerificationError);
+        }
+
+        let e = Signature::challenge(transcript, self, &signature.R);
+
+        if EmbeddedPoint::generator() * signature.s == signature.R + self.0 * e {
+            Ok(())
+        } else {
+            Err(R1CSError::VerificationError)
+        }
+    }
+}
+
+impl Signature {
+    /// Computes the [`CHALLENGE_BITS`]-bit challenge \\(e\\) for the
+    /// verification key `A` and the nonce commitment `R`.
+    ///
+    /// Use this to compute the challenge for the
+    /// [`verify_signature`](super::verify_signature) gadget.
+    pub fn challenge(
+        transcript: &mut Transcript,
+        A: &VerificationKey,
+        R: &EmbeddedPoint,
+    ) -> EmbeddedScalar {
+        transcript.append_message(b"dom-sep", b"EmbeddedSchnorr");
+        transcript.append_scalar(b"A_x", &A.0.x());
+        transcript.append_scalar(b"A_y", &A.0.y());
+        transcript.append_scalar(b"R_x", &R.x());
+        transcript.append_scalar(b"R_y", &R.y());
+
+        let mut bytes = [0u8; CHALLENGE_BITS / 8];
+        transcript.challenge_bytes(b"e", &mut bytes);
+        EmbeddedScalar::from_bytes_mod_order(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::scalar::Scalar;
+
+    use crate::r1cs::embedded_curve::{
+        verify_signature, AllocatedPoint, AllocatedScalar, SCALAR_BITS,
+    };
+    use crate::r1cs::{ConstraintSystem, Prover, Verifier};
+    use crate::{BulletproofGens, PedersenGens};
+
+    fn message_transcript(message: &[u8]) -> Transcript {
+        let mut transcript = Transcript::new(b"SchnorrTest");
+        transcript.append_message(b"message", message);
+        transcript
+    }
+
+    #[test]
+    fn sign_and_verify() {
+        let mut rng = rand::thread_rng();
+        let key = SigningKey::random(&mut rng);
+        let vk = key.verification_key();
+
+        let signature = key.sign(&mut message_transcript(b"hello"), &mut rng);
+        assert!(vk
+            .verify(&mut message_transcript(b"hello"), &signature)
+            .is_ok());
+        assert!(vk
+            .verify(&mut message_transcript(b"world"), &signature)
+            .is_err());
+
+        let other = SigningKey::random(&mut rng).verification_key();
+        assert!(other
+            .verify(&mut message_transcript(b"hello"), &signature)
+            .is_err());
+
+        let forged = Signature {
+            R: signature.R,
+            s: signature.s + EmbeddedScalar::one(),
+        };
+        assert!(vk
+            .verify(&mut message_transcript(b"hello"), &forged)
+            .is_err());
+    }
+
+    #[test]
+    fn deserialize_checks_the_subgroup() {
+        let vk = SigningKey::random(&mut rand::thread_rng()).verification_key();
+        let bytes = bincode::serialize(&vk).unwrap();
+        assert_eq!(bincode::deserialize::<VerificationKey>(&bytes).unwrap(), vk);
+
+        // (0, -1) is on the curve but has order 2.
+        let t = EmbeddedPoint::from_affine(Scalar::zero(), -Scalar::one()).unwrap();
+        let bytes = bincode::serialize(&t).unwrap();
+        assert!(bincode::deserialize::<EmbeddedPoint>(&bytes).is_ok());
+        assert!(bincode::deserialize::<VerificationKey>(&bytes).is_err());
+    }
+
+    /// Enforces that the witness `(R, s)` is a signature under the witness
+    /// key `A` for the public challenge `e`.
+    fn signature_gadget<CS: ConstraintSystem>(
+        cs: &mut CS,
+        A: Option<EmbeddedPoint>,
+        signature: Option<Signature>,
+        e: EmbeddedScalar,
+    ) -> Result<(), R1CSError> {
+        let A = AllocatedPoint::allocate(cs, A)?;
+        let R = AllocatedPoint::allocate(cs, signature.map(|sig| sig.R))?;
+        let s = AllocatedScalar::allocate(cs, signature.map(|sig| sig.s), SCALAR_BITS)?;
+        let e = AllocatedScalar::constant(e, CHALLENGE_BITS);
+
+        verify_signature(cs, &A, &R, &s, &e)
+    }
+
+    fn signature_gadget_helper(
+        vk: VerificationKey,
+        signature: Signature,
+        e: EmbeddedScalar,
+    ) -> Result<(), R1CSError> {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(4096, 1);
+
+        let proof = {
+            let mut transcript = Transcript::new(b"SchnorrGadgetTest");
+            let mut prover = Prover::new(&pc_gens, &mut transcript);
+            signature_gadget(&mut prover, Some(vk.as_point()), Some(signature), e)?;
+            prover.prove(&bp_gens)?
+        };
+
+        let mut transcript = Transcript::new(b"SchnorrGadgetTest");
+        let mut verifier = Verifier::new(&mut transcript);
+        signature_gadget(&mut verifier, None, None, e)?;
+        verifier.verify(&proof, &pc_gens, &bp_gens)
+    }
+
+    #[test]
+    fn signature_gadget_test() {
+        let mut rng = rand::thread_rng();
+        let key = SigningKey::random(&mut rng);
+        let vk = key.verification_key();
+
+        let signature = key.sign(&mut message_transcript(b"hello"), &mut rng);
+        let e = Signature::challenge(&mut message_transcript(b"hello"), &vk, &signature.R);
+        assert!(signature_gadget_helper(vk, signature, e).is_ok());
+
+        let e = Signature::challenge(&mut message_transcript(b"world"), &vk, &signature.R);
+        assert!(signature_gadget_helper(vk, signature, e).is_err());
+    }
+}
@@ -2,19 +2,12 @@
 #![allow(dead_code)]
 
 use super::util::*;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
 use curve25519_dalek::scalar::Scalar;
-use std::collections::HashMap;
 
-use std::fmt;
-
-#[derive(Clone, Debug)]
-pub struct MatCheckError;
-
-impl fmt::Display for MatCheckError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "matrice mult dont hold")
-   }
-}
+use crate::errors::R1CSError;
 
 /// enum for either matrix or vector return values
 pub enum MatorVec {
@@ -37,8 +30,8 @@ pub enum MatorVec {
 /// needed for verification
 #[derive(Clone, Default)]
 pub struct VarVecs {
-    pub vectors: HashMap<String, Vec<Scalar>>,
-    pub matrices: HashMap<String, Vec<Vec<Scalar>>>,
+    pub vectors: BTreeMap<String, Vec<Scalar>>,
+    pub matrices: BTreeMap<String, Vec<Vec<Scalar>>>,
 }
 
 impl VarVecs {
@@ -78,8 +71,8 @@ impl VarVecs {
     ///     Vectors: [aL, aR, aO, c, v], empty values are ok
     ///     Vectors: [wL, wR, wO, wV], empty values are ok
     pub fn new(vectors: &[Vec<Scalar>], matrices: &[Vec<Vec<Scalar>>]) -> Self {
-        let mut vecs: HashMap<String, Vec<Scalar>> = HashMap::new();
-        let mut mats: HashMap<String, Vec<Vec<Scalar>>> = HashMap::new();
+        let mut vecs: BTreeMap<String, Vec<Scalar>> = BTreeMap::new();
+        let mut mats: BTreeMap<String, Vec<Vec<Scalar>>> = BTreeMap::new();
 
         assert!(vectors.len() <= 5, "aL, aR, aO, c, v are the only 4 vectors");
         assert!(matrices.len() <= 4, "wL, wR, wO, wV are the only 4 vectors");
@@ -113,31 +106,18 @@ impl VarVecs {
     ///Verify the equation:
     /// wL*aL + wR*aR - wO*aO = wV*v + c
     ///Return () if holds
-    ///       MatCheckError if not
-    pub fn verify(&self) -> Result<(), MatCheckError>{
-        let aL: Vec<Scalar> = self.vectors["aL"].clone();
-        let aR: Vec<Scalar> = self.vectors["aR"].clone();
-        let aO: Vec<Scalar> = self.vectors["aO"].clone();
-        let v: Vec<Scalar> = self.vectors["v"].clone();
-        let c: Vec<Scalar> = self.vectors["c"].clone();
-
-        let wL: Vec<Vec<Scalar>> = self.matrices["wL"].clone();
-        let wR: Vec<Vec<Scalar>> = self.matrices["wR"].clone();
-        let wO: Vec<Vec<Scalar>> = self.matrices["wO"].clone();
-        let wV: Vec<Vec<Scalar>> = self.matrices["wV"].clone();
-
-        let L = mv_mult(&wL, &aL);
-        let R = mv_mult(&wR, &aR);
-        let O = mv_mult(&wO, &aO);
-        let V = mv_mult(&wV, &v);
-
-        println!("{}", self.print());
-
-        println!("multiplication term L: {}", print_scalar_vec(&L));
-        println!("multiplication term R: {}", print_scalar_vec(&R));
-        println!("multiplication term O: {}", print_scalar_vec(&O));
-        println!("multiplication term V: {}", print_scalar_vec(&V));
-        println!("constant term c: {}", print_scalar_vec(&c));
+    ///       R1CSError::VerificationError if not,
+    ///       or if a vector or matrix is missing or has the wrong size
+    pub fn verify(&self) -> Result<(), R1CSError> {
+        let L = self.weighted("wL", "aL")?;
+        let R = self.weighted("wR", "aR")?;
+        let O = self.weighted("wO", "aO")?;
+        let V = self.weighted("wV", "v")?;
+        let c = self.vectors.get("c").ok_or(R1CSError::VerificationError)?;
+
+        if c.len() != L.len() || R.len() != L.len() || O.len() != L.len() || V.len() != L.len() {
+            return Err(R1CSError::VerificationError);
+        }
 
         let left_side: Vec<Scalar> = L.iter()
             .zip(R.iter()
@@ -149,20 +129,23 @@ impl VarVecs {
             .map(|(v_, c_)| v_ + c_)
             .collect();
 
-        println!("left hand side  = {}", print_scalar_vec(&left_side));
-        println!("right hand side = {}", print_scalar_vec(&right_side));
-        let results: Vec<bool> = left_side.iter()
-            .zip(right_side.iter())
-            .map(|(l, r)| l == r)
-            .collect();
-
-        if results.iter().all(|x| *x) {
+        if left_side == right_side {
             Ok(())
         } else {
-            Err(MatCheckError)
+            Err(R1CSError::VerificationError)
         }
-        
     }
 
+    ///Return the product of the weight matrix w and the vector a,
+    /// or R1CSError::VerificationError if either is missing
+    /// or their sizes do not match
+    fn weighted(&self, w: &str, a: &str) -> Result<Vec<Scalar>, R1CSError> {
+        let w = self.matrices.get(w).ok_or(R1CSError::VerificationError)?;
+        let a = self.vectors.get(a).ok_or(R1CSError::VerificationError)?;
 
+        if w.is_empty() || w.iter().any(|row| row.len() != a.len()) {
+            return Err(R1CSError::VerificationError);
+        }
+        Ok(mv_mult(w, a))
+    }
 }
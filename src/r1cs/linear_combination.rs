@@ -1,8 +1,12 @@
 //! Definition of linear combinations.
 
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::FromIterator;
 use curve25519_dalek::scalar::Scalar;
-use std::iter::FromIterator;
-use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::curve::CurveScalar;
+use core::ops::{Add, Mul, Neg, Sub};
 
 /// Represents a variable in a constraint system.
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -19,10 +23,10 @@ pub enum Variable {
     One(),
 }
 
-impl From<Variable> for LinearCombination {
-    fn from(v: Variable) -> LinearCombination {
+impl<S: CurveScalar> From<Variable> for LinearCombination<S> {
+    fn from(v: Variable) -> LinearCombination<S> {
         LinearCombination {
-            terms: vec![(v, Scalar::one())],
+            terms: vec![(v, S::one())],
         }
     }
 }
@@ -35,7 +39,9 @@ impl<S: Into<Scalar>> From<S> for LinearCombination {
     }
 }
 
-// Arithmetic on variables produces linear combinations
+// Arithmetic on variables produces linear combinations of `ristretto255`
+// scalars.  For other groups, variables are converted into a
+// `LinearCombination` first.
 
 impl Neg for Variable {
     type Output = LinearCombination;
@@ -105,22 +111,23 @@ impl Mul<Variable> for Scalar {
 
 /// Represents a linear combination of
 /// [`Variables`](::r1cs::Variable).  Each term is represented by a
-/// `(Variable, Scalar)` pair.
+/// `(Variable, Scalar)` pair, whose scalar is a `ristretto255` scalar by
+/// default.
 #[derive(Clone, Debug, PartialEq)]
-pub struct LinearCombination {
-    pub(super) terms: Vec<(Variable, Scalar)>,
+pub struct LinearCombination<S: CurveScalar = Scalar> {
+    pub(super) terms: Vec<(Variable, S)>,
 }
 
-impl Default for LinearCombination {
+impl<S: CurveScalar> Default for LinearCombination<S> {
     fn default() -> Self {
         LinearCombination { terms: Vec::new() }
     }
 }
 
-impl FromIterator<(Variable, Scalar)> for LinearCombination {
+impl<S: CurveScalar> FromIterator<(Variable, S)> for LinearCombination<S> {
     fn from_iter<T>(iter: T) -> Self
     where
-        T: IntoIterator<Item = (Variable, Scalar)>,
+        T: IntoIterator<Item = (Variable, S)>,
     {
         LinearCombination {
             terms: iter.into_iter().collect(),
@@ -128,10 +135,10 @@ impl FromIterator<(Variable, Scalar)> for LinearCombination {
     }
 }
 
-impl<'a> FromIterator<&'a (Variable, Scalar)> for LinearCombination {
+impl<'a, S: CurveScalar> FromIterator<&'a (Variable, S)> for LinearCombination<S> {
     fn from_iter<T>(iter: T) -> Self
     where
-        T: IntoIterator<Item = &'a (Variable, Scalar)>,
+        T: IntoIterator<Item = &'a (Variable, S)>,
     {
         LinearCombination {
             terms: iter.into_iter().cloned().collect(),
@@ -141,7 +148,7 @@ impl<'a> FromIterator<&'a (Variable, Scalar)> for LinearCombination {
 
 // Arithmetic on linear combinations
 
-impl<L: Into<LinearCombination>> Add<L> for LinearCombination {
+impl<S: CurveScalar, L: Into<LinearCombination<S>>> Add<L> for LinearCombination<S> {
     type Output = Self;
 
     fn add(mut self, rhs: L) -> Self::Output {
@@ -150,12 +157,12 @@ impl<L: Into<LinearCombination>> Add<L> for LinearCombination {
     }
 }
 
-impl<L: Into<LinearCombination>> Sub<L> for LinearCombination {
+impl<S: CurveScalar, L: Into<LinearCombination<S>>> Sub<L> for LinearCombination<S> {
     type Output = Self;
 
     fn sub(mut self, rhs: L) -> Self::Output {
         self.terms
-            .extend(rhs.into().terms.iter().map(|(var, coeff)| (*var, -coeff)));
+            .extend(rhs.into().terms.iter().map(|(var, coeff)| (*var, -*coeff)));
         LinearCombination { terms: self.terms }
     }
 }
@@ -173,7 +180,7 @@ impl Mul<LinearCombination> for Scalar {
     }
 }
 
-impl Neg for LinearCombination {
+impl<S: CurveScalar> Neg for LinearCombination<S> {
     type Output = Self;
 
     fn neg(mut self) -> Self::Output {
@@ -184,10 +191,10 @@ impl Neg for LinearCombination {
     }
 }
 
-impl<S: Into<Scalar>> Mul<S> for LinearCombination {
+impl<S: CurveScalar, T: Into<S>> Mul<T> for LinearCombination<S> {
     type Output = Self;
 
-    fn mul(mut self, other: S) -> Self::Output {
+    fn mul(mut self, other: T) -> Self::Output {
         let other = other.into();
         for (_, s) in self.terms.iter_mut() {
             *s *= other
@@ -0,0 +1,944 @@
+//! Verifiable mental poker over ElGamal-encrypted cards.
+//!
+//! Cards are encoded as Ristretto points and encrypted under the joint
+//! public key of all players.  Starting from the open (unencrypted)
+//! deck, every player in turn re-encrypts and permutes the deck and
+//! publishes a [`ShuffleProof`] that the output deck holds the same
+//! cards as the input deck.  A card is opened by combining a
+//! [`DecryptionShare`] of every player, each of which carries a proof
+//! of correct partial decryption.
+//!
+//! # Shuffle argument
+//!
+//! For an input deck \\(C_0, \dots, C_{n-1}\\), the output deck is
+//! \\(C'_i = C_{\pi(i)} + \mathrm{Enc}(0; r_i)\\).  The prover:
+//!
+//! 1. commits to the indices \\(a_i = \pi(i)\\) as high-level variables of a
+//!    constraint system;
+//! 2. receives a challenge \\(x\\) and commits to the powers \\(b_i = x^{\pi(i)}\\);
+//! 3. proves with the tuple shuffle of [`PermProof`] that the pairs
+//!    \\((a_i, b_i)\\) are a permutation of the public pairs \\((j, x^j)\\);
+//! 4. proves with a sigma protocol that it knows openings of the
+//!    commitments to \\(b_i\\) and a scalar \\(\rho\\) such that
+//!    \\(\sum_i b_i C'_i - \mathrm{Enc}(0; \rho) = \sum_j x^j C_j\\).
+//!
+//! Since \\(\pi\\) is bound before \\(x\\) is chosen, the last equation
+//! holds for a random \\(x\\) only if every \\(C'_i - C_{\pi(i)}\\) is an
+//! encryption of zero.
+
+#![allow(non_snake_case)]
+
+extern crate rand;
+
+use alloc::vec;
+use alloc::vec::Vec;
+use clear_on_drop::clear::Clear;
+use core::iter;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, IsIdentity, MultiscalarMul, VartimeMultiscalarMul};
+use merlin::Transcript;
+use rand::seq::SliceRandom;
+use rand_core::{CryptoRng, RngCore};
+
+use super::{
+    ConstraintSystem, LinearCombination, PermProof, Prover, R1CSError, R1CSProof,
+    RandomizableConstraintSystem, RandomizedConstraintSystem, Variable, Verifier,
+};
+use crate::generators::{BulletproofGens, PedersenGens};
+use crate::transcript::TranscriptProtocol;
+use crate::util;
+
+/// A card of the deck, identified by its position in the unshuffled deck.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Card(pub u32);
+
+impl Card {
+    /// Encodes the card as the point \\((i + 1) \cdot G\\), where \\(G\\)
+    /// is the `ristretto255` basepoint and \\(i\\) is the card index.
+    pub fn to_point(&self) -> RistrettoPoint {
+        Scalar::from(self.0 as u64 + 1) * RISTRETTO_BASEPOINT_POINT
+    }
+
+    /// Decodes a point into a card of a deck of `deck_size` cards.
+    ///
+    /// Returns `None` if the point does not encode any of the cards.
+    pub fn from_point(point: &RistrettoPoint, deck_size: u32) -> Option<Card> {
+        let mut candidate = RISTRETTO_BASEPOINT_POINT;
+        for i in 0..deck_size {
+            if candidate == *point {
+                return Some(Card(i));
+            }
+            candidate += RISTRETTO_BASEPOINT_POINT;
+        }
+        None
+    }
+}
+
+/// A player's share of the joint decryption key.
+pub struct SecretKey(Scalar);
+
+/// A player's public key, or the joint public key of all players.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PublicKey(RistrettoPoint);
+
+/// A proof of knowledge of the secret key behind a [`PublicKey`].
+///
+/// Players must check these proofs before aggregating the keys,
+/// otherwise a player could pick its key as a function of the others
+/// and decrypt the deck on its own.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct KeyOwnershipProof {
+    challenge: Scalar,
+    response: Scalar,
+}
+
+/// An ElGamal encryption \\((r G, M + r P)\\) of a card point \\(M\\)
+/// under the public key \\(P\\).
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Ciphertext {
+    c1: RistrettoPoint,
+    c2: RistrettoPoint,
+}
+
+/// A player's partial decryption of a [`Ciphertext`], together with a
+/// proof that it was computed with the secret key behind the player's
+/// [`PublicKey`].
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+pub struct DecryptionShare {
+    share: CompressedRistretto,
+    challenge: Scalar,
+    response: Scalar,
+}
+
+/// A proof that a deck of ciphertexts is a re-encrypted permutation
+/// of another deck of ciphertexts.
+///
+/// See the [module documentation](self) for the description of the argument.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ShuffleProof {
+    /// Commitments to the permutation indices \\(a_i = \pi(i)\\)
+    index_commitments: Vec<CompressedRistretto>,
+    /// Commitments to the permuted powers \\(b_i = x^{\pi(i)}\\)
+    power_commitments: Vec<CompressedRistretto>,
+    /// Proof that the \\((a_i, b_i)\\) pairs are a permutation of the \\((j, x^j)\\) pairs
+    r1cs_proof: R1CSProof,
+    /// Sigma protocol commitments to the openings of the powers
+    A_vec: Vec<CompressedRistretto>,
+    /// Sigma protocol commitment to the first component of the re-encryption
+    E_1: CompressedRistretto,
+    /// Sigma protocol commitment to the second component of the re-encryption
+    E_2: CompressedRistretto,
+    /// Sigma protocol responses for the powers
+    z_vec: Vec<Scalar>,
+    /// Sigma protocol responses for the blinding factors of the powers
+    s_vec: Vec<Scalar>,
+    /// Sigma protocol response for the aggregated re-encryption factor
+    t: Scalar,
+}
+
+/// The public decks and key of a shuffle.
+#[derive(Copy, Clone, Debug)]
+pub struct ShuffleStatement<'a> {
+    /// The joint public key the decks are encrypted under
+    pub pk: &'a PublicKey,
+    /// The deck before the shuffle
+    pub input: &'a [Ciphertext],
+    /// The deck after the shuffle
+    pub output: &'a [Ciphertext],
+}
+
+/// The secret permutation and re-encryption factors of a shuffle.
+///
+/// The card at position `i` of the output deck is the card at position
+/// `permutation[i]` of the input deck, re-encrypted with `rerandomizers[i]`.
+#[derive(Copy, Clone, Debug)]
+pub struct ShuffleWitness<'a> {
+    /// The input position of every output card
+    pub permutation: &'a [usize],
+    /// The re-encryption factor of every output card
+    pub rerandomizers: &'a [Scalar],
+}
+
+/// Returns an open (unencrypted) deck of `deck_size` cards in order.
+pub fn open_deck(deck_size: u32) -> Vec<Ciphertext> {
+    (0..deck_size).map(|i| Ciphertext::open(Card(i))).collect()
+}
+
+impl SecretKey {
+    /// Samples a random secret key.
+    pub fn random<T: RngCore + CryptoRng>(rng: &mut T) -> Self {
+        SecretKey(Scalar::random(rng))
+    }
+
+    /// Returns the public key \\(x G\\) of this secret key \\(x\\).
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.0 * RISTRETTO_BASEPOINT_POINT)
+    }
+
+    /// Creates a Schnorr proof of knowledge of this secret key.
+    pub fn prove_ownership<T: RngCore + CryptoRng>(
+        &self,
+        transcript: &mut Transcript,
+        rng: &mut T,
+    ) -> KeyOwnershipProof {
+        transcript.append_message(b"dom-sep", b"MentalPokerKey");
+        transcript.append_point(b"P", &self.public_key().0.compress());
+
+        let mut k = Scalar::random(rng);
+        transcript.append_point(b"R", &(k * RISTRETTO_BASEPOINT_POINT).compress());
+
+        let challenge: Scalar = transcript.challenge_scalar(b"e");
+        let response = k + challenge * self.0;
+        k.clear();
+
+        KeyOwnershipProof {
+            challenge,
+            response,
+        }
+    }
+
+    /// Computes this player's partial decryption \\(x \cdot c_1\\) of the
+    /// `ciphertext`, with a Chaum-Pedersen proof that the same secret key
+    /// \\(x\\) is behind the player's public key.
+    pub fn decryption_share<T: RngCore + CryptoRng>(
+        &self,
+        transcript: &mut Transcript,
+        ciphertext: &Ciphertext,
+        rng: &mut T,
+    ) -> DecryptionShare {
+        let share = (self.0 * ciphertext.c1).compress();
+        DecryptionShare::transcript_prelude(transcript, &self.public_key(), ciphertext, &share);
+
+        let mut k = Scalar::random(rng);
+        transcript.append_point(b"R_1", &(k * RISTRETTO_BASEPOINT_POINT).compress());
+        transcript.append_point(b"R_2", &(k * ciphertext.c1).compress());
+
+        let challenge: Scalar = transcript.challenge_scalar(b"e");
+        let response = k + challenge * self.0;
+        k.clear();
+
+        DecryptionShare {
+            share,
+            challenge,
+            response,
+        }
+    }
+}
+
+/// Overwrite secrets with null bytes when they go out of scope.
+impl Drop for SecretKey {
+    fn drop(&mut self) {
+        self.0.clear();
+    }
+}
+
+impl PublicKey {
+    /// Verifies a proof of knowledge of the secret key behind this public key.
+    pub fn verify_ownership(
+        &self,
+        transcript: &mut Transcript,
+        proof: &KeyOwnershipProof,
+    ) -> Result<(), R1CSError> {
+        transcript.append_message(b"dom-sep", b"MentalPokerKey");
+        transcript.append_point(b"P", &self.0.compress());
+
+        let R = RistrettoPoint::vartime_multiscalar_mul(
+            &[proof.response, -proof.challenge],
+            &[RISTRETTO_BASEPOINT_POINT, self.0],
+        );
+        transcript.append_point(b"R", &R.compress());
+
+        if transcript.challenge_scalar::<Scalar>(b"e") == proof.challenge {
+            Ok(())
+        } else {
+            Err(R1CSError::VerificationError)
+        }
+    }
+
+    /// Combines the players' public keys into the joint public key
+    /// that the deck is encrypted under.
+    pub fn aggregate(keys: &[PublicKey]) -> PublicKey {
+        PublicKey(keys.iter().map(|key| key.0).sum())
+    }
+}
+
+impl Ciphertext {
+    /// Creates the trivial encryption \\((0, M)\\) of a card, as used by
+    /// the open deck before the first shuffle.
+    pub fn open(card: Card) -> Self {
+        Ciphertext {
+            c1: RistrettoPoint::identity(),
+            c2: card.to_point(),
+        }
+    }
+
+    /// Encrypts a card under the public key `pk` with the randomness `r`.
+    pub fn encrypt(pk: &PublicKey, card: Card, r: &Scalar) -> Self {
+        Ciphertext::open(card).rerandomize(pk, r)
+    }
+
+    /// Re-encrypts the ciphertext by adding an encryption of zero
+    /// with the randomness `r`.
+    pub fn rerandomize(&self, pk: &PublicKey, r: &Scalar) -> Self {
+        Ciphertext {
+            c1: self.c1 + r * RISTRETTO_BASEPOINT_POINT,
+            c2: self.c2 + r * pk.0,
+        }
+    }
+
+    /// Combines the decryption shares of all players into the card point.
+    ///
+    /// The shares must have been checked with [`DecryptionShare::verify`].
+    pub fn decrypt(&self, shares: &[DecryptionShare]) -> Result<RistrettoPoint, R1CSError> {
+        let shares = shares
+            .iter()
+            .map(|share| share.share.decompress())
+            .collect::<Option<Vec<_>>>()
+            .ok_or(R1CSError::FormatError)?;
+
+        Ok(self.c2 - shares.iter().sum::<RistrettoPoint>())
+    }
+
+    /// Combines the decryption shares of all players and decodes the
+    /// card of a deck of `deck_size` cards.
+    pub fn decrypt_card(
+        &self,
+        shares: &[DecryptionShare],
+        deck_size: u32,
+    ) -> Result<Card, R1CSError> {
+        Card::from_point(&self.decrypt(shares)?, deck_size).ok_or(R1CSError::VerificationError)
+    }
+
+    fn append_to_transcript(&self, transcript: &mut Transcript) {
+        transcript.append_point(b"c_1", &self.c1.compress());
+        transcript.append_point(b"c_2", &self.c2.compress());
+    }
+}
+
+impl DecryptionShare {
+    /// Verifies that the share is the partial decryption of `ciphertext`
+    /// under the secret key behind the player's public key `pk`.
+    pub fn verify(
+        &self,
+        transcript: &mut Transcript,
+        pk: &PublicKey,
+        ciphertext: &Ciphertext,
+    ) -> Result<(), R1CSError> {
+        let D = self.share.decompress().ok_or(R1CSError::FormatError)?;
+        DecryptionShare::transcript_prelude(transcript, pk, ciphertext, &self.share);
+
+        let R_1 = RistrettoPoint::vartime_multiscalar_mul(
+            &[self.response, -self.challenge],
+            &[RISTRETTO_BASEPOINT_POINT, pk.0],
+        );
+        let R_2 = RistrettoPoint::vartime_multiscalar_mul(
+            &[self.response, -self.challenge],
+            &[ciphertext.c1, D],
+        );
+        transcript.append_point(b"R_1", &R_1.compress());
+        transcript.append_point(b"R_2", &R_2.compress());
+
+        if transcript.challenge_scalar::<Scalar>(b"e") == self.challenge {
+            Ok(())
+        } else {
+            Err(R1CSError::VerificationError)
+        }
+    }
+
+    fn transcript_prelude(
+        transcript: &mut Transcript,
+        pk: &PublicKey,
+        ciphertext: &Ciphertext,
+        share: &CompressedRistretto,
+    ) {
+        transcript.append_message(b"dom-sep", b"MentalPokerDecryption");
+        transcript.append_point(b"P", &pk.0.compress());
+        ciphertext.append_to_transcript(transcript);
+        transcript.append_point(b"D", share);
+    }
+}
+
+impl ShuffleProof {
+    /// Constrains the committed pairs \\((a_i, b_i)\\) to be a permutation
+    /// of the public pairs \\((j, x^j)\\), using the tuple shuffle of
+    /// [`PermProof`] in the randomized phase.
+    fn gadget<CS: RandomizableConstraintSystem>(
+        cs: &mut CS,
+        indices: Vec<Variable>,
+        powers: Vec<Variable>,
+        x: Scalar,
+    ) -> Result<(), R1CSError> {
+        let n = indices.len();
+
+        cs.specify_randomized_constraints(move |cs| {
+            let w = cs.challenge_scalar(b"tuple shuffle challenge");
+            let c = cs.challenge_scalar(b"shuffle challenge");
+
+            let public: Vec<LinearCombination> = util::exp_iter(x)
+                .take(n)
+                .enumerate()
+                .map(|(j, exp_x)| (Scalar::from(j as u64) + w * exp_x).into())
+                .collect();
+            let committed: Vec<LinearCombination> = indices
+                .iter()
+                .zip(powers.iter())
+                .map(|(a, b)| *a + *b * w)
+                .collect();
+
+            PermProof::create_lc_constraints(cs, public, committed, &c)
+        })
+    }
+
+    fn transcript_prelude(
+        transcript: &mut Transcript,
+        pk: &PublicKey,
+        input: &[Ciphertext],
+        output: &[Ciphertext],
+    ) {
+        transcript.append_message(b"dom-sep", b"MentalPokerShuffle");
+        transcript.append_u64(b"n", input.len() as u64);
+        transcript.append_point(b"P", &pk.0.compress());
+        for ciphertext in input.iter().chain(output.iter()) {
+            ciphertext.append_to_transcript(transcript);
+        }
+    }
+
+    /// Re-encrypts and shuffles the `input` deck with a random permutation
+    /// and random re-encryption factors, and proves that the shuffle is correct.
+    ///
+    /// This is a convenience wrapper around [`ShuffleProof::prove`].
+    ///
+    /// # Returns
+    ///
+    /// Returns a pair of the proof and the shuffled deck.
+    pub fn shuffle<T: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        pk: &PublicKey,
+        input: &[Ciphertext],
+        rng: &mut T,
+    ) -> Result<(ShuffleProof, Vec<Ciphertext>), R1CSError> {
+        let mut permutation: Vec<usize> = (0..input.len()).collect();
+        permutation.shuffle(rng);
+        let rerandomizers: Vec<Scalar> = (0..input.len()).map(|_| Scalar::random(rng)).collect();
+
+        let witness = ShuffleWitness {
+            permutation: &permutation,
+            rerandomizers: &rerandomizers,
+        };
+        ShuffleProof::prove(pc_gens, bp_gens, transcript, pk, input, witness, rng)
+    }
+
+    /// Re-encrypts and shuffles the `input` deck as the `witness`
+    /// describes, and proves that the shuffle is correct.
+    ///
+    /// The `bp_gens` must have capacity for \\(2n + 1\\) multipliers,
+    /// rounded up to the next power of two, for a deck of \\(n\\) cards.
+    ///
+    /// # Returns
+    ///
+    /// Returns a pair of the proof and the shuffled deck.
+    pub fn prove<T: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        pk: &PublicKey,
+        input: &[Ciphertext],
+        witness: ShuffleWitness,
+        rng: &mut T,
+    ) -> Result<(ShuffleProof, Vec<Ciphertext>), R1CSError> {
+        let ShuffleWitness {
+            permutation,
+            rerandomizers,
+        } = witness;
+        let n = input.len();
+        if n == 0 || permutation.len() != n || rerandomizers.len() != n {
+            return Err(R1CSError::GadgetError {
+                description:
+                    "shuffle needs one permutation index and re-encryption factor per card".into(),
+            });
+        }
+        let mut seen = vec![false; n];
+        for &p in permutation {
+            if p >= n || seen[p] {
+                return Err(R1CSError::GadgetError {
+                    description: "shuffle indices are not a permutation of the deck".into(),
+                });
+            }
+            seen[p] = true;
+        }
+
+        let output: Vec<Ciphertext> = permutation
+            .iter()
+            .zip(rerandomizers.iter())
+            .map(|(&p, r)| input[p].rerandomize(pk, r))
+            .collect();
+
+        ShuffleProof::transcript_prelude(transcript, pk, input, &output);
+
+        let mut prover = Prover::new(pc_gens, transcript);
+
+        let (index_commitments, index_vars): (Vec<_>, Vec<_>) = permutation
+            .iter()
+            .map(|&p| prover.commit(Scalar::from(p as u64), Scalar::random(rng)))
+            .unzip();
+
+        // The challenge x is bound to the committed permutation.
+        let x = prover.transcript().challenge_scalar(b"x");
+        let exp_x: Vec<Scalar> = util::exp_iter(x).take(n).collect();
+        let mut powers: Vec<Scalar> = permutation.iter().map(|&p| exp_x[p]).collect();
+        let mut power_blindings: Vec<Scalar> = (0..n).map(|_| Scalar::random(rng)).collect();
+
+        let (power_commitments, power_vars): (Vec<_>, Vec<_>) = powers
+            .iter()
+            .zip(power_blindings.iter())
+            .map(|(b, b_blinding)| prover.commit(*b, *b_blinding))
+            .unzip();
+
+        ShuffleProof::gadget(&mut prover, index_vars, power_vars, x)?;
+
+        let r1cs_proof = prover.prove_with_rng(bp_gens, rng)?;
+
+        // Sigma protocol for the knowledge of b, b_blinding and rho such that
+        // V_i = b_i * B + b_blinding_i * B_blinding and
+        // sum(b_i * C'_i) - Enc(0; rho) = sum(x^j * C_j).
+        let mut rho: Scalar = powers
+            .iter()
+            .zip(rerandomizers.iter())
+            .map(|(b, r)| b * r)
+            .sum();
+
+        let mut k_vec: Vec<Scalar> = (0..n).map(|_| Scalar::random(rng)).collect();
+        let mut l_vec: Vec<Scalar> = (0..n).map(|_| Scalar::random(rng)).collect();
+        let mut m = Scalar::random(rng);
+
+        let A_vec: Vec<CompressedRistretto> = k_vec
+            .iter()
+            .zip(l_vec.iter())
+            .map(|(k, l)| pc_gens.commit(*k, *l).compress())
+            .collect();
+
+        // E = sum(k_i * C'_i) - Enc(0; m)
+        let E_1 = RistrettoPoint::multiscalar_mul(
+            k_vec.iter().chain(iter::once(&-m)),
+            output
+                .iter()
+                .map(|c| c.c1)
+                .chain(iter::once(RISTRETTO_BASEPOINT_POINT)),
+        )
+        .compress();
+        let E_2 = RistrettoPoint::multiscalar_mul(
+            k_vec.iter().chain(iter::once(&-m)),
+            output.iter().map(|c| c.c2).chain(iter::once(pk.0)),
+        )
+        .compress();
+
+        for A in A_vec.iter() {
+            transcript.append_point(b"A", A);
+        }
+        transcript.append_point(b"E_1", &E_1);
+        transcript.append_point(b"E_2", &E_2);
+
+        let e: Scalar = transcript.challenge_scalar(b"e");
+
+        let z_vec: Vec<Scalar> = k_vec
+            .iter()
+            .zip(powers.iter())
+            .map(|(k, b)| k + e * b)
+            .collect();
+        let s_vec: Vec<Scalar> = l_vec
+            .iter()
+            .zip(power_blindings.iter())
+            .map(|(l, b_blinding)| l + e * b_blinding)
+            .collect();
+        let t = m + e * rho;
+
+        rho.clear();
+        m.clear();
+        for scalar in powers
+            .iter_mut()
+            .chain(power_blindings.iter_mut())
+            .chain(k_vec.iter_mut())
+            .chain(l_vec.iter_mut())
+        {
+            scalar.clear();
+        }
+
+        Ok((
+            ShuffleProof {
+                index_commitments,
+                power_commitments,
+                r1cs_proof,
+                A_vec,
+                E_1,
+                E_2,
+                z_vec,
+                s_vec,
+                t,
+            },
+            output,
+        ))
+    }
+
+    /// Verifies that the `output` deck of the `statement` is a re-encrypted
+    /// permutation of its `input` deck under its joint public key `pk`.
+    ///
+    /// This is a convenience wrapper around [`ShuffleProof::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify(
+        &self,
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        statement: ShuffleStatement,
+    ) -> Result<(), R1CSError> {
+        self.verify_with_rng(
+            pc_gens,
+            bp_gens,
+            transcript,
+            statement,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Verifies the shuffle as [`ShuffleProof::verify`] does, using `rng`
+    /// for the random weights of the verification equations.
+    pub fn verify_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        statement: ShuffleStatement,
+        rng: &mut T,
+    ) -> Result<(), R1CSError> {
+        let ShuffleStatement { pk, input, output } = statement;
+        let n = input.len();
+        if n == 0
+            || output.len() != n
+            || self.index_commitments.len() != n
+            || self.power_commitments.len() != n
+            || self.A_vec.len() != n
+            || self.z_vec.len() != n
+            || self.s_vec.len() != n
+        {
+            return Err(R1CSError::VerificationError);
+        }
+
+        ShuffleProof::transcript_prelude(transcript, pk, input, output);
+
+        let mut verifier = Verifier::new(transcript);
+
+        let index_vars: Vec<_> = self
+            .index_commitments
+            .iter()
+            .map(|V| verifier.commit(*V))
+            .collect();
+
+        let x = verifier.transcript().challenge_scalar(b"x");
+
+        let power_vars: Vec<_> = self
+            .power_commitments
+            .iter()
+            .map(|V| verifier.commit(*V))
+            .collect();
+
+        ShuffleProof::gadget(&mut verifier, index_vars, power_vars, x)?;
+
+        verifier.verify_with_rng(&self.r1cs_proof, pc_gens, bp_gens, rng)?;
+
+        for A in self.A_vec.iter() {
+            transcript.validate_and_append_point(b"A", A)?;
+        }
+        transcript.validate_and_append_point(b"E_1", &self.E_1)?;
+        transcript.validate_and_append_point(b"E_2", &self.E_2)?;
+
+        let e: Scalar = transcript.challenge_scalar(b"e");
+
+        // Combine the n + 2 verification equations with random weights:
+        //   z_i * B + s_i * B_blinding - A_i - e * V_i = 0
+        //   sum(z_i * c'_1i) - t * G - E_1 - e * sum(x^j * c_1j) = 0
+        //   sum(z_i * c'_2i) - t * P - E_2 - e * sum(x^j * c_2j) = 0
+        let mut rng = transcript.build_rng().finalize(rng);
+        let weights: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let weight_1 = Scalar::random(&mut rng);
+        let weight_2 = Scalar::random(&mut rng);
+
+        let exp_x: Vec<Scalar> = util::exp_iter(x).take(n).collect();
+
+        let B_scalar: Scalar = weights
+            .iter()
+            .zip(self.z_vec.iter())
+            .map(|(w, z)| w * z)
+            .sum();
+        let B_blinding_scalar: Scalar = weights
+            .iter()
+            .zip(self.s_vec.iter())
+            .map(|(w, s)| w * s)
+            .sum();
+
+        let check = RistrettoPoint::optional_multiscalar_mul(
+            iter::once(B_scalar)
+                .chain(iter::once(B_blinding_scalar))
+                .chain(weights.iter().map(|w| -w)) // A_i
+                .chain(weights.iter().map(|w| -(w * e))) // V_i
+                .chain(self.z_vec.iter().map(|z| weight_1 * z)) // c'_1i
+                .chain(self.z_vec.iter().map(|z| weight_2 * z)) // c'_2i
+                .chain(exp_x.iter().map(|exp_x| -(weight_1 * e * exp_x))) // c_1j
+                .chain(exp_x.iter().map(|exp_x| -(weight_2 * e * exp_x))) // c_2j
+                .chain(iter::once(-(weight_1 * self.t))) // G
+                .chain(iter::once(-(weight_2 * self.t))) // P
+                .chain(iter::once(-weight_1)) // E_1
+                .chain(iter::once(-weight_2)), // E_2
+            iter::once(Some(pc_gens.B))
+                .chain(iter::once(Some(pc_gens.B_blinding)))
+                .chain(self.A_vec.iter().map(|A| A.decompress()))
+                .chain(self.power_commitments.iter().map(|V| V.decompress()))
+                .chain(output.iter().map(|c| Some(c.c1)))
+                .chain(output.iter().map(|c| Some(c.c2)))
+                .chain(input.iter().map(|c| Some(c.c1)))
+                .chain(input.iter().map(|c| Some(c.c2)))
+                .chain(iter::once(Some(RISTRETTO_BASEPOINT_POINT)))
+                .chain(iter::once(Some(pk.0)))
+                .chain(iter::once(self.E_1.decompress()))
+                .chain(iter::once(self.E_2.decompress())),
+        )
+        .ok_or(R1CSError::VerificationError)?;
+
+        if check.is_identity() {
+            Ok(())
+        } else {
+            Err(R1CSError::VerificationError)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DECK_SIZE: u32 = 52;
+
+    #[test]
+    fn card_encoding_roundtrip() {
+        for i in 0..DECK_SIZE {
+            assert_eq!(
+                Card::from_point(&Card(i).to_point(), DECK_SIZE),
+                Some(Card(i))
+            );
+        }
+        assert_eq!(
+            Card::from_point(&Card(DECK_SIZE).to_point(), DECK_SIZE),
+            None
+        );
+    }
+
+    #[test]
+    fn key_ownership() {
+        let mut rng = rand::thread_rng();
+        let sk = SecretKey::random(&mut rng);
+        let proof = sk.prove_ownership(&mut Transcript::new(b"MentalPokerTest"), &mut rng);
+
+        assert!(sk
+            .public_key()
+            .verify_ownership(&mut Transcript::new(b"MentalPokerTest"), &proof)
+            .is_ok());
+
+        let other = SecretKey::random(&mut rng).public_key();
+        assert!(other
+            .verify_ownership(&mut Transcript::new(b"MentalPokerTest"), &proof)
+            .is_err());
+    }
+
+    #[test]
+    fn three_player_game() {
+        let mut rng = rand::thread_rng();
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(128, 1);
+
+        // Every player publishes a key with a proof of ownership.
+        let secret_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random(&mut rng)).collect();
+        let public_keys: Vec<PublicKey> = secret_keys.iter().map(|sk| sk.public_key()).collect();
+        for (sk, pk) in secret_keys.iter().zip(public_keys.iter()) {
+            let proof = sk.prove_ownership(&mut Transcript::new(b"MentalPokerTest"), &mut rng);
+            assert!(pk
+                .verify_ownership(&mut Transcript::new(b"MentalPokerTest"), &proof)
+                .is_ok());
+        }
+        let joint_key = PublicKey::aggregate(&public_keys);
+
+        // Every player shuffles the deck in turn, and the others check the shuffle.
+        let mut deck = open_deck(DECK_SIZE);
+        let mut order: Vec<u32> = (0..DECK_SIZE).collect();
+        for _ in 0..3 {
+            let mut permutation: Vec<usize> = (0..DECK_SIZE as usize).collect();
+            permutation.shuffle(&mut rng);
+            let rerandomizers: Vec<Scalar> =
+                (0..DECK_SIZE).map(|_| Scalar::random(&mut rng)).collect();
+
+            let (proof, shuffled) = ShuffleProof::prove(
+                &pc_gens,
+                &bp_gens,
+                &mut Transcript::new(b"MentalPokerTest"),
+                &joint_key,
+                &deck,
+                ShuffleWitness {
+                    permutation: &permutation,
+                    rerandomizers: &rerandomizers,
+                },
+                &mut rng,
+            )
+            .unwrap();
+
+            assert!(proof
+                .verify(
+                    &pc_gens,
+                    &bp_gens,
+                    &mut Transcript::new(b"MentalPokerTest"),
+                    ShuffleStatement {
+                        pk: &joint_key,
+                        input: &deck,
+                        output: &shuffled,
+                    },
+                )
+                .is_ok());
+
+            order = permutation.iter().map(|&p| order[p]).collect();
+            deck = shuffled;
+        }
+
+        // Open the first few cards with verified decryption shares.
+        for (position, ciphertext) in deck.iter().enumerate().take(5) {
+            let shares: Vec<DecryptionShare> = secret_keys
+                .iter()
+                .map(|sk| {
+                    sk.decryption_share(
+                        &mut Transcript::new(b"MentalPokerTest"),
+                        ciphertext,
+                        &mut rng,
+                    )
+                })
+                .collect();
+            for (share, pk) in shares.iter().zip(public_keys.iter()) {
+                assert!(share
+                    .verify(&mut Transcript::new(b"MentalPokerTest"), pk, ciphertext)
+                    .is_ok());
+            }
+
+            let card = ciphertext.decrypt_card(&shares, DECK_SIZE).unwrap();
+            assert_eq!(card, Card(order[position]));
+        }
+    }
+
+    #[test]
+    fn tampered_shuffle_fails() {
+        let mut rng = rand::thread_rng();
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+
+        let sk = SecretKey::random(&mut rng);
+        let pk = sk.public_key();
+        let deck: Vec<Ciphertext> = (0..8)
+            .map(|i| Ciphertext::encrypt(&pk, Card(i), &Scalar::random(&mut rng)))
+            .collect();
+
+        let (proof, mut shuffled) = ShuffleProof::shuffle(
+            &pc_gens,
+            &bp_gens,
+            &mut Transcript::new(b"MentalPokerTest"),
+            &pk,
+            &deck,
+            &mut rng,
+        )
+        .unwrap();
+
+        assert!(proof
+            .verify(
+                &pc_gens,
+                &bp_gens,
+                &mut Transcript::new(b"MentalPokerTest"),
+                ShuffleStatement {
+                    pk: &pk,
+                    input: &deck,
+                    output: &shuffled,
+                },
+            )
+            .is_ok());
+
+        // Replace a card with a fresh encryption of a card that is not in the deck.
+        shuffled[3] = Ciphertext::encrypt(&pk, Card(42), &Scalar::random(&mut rng));
+        assert!(proof
+            .verify(
+                &pc_gens,
+                &bp_gens,
+                &mut Transcript::new(b"MentalPokerTest"),
+                ShuffleStatement {
+                    pk: &pk,
+                    input: &deck,
+                    output: &shuffled,
+                },
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_permutation_is_rejected() {
+        let mut rng = rand::thread_rng();
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 1);
+        let pk = SecretKey::random(&mut rng).public_key();
+        let deck = open_deck(4);
+
+        assert!(ShuffleProof::prove(
+            &pc_gens,
+            &bp_gens,
+            &mut Transcript::new(b"MentalPokerTest"),
+            &pk,
+            &deck,
+            ShuffleWitness {
+                permutation: &[0, 1, 1, 3],
+                rerandomizers: &[Scalar::zero(); 4],
+            },
+            &mut rng,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn wrong_decryption_share_fails() {
+        let mut rng = rand::thread_rng();
+        let sk = SecretKey::random(&mut rng);
+        let other = SecretKey::random(&mut rng);
+        let ciphertext = Ciphertext::encrypt(&sk.public_key(), Card(7), &Scalar::random(&mut rng));
+
+        let share = other.decryption_share(
+            &mut Transcript::new(b"MentalPokerTest"),
+            &ciphertext,
+            &mut rng,
+        );
+        assert!(share
+            .verify(
+                &mut Transcript::new(b"MentalPokerTest"),
+                &sk.public_key(),
+                &ciphertext
+            )
+            .is_err());
+
+        let share = sk.decryption_share(
+            &mut Transcript::new(b"MentalPokerTest"),
+            &ciphertext,
+            &mut rng,
+        );
+        assert!(share
+            .verify(
+                &mut Transcript::new(b"MentalPokerTest"),
+                &sk.public_key(),
+                &ciphertext
+            )
+            .is_ok());
+        assert_eq!(ciphertext.decrypt_card(&[share], 52).unwrap(), Card(7));
+    }
+}
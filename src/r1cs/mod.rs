@@ -9,15 +9,22 @@ mod proof;
 mod prover;
 mod verifier;
 mod perm_proof;
+mod set_membership;
 mod enums;
 mod util;
 
+pub mod cloak;
+pub mod embedded_curve;
+pub mod mental_poker;
+
 pub use self::constraint_system::{
     ConstraintSystem, RandomizableConstraintSystem, RandomizedConstraintSystem,
 };
 pub use self::linear_combination::{LinearCombination, Variable};
+pub use self::perm_proof::{PermProof, PermStatement};
 pub use self::proof::R1CSProof;
 pub use self::prover::Prover;
+pub use self::set_membership::SetMembershipProof;
 pub use self::verifier::Verifier;
 
 pub use crate::errors::R1CSError;
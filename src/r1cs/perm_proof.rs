@@ -6,14 +6,33 @@ extern crate merlin;
 extern crate rand;
 
 use super::*;
+use alloc::vec;
+use alloc::vec::Vec;
 use crate::{BulletproofGens, PedersenGens};
+use crate::util;
 use crate::r1cs::enums::*;
 use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
+#[cfg(test)]
 use rand::seq::SliceRandom;
-
-struct PermProof(R1CSProof);
+use rand_core::{CryptoRng, RngCore};
+
+/// A proof that one committed deck is a permutation of another,
+/// built on the product argument of [`PermProof::create_constraints`].
+pub struct PermProof(R1CSProof);
+
+/// The commitments to the two decks and the challenge
+/// that a [`PermProof`] is verified against.
+#[derive(Copy, Clone, Debug)]
+pub struct PermStatement<'a> {
+    /// Commitments to the unshuffled deck
+    pub input_commits: &'a [CompressedRistretto],
+    /// Commitments to the shuffled deck
+    pub output_commits: &'a [CompressedRistretto],
+    /// The challenge scalar of the permutation circuit
+    pub chall: &'a Scalar,
+}
 
 impl PermProof {
     ///Create the input and output vectors non-blinded(for verification)
@@ -72,32 +91,51 @@ impl PermProof {
     ///by the permutation circuit of x and x_ 
     ///in the ConstraintSystem cs
     ///with the challenge scalar c
-    fn create_constraints<CS: ConstraintSystem>(
+    pub fn create_constraints<CS: ConstraintSystem>(
         cs: &mut CS,
         x: Vec<Variable>,
         x_: Vec<Variable>,
         c: &Scalar
     ) -> Result<(), R1CSError> {
         assert_eq!(x.len(), x_.len());
+
+        PermProof::create_lc_constraints(
+            cs,
+            x.into_iter().map(LinearCombination::from).collect(),
+            x_.into_iter().map(LinearCombination::from).collect(),
+            c,
+        )
+    }
+
+    ///Create the product argument constraints
+    ///  (x_0 - c)(x_1 - c)...(x_k-1 - c) = (x'_0 - c)(x'_1 - c)...(x'_k-1 - c)
+    ///over linear combinations, so that compressed tuples
+    ///can be shuffled the same way as single variables
+    pub(crate) fn create_lc_constraints<CS: ConstraintSystem>(
+        cs: &mut CS,
+        x: Vec<LinearCombination>,
+        x_: Vec<LinearCombination>,
+        c: &Scalar
+    ) -> Result<(), R1CSError> {
         let k = x.len();
 
         if k == 1 {
-            cs.constrain(x_[0] - x[0]);
+            cs.constrain(x_[0].clone() - x[0].clone());
             return Ok(());
         }
 
         //Original version multiplication constraints
-        let (_, _, mut original_out) = cs.multiply(x[0] - *c, x[1] - *c);
+        let (_, _, mut original_out) = cs.multiply(x[0].clone() - *c, x[1].clone() - *c);
 
         for i in 2..k {
-            (_, _, original_out) = cs.multiply(original_out.into(), x[i] - *c);
+            (_, _, original_out) = cs.multiply(original_out.into(), x[i].clone() - *c);
         }
 
         //Permutated version multiplication constraints
-        let (_, _, mut permd_out) = cs.multiply(x_[0] - *c, x_[1] - *c);
+        let (_, _, mut permd_out) = cs.multiply(x_[0].clone() - *c, x_[1].clone() - *c);
 
         for i in 2..k {
-            (_, _, permd_out) = cs.multiply(permd_out.into(), x_[i] - *c);
+            (_, _, permd_out) = cs.multiply(permd_out.into(), x_[i].clone() - *c);
         }
 
         (_, _, permd_out) = cs.multiply(permd_out.into(), (-Scalar::one()).into());
@@ -111,6 +149,94 @@ impl PermProof {
         Ok(())
     }
 
+    ///Check that x and x_ hold the same amount of rows
+    ///and that every row is a tuple of the same width
+    ///Returns: the width of the tuples
+    fn check_tuples(
+        x: &[Vec<Variable>],
+        x_: &[Vec<Variable>],
+    ) -> Result<usize, R1CSError> {
+        if x.len() != x_.len() {
+            return Err(R1CSError::GadgetError {
+                description: "tuple shuffle needs the same amount of input and output rows".into(),
+            });
+        }
+        if x.is_empty() {
+            return Err(R1CSError::GadgetError {
+                description: "tuple shuffle needs at least one row".into(),
+            });
+        }
+
+        let width = x[0].len();
+        if width == 0 || x.iter().chain(x_.iter()).any(|row| row.len() != width) {
+            return Err(R1CSError::GadgetError {
+                description: "tuple shuffle rows must all have the same non-zero width".into(),
+            });
+        }
+
+        Ok(width)
+    }
+
+    ///Compress every k-tuple row into a single linear combination
+    ///with the powers of the challenge scalar w:
+    ///     row = x_0 + w*x_1 + w^2*x_2 + ... + w^(k-1)*x_(k-1)
+    fn compress_rows(rows: Vec<Vec<Variable>>, w: &Scalar) -> Vec<LinearCombination> {
+        rows.into_iter()
+            .map(|row| {
+                row.into_iter()
+                    .zip(util::exp_iter(*w))
+                    .collect()
+            })
+            .collect()
+    }
+
+    ///Create the Linear Constraints System defined
+    ///by the permutation circuit of the k-tuple rows of x and x_
+    ///in the ConstraintSystem cs.
+    ///Every row is moved as a whole, e.g. (suit, rank) of a card
+    ///or (owner, amount, asset) of a record.
+    ///The rows are compressed with the challenge scalar w
+    ///before the product argument runs with the challenge scalar c,
+    ///so both challenges must be bound to the committed rows
+    ///(use `create_randomized_tuple_constraints` to draw them
+    ///from the transcript in the second phase)
+    pub fn create_tuple_constraints<CS: ConstraintSystem>(
+        cs: &mut CS,
+        x: Vec<Vec<Variable>>,
+        x_: Vec<Vec<Variable>>,
+        w: &Scalar,
+        c: &Scalar
+    ) -> Result<(), R1CSError> {
+        PermProof::check_tuples(&x, &x_)?;
+
+        PermProof::create_lc_constraints(
+            cs,
+            PermProof::compress_rows(x, w),
+            PermProof::compress_rows(x_, w),
+            c,
+        )
+    }
+
+    ///Create the Linear Constraints System defined
+    ///by the permutation circuit of the k-tuple rows of x and x_
+    ///as randomized constraints of the second phase:
+    ///the compression challenge w and the product challenge c
+    ///are drawn from the transcript after the rows are committed
+    pub fn create_randomized_tuple_constraints<CS: RandomizableConstraintSystem>(
+        cs: &mut CS,
+        x: Vec<Vec<Variable>>,
+        x_: Vec<Vec<Variable>>,
+    ) -> Result<(), R1CSError> {
+        PermProof::check_tuples(&x, &x_)?;
+
+        cs.specify_randomized_constraints(move |cs| {
+            let w = cs.challenge_scalar(b"tuple shuffle challenge");
+            let c = cs.challenge_scalar(b"shuffle challenge");
+
+            PermProof::create_tuple_constraints(cs, x.clone(), x_.clone(), &w, &c)
+        })
+    }
+
     ///Create the proof from:
     /// pc_gens for commitments
     /// bp_gens for commitments to bulletproofs
@@ -123,6 +249,10 @@ impl PermProof {
     /// input commitments
     /// output commitments
     /// VarVecs bin
+    ///
+    /// This is a convenience wrapper around [`PermProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
     pub fn prove<'a, 'b>(
         pc_gens: &'b PedersenGens,
         bp_gens: &'b BulletproofGens,
@@ -130,6 +260,29 @@ impl PermProof {
         input: &[Scalar],
         output:&[Scalar],
         chall: &Scalar,
+    ) -> Result<(PermProof, Vec<CompressedRistretto>, Vec<CompressedRistretto>, VarVecs), R1CSError> {
+        PermProof::prove_with_rng(
+            pc_gens,
+            bp_gens,
+            transcript,
+            input,
+            output,
+            chall,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    ///Create the proof as [`PermProof::prove`] does, with the
+    /// commitment blindings and the proof's blinding factors
+    /// drawn from rng
+    pub fn prove_with_rng<'b, R: RngCore + CryptoRng>(
+        pc_gens: &'b PedersenGens,
+        bp_gens: &'b BulletproofGens,
+        transcript: &mut Transcript,
+        input: &[Scalar],
+        output:&[Scalar],
+        chall: &Scalar,
+        rng: &mut R,
     ) -> Result<(PermProof, Vec<CompressedRistretto>, Vec<CompressedRistretto>, VarVecs), R1CSError> {
         let k = input.len();
         transcript.append_message(b"dom-sep", b"PermProof");
@@ -149,30 +302,28 @@ impl PermProof {
 
         let mut prover = Prover::new(&pc_gens, transcript);
 
-        let mut blinding_rng = rand::thread_rng();
-
         let (input_commits, input_vars): (Vec<_>, Vec<_>) = input.into_iter()
             .map(|v| 
-                 prover.commit(*v, Scalar::random(&mut blinding_rng))
+                 prover.commit(*v, Scalar::random(rng))
             )
             .unzip();
 
         let (output_commits, output_vars): (Vec<_>, Vec<_>) = output.into_iter()
             .map(|v| 
-                 prover.commit(*v, Scalar::random(&mut blinding_rng))
+                 prover.commit(*v, Scalar::random(rng))
             )
             .unzip();
 
         PermProof::create_constraints(&mut prover, input_vars, output_vars, chall)?;
 
-        let proof = prover.prove(&bp_gens)?;
+        let proof = prover.prove_with_rng(&bp_gens, rng)?;
 
         Ok((PermProof(proof), input_commits, output_commits, vector_bin))
                                           
     }
 
-    ///Verify the proof created bound to 
-    /// input and output commitments and PermProof
+    ///Verify the proof created bound to
+    /// the input and output commitments and challenge of the statement
     ///Verification is two fold:
     ///     First the weights and variables 
     ///     are verified to hold the statement:
@@ -180,19 +331,46 @@ impl PermProof {
     ///     Second the algorithm of bulletproof R1CS proof runs
     /// Returns:
     ///     (): if holds
-    ///     R1CSError: if R1CS doesnt hold
-    ///     MatCheckError(panicks): if the weights doesnt hold
+    ///     R1CSError::VerificationError: if the weights
+    ///     or the R1CS dont hold
+    ///
+    /// This is a convenience wrapper around [`PermProof::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
     pub fn verify <'a, 'b>(
         &self,
         pc_gens: &'b PedersenGens,
         bp_gens: &'b BulletproofGens,
         transcript: &'a mut Transcript,
-        input_commits: &Vec<CompressedRistretto>,
-        output_commits: &Vec<CompressedRistretto>,
-        chall: &Scalar,
+        statement: PermStatement,
+        vec_bin: &mut VarVecs,
+    ) -> Result<(), R1CSError> {
+        self.verify_with_rng(
+            pc_gens,
+            bp_gens,
+            transcript,
+            statement,
+            vec_bin,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    ///Verify the proof as [`PermProof::verify`] does, with the
+    /// random weights of the R1CS verification drawn from rng
+    pub fn verify_with_rng<'b, R: RngCore + CryptoRng>(
+        &self,
+        pc_gens: &'b PedersenGens,
+        bp_gens: &'b BulletproofGens,
+        transcript: &mut Transcript,
+        statement: PermStatement,
         vec_bin: &mut VarVecs,
+        rng: &mut R,
     ) -> Result<(), R1CSError> {
+        let PermStatement { input_commits, output_commits, chall } = statement;
         let k = input_commits.len();
+        if k == 0 || output_commits.len() != k {
+            return Err(R1CSError::VerificationError);
+        }
 
         transcript.append_message(b"dom-sep", b"PermProof");
         transcript.append_message(b"k", Scalar::from(k as u64).as_bytes());
@@ -221,10 +399,10 @@ impl PermProof {
         vec_bin.add("wV", MatorVec::Matrix(wV));
 
         //Verify weights
-        assert!(vec_bin.verify().is_ok());
+        vec_bin.verify()?;
 
         //Verify R1CS
-        verifier.verify(&self.0, &pc_gens, &bp_gens)
+        verifier.verify_with_rng(&self.0, &pc_gens, &bp_gens, rng)
 
     }
 }
@@ -268,11 +446,125 @@ fn perm_basic_test() {
     let mut verifier_transcript = Transcript::new(b"PermProofTest");
     assert!(
         proof
-            .verify(&pc_gens, &bp_gens, &mut verifier_transcript, &in_commitments, &out_commitments, &c, &mut spaces)
+            .verify(
+                &pc_gens,
+                &bp_gens,
+                &mut verifier_transcript,
+                PermStatement {
+                    input_commits: &in_commitments,
+                    output_commits: &out_commitments,
+                    chall: &c,
+                },
+                &mut spaces,
+            )
             .is_ok()
     );
 }
 
+#[test]
+fn perm_wrong_weights_test() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+
+    let c = Scalar::from(3u64);
+    let inputs: Vec<Scalar> = [1u64, 2, 4, 0].iter().map(|x| Scalar::from(*x)).collect();
+    let outputs: Vec<Scalar> = [1u64, 0, 2, 4].iter().map(|x| Scalar::from(*x)).collect();
+
+    let (proof, in_commitments, out_commitments, mut spaces) = PermProof::prove(
+        &pc_gens,
+        &bp_gens,
+        &mut Transcript::new(b"PermProofTest"),
+        &inputs,
+        &outputs,
+        &c,
+    )
+    .unwrap();
+
+    //A bin whose variables dont satisfy the weights is an error, not a panic
+    spaces.add("v", MatorVec::Vector(vec![Scalar::zero(); 9]));
+    assert_eq!(
+        proof.verify(
+            &pc_gens,
+            &bp_gens,
+            &mut Transcript::new(b"PermProofTest"),
+            PermStatement {
+                input_commits: &in_commitments,
+                output_commits: &out_commitments,
+                chall: &c,
+            },
+            &mut spaces,
+        ),
+        Err(R1CSError::VerificationError)
+    );
+
+    //So is a statement with a different number of input and output commitments
+    assert_eq!(
+        proof.verify(
+            &pc_gens,
+            &bp_gens,
+            &mut Transcript::new(b"PermProofTest"),
+            PermStatement {
+                input_commits: &in_commitments,
+                output_commits: &out_commitments[..3],
+                chall: &c,
+            },
+            &mut VarVecs::default(),
+        ),
+        Err(R1CSError::VerificationError)
+    );
+}
+
+#[test]
+fn perm_deterministic_test() {
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+
+    let c = Scalar::from(3u64);
+    let inputs: Vec<Scalar> = [1u64, 2, 4, 0].iter().map(|x| Scalar::from(*x)).collect();
+    let outputs: Vec<Scalar> = [1u64, 0, 2, 4].iter().map(|x| Scalar::from(*x)).collect();
+
+    let seeded_proof = |seed: [u8; 32]| {
+        let mut prover_transcript = Transcript::new(b"PermProofTest");
+        PermProof::prove_with_rng(
+            &pc_gens,
+            &bp_gens,
+            &mut prover_transcript,
+            &inputs,
+            &outputs,
+            &c,
+            &mut ChaChaRng::from_seed(seed),
+        )
+        .unwrap()
+    };
+
+    // The same seed gives the same commitments and proof.
+    let (proof, in_commitments, out_commitments, mut spaces) = seeded_proof([9u8; 32]);
+    let (again, in_again, out_again, _) = seeded_proof([9u8; 32]);
+    assert_eq!(proof.0.to_bytes(), again.0.to_bytes());
+    assert_eq!(in_commitments, in_again);
+    assert_eq!(out_commitments, out_again);
+
+    let mut verifier_transcript = Transcript::new(b"PermProofTest");
+    assert!(proof
+        .verify_with_rng(
+            &pc_gens,
+            &bp_gens,
+            &mut verifier_transcript,
+            PermStatement {
+                input_commits: &in_commitments,
+                output_commits: &out_commitments,
+                chall: &c,
+            },
+            &mut spaces,
+            &mut ChaChaRng::from_seed([10u8; 32]),
+        )
+        .is_ok());
+}
+
+#[cfg(test)]
 fn test_helper(k: usize) {
     use rand::Rng;
     let mut rng = rand::thread_rng();
@@ -306,9 +598,11 @@ fn test_helper(k: usize) {
                 &pc_gens,
                 &bp_gens,
                 &mut verifier_transcript,
-                &input_commits,
-                &output_commits,
-                &challenge_scalar,
+                PermStatement {
+                    input_commits: &input_commits,
+                    output_commits: &output_commits,
+                    chall: &challenge_scalar,
+                },
                 &mut spaces,
                 ).is_ok());
     }
@@ -318,3 +612,103 @@ fn test_helper(k: usize) {
 fn perm_test_1() {
     test_helper(52 as usize);
 }
+
+#[cfg(test)]
+fn tuple_test_helper(k: usize, width: usize, randomized: bool, tamper: bool) -> Result<(), R1CSError> {
+    use crate::transcript::TranscriptProtocol;
+    use core::iter;
+    use rand::Rng;
+    let mut rng = rand::thread_rng();
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new((2 * k + 2).next_power_of_two(), 1);
+
+    let (proof, input_commits, output_commits) = {
+        //The first column is distinct in every row, e.g. the rank of a card
+        let input: Vec<Vec<Scalar>> = (0..k)
+            .map(|i| iter::once(Scalar::from(i as u64))
+                 .chain((1..width).map(|_| Scalar::from(rng.gen_range(0u64, 4u64))))
+                 .collect())
+            .collect();
+        let mut output = input.clone();
+        output.shuffle(&mut rand::thread_rng());
+        if tamper {
+            //Swap the last column of two rows with different values there:
+            //every column stays a permutation on its own, but the tuples break
+            let j = (1..k).find(|j| output[*j][width - 1] != output[0][width - 1]).unwrap();
+            let tmp = output[0][width - 1];
+            output[0][width - 1] = output[j][width - 1];
+            output[j][width - 1] = tmp;
+        }
+
+        let mut prover_transcript = Transcript::new(b"TuplePermProofTest");
+        let mut prover = Prover::new(&pc_gens, &mut prover_transcript);
+
+        let (input_commits, input_vars): (Vec<Vec<_>>, Vec<Vec<_>>) = input.iter()
+            .map(|row| row.iter()
+                 .map(|v| prover.commit(*v, Scalar::random(&mut rng)))
+                 .unzip())
+            .unzip();
+        let (output_commits, output_vars): (Vec<Vec<_>>, Vec<Vec<_>>) = output.iter()
+            .map(|row| row.iter()
+                 .map(|v| prover.commit(*v, Scalar::random(&mut rng)))
+                 .unzip())
+            .unzip();
+
+        if randomized {
+            PermProof::create_randomized_tuple_constraints(&mut prover, input_vars, output_vars)?;
+        } else {
+            let w = prover.transcript().challenge_scalar(b"w");
+            let c = prover.transcript().challenge_scalar(b"c");
+            PermProof::create_tuple_constraints(&mut prover, input_vars, output_vars, &w, &c)?;
+        }
+
+        (prover.prove(&bp_gens)?, input_commits, output_commits)
+    };
+
+    let mut verifier_transcript = Transcript::new(b"TuplePermProofTest");
+    let mut verifier = Verifier::new(&mut verifier_transcript);
+
+    let input_vars: Vec<Vec<_>> = input_commits.iter()
+        .map(|row| row.iter().map(|commit| verifier.commit(*commit)).collect())
+        .collect();
+    let output_vars: Vec<Vec<_>> = output_commits.iter()
+        .map(|row| row.iter().map(|commit| verifier.commit(*commit)).collect())
+        .collect();
+
+    if randomized {
+        PermProof::create_randomized_tuple_constraints(&mut verifier, input_vars, output_vars)?;
+    } else {
+        let w = verifier.transcript().challenge_scalar(b"w");
+        let c = verifier.transcript().challenge_scalar(b"c");
+        PermProof::create_tuple_constraints(&mut verifier, input_vars, output_vars, &w, &c)?;
+    }
+
+    verifier.verify(&proof, &pc_gens, &bp_gens)
+}
+
+#[test]
+fn tuple_perm_test() {
+    assert!(tuple_test_helper(1, 2, false, false).is_ok());
+    assert!(tuple_test_helper(52, 2, false, false).is_ok());
+    assert!(tuple_test_helper(16, 3, false, false).is_ok());
+    assert!(tuple_test_helper(16, 3, false, true).is_err());
+}
+
+#[test]
+fn tuple_perm_randomized_test() {
+    assert!(tuple_test_helper(1, 2, true, false).is_ok());
+    assert!(tuple_test_helper(52, 2, true, false).is_ok());
+    assert!(tuple_test_helper(16, 3, true, false).is_ok());
+    assert!(tuple_test_helper(16, 3, true, true).is_err());
+}
+
+#[test]
+fn tuple_perm_mismatched_rows() {
+    let mut transcript = Transcript::new(b"TuplePermProofTest");
+    let mut verifier: Verifier = Verifier::new(&mut transcript);
+    let x = vec![vec![Variable::One(), Variable::One()]];
+    let x_ = vec![vec![Variable::One()]];
+
+    assert!(PermProof::create_randomized_tuple_constraints(&mut verifier, x, x_).is_err());
+}
@@ -1,10 +1,11 @@
 #![allow(non_snake_case)]
 //! Definition of the proof struct.
 
-use curve25519_dalek::ristretto::CompressedRistretto;
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::{Identity, IsIdentity};
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use curve25519_dalek::ristretto::RistrettoPoint;
 
+use crate::curve::{CompressedPoint, CurvePoint, CurveScalar};
 use crate::errors::R1CSError;
 use crate::inner_product_proof::InnerProductProof;
 use crate::util;
@@ -31,43 +32,45 @@ const TWO_PHASE_COMMITMENTS: u8 = 1;
 /// the constraint system using
 /// [`VerifierCS::verify`](::r1cs::VerifierCS::verify) to verify the
 /// proof.
+///
+/// The proof is over the group `C`, which is `ristretto255` by default.
 #[derive(Clone, Debug)]
 #[allow(non_snake_case)]
-pub struct R1CSProof {
+pub struct R1CSProof<C: CurvePoint = RistrettoPoint> {
     /// Commitment to the values of input wires in the first phase.
-    pub(super) A_I1: CompressedRistretto,
+    pub(super) A_I1: C::Compressed,
     /// Commitment to the values of output wires in the first phase.
-    pub(super) A_O1: CompressedRistretto,
+    pub(super) A_O1: C::Compressed,
     /// Commitment to the blinding factors in the first phase.
-    pub(super) S1: CompressedRistretto,
+    pub(super) S1: C::Compressed,
     /// Commitment to the values of input wires in the second phase.
-    pub(super) A_I2: CompressedRistretto,
+    pub(super) A_I2: C::Compressed,
     /// Commitment to the values of output wires in the second phase.
-    pub(super) A_O2: CompressedRistretto,
+    pub(super) A_O2: C::Compressed,
     /// Commitment to the blinding factors in the second phase.
-    pub(super) S2: CompressedRistretto,
+    pub(super) S2: C::Compressed,
     /// Commitment to the \\(t_1\\) coefficient of \\( t(x) \\)
-    pub(super) T_1: CompressedRistretto,
+    pub(super) T_1: C::Compressed,
     /// Commitment to the \\(t_3\\) coefficient of \\( t(x) \\)
-    pub(super) T_3: CompressedRistretto,
+    pub(super) T_3: C::Compressed,
     /// Commitment to the \\(t_4\\) coefficient of \\( t(x) \\)
-    pub(super) T_4: CompressedRistretto,
+    pub(super) T_4: C::Compressed,
     /// Commitment to the \\(t_5\\) coefficient of \\( t(x) \\)
-    pub(super) T_5: CompressedRistretto,
+    pub(super) T_5: C::Compressed,
     /// Commitment to the \\(t_6\\) coefficient of \\( t(x) \\)
-    pub(super) T_6: CompressedRistretto,
+    pub(super) T_6: C::Compressed,
     /// Evaluation of the polynomial \\(t(x)\\) at the challenge point \\(x\\)
-    pub(super) t_x: Scalar,
+    pub(super) t_x: C::Scalar,
     /// Blinding factor for the synthetic commitment to \\( t(x) \\)
-    pub(super) t_x_blinding: Scalar,
+    pub(super) t_x_blinding: C::Scalar,
     /// Blinding factor for the synthetic commitment to the
     /// inner-product arguments
-    pub(super) e_blinding: Scalar,
+    pub(super) e_blinding: C::Scalar,
     /// Proof data for the inner-product argument.
-    pub(super) ipp_proof: InnerProductProof,
+    pub(super) ipp_proof: InnerProductProof<C>,
 }
 
-impl R1CSProof {
+impl<C: CurvePoint> R1CSProof<C> {
     /// Serializes the proof into a byte array of 1 version byte + \\((13 or 16) + 2k\\) 32-byte elements,
     /// where \\(k=\lceil \log_2(n) \rceil\\) and \\(n\\) is the number of multiplication gates.
     ///
@@ -80,6 +83,9 @@ impl R1CSProof {
     /// * three scalars \\(t_x, \tilde{t}_x, \tilde{e}\\),
     /// * \\(k\\) pairs of compressed Ristretto points \\(L_0,R_0\dots,L_{k-1},R_{k-1}\\),
     /// * two scalars \\(a, b\\).
+    ///
+    /// For groups other than `ristretto255`, the points take
+    /// `C::Compressed::SIZE` bytes each instead of 32.
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(self.serialized_size());
         if self.missing_phase2_commitments() {
@@ -101,88 +107,91 @@ impl R1CSProof {
         buf.extend_from_slice(self.T_4.as_bytes());
         buf.extend_from_slice(self.T_5.as_bytes());
         buf.extend_from_slice(self.T_6.as_bytes());
-        buf.extend_from_slice(self.t_x.as_bytes());
-        buf.extend_from_slice(self.t_x_blinding.as_bytes());
-        buf.extend_from_slice(self.e_blinding.as_bytes());
+        buf.extend_from_slice(&self.t_x.to_bytes());
+        buf.extend_from_slice(&self.t_x_blinding.to_bytes());
+        buf.extend_from_slice(&self.e_blinding.to_bytes());
         buf.extend(self.ipp_proof.to_bytes_iter());
         buf
     }
 
     /// Returns the size in bytes required to serialize the `R1CSProof`.
     pub fn serialized_size(&self) -> usize {
-        // version tag + (11 or 14) elements + the ipp
-        let elements = if self.missing_phase2_commitments() {
-            11
+        // version tag + (8 or 11) points + 3 scalars + the ipp
+        let points = if self.missing_phase2_commitments() {
+            8
         } else {
-            14
+            11
         };
-        1 + elements * 32 + self.ipp_proof.serialized_size()
+        1 + points * C::Compressed::SIZE + 3 * 32 + self.ipp_proof.serialized_size()
     }
 
     fn missing_phase2_commitments(&self) -> bool {
         self.A_I2.is_identity() && self.A_O2.is_identity() && self.S2.is_identity()
     }
 
-    /// Deserializes the proof from a byte slice.
+    /// Deserializes a proof over the group `C` from a byte slice, in the
+    /// layout of [`R1CSProof::to_bytes`].
     ///
     /// Returns an error if the byte slice cannot be parsed into a `R1CSProof`.
-    pub fn from_bytes(slice: &[u8]) -> Result<R1CSProof, R1CSError> {
-        if slice.len() < 1 {
+    pub fn decode(slice: &[u8]) -> Result<R1CSProof<C>, R1CSError> {
+        if slice.is_empty() {
             return Err(R1CSError::FormatError);
         }
         let version = slice[0];
         let mut slice = &slice[1..];
 
-        if slice.len() % 32 != 0 {
-            return Err(R1CSError::FormatError);
-        }
-
-        let minlength = match version {
-            ONE_PHASE_COMMITMENTS => 11 * 32,
-            TWO_PHASE_COMMITMENTS => 14 * 32,
+        let point_size = C::Compressed::SIZE;
+        let points = match version {
+            ONE_PHASE_COMMITMENTS => 8,
+            TWO_PHASE_COMMITMENTS => 11,
             _ => return Err(R1CSError::FormatError),
         };
 
-        if slice.len() < minlength {
+        if slice.len() < points * point_size + 3 * 32 {
             return Err(R1CSError::FormatError);
         }
 
-        // This macro takes care of counting bytes in the slice
-        macro_rules! read32 {
+        // These macros take care of counting bytes in the slice
+        macro_rules! read_point {
             () => {{
-                let tmp = util::read32(slice);
+                let tmp = C::Compressed::from_slice(&slice[..point_size])
+                    .ok_or(R1CSError::FormatError)?;
+                slice = &slice[point_size..];
+                tmp
+            }};
+        }
+        macro_rules! read_scalar {
+            () => {{
+                let tmp = C::Scalar::from_canonical_bytes(util::read32(slice))
+                    .ok_or(R1CSError::FormatError)?;
                 slice = &slice[32..];
                 tmp
             }};
         }
 
-        let A_I1 = CompressedRistretto(read32!());
-        let A_O1 = CompressedRistretto(read32!());
-        let S1 = CompressedRistretto(read32!());
+        let A_I1 = read_point!();
+        let A_O1 = read_point!();
+        let S1 = read_point!();
         let (A_I2, A_O2, S2) = if version == ONE_PHASE_COMMITMENTS {
             (
-                CompressedRistretto::identity(),
-                CompressedRistretto::identity(),
-                CompressedRistretto::identity(),
+                C::Compressed::identity(),
+                C::Compressed::identity(),
+                C::Compressed::identity(),
             )
         } else {
-            (
-                CompressedRistretto(read32!()),
-                CompressedRistretto(read32!()),
-                CompressedRistretto(read32!()),
-            )
+            (read_point!(), read_point!(), read_point!())
         };
-        let T_1 = CompressedRistretto(read32!());
-        let T_3 = CompressedRistretto(read32!());
-        let T_4 = CompressedRistretto(read32!());
-        let T_5 = CompressedRistretto(read32!());
-        let T_6 = CompressedRistretto(read32!());
-        let t_x = Scalar::from_canonical_bytes(read32!()).ok_or(R1CSError::FormatError)?;
-        let t_x_blinding = Scalar::from_canonical_bytes(read32!()).ok_or(R1CSError::FormatError)?;
-        let e_blinding = Scalar::from_canonical_bytes(read32!()).ok_or(R1CSError::FormatError)?;
+        let T_1 = read_point!();
+        let T_3 = read_point!();
+        let T_4 = read_point!();
+        let T_5 = read_point!();
+        let T_6 = read_point!();
+        let t_x = read_scalar!();
+        let t_x_blinding = read_scalar!();
+        let e_blinding = read_scalar!();
 
         // XXX: IPPProof from_bytes gives ProofError.
-        let ipp_proof = InnerProductProof::from_bytes(slice).map_err(|_| R1CSError::FormatError)?;
+        let ipp_proof = InnerProductProof::decode(slice).map_err(|_| R1CSError::FormatError)?;
 
         Ok(R1CSProof {
             A_I1,
@@ -204,7 +213,18 @@ impl R1CSProof {
     }
 }
 
-impl Serialize for R1CSProof {
+impl R1CSProof {
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a `R1CSProof`.
+    ///
+    /// Use [`R1CSProof::decode`] for proofs over other groups.
+    pub fn from_bytes(slice: &[u8]) -> Result<R1CSProof, R1CSError> {
+        R1CSProof::decode(slice)
+    }
+}
+
+impl<C: CurvePoint> Serialize for R1CSProof<C> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -213,35 +233,35 @@ impl Serialize for R1CSProof {
     }
 }
 
-impl<'de> Deserialize<'de> for R1CSProof {
+impl<'de, C: CurvePoint> Deserialize<'de> for R1CSProof<C> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct R1CSProofVisitor;
+        struct R1CSProofVisitor<C>(PhantomData<C>);
 
-        impl<'de> Visitor<'de> for R1CSProofVisitor {
-            type Value = R1CSProof;
+        impl<'de, C: CurvePoint> Visitor<'de> for R1CSProofVisitor<C> {
+            type Value = R1CSProof<C>;
 
             fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
                 formatter.write_str("a valid R1CSProof")
             }
 
-            fn visit_bytes<E>(self, v: &[u8]) -> Result<R1CSProof, E>
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<R1CSProof<C>, E>
             where
                 E: serde::de::Error,
             {
                 // Using Error::custom requires T: Display, which our error
                 // type only implements when it implements std::error::Error.
                 #[cfg(feature = "std")]
-                return R1CSProof::from_bytes(v).map_err(serde::de::Error::custom);
+                return R1CSProof::decode(v).map_err(serde::de::Error::custom);
                 // In no-std contexts, drop the error message.
                 #[cfg(not(feature = "std"))]
-                return R1CSProof::from_bytes(v)
+                return R1CSProof::decode(v)
                     .map_err(|_| serde::de::Error::custom("deserialization error"));
             }
         }
 
-        deserializer.deserialize_bytes(R1CSProofVisitor)
+        deserializer.deserialize_bytes(R1CSProofVisitor(PhantomData))
     }
 }
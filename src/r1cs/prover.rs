@@ -1,21 +1,29 @@
 #![allow(non_snake_case)]
 
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
 use clear_on_drop::clear::Clear;
 use core::mem;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::{Identity, MultiscalarMul};
+use curve25519_dalek::ristretto::RistrettoPoint;
 use merlin::Transcript;
+#[cfg(feature = "std")]
+use rand::thread_rng;
+use rand_core::{CryptoRng, RngCore};
 
 use super::{
     ConstraintSystem, LinearCombination, R1CSProof, RandomizableConstraintSystem,
     RandomizedConstraintSystem, Variable,
 };
 
+use crate::curve::{CompressedPoint, CurvePoint, CurveScalar};
 use crate::errors::R1CSError;
-use crate::generators::{BulletproofGens, PedersenGens};
+use crate::generators::{BulletproofGens, PedersenBases, PedersenGens, PedersenGensTable};
 use crate::inner_product_proof::InnerProductProof;
-use crate::transcript::TranscriptProtocol;
+use crate::transcript::{ProofTranscript, TranscriptProtocol};
+
+/// The flattened constraint weights `(wL, wR, wO, wV)`.
+type FlattenedConstraints<S> = (Vec<S>, Vec<S>, Vec<S>, Vec<S>);
 
 /// A [`ConstraintSystem`] implementation for use by the prover.
 ///
@@ -26,25 +34,28 @@ use crate::transcript::TranscriptProtocol;
 /// When all constraints are added, the proving code calls `prove`
 /// which consumes the `Prover` instance, samples random challenges
 /// that instantiate the randomized constraints, and creates a complete proof.
-pub struct Prover<'t, 'g> {
-    transcript: &'t mut Transcript,
-    pc_gens: &'g PedersenGens,
+///
+/// The proof is over the group `C`, which is `ristretto255` by default.
+pub struct Prover<'t, 'g, T: ProofTranscript = Transcript, C: CurvePoint = RistrettoPoint> {
+    transcript: &'t mut T,
+    pc_gens: PedersenBases<'g, C>,
     /// The constraints accumulated so far.
-    constraints: Vec<LinearCombination>,
+    constraints: Vec<LinearCombination<C::Scalar>>,
     /// Stores assignments to the "left" of multiplication gates
-    a_L: Vec<Scalar>,
+    a_L: Vec<C::Scalar>,
     /// Stores assignments to the "right" of multiplication gates
-    a_R: Vec<Scalar>,
+    a_R: Vec<C::Scalar>,
     /// Stores assignments to the "output" of multiplication gates
-    a_O: Vec<Scalar>,
+    a_O: Vec<C::Scalar>,
     /// High-level witness data (value openings to V commitments)
-    v: Vec<Scalar>,
+    v: Vec<C::Scalar>,
     /// High-level witness data (blinding openings to V commitments)
-    v_blinding: Vec<Scalar>,
+    v_blinding: Vec<C::Scalar>,
 
     /// This list holds closures that will be called in the second phase of the protocol,
     /// when non-randomized variables are committed.
-    deferred_constraints: Vec<Box<dyn Fn(&mut RandomizingProver<'t, 'g>) -> Result<(), R1CSError>>>,
+    deferred_constraints:
+        Vec<Box<dyn Fn(&mut RandomizingProver<'t, 'g, T, C>) -> Result<(), R1CSError>>>,
 
     /// Index of a pending multiplier that's not fully assigned yet.
     pending_multiplier: Option<usize>,
@@ -57,12 +68,17 @@ pub struct Prover<'t, 'g> {
 /// monomorphize the closures for the proving and verifying code.
 /// However, this type cannot be instantiated by the user and therefore can only be used within
 /// the callback provided to `specify_randomized_constraints`.
-pub struct RandomizingProver<'t, 'g> {
-    prover: Prover<'t, 'g>,
+pub struct RandomizingProver<
+    't,
+    'g,
+    T: ProofTranscript = Transcript,
+    C: CurvePoint = RistrettoPoint,
+> {
+    prover: Prover<'t, 'g, T, C>,
 }
 
 /// Overwrite secrets with null bytes when they go out of scope.
-impl<'t, 'g> Drop for Prover<'t, 'g> {
+impl<'t, 'g, T: ProofTranscript, C: CurvePoint> Drop for Prover<'t, 'g, T, C> {
     fn drop(&mut self) {
         self.v.clear();
         self.v_blinding.clear();
@@ -85,15 +101,19 @@ impl<'t, 'g> Drop for Prover<'t, 'g> {
     }
 }
 
-impl<'t, 'g> ConstraintSystem for Prover<'t, 'g> {
-    fn transcript(&mut self) -> &mut Transcript {
+impl<'t, 'g, T: ProofTranscript, C: CurvePoint> ConstraintSystem<C::Scalar>
+    for Prover<'t, 'g, T, C>
+{
+    type Transcript = T;
+
+    fn transcript(&mut self) -> &mut T {
         self.transcript
     }
 
     fn multiply(
         &mut self,
-        mut left: LinearCombination,
-        mut right: LinearCombination,
+        mut left: LinearCombination<C::Scalar>,
+        mut right: LinearCombination<C::Scalar>,
     ) -> (Variable, Variable, Variable) {
         // Synthesize the assignments for l,r,o
         let l = self.eval(&left);
@@ -110,15 +130,15 @@ impl<'t, 'g> ConstraintSystem for Prover<'t, 'g> {
         self.a_O.push(o);
 
         // Constrain l,r,o:
-        left.terms.push((l_var, -Scalar::one()));
-        right.terms.push((r_var, -Scalar::one()));
+        left.terms.push((l_var, -C::Scalar::one()));
+        right.terms.push((r_var, -C::Scalar::one()));
         self.constrain(left);
         self.constrain(right);
 
         (l_var, r_var, o_var)
     }
 
-    fn allocate(&mut self, assignment: Option<Scalar>) -> Result<Variable, R1CSError> {
+    fn allocate(&mut self, assignment: Option<C::Scalar>) -> Result<Variable, R1CSError> {
         let scalar = assignment.ok_or(R1CSError::MissingAssignment)?;
 
         match self.pending_multiplier {
@@ -126,8 +146,8 @@ impl<'t, 'g> ConstraintSystem for Prover<'t, 'g> {
                 let i = self.a_L.len();
                 self.pending_multiplier = Some(i);
                 self.a_L.push(scalar);
-                self.a_R.push(Scalar::zero());
-                self.a_O.push(Scalar::zero());
+                self.a_R.push(C::Scalar::zero());
+                self.a_O.push(C::Scalar::zero());
                 Ok(Variable::MultiplierLeft(i))
             }
             Some(i) => {
@@ -141,7 +161,7 @@ impl<'t, 'g> ConstraintSystem for Prover<'t, 'g> {
 
     fn allocate_multiplier(
         &mut self,
-        input_assignments: Option<(Scalar, Scalar)>,
+        input_assignments: Option<(C::Scalar, C::Scalar)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError> {
         let (l, r) = input_assignments.ok_or(R1CSError::MissingAssignment)?;
         let o = l * r;
@@ -162,15 +182,17 @@ impl<'t, 'g> ConstraintSystem for Prover<'t, 'g> {
         self.a_L.len()
     }
 
-    fn constrain(&mut self, lc: LinearCombination) {
+    fn constrain(&mut self, lc: LinearCombination<C::Scalar>) {
         // TODO: check that the linear combinations are valid
         // (e.g. that variables are valid, that the linear combination evals to 0 for prover, etc).
         self.constraints.push(lc);
     }
 }
 
-impl<'t, 'g> RandomizableConstraintSystem for Prover<'t, 'g> {
-    type RandomizedCS = RandomizingProver<'t, 'g>;
+impl<'t, 'g, T: ProofTranscript, C: CurvePoint> RandomizableConstraintSystem<C::Scalar>
+    for Prover<'t, 'g, T, C>
+{
+    type RandomizedCS = RandomizingProver<'t, 'g, T, C>;
 
     fn specify_randomized_constraints<F>(&mut self, callback: F) -> Result<(), R1CSError>
     where
@@ -181,26 +203,30 @@ impl<'t, 'g> RandomizableConstraintSystem for Prover<'t, 'g> {
     }
 }
 
-impl<'t, 'g> ConstraintSystem for RandomizingProver<'t, 'g> {
-    fn transcript(&mut self) -> &mut Transcript {
+impl<'t, 'g, T: ProofTranscript, C: CurvePoint> ConstraintSystem<C::Scalar>
+    for RandomizingProver<'t, 'g, T, C>
+{
+    type Transcript = T;
+
+    fn transcript(&mut self) -> &mut T {
         self.prover.transcript
     }
 
     fn multiply(
         &mut self,
-        left: LinearCombination,
-        right: LinearCombination,
+        left: LinearCombination<C::Scalar>,
+        right: LinearCombination<C::Scalar>,
     ) -> (Variable, Variable, Variable) {
         self.prover.multiply(left, right)
     }
 
-    fn allocate(&mut self, assignment: Option<Scalar>) -> Result<Variable, R1CSError> {
+    fn allocate(&mut self, assignment: Option<C::Scalar>) -> Result<Variable, R1CSError> {
         self.prover.allocate(assignment)
     }
 
     fn allocate_multiplier(
         &mut self,
-        input_assignments: Option<(Scalar, Scalar)>,
+        input_assignments: Option<(C::Scalar, C::Scalar)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError> {
         self.prover.allocate_multiplier(input_assignments)
     }
@@ -209,18 +235,20 @@ impl<'t, 'g> ConstraintSystem for RandomizingProver<'t, 'g> {
         self.prover.multipliers_len()
     }
 
-    fn constrain(&mut self, lc: LinearCombination) {
+    fn constrain(&mut self, lc: LinearCombination<C::Scalar>) {
         self.prover.constrain(lc)
     }
 }
 
-impl<'t, 'g> RandomizedConstraintSystem for RandomizingProver<'t, 'g> {
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+impl<'t, 'g, T: ProofTranscript, C: CurvePoint> RandomizedConstraintSystem<C::Scalar>
+    for RandomizingProver<'t, 'g, T, C>
+{
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> C::Scalar {
         self.prover.transcript.challenge_scalar(label)
     }
 }
 
-impl<'t, 'g> Prover<'t, 'g> {
+impl<'t, 'g, T: ProofTranscript, C: CurvePoint> Prover<'t, 'g, T, C> {
     /// Construct an empty constraint system with specified external
     /// input variables.
     ///
@@ -232,7 +260,8 @@ impl<'t, 'g> Prover<'t, 'g> {
     /// the number of multiplication constraints that will eventually
     /// be added into the constraint system.
     ///
-    /// The `transcript` parameter is a Merlin proof transcript.  The
+    /// The `transcript` parameter is a proof transcript, by default a
+    /// Merlin transcript.  The
     /// `ProverCS` holds onto the `&mut Transcript` until it consumes
     /// itself during [`ProverCS::prove`], releasing its borrow of the
     /// transcript.  This ensures that the transcript cannot be
@@ -241,7 +270,21 @@ impl<'t, 'g> Prover<'t, 'g> {
     /// # Returns
     ///
     /// Returns a new `Prover` instance.
-    pub fn new(pc_gens: &'g PedersenGens, transcript: &'t mut Transcript) -> Self {
+    pub fn new(pc_gens: &'g PedersenGens<C>, transcript: &'t mut T) -> Self {
+        Prover::create(pc_gens.into(), transcript)
+    }
+
+    /// Construct an empty constraint system with specified external
+    /// input variables, which commits to them and to the \(T_i\)
+    /// with the precomputed tables of `pc_table`.
+    ///
+    /// The proof is the same as with [`Prover::new`] given the bases
+    /// `pc_table.gens()`.
+    pub fn new_with_table(pc_table: &'g PedersenGensTable<C>, transcript: &'t mut T) -> Self {
+        Prover::create(pc_table.into(), transcript)
+    }
+
+    fn create(pc_gens: PedersenBases<'g, C>, transcript: &'t mut T) -> Self {
         transcript.r1cs_domain_sep();
 
         Prover {
@@ -273,9 +316,9 @@ impl<'t, 'g> Prover<'t, 'g> {
     ///
     /// # Returns
     ///
-    /// Returns a pair of a Pedersen commitment (as a compressed Ristretto point),
+    /// Returns a pair of a Pedersen commitment (as a compressed point),
     /// and a [`Variable`] corresponding to it, which can be used to form constraints.
-    pub fn commit(&mut self, v: Scalar, v_blinding: Scalar) -> (CompressedRistretto, Variable) {
+    pub fn commit(&mut self, v: C::Scalar, v_blinding: C::Scalar) -> (C::Compressed, Variable) {
         let i = self.v.len();
         self.v.push(v);
         self.v_blinding.push(v_blinding);
@@ -298,56 +341,53 @@ impl<'t, 'g> Prover<'t, 'g> {
     /// (wL, wR, wO, wV)
     /// ```
     /// where `w{L,R,O}` is \\( z \cdot z^Q \cdot W_{L,R,O} \\).
-    fn flattened_constraints(
-        &mut self,
-        z: &Scalar,
-    ) -> (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Vec<Scalar>) {
+    fn flattened_constraints(&mut self, z: &C::Scalar) -> FlattenedConstraints<C::Scalar> {
         let n = self.a_L.len();
         let m = self.v.len();
 
-        let mut wL = vec![Scalar::zero(); n];
-        let mut wR = vec![Scalar::zero(); n];
-        let mut wO = vec![Scalar::zero(); n];
-        let mut wV = vec![Scalar::zero(); m];
+        let mut wL = vec![C::Scalar::zero(); n];
+        let mut wR = vec![C::Scalar::zero(); n];
+        let mut wO = vec![C::Scalar::zero(); n];
+        let mut wV = vec![C::Scalar::zero(); m];
 
         let mut exp_z = *z;
         for lc in self.constraints.iter() {
             for (var, coeff) in &lc.terms {
                 match var {
                     Variable::MultiplierLeft(i) => {
-                        wL[*i] += exp_z * coeff;
+                        wL[*i] += exp_z * *coeff;
                     }
                     Variable::MultiplierRight(i) => {
-                        wR[*i] += exp_z * coeff;
+                        wR[*i] += exp_z * *coeff;
                     }
                     Variable::MultiplierOutput(i) => {
-                        wO[*i] += exp_z * coeff;
+                        wO[*i] += exp_z * *coeff;
                     }
                     Variable::Committed(i) => {
-                        wV[*i] -= exp_z * coeff;
+                        wV[*i] -= exp_z * *coeff;
                     }
                     Variable::One() => {
                         // The prover doesn't need to handle constant terms
                     }
                 }
             }
-            exp_z *= z;
+            exp_z *= *z;
         }
 
         (wL, wR, wO, wV)
     }
 
-    fn eval(&self, lc: &LinearCombination) -> Scalar {
+    fn eval(&self, lc: &LinearCombination<C::Scalar>) -> C::Scalar {
         lc.terms
             .iter()
             .map(|(var, coeff)| {
-                coeff
+                *coeff
                     * match var {
                         Variable::MultiplierLeft(i) => self.a_L[*i],
                         Variable::MultiplierRight(i) => self.a_R[*i],
                         Variable::MultiplierOutput(i) => self.a_O[*i],
                         Variable::Committed(i) => self.v[*i],
-                        Variable::One() => Scalar::one(),
+                        Variable::One() => C::Scalar::one(),
                     }
             })
             .sum()
@@ -377,9 +417,29 @@ impl<'t, 'g> Prover<'t, 'g> {
     }
 
     /// Consume this `ConstraintSystem` to produce a proof.
-    pub fn prove(mut self, bp_gens: &BulletproofGens) -> Result<R1CSProof, R1CSError> {
+    ///
+    /// This is a convenience wrapper around [`Prover::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove(self, bp_gens: &BulletproofGens<C>) -> Result<R1CSProof<C>, R1CSError> {
+        self.prove_with_rng(bp_gens, &mut thread_rng())
+    }
+
+    /// Consume this `ConstraintSystem` to produce a proof, using `rng`
+    /// for the blinding factors of the proof.
+    ///
+    /// The blinding factors come from the transcript RNG, which is seeded
+    /// by the transcript, the blinding factors of the committed values and
+    /// randomness from `rng`.  With a seeded `rng`, such as a `ChaChaRng`,
+    /// and fixed blinding factors, the proof is fully deterministic, which
+    /// is meant for tests and test vectors only.
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        mut self,
+        bp_gens: &BulletproofGens<C>,
+        rng: &mut R,
+    ) -> Result<R1CSProof<C>, R1CSError> {
         use crate::util;
-        use std::iter;
+        use core::iter;
 
         // Commit a length _suffix_ for the number of high-level variables.
         // We cannot do this in advance because user can commit variables one-by-one,
@@ -401,15 +461,15 @@ impl<'t, 'g> Prover<'t, 'g> {
         // protect the v's in the commitments), we don't gain much by
         // committing the v's as well as the v_blinding's.
         let mut rng = {
-            let mut builder = self.transcript.build_rng();
-
             // Commit the blinding factors for the input wires
-            for v_b in &self.v_blinding {
-                builder = builder.rekey_with_witness_bytes(b"v_blinding", v_b.as_bytes());
-            }
-
-            use rand::thread_rng;
-            builder.finalize(&mut thread_rng())
+            let v_blinding_bytes: Vec<[u8; 32]> =
+                self.v_blinding.iter().map(|v_b| v_b.to_bytes()).collect();
+            let witness: Vec<(&'static [u8], &[u8])> = v_blinding_bytes
+                .iter()
+                .map(|v_b| (&b"v_blinding"[..], &v_b[..]))
+                .collect();
+
+            self.transcript.witness_rng(&witness, rng)
         };
 
         // Commit to the first-phase low-level witness variables.
@@ -422,15 +482,15 @@ impl<'t, 'g> Prover<'t, 'g> {
         // We are performing a single-party circuit proof, so party index is 0.
         let gens = bp_gens.share(0);
 
-        let i_blinding1 = Scalar::random(&mut rng);
-        let o_blinding1 = Scalar::random(&mut rng);
-        let s_blinding1 = Scalar::random(&mut rng);
+        let i_blinding1 = C::Scalar::random(&mut rng);
+        let o_blinding1 = C::Scalar::random(&mut rng);
+        let s_blinding1 = C::Scalar::random(&mut rng);
 
-        let mut s_L1: Vec<Scalar> = (0..n1).map(|_| Scalar::random(&mut rng)).collect();
-        let mut s_R1: Vec<Scalar> = (0..n1).map(|_| Scalar::random(&mut rng)).collect();
+        let mut s_L1: Vec<C::Scalar> = (0..n1).map(|_| C::Scalar::random(&mut rng)).collect();
+        let mut s_R1: Vec<C::Scalar> = (0..n1).map(|_| C::Scalar::random(&mut rng)).collect();
 
         // A_I = <a_L, G> + <a_R, H> + i_blinding * B_blinding
-        let A_I1 = RistrettoPoint::multiscalar_mul(
+        let A_I1 = C::multiscalar_mul(
             iter::once(&i_blinding1)
                 .chain(self.a_L.iter())
                 .chain(self.a_R.iter()),
@@ -441,14 +501,14 @@ impl<'t, 'g> Prover<'t, 'g> {
         .compress();
 
         // A_O = <a_O, G> + o_blinding * B_blinding
-        let A_O1 = RistrettoPoint::multiscalar_mul(
+        let A_O1 = C::multiscalar_mul(
             iter::once(&o_blinding1).chain(self.a_O.iter()),
             iter::once(&self.pc_gens.B_blinding).chain(gens.G(n1)),
         )
         .compress();
 
         // S = <s_L, G> + <s_R, H> + s_blinding * B_blinding
-        let S1 = RistrettoPoint::multiscalar_mul(
+        let S1 = C::multiscalar_mul(
             iter::once(&s_blinding1)
                 .chain(s_L1.iter())
                 .chain(s_R1.iter()),
@@ -483,21 +543,21 @@ impl<'t, 'g> Prover<'t, 'g> {
 
         let (i_blinding2, o_blinding2, s_blinding2) = if has_2nd_phase_commitments {
             (
-                Scalar::random(&mut rng),
-                Scalar::random(&mut rng),
-                Scalar::random(&mut rng),
+                C::Scalar::random(&mut rng),
+                C::Scalar::random(&mut rng),
+                C::Scalar::random(&mut rng),
             )
         } else {
-            (Scalar::zero(), Scalar::zero(), Scalar::zero())
+            (C::Scalar::zero(), C::Scalar::zero(), C::Scalar::zero())
         };
 
-        let mut s_L2: Vec<Scalar> = (0..n2).map(|_| Scalar::random(&mut rng)).collect();
-        let mut s_R2: Vec<Scalar> = (0..n2).map(|_| Scalar::random(&mut rng)).collect();
+        let mut s_L2: Vec<C::Scalar> = (0..n2).map(|_| C::Scalar::random(&mut rng)).collect();
+        let mut s_R2: Vec<C::Scalar> = (0..n2).map(|_| C::Scalar::random(&mut rng)).collect();
 
         let (A_I2, A_O2, S2) = if has_2nd_phase_commitments {
             (
                 // A_I = <a_L, G> + <a_R, H> + i_blinding * B_blinding
-                RistrettoPoint::multiscalar_mul(
+                C::multiscalar_mul(
                     iter::once(&i_blinding2)
                         .chain(self.a_L.iter().skip(n1))
                         .chain(self.a_R.iter().skip(n1)),
@@ -507,13 +567,13 @@ impl<'t, 'g> Prover<'t, 'g> {
                 )
                 .compress(),
                 // A_O = <a_O, G> + o_blinding * B_blinding
-                RistrettoPoint::multiscalar_mul(
+                C::multiscalar_mul(
                     iter::once(&o_blinding2).chain(self.a_O.iter().skip(n1)),
                     iter::once(&self.pc_gens.B_blinding).chain(gens.G(n).skip(n1)),
                 )
                 .compress(),
                 // S = <s_L, G> + <s_R, H> + s_blinding * B_blinding
-                RistrettoPoint::multiscalar_mul(
+                C::multiscalar_mul(
                     iter::once(&s_blinding2)
                         .chain(s_L2.iter())
                         .chain(s_R2.iter()),
@@ -529,9 +589,9 @@ impl<'t, 'g> Prover<'t, 'g> {
             // the commitments _must_ be identity points,
             // so we can hardcode them saving 3 mults+compressions.
             (
-                CompressedRistretto::identity(),
-                CompressedRistretto::identity(),
-                CompressedRistretto::identity(),
+                C::Compressed::identity(),
+                C::Compressed::identity(),
+                C::Compressed::identity(),
             )
         };
 
@@ -541,15 +601,15 @@ impl<'t, 'g> Prover<'t, 'g> {
 
         // 4. Compute blinded vector polynomials l(x) and r(x)
 
-        let y = self.transcript.challenge_scalar(b"y");
-        let z = self.transcript.challenge_scalar(b"z");
+        let y: C::Scalar = self.transcript.challenge_scalar(b"y");
+        let z: C::Scalar = self.transcript.challenge_scalar(b"z");
 
         let (wL, wR, wO, wV) = self.flattened_constraints(&z);
 
         let mut l_poly = util::VecPoly3::zero(n);
         let mut r_poly = util::VecPoly3::zero(n);
 
-        let mut exp_y = Scalar::one(); // y^n starting at n=0
+        let mut exp_y = C::Scalar::one(); // y^n starting at n=0
         let y_inv = y.invert();
         let exp_y_inv = util::exp_iter(y_inv).take(padded_n).collect::<Vec<_>>();
 
@@ -571,18 +631,18 @@ impl<'t, 'g> Prover<'t, 'g> {
             r_poly.1[i] = exp_y * self.a_R[i] + wL[i];
             // r_poly.2 = 0
             // r_poly.3 = y^n * s_R
-            r_poly.3[i] = exp_y * sr;
+            r_poly.3[i] = exp_y * *sr;
 
             exp_y = exp_y * y; // y^i -> y^(i+1)
         }
 
         let t_poly = util::VecPoly3::special_inner_product(&l_poly, &r_poly);
 
-        let t_1_blinding = Scalar::random(&mut rng);
-        let t_3_blinding = Scalar::random(&mut rng);
-        let t_4_blinding = Scalar::random(&mut rng);
-        let t_5_blinding = Scalar::random(&mut rng);
-        let t_6_blinding = Scalar::random(&mut rng);
+        let t_1_blinding = C::Scalar::random(&mut rng);
+        let t_3_blinding = C::Scalar::random(&mut rng);
+        let t_4_blinding = C::Scalar::random(&mut rng);
+        let t_5_blinding = C::Scalar::random(&mut rng);
+        let t_6_blinding = C::Scalar::random(&mut rng);
 
         let T_1 = self.pc_gens.commit(t_poly.t1, t_1_blinding).compress();
         let T_3 = self.pc_gens.commit(t_poly.t3, t_3_blinding).compress();
@@ -596,15 +656,15 @@ impl<'t, 'g> Prover<'t, 'g> {
         self.transcript.append_point(b"T_5", &T_5);
         self.transcript.append_point(b"T_6", &T_6);
 
-        let u = self.transcript.challenge_scalar(b"u");
-        let x = self.transcript.challenge_scalar(b"x");
+        let u: C::Scalar = self.transcript.challenge_scalar(b"u");
+        let x: C::Scalar = self.transcript.challenge_scalar(b"x");
 
         // t_2_blinding = <z*z^Q, W_V * v_blinding>
         // in the t_x_blinding calculations, line 76.
         let t_2_blinding = wV
             .iter()
             .zip(self.v_blinding.iter())
-            .map(|(c, v_blinding)| c * v_blinding)
+            .map(|(c, v_blinding)| *c * *v_blinding)
             .sum();
 
         let t_blinding_poly = util::Poly6 {
@@ -619,10 +679,10 @@ impl<'t, 'g> Prover<'t, 'g> {
         let t_x = t_poly.eval(x);
         let t_x_blinding = t_blinding_poly.eval(x);
         let mut l_vec = l_poly.eval(x);
-        l_vec.append(&mut vec![Scalar::zero(); pad]);
+        l_vec.append(&mut vec![C::Scalar::zero(); pad]);
 
         let mut r_vec = r_poly.eval(x);
-        r_vec.append(&mut vec![Scalar::zero(); pad]);
+        r_vec.append(&mut vec![C::Scalar::zero(); pad]);
 
         // XXX this should refer to the notes to explain why this is correct
         for i in n..padded_n {
@@ -642,17 +702,17 @@ impl<'t, 'g> Prover<'t, 'g> {
         self.transcript.append_scalar(b"e_blinding", &e_blinding);
 
         // Get a challenge value to combine statements for the IPP
-        let w = self.transcript.challenge_scalar(b"w");
-        let Q = w * self.pc_gens.B;
+        let w: C::Scalar = self.transcript.challenge_scalar(b"w");
+        let Q = self.pc_gens.B * w;
 
-        let G_factors = iter::repeat(Scalar::one())
+        let G_factors = iter::repeat(C::Scalar::one())
             .take(n1)
             .chain(iter::repeat(u).take(n2 + pad))
             .collect::<Vec<_>>();
         let H_factors = exp_y_inv
             .into_iter()
             .zip(G_factors.iter())
-            .map(|(y, u_or_1)| y * u_or_1)
+            .map(|(y, u_or_1)| y * *u_or_1)
             .collect::<Vec<_>>();
 
         let ipp_proof = InnerProductProof::create(
@@ -0,0 +1,378 @@
+//! Definition of the one-of-many set membership proof.
+
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+#[cfg(feature = "std")]
+use rand::thread_rng;
+use rand_core::{CryptoRng, RngCore};
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    ConstraintSystem, LinearCombination, Prover, R1CSError, R1CSProof, Variable, Verifier,
+};
+use crate::generators::{BulletproofGens, PedersenGens};
+use crate::transcript::TranscriptProtocol;
+
+/// A proof that a committed value is one of the elements of a public set,
+/// without revealing which one.
+///
+/// The statement is expressed with a vector of selector bits
+/// \\(b_0, \dots, b_{n-1}\\), one per element of the set, with the constraints
+/// \\[
+/// b_i (1 - b_i) = 0, \quad \sum_i b_i = 1, \quad \sum_i b_i s_i = v,
+/// \\]
+/// so that exactly one bit is set and it selects the committed value \\(v\\).
+/// The proof uses \\(n\\) multipliers, so `bp_gens` must have capacity for
+/// the size of the set rounded up to the next power of two.
+#[derive(Clone, Debug)]
+pub struct SetMembershipProof(R1CSProof);
+
+impl SetMembershipProof {
+    /// Enforces that the variable `v` is equal to one of the elements of `set`.
+    ///
+    /// The prover passes the position of `v` in the set as `index`,
+    /// the verifier passes `None`.
+    pub fn create_constraints<CS: ConstraintSystem>(
+        cs: &mut CS,
+        v: Variable,
+        set: &[Scalar],
+        index: Option<usize>,
+    ) -> Result<(), R1CSError> {
+        if set.is_empty() {
+            return Err(R1CSError::GadgetError {
+                description: "set membership needs a non-empty set".into(),
+            });
+        }
+
+        let mut selected = LinearCombination::default();
+        let mut bit_sum = LinearCombination::default();
+
+        for (i, s_i) in set.iter().enumerate() {
+            // Allocate the selector bit as b_i * (1 - b_i)
+            let (a, b, o) = cs.allocate_multiplier(index.map(|index| {
+                let bit = (index == i) as u64;
+                (bit.into(), (1 - bit).into())
+            }))?;
+
+            // Enforce a * b = 0 and a + b = 1, so that a is a bit
+            cs.constrain(o.into());
+            cs.constrain(a + (b - 1u64));
+
+            selected = selected + a * *s_i;
+            bit_sum = bit_sum + a;
+        }
+
+        // Enforce that exactly one bit is set, and that it selects v
+        cs.constrain(bit_sum - 1u64);
+        cs.constrain(selected - v);
+
+        Ok(())
+    }
+
+    fn transcript_prelude(transcript: &mut Transcript, set: &[Scalar]) {
+        transcript.append_message(b"dom-sep", b"SetMembershipProof");
+        transcript.append_u64(b"n", set.len() as u64);
+        for s_i in set {
+            transcript.append_scalar(b"s", s_i);
+        }
+    }
+
+    /// Create a proof that the value `v` is one of the elements of `set`.
+    ///
+    /// # Returns
+    ///
+    /// Returns a pair of the proof and the Pedersen commitment to `v`
+    /// with the blinding factor `v_blinding`.
+    ///
+    /// This is a convenience wrapper around [`SetMembershipProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove(
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        v: Scalar,
+        v_blinding: &Scalar,
+        set: &[Scalar],
+    ) -> Result<(SetMembershipProof, CompressedRistretto), R1CSError> {
+        SetMembershipProof::prove_with_rng(
+            pc_gens,
+            bp_gens,
+            transcript,
+            v,
+            v_blinding,
+            set,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Create a proof that the value `v` is one of the elements of `set`,
+    /// using `rng` for the blinding factors of the proof.
+    pub fn prove_with_rng<R: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        v: Scalar,
+        v_blinding: &Scalar,
+        set: &[Scalar],
+        rng: &mut R,
+    ) -> Result<(SetMembershipProof, CompressedRistretto), R1CSError> {
+        let index = set
+            .iter()
+            .position(|s_i| *s_i == v)
+            .ok_or(R1CSError::GadgetError {
+                description: "value is not an element of the set".into(),
+            })?;
+
+        SetMembershipProof::transcript_prelude(transcript, set);
+
+        let mut prover = Prover::new(pc_gens, transcript);
+        let (commitment, var) = prover.commit(v, *v_blinding);
+
+        SetMembershipProof::create_constraints(&mut prover, var, set, Some(index))?;
+
+        let proof = prover.prove_with_rng(bp_gens, rng)?;
+
+        Ok((SetMembershipProof(proof), commitment))
+    }
+
+    /// Verifies that the `commitment` opens to one of the elements of `set`.
+    ///
+    /// This is a convenience wrapper around [`SetMembershipProof::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify(
+        &self,
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        commitment: &CompressedRistretto,
+        set: &[Scalar],
+    ) -> Result<(), R1CSError> {
+        self.verify_with_rng(
+            pc_gens,
+            bp_gens,
+            transcript,
+            commitment,
+            set,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Verifies that the `commitment` opens to one of the elements of `set`,
+    /// using `rng` for the random weights of the verification equation.
+    pub fn verify_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        transcript: &mut Transcript,
+        commitment: &CompressedRistretto,
+        set: &[Scalar],
+        rng: &mut R,
+    ) -> Result<(), R1CSError> {
+        SetMembershipProof::transcript_prelude(transcript, set);
+
+        let mut verifier = Verifier::new(transcript);
+        let var = verifier.commit(*commitment);
+
+        SetMembershipProof::create_constraints(&mut verifier, var, set, None)?;
+
+        verifier.verify_with_rng(&self.0, pc_gens, bp_gens, rng)
+    }
+
+    /// Serializes the proof into a byte array, see [`R1CSProof::to_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    /// Returns the size in bytes required to serialize the proof.
+    pub fn serialized_size(&self) -> usize {
+        self.0.serialized_size()
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a `SetMembershipProof`.
+    pub fn from_bytes(slice: &[u8]) -> Result<SetMembershipProof, R1CSError> {
+        R1CSProof::from_bytes(slice).map(SetMembershipProof)
+    }
+}
+
+impl Serialize for SetMembershipProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for SetMembershipProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SetMembershipProofVisitor;
+
+        impl<'de> Visitor<'de> for SetMembershipProofVisitor {
+            type Value = SetMembershipProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                formatter.write_str("a valid SetMembershipProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<SetMembershipProof, E>
+            where
+                E: serde::de::Error,
+            {
+                // Using Error::custom requires T: Display, which our error
+                // type only implements when it implements std::error::Error.
+                #[cfg(feature = "std")]
+                return SetMembershipProof::from_bytes(v).map_err(serde::de::Error::custom);
+                // In no-std contexts, drop the error message.
+                #[cfg(not(feature = "std"))]
+                return SetMembershipProof::from_bytes(v)
+                    .map_err(|_| serde::de::Error::custom("deserialization error"));
+            }
+        }
+
+        deserializer.deserialize_bytes(SetMembershipProofVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set_membership_helper(v: u64, set: &[u64]) -> Result<(), R1CSError> {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 1);
+        let set: Vec<Scalar> = set.iter().map(|s| Scalar::from(*s)).collect();
+
+        let mut transcript = Transcript::new(b"SetMembershipTest");
+        let (proof, commitment) = SetMembershipProof::prove(
+            &pc_gens,
+            &bp_gens,
+            &mut transcript,
+            v.into(),
+            &Scalar::random(&mut rand::thread_rng()),
+            &set,
+        )?;
+
+        let mut transcript = Transcript::new(b"SetMembershipTest");
+        proof.verify(&pc_gens, &bp_gens, &mut transcript, &commitment, &set)
+    }
+
+    #[test]
+    fn set_membership() {
+        assert!(set_membership_helper(7, &[7]).is_ok());
+        assert!(set_membership_helper(3, &[1, 2, 3, 4, 5]).is_ok());
+        assert!(set_membership_helper(16, &[1, 2, 4, 8, 16]).is_ok());
+        assert!(set_membership_helper(6, &[1, 2, 3, 4, 5]).is_err());
+        assert!(set_membership_helper(1, &[]).is_err());
+    }
+
+    #[test]
+    fn set_membership_wrong_set() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 1);
+        let set: Vec<Scalar> = (1u64..=5).map(Scalar::from).collect();
+        let other_set: Vec<Scalar> = (6u64..=10).map(Scalar::from).collect();
+
+        let mut transcript = Transcript::new(b"SetMembershipTest");
+        let (proof, commitment) = SetMembershipProof::prove(
+            &pc_gens,
+            &bp_gens,
+            &mut transcript,
+            2u64.into(),
+            &Scalar::random(&mut rand::thread_rng()),
+            &set,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"SetMembershipTest");
+        assert!(proof
+            .verify(&pc_gens, &bp_gens, &mut transcript, &commitment, &other_set)
+            .is_err());
+    }
+
+    #[test]
+    fn set_membership_serialization() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 1);
+        let set: Vec<Scalar> = (1u64..=5).map(Scalar::from).collect();
+
+        let mut transcript = Transcript::new(b"SetMembershipTest");
+        let (proof, commitment) = SetMembershipProof::prove(
+            &pc_gens,
+            &bp_gens,
+            &mut transcript,
+            4u64.into(),
+            &Scalar::random(&mut rand::thread_rng()),
+            &set,
+        )
+        .unwrap();
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), proof.serialized_size());
+        let proof = SetMembershipProof::from_bytes(&bytes).unwrap();
+
+        let proof: SetMembershipProof =
+            bincode::deserialize(&bincode::serialize(&proof).unwrap()).unwrap();
+
+        let mut transcript = Transcript::new(b"SetMembershipTest");
+        assert!(proof
+            .verify(&pc_gens, &bp_gens, &mut transcript, &commitment, &set)
+            .is_ok());
+
+        assert!(SetMembershipProof::from_bytes(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn set_membership_deterministic() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaChaRng;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 1);
+        let set: Vec<Scalar> = (1u64..=5).map(Scalar::from).collect();
+
+        let seeded_proof = |seed: [u8; 32]| {
+            let mut rng = ChaChaRng::from_seed(seed);
+            let mut transcript = Transcript::new(b"SetMembershipTest");
+            SetMembershipProof::prove_with_rng(
+                &pc_gens,
+                &bp_gens,
+                &mut transcript,
+                2u64.into(),
+                &Scalar::random(&mut rng),
+                &set,
+                &mut rng,
+            )
+            .unwrap()
+        };
+
+        // The same seed gives the same commitment and proof.
+        let (proof, commitment) = seeded_proof([3u8; 32]);
+        let (again, commitment_again) = seeded_proof([3u8; 32]);
+        assert_eq!(proof.to_bytes(), again.to_bytes());
+        assert_eq!(commitment, commitment_again);
+
+        let mut transcript = Transcript::new(b"SetMembershipTest");
+        assert!(proof
+            .verify_with_rng(
+                &pc_gens,
+                &bp_gens,
+                &mut transcript,
+                &commitment,
+                &set,
+                &mut ChaChaRng::from_seed([4u8; 32]),
+            )
+            .is_ok());
+    }
+}
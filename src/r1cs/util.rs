@@ -1,5 +1,8 @@
 #![allow(non_snake_case)]
 #![allow(dead_code)]
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
 use curve25519_dalek::scalar::Scalar;
 use ethnum::I256;
 
@@ -1,19 +1,28 @@
 #![allow(non_snake_case)]
 
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
 use core::mem;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::VartimeMultiscalarMul;
+use curve25519_dalek::ristretto::RistrettoPoint;
 use merlin::Transcript;
+#[cfg(feature = "std")]
+use rand::thread_rng;
+use rand_core::{CryptoRng, RngCore};
 
 use super::{
     ConstraintSystem, LinearCombination, R1CSProof, RandomizableConstraintSystem,
     RandomizedConstraintSystem, Variable,
 };
 
+use crate::batch_verifier::BatchVerifier;
+use crate::curve::{CompressedPoint, CurvePoint, CurveScalar};
 use crate::errors::R1CSError;
 use crate::generators::{BulletproofGens, PedersenGens};
-use crate::transcript::TranscriptProtocol;
+use crate::transcript::{ProofTranscript, TranscriptProtocol};
+
+/// The flattened constraint weights `(wL, wR, wO, wV, wc)`.
+type FlattenedConstraints<S> = (Vec<S>, Vec<S>, Vec<S>, Vec<S>, S);
 
 /// A [`ConstraintSystem`] implementation for use by the verifier.
 ///
@@ -24,9 +33,11 @@ use crate::transcript::TranscriptProtocol;
 /// When all constraints are added, the verifying code calls `verify`
 /// which consumes the `Verifier` instance, samples random challenges
 /// that instantiate the randomized constraints, and verifies the proof.
-pub struct Verifier<'t> {
-    transcript: &'t mut Transcript,
-    constraints: Vec<LinearCombination>,
+///
+/// The proof is over the group `C`, which is `ristretto255` by default.
+pub struct Verifier<'t, T: ProofTranscript = Transcript, C: CurvePoint = RistrettoPoint> {
+    transcript: &'t mut T,
+    constraints: Vec<LinearCombination<C::Scalar>>,
 
     /// Records the number of low-level variables allocated in the
     /// constraint system.
@@ -36,13 +47,14 @@ pub struct Verifier<'t> {
     /// `Missing`), so the `num_vars` isn't kept implicitly in the
     /// variable assignments.
     num_vars: usize,
-    V: Vec<CompressedRistretto>,
+    V: Vec<C::Compressed>,
 
     /// This list holds closures that will be called in the second phase of the protocol,
     /// when non-randomized variables are committed.
     /// After that, the option will flip to None and additional calls to `randomize_constraints`
     /// will invoke closures immediately.
-    deferred_constraints: Vec<Box<dyn Fn(&mut RandomizingVerifier<'t>) -> Result<(), R1CSError>>>,
+    deferred_constraints:
+        Vec<Box<dyn Fn(&mut RandomizingVerifier<'t, T, C>) -> Result<(), R1CSError>>>,
 
     /// Index of a pending multiplier that's not fully assigned yet.
     pending_multiplier: Option<usize>,
@@ -55,19 +67,22 @@ pub struct Verifier<'t> {
 /// monomorphize the closures for the proving and verifying code.
 /// However, this type cannot be instantiated by the user and therefore can only be used within
 /// the callback provided to `specify_randomized_constraints`.
-pub struct RandomizingVerifier<'t> {
-    verifier: Verifier<'t>,
+pub struct RandomizingVerifier<'t, T: ProofTranscript = Transcript, C: CurvePoint = RistrettoPoint>
+{
+    verifier: Verifier<'t, T, C>,
 }
 
-impl<'t> ConstraintSystem for Verifier<'t> {
-    fn transcript(&mut self) -> &mut Transcript {
+impl<'t, T: ProofTranscript, C: CurvePoint> ConstraintSystem<C::Scalar> for Verifier<'t, T, C> {
+    type Transcript = T;
+
+    fn transcript(&mut self) -> &mut T {
         self.transcript
     }
 
     fn multiply(
         &mut self,
-        mut left: LinearCombination,
-        mut right: LinearCombination,
+        mut left: LinearCombination<C::Scalar>,
+        mut right: LinearCombination<C::Scalar>,
     ) -> (Variable, Variable, Variable) {
         let var = self.num_vars;
         self.num_vars += 1;
@@ -78,15 +93,15 @@ impl<'t> ConstraintSystem for Verifier<'t> {
         let o_var = Variable::MultiplierOutput(var);
 
         // Constrain l,r,o:
-        left.terms.push((l_var, -Scalar::one()));
-        right.terms.push((r_var, -Scalar::one()));
+        left.terms.push((l_var, -C::Scalar::one()));
+        right.terms.push((r_var, -C::Scalar::one()));
         self.constrain(left);
         self.constrain(right);
 
         (l_var, r_var, o_var)
     }
 
-    fn allocate(&mut self, _: Option<Scalar>) -> Result<Variable, R1CSError> {
+    fn allocate(&mut self, _: Option<C::Scalar>) -> Result<Variable, R1CSError> {
         match self.pending_multiplier {
             None => {
                 let i = self.num_vars;
@@ -103,7 +118,7 @@ impl<'t> ConstraintSystem for Verifier<'t> {
 
     fn allocate_multiplier(
         &mut self,
-        _: Option<(Scalar, Scalar)>,
+        _: Option<(C::Scalar, C::Scalar)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError> {
         let var = self.num_vars;
         self.num_vars += 1;
@@ -120,7 +135,7 @@ impl<'t> ConstraintSystem for Verifier<'t> {
         self.num_vars
     }
 
-    fn constrain(&mut self, lc: LinearCombination) {
+    fn constrain(&mut self, lc: LinearCombination<C::Scalar>) {
         // TODO: check that the linear combinations are valid
         // (e.g. that variables are valid, that the linear combination
         // evals to 0 for prover, etc).
@@ -128,8 +143,10 @@ impl<'t> ConstraintSystem for Verifier<'t> {
     }
 }
 
-impl<'t> RandomizableConstraintSystem for Verifier<'t> {
-    type RandomizedCS = RandomizingVerifier<'t>;
+impl<'t, T: ProofTranscript, C: CurvePoint> RandomizableConstraintSystem<C::Scalar>
+    for Verifier<'t, T, C>
+{
+    type RandomizedCS = RandomizingVerifier<'t, T, C>;
 
     fn specify_randomized_constraints<F>(&mut self, callback: F) -> Result<(), R1CSError>
     where
@@ -140,26 +157,30 @@ impl<'t> RandomizableConstraintSystem for Verifier<'t> {
     }
 }
 
-impl<'t> ConstraintSystem for RandomizingVerifier<'t> {
-    fn transcript(&mut self) -> &mut Transcript {
+impl<'t, T: ProofTranscript, C: CurvePoint> ConstraintSystem<C::Scalar>
+    for RandomizingVerifier<'t, T, C>
+{
+    type Transcript = T;
+
+    fn transcript(&mut self) -> &mut T {
         self.verifier.transcript
     }
 
     fn multiply(
         &mut self,
-        left: LinearCombination,
-        right: LinearCombination,
+        left: LinearCombination<C::Scalar>,
+        right: LinearCombination<C::Scalar>,
     ) -> (Variable, Variable, Variable) {
         self.verifier.multiply(left, right)
     }
 
-    fn allocate(&mut self, assignment: Option<Scalar>) -> Result<Variable, R1CSError> {
+    fn allocate(&mut self, assignment: Option<C::Scalar>) -> Result<Variable, R1CSError> {
         self.verifier.allocate(assignment)
     }
 
     fn allocate_multiplier(
         &mut self,
-        input_assignments: Option<(Scalar, Scalar)>,
+        input_assignments: Option<(C::Scalar, C::Scalar)>,
     ) -> Result<(Variable, Variable, Variable), R1CSError> {
         self.verifier.allocate_multiplier(input_assignments)
     }
@@ -168,24 +189,27 @@ impl<'t> ConstraintSystem for RandomizingVerifier<'t> {
         self.verifier.multipliers_len()
     }
 
-    fn constrain(&mut self, lc: LinearCombination) {
+    fn constrain(&mut self, lc: LinearCombination<C::Scalar>) {
         self.verifier.constrain(lc)
     }
 }
 
-impl<'t> RandomizedConstraintSystem for RandomizingVerifier<'t> {
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+impl<'t, T: ProofTranscript, C: CurvePoint> RandomizedConstraintSystem<C::Scalar>
+    for RandomizingVerifier<'t, T, C>
+{
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> C::Scalar {
         self.verifier.transcript.challenge_scalar(label)
     }
 }
 
-impl<'t> Verifier<'t> {
+impl<'t, T: ProofTranscript, C: CurvePoint> Verifier<'t, T, C> {
     /// Construct an empty constraint system with specified external
     /// input variables.
     ///
     /// # Inputs
     ///
-    /// The `transcript` parameter is a Merlin proof transcript.  The
+    /// The `transcript` parameter is a proof transcript, by default a
+    /// Merlin transcript.  The
     /// `VerifierCS` holds onto the `&mut Transcript` until it consumes
     /// itself during [`VerifierCS::verify`], releasing its borrow of the
     /// transcript.  This ensures that the transcript cannot be
@@ -205,7 +229,7 @@ impl<'t> Verifier<'t> {
     ///
     /// The second element is a list of [`Variable`]s corresponding to
     /// the external inputs, which can be used to form constraints.
-    pub fn new(transcript: &'t mut Transcript) -> Self {
+    pub fn new(transcript: &'t mut T) -> Self {
         transcript.r1cs_domain_sep();
 
         Verifier {
@@ -230,9 +254,9 @@ impl<'t> Verifier<'t> {
     ///
     /// # Returns
     ///
-    /// Returns a pair of a Pedersen commitment (as a compressed Ristretto point),
+    /// Returns a pair of a Pedersen commitment (as a compressed point),
     /// and a [`Variable`] corresponding to it, which can be used to form constraints.
-    pub fn commit(&mut self, commitment: CompressedRistretto) -> Variable {
+    pub fn commit(&mut self, commitment: C::Compressed) -> Variable {
         let i = self.V.len();
         self.V.push(commitment);
 
@@ -244,19 +268,19 @@ impl<'t> Verifier<'t> {
 
 
 
-    pub fn get_weights(&self) -> (Vec<Vec<Scalar>>, 
-                                      Vec<Vec<Scalar>>, 
-                                      Vec<Vec<Scalar>>, 
-                                      Vec<Vec<Scalar>>, 
-                                      Vec<Scalar>) {
+    pub fn get_weights(&self) -> (Vec<Vec<C::Scalar>>, 
+                                      Vec<Vec<C::Scalar>>, 
+                                      Vec<Vec<C::Scalar>>, 
+                                      Vec<Vec<C::Scalar>>, 
+                                      Vec<C::Scalar>) {
         let n = self.num_vars;
         let Q = self.constraints.len();
 
-        let mut wL = vec![vec![Scalar::zero(); n]; Q-1];
-        let mut wR = vec![vec![Scalar::zero(); n]; Q-1];
-        let mut wO = vec![vec![Scalar::zero(); n]; Q-1];
-        let mut wV = vec![vec![Scalar::zero(); n+1]; Q-1];
-        let mut wc = vec![Scalar::zero(); Q-1];
+        let mut wL = vec![vec![C::Scalar::zero(); n]; Q-1];
+        let mut wR = vec![vec![C::Scalar::zero(); n]; Q-1];
+        let mut wO = vec![vec![C::Scalar::zero(); n]; Q-1];
+        let mut wV = vec![vec![C::Scalar::zero(); n+1]; Q-1];
+        let mut wc = vec![C::Scalar::zero(); Q-1];
         for (j, lc) in self.constraints.iter().enumerate() {
             for (var, coeff) in &lc.terms {
                 let mut gate_no: usize = j / 2;
@@ -274,7 +298,7 @@ impl<'t> Verifier<'t> {
                         if j % 2 != 0 {gate_no += n;}
                         wV[gate_no][*i] = *coeff;
                         if let Some(last) = wV[gate_no].last_mut() {
-                            *last = -Scalar::one();
+                            *last = -C::Scalar::one();
                         }
                     }
                     Variable::One() => {
@@ -303,41 +327,38 @@ impl<'t> Verifier<'t> {
     /// This has the same logic as `ProverCS::flattened_constraints()`
     /// but also computes the constant terms (which the prover skips
     /// because they're not needed to construct the proof).
-    pub fn flattened_constraints(
-        &mut self,
-        z: &Scalar,
-    ) -> (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Scalar) {
+    pub fn flattened_constraints(&mut self, z: &C::Scalar) -> FlattenedConstraints<C::Scalar> {
         let n = self.num_vars;
         let m = self.V.len();
 
-        let mut wL = vec![Scalar::zero(); n];
-        let mut wR = vec![Scalar::zero(); n];
-        let mut wO = vec![Scalar::zero(); n];
-        let mut wV = vec![Scalar::zero(); m];
-        let mut wc = Scalar::zero();
+        let mut wL = vec![C::Scalar::zero(); n];
+        let mut wR = vec![C::Scalar::zero(); n];
+        let mut wO = vec![C::Scalar::zero(); n];
+        let mut wV = vec![C::Scalar::zero(); m];
+        let mut wc = C::Scalar::zero();
 
         let mut exp_z = *z;
         for lc in self.constraints.iter() {
             for (var, coeff) in &lc.terms {
                 match var {
                     Variable::MultiplierLeft(i) => {
-                        wL[*i] += exp_z * coeff;
+                        wL[*i] += exp_z * *coeff;
                     }
                     Variable::MultiplierRight(i) => {
-                        wR[*i] += exp_z * coeff;
+                        wR[*i] += exp_z * *coeff;
                     }
                     Variable::MultiplierOutput(i) => {
-                        wO[*i] += exp_z * coeff;
+                        wO[*i] += exp_z * *coeff;
                     }
                     Variable::Committed(i) => {
-                        wV[*i] -= exp_z * coeff;
+                        wV[*i] -= exp_z * *coeff;
                     }
                     Variable::One() => {
-                        wc -= exp_z * coeff;
+                        wc -= exp_z * *coeff;
                     }
                 }
             }
-            exp_z *= z;
+            exp_z *= *z;
         }
 
         (wL, wR, wO, wV, wc)
@@ -372,11 +393,46 @@ impl<'t> Verifier<'t> {
     /// [`BulletproofGens`] should have `gens_capacity` greater than
     /// the number of multiplication constraints that will eventually
     /// be added into the constraint system.
+    ///
+    /// This is a convenience wrapper around [`Verifier::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
     pub fn verify(
+        self,
+        proof: &R1CSProof<C>,
+        pc_gens: &PedersenGens<C>,
+        bp_gens: &BulletproofGens<C>,
+    ) -> Result<(), R1CSError> {
+        self.verify_with_rng(proof, pc_gens, bp_gens, &mut thread_rng())
+    }
+
+    /// Consume this `VerifierCS` and attempt to verify the supplied `proof`,
+    /// using `rng` for the random weights of the verification equation.
+    ///
+    /// The generators are the same as for [`Verifier::verify`].
+    pub fn verify_with_rng<R: RngCore + CryptoRng>(
+        self,
+        proof: &R1CSProof<C>,
+        pc_gens: &PedersenGens<C>,
+        bp_gens: &BulletproofGens<C>,
+        rng: &mut R,
+    ) -> Result<(), R1CSError> {
+        let mut batch = BatchVerifier::new(rng);
+        self.batch_verify(&mut batch, proof, pc_gens, bp_gens)?;
+        batch.verify().map_err(|_| R1CSError::VerificationError)
+    }
+
+    /// Consume this `VerifierCS` and append the verification equation of
+    /// the supplied `proof` to the `batch`, which checks it together with
+    /// the equations of other proofs in [`BatchVerifier::verify`].
+    ///
+    /// The generators are the same as for [`Verifier::verify`].
+    pub fn batch_verify<R: RngCore + CryptoRng>(
         mut self,
-        proof: &R1CSProof,
-        pc_gens: &PedersenGens,
-        bp_gens: &BulletproofGens,
+        batch: &mut BatchVerifier<R, C>,
+        proof: &R1CSProof<C>,
+        pc_gens: &PedersenGens<C>,
+        bp_gens: &BulletproofGens<C>,
     ) -> Result<(), R1CSError> {
         // Commit a length _suffix_ for the number of high-level variables.
         // We cannot do this in advance because user can commit variables one-by-one,
@@ -403,7 +459,7 @@ impl<'t> Verifier<'t> {
 
         use crate::inner_product_proof::inner_product;
         use crate::util;
-        use std::iter;
+        use core::iter;
 
         if bp_gens.gens_capacity < padded_n {
             return Err(R1CSError::InvalidGeneratorsLength);
@@ -416,8 +472,8 @@ impl<'t> Verifier<'t> {
         self.transcript.append_point(b"A_O2", &proof.A_O2);
         self.transcript.append_point(b"S2", &proof.S2);
 
-        let y = self.transcript.challenge_scalar(b"y");
-        let z = self.transcript.challenge_scalar(b"z");
+        let y: C::Scalar = self.transcript.challenge_scalar(b"y");
+        let z: C::Scalar = self.transcript.challenge_scalar(b"z");
 
         self.transcript
             .validate_and_append_point(b"T_1", &proof.T_1)?;
@@ -430,8 +486,8 @@ impl<'t> Verifier<'t> {
         self.transcript
             .validate_and_append_point(b"T_6", &proof.T_6)?;
 
-        let u = self.transcript.challenge_scalar(b"u");
-        let x = self.transcript.challenge_scalar(b"x");
+        let u: C::Scalar = self.transcript.challenge_scalar(b"u");
+        let x: C::Scalar = self.transcript.challenge_scalar(b"x");
 
         self.transcript.append_scalar(b"t_x", &proof.t_x);
         self.transcript
@@ -439,7 +495,7 @@ impl<'t> Verifier<'t> {
         self.transcript
             .append_scalar(b"e_blinding", &proof.e_blinding);
 
-        let w = self.transcript.challenge_scalar(b"w");
+        let w: C::Scalar = self.transcript.challenge_scalar(b"w");
 
         let (wL, wR, wO, wV, wc) = self.flattened_constraints(&z);
         /*
@@ -462,17 +518,17 @@ impl<'t> Verifier<'t> {
         let y_inv = y.invert();
         let y_inv_vec = util::exp_iter(y_inv)
             .take(padded_n)
-            .collect::<Vec<Scalar>>();
+            .collect::<Vec<C::Scalar>>();
         let yneg_wR = wR
             .into_iter()
             .zip(y_inv_vec.iter())
-            .map(|(wRi, exp_y_inv)| wRi * exp_y_inv)
-            .chain(iter::repeat(Scalar::zero()).take(pad))
-            .collect::<Vec<Scalar>>();
+            .map(|(wRi, exp_y_inv)| wRi * *exp_y_inv)
+            .chain(iter::repeat(C::Scalar::zero()).take(pad))
+            .collect::<Vec<C::Scalar>>();
 
         let delta = inner_product(&yneg_wR[0..n], &wL);
 
-        let u_for_g = iter::repeat(Scalar::one())
+        let u_for_g = iter::repeat(C::Scalar::one())
             .take(n1)
             .chain(iter::repeat(u).take(n2 + pad));
         let u_for_h = u_for_g.clone();
@@ -482,24 +538,29 @@ impl<'t> Verifier<'t> {
             .iter()
             .zip(u_for_g)
             .zip(s.iter().take(padded_n))
-            .map(|((yneg_wRi, u_or_1), s_i)| u_or_1 * (x * yneg_wRi - a * s_i));
+            .map(|((yneg_wRi, u_or_1), s_i)| u_or_1 * (x * *yneg_wRi - a * *s_i));
 
         let h_scalars = y_inv_vec
             .iter()
             .zip(u_for_h)
             .zip(s.iter().rev().take(padded_n))
-            .zip(wL.into_iter().chain(iter::repeat(Scalar::zero()).take(pad)))
-            .zip(wO.into_iter().chain(iter::repeat(Scalar::zero()).take(pad)))
+            .zip(
+                wL.into_iter()
+                    .chain(iter::repeat(C::Scalar::zero()).take(pad)),
+            )
+            .zip(
+                wO.into_iter()
+                    .chain(iter::repeat(C::Scalar::zero()).take(pad)),
+            )
             .map(|((((y_inv_i, u_or_1), s_i_inv), wLi), wOi)| {
-                u_or_1 * (y_inv_i * (x * wLi + wOi - b * s_i_inv) - Scalar::one())
+                u_or_1 * (*y_inv_i * (x * wLi + wOi - b * *s_i_inv) - C::Scalar::one())
             });
 
-        // Create a `TranscriptRng` from the transcript. The verifier
+        // Create a transcript rng from the transcript. The verifier
         // has no witness data to commit, so this just mixes external
         // randomness into the existing transcript.
-        use rand::thread_rng;
-        let mut rng = self.transcript.build_rng().finalize(&mut thread_rng());
-        let r = Scalar::random(&mut rng);
+        let mut rng = self.transcript.witness_rng(&[], batch.rng());
+        let r = C::Scalar::random(&mut rng);
 
         let xx = x * x;
         let rxx = r * xx;
@@ -509,14 +570,14 @@ impl<'t> Verifier<'t> {
         let T_scalars = [r * x, rxx * x, rxx * xx, rxx * xxx, rxx * xx * xx];
         let T_points = [proof.T_1, proof.T_3, proof.T_4, proof.T_5, proof.T_6];
 
-        let mega_check = RistrettoPoint::optional_multiscalar_mul(
+        batch.append(
             iter::once(x) // A_I1
                 .chain(iter::once(xx)) // A_O1
                 .chain(iter::once(xxx)) // S1
                 .chain(iter::once(u * x)) // A_I2
                 .chain(iter::once(u * xx)) // A_O2
                 .chain(iter::once(u * xxx)) // S2
-                .chain(wV.iter().map(|wVi| wVi * rxx)) // V
+                .chain(wV.iter().map(|wVi| *wVi * rxx)) // V
                 .chain(T_scalars.iter().cloned()) // T_points
                 .chain(iter::once(
                     w * (proof.t_x - a * b) + r * (xx * (wc + delta) - proof.t_x),
@@ -540,14 +601,7 @@ impl<'t> Verifier<'t> {
                 .chain(gens.H(padded_n).map(|&H_i| Some(H_i)))
                 .chain(proof.ipp_proof.L_vec.iter().map(|L_i| L_i.decompress()))
                 .chain(proof.ipp_proof.R_vec.iter().map(|R_i| R_i.decompress())),
-        )
-        .ok_or_else(|| R1CSError::VerificationError)?;
-
-        use curve25519_dalek::traits::IsIdentity;
-
-        if !mega_check.is_identity() {
-            return Err(R1CSError::VerificationError);
-        }
+        )?;
 
         Ok(())
     }
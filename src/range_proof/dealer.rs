@@ -11,14 +11,14 @@ extern crate alloc;
 use alloc::vec::Vec;
 
 use curve25519_dalek::ristretto::RistrettoPoint;
-use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 
+use crate::curve::{CompressedPoint, CurvePoint, CurveScalar};
 use crate::errors::MPCError;
 use crate::generators::{BulletproofGens, PedersenGens};
 use crate::inner_product_proof;
-use crate::range_proof::RangeProof;
-use crate::transcript::TranscriptProtocol;
+use crate::range_proof::{RangeProof, ValueGenTerms, ValueGens};
+use crate::transcript::{ProofTranscript, TranscriptProtocol};
 
 use rand_core::{CryptoRng, RngCore};
 
@@ -34,13 +34,42 @@ pub struct Dealer {}
 
 impl Dealer {
     /// Creates a new dealer coordinating `m` parties proving `n`-bit ranges.
-    pub fn new<'a, 'b>(
-        bp_gens: &'b BulletproofGens,
-        pc_gens: &'b PedersenGens,
-        transcript: &'a mut Transcript,
+    pub fn new<'a, 'b, T: ProofTranscript, C: CurvePoint>(
+        bp_gens: &'b BulletproofGens<C>,
+        pc_gens: &'b PedersenGens<C>,
+        transcript: &'a mut T,
         n: usize,
         m: usize,
-    ) -> Result<DealerAwaitingBitCommitments<'a, 'b>, MPCError> {
+    ) -> Result<DealerAwaitingBitCommitments<'a, 'b, T, C>, MPCError> {
+        Dealer::create(bp_gens, pc_gens, transcript, n, m, None)
+    }
+
+    /// Creates a new dealer coordinating parties proving `n`-bit ranges
+    /// for commitments with different value generators, such as the
+    /// generators of different assets.
+    ///
+    /// The `j`-th party commits to its value with the value generator
+    /// `value_gens[j]` in place of `pc_gens.B`, and with the blinding
+    /// base `pc_gens.B_blinding`; see [`Party::new`](super::party::Party::new).
+    pub fn new_with_value_gens<'a, 'b, T: ProofTranscript, C: CurvePoint>(
+        bp_gens: &'b BulletproofGens<C>,
+        pc_gens: &'b PedersenGens<C>,
+        transcript: &'a mut T,
+        n: usize,
+        value_gens: Vec<C>,
+    ) -> Result<DealerAwaitingBitCommitments<'a, 'b, T, C>, MPCError> {
+        let m = value_gens.len();
+        Dealer::create(bp_gens, pc_gens, transcript, n, m, Some(value_gens))
+    }
+
+    fn create<'a, 'b, T: ProofTranscript, C: CurvePoint>(
+        bp_gens: &'b BulletproofGens<C>,
+        pc_gens: &'b PedersenGens<C>,
+        transcript: &'a mut T,
+        n: usize,
+        m: usize,
+        value_gens: Option<Vec<C>>,
+    ) -> Result<DealerAwaitingBitCommitments<'a, 'b, T, C>, MPCError> {
         if !(n == 8 || n == 16 || n == 32 || n == 64) {
             return Err(MPCError::InvalidBitsize);
         }
@@ -68,7 +97,15 @@ impl Dealer {
         // state.
         let initial_transcript = transcript.clone();
 
-        transcript.rangeproof_domain_sep(n as u64, m as u64);
+        match value_gens {
+            Some(ref value_gens) => {
+                transcript.rangeproof_value_gens_domain_sep(n as u64, m as u64);
+                for B_j in value_gens.iter() {
+                    transcript.append_point(b"B", &B_j.compress());
+                }
+            }
+            None => transcript.rangeproof_domain_sep(n as u64, m as u64),
+        }
 
         Ok(DealerAwaitingBitCommitments {
             bp_gens,
@@ -77,28 +114,36 @@ impl Dealer {
             initial_transcript,
             n,
             m,
+            value_gens,
         })
     }
 }
 
 /// A dealer waiting for the parties to send their [`BitCommitment`]s.
-pub struct DealerAwaitingBitCommitments<'a, 'b> {
-    bp_gens: &'b BulletproofGens,
-    pc_gens: &'b PedersenGens,
-    transcript: &'a mut Transcript,
+pub struct DealerAwaitingBitCommitments<
+    'a,
+    'b,
+    T: ProofTranscript = Transcript,
+    C: CurvePoint = RistrettoPoint,
+> {
+    bp_gens: &'b BulletproofGens<C>,
+    pc_gens: &'b PedersenGens<C>,
+    transcript: &'a mut T,
     /// The dealer keeps a copy of the initial transcript state, so
     /// that it can attempt to verify the aggregated proof at the end.
-    initial_transcript: Transcript,
+    initial_transcript: T,
     n: usize,
     m: usize,
+    /// Per-party value generators, if the parties do not all use `pc_gens.B`.
+    value_gens: Option<Vec<C>>,
 }
 
-impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
+impl<'a, 'b, T: ProofTranscript, C: CurvePoint> DealerAwaitingBitCommitments<'a, 'b, T, C> {
     /// Receive each party's [`BitCommitment`]s and compute the [`BitChallenge`].
     pub fn receive_bit_commitments(
         self,
-        bit_commitments: Vec<BitCommitment>,
-    ) -> Result<(DealerAwaitingPolyCommitments<'a, 'b>, BitChallenge), MPCError> {
+        bit_commitments: Vec<BitCommitment<C>>,
+    ) -> Result<(DealerAwaitingPolyCommitments<'a, 'b, T, C>, BitChallenge<C>), MPCError> {
         if self.m != bit_commitments.len() {
             return Err(MPCError::WrongNumBitCommitments);
         }
@@ -109,14 +154,14 @@ impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
         }
 
         // Commit aggregated A_j, S_j
-        let A: RistrettoPoint = bit_commitments.iter().map(|vc| vc.A_j).sum();
+        let A: C = bit_commitments.iter().map(|vc| vc.A_j).sum();
         self.transcript.append_point(b"A", &A.compress());
 
-        let S: RistrettoPoint = bit_commitments.iter().map(|vc| vc.S_j).sum();
+        let S: C = bit_commitments.iter().map(|vc| vc.S_j).sum();
         self.transcript.append_point(b"S", &S.compress());
 
-        let y = self.transcript.challenge_scalar(b"y");
-        let z = self.transcript.challenge_scalar(b"z");
+        let y: C::Scalar = self.transcript.challenge_scalar(b"y");
+        let z: C::Scalar = self.transcript.challenge_scalar(b"z");
         let bit_challenge = BitChallenge { y, z };
 
         Ok((
@@ -127,6 +172,7 @@ impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
                 initial_transcript: self.initial_transcript,
                 bp_gens: self.bp_gens,
                 pc_gens: self.pc_gens,
+                value_gens: self.value_gens,
                 bit_challenge,
                 bit_commitments,
                 A,
@@ -139,40 +185,55 @@ impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
 
 /// A dealer which has sent the [`BitChallenge`] to the parties and
 /// is waiting for their [`PolyCommitment`]s.
-pub struct DealerAwaitingPolyCommitments<'a, 'b> {
+pub struct DealerAwaitingPolyCommitments<
+    'a,
+    'b,
+    T: ProofTranscript = Transcript,
+    C: CurvePoint = RistrettoPoint,
+> {
     n: usize,
     m: usize,
-    transcript: &'a mut Transcript,
-    initial_transcript: Transcript,
-    bp_gens: &'b BulletproofGens,
-    pc_gens: &'b PedersenGens,
-    bit_challenge: BitChallenge,
-    bit_commitments: Vec<BitCommitment>,
+    transcript: &'a mut T,
+    initial_transcript: T,
+    bp_gens: &'b BulletproofGens<C>,
+    pc_gens: &'b PedersenGens<C>,
+    value_gens: Option<Vec<C>>,
+    bit_challenge: BitChallenge<C>,
+    bit_commitments: Vec<BitCommitment<C>>,
     /// Aggregated commitment to the parties' bits
-    A: RistrettoPoint,
+    A: C,
     /// Aggregated commitment to the parties' bit blindings
-    S: RistrettoPoint,
+    S: C,
 }
 
-impl<'a, 'b> DealerAwaitingPolyCommitments<'a, 'b> {
+impl<'a, 'b, T: ProofTranscript, C: CurvePoint> DealerAwaitingPolyCommitments<'a, 'b, T, C> {
     /// Receive [`PolyCommitment`]s from the parties and compute the
     /// [`PolyChallenge`].
     pub fn receive_poly_commitments(
         self,
-        poly_commitments: Vec<PolyCommitment>,
-    ) -> Result<(DealerAwaitingProofShares<'a, 'b>, PolyChallenge), MPCError> {
+        poly_commitments: Vec<PolyCommitment<C>>,
+    ) -> Result<(DealerAwaitingProofShares<'a, 'b, T, C>, PolyChallenge<C>), MPCError> {
         if self.m != poly_commitments.len() {
             return Err(MPCError::WrongNumPolyCommitments);
         }
 
-        // Commit sums of T_1_j's and T_2_j's
-        let T_1: RistrettoPoint = poly_commitments.iter().map(|pc| pc.T_1_j).sum();
-        let T_2: RistrettoPoint = poly_commitments.iter().map(|pc| pc.T_2_j).sum();
+        let T_1: C = poly_commitments.iter().map(|pc| pc.T_1_j).sum();
+        let T_2: C = poly_commitments.iter().map(|pc| pc.T_2_j).sum();
 
-        self.transcript.append_point(b"T_1", &T_1.compress());
-        self.transcript.append_point(b"T_2", &T_2.compress());
+        if self.value_gens.is_some() {
+            // Commit each T_1_j, T_2_j individually, since they are
+            // checked against each party's own value generator
+            for pc in poly_commitments.iter() {
+                self.transcript.append_point(b"T_1", &pc.T_1_j.compress());
+                self.transcript.append_point(b"T_2", &pc.T_2_j.compress());
+            }
+        } else {
+            // Commit sums of T_1_j's and T_2_j's
+            self.transcript.append_point(b"T_1", &T_1.compress());
+            self.transcript.append_point(b"T_2", &T_2.compress());
+        }
 
-        let x = self.transcript.challenge_scalar(b"x");
+        let x: C::Scalar = self.transcript.challenge_scalar(b"x");
         let poly_challenge = PolyChallenge { x };
 
         Ok((
@@ -183,6 +244,7 @@ impl<'a, 'b> DealerAwaitingPolyCommitments<'a, 'b> {
                 initial_transcript: self.initial_transcript,
                 bp_gens: self.bp_gens,
                 pc_gens: self.pc_gens,
+                value_gens: self.value_gens,
                 bit_challenge: self.bit_challenge,
                 bit_commitments: self.bit_commitments,
                 A: self.A,
@@ -200,30 +262,39 @@ impl<'a, 'b> DealerAwaitingPolyCommitments<'a, 'b> {
 /// A dealer which has sent the [`PolyChallenge`] to the parties and
 /// is waiting to aggregate their [`ProofShare`]s into a
 /// [`RangeProof`].
-pub struct DealerAwaitingProofShares<'a, 'b> {
+pub struct DealerAwaitingProofShares<
+    'a,
+    'b,
+    T: ProofTranscript = Transcript,
+    C: CurvePoint = RistrettoPoint,
+> {
     n: usize,
     m: usize,
-    transcript: &'a mut Transcript,
-    initial_transcript: Transcript,
-    bp_gens: &'b BulletproofGens,
-    pc_gens: &'b PedersenGens,
-    bit_challenge: BitChallenge,
-    bit_commitments: Vec<BitCommitment>,
-    poly_challenge: PolyChallenge,
-    poly_commitments: Vec<PolyCommitment>,
-    A: RistrettoPoint,
-    S: RistrettoPoint,
-    T_1: RistrettoPoint,
-    T_2: RistrettoPoint,
+    transcript: &'a mut T,
+    initial_transcript: T,
+    bp_gens: &'b BulletproofGens<C>,
+    pc_gens: &'b PedersenGens<C>,
+    value_gens: Option<Vec<C>>,
+    bit_challenge: BitChallenge<C>,
+    bit_commitments: Vec<BitCommitment<C>>,
+    poly_challenge: PolyChallenge<C>,
+    poly_commitments: Vec<PolyCommitment<C>>,
+    A: C,
+    S: C,
+    T_1: C,
+    T_2: C,
 }
 
-impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
+impl<'a, 'b, T: ProofTranscript, C: CurvePoint> DealerAwaitingProofShares<'a, 'b, T, C> {
     /// Assembles proof shares into an `RangeProof`.
     ///
     /// Used as a helper function by `receive_trusted_shares` (which
     /// just hands back the result) and `receive_shares` (which
     /// validates the proof shares.
-    fn assemble_shares(&mut self, proof_shares: &[ProofShare]) -> Result<RangeProof, MPCError> {
+    fn assemble_shares(
+        &mut self,
+        proof_shares: &[ProofShare<C>],
+    ) -> Result<RangeProof<C>, MPCError> {
         if self.m != proof_shares.len() {
             return Err(MPCError::WrongNumProofShares);
         }
@@ -242,29 +313,60 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
             return Err(MPCError::MalformedProofShares { bad_shares });
         }
 
-        let t_x: Scalar = proof_shares.iter().map(|ps| ps.t_x).sum();
-        let t_x_blinding: Scalar = proof_shares.iter().map(|ps| ps.t_x_blinding).sum();
-        let e_blinding: Scalar = proof_shares.iter().map(|ps| ps.e_blinding).sum();
+        let t_x: C::Scalar = proof_shares.iter().map(|ps| ps.t_x).sum();
+        let e_blinding: C::Scalar = proof_shares.iter().map(|ps| ps.e_blinding).sum();
+
+        let (t_x_blinding, value_gen_terms) = if self.value_gens.is_some() {
+            for ps in proof_shares.iter() {
+                self.transcript.append_scalar(b"t_x", &ps.t_x);
+            }
+
+            // Get a challenge value to combine the parties' checks of t_x
+            let rho: C::Scalar = self.transcript.challenge_scalar(b"rho");
+            let t_x_blinding: C::Scalar = proof_shares
+                .iter()
+                .zip(util::exp_iter(rho))
+                .map(|(ps, exp_rho)| exp_rho * ps.t_x_blinding)
+                .sum();
+
+            let value_gen_terms = proof_shares
+                .iter()
+                .zip(self.poly_commitments.iter())
+                .map(|(ps, pc)| ValueGenTerms {
+                    T_1: pc.T_1_j.compress(),
+                    T_2: pc.T_2_j.compress(),
+                    t_x: ps.t_x,
+                })
+                .collect();
+
+            (t_x_blinding, value_gen_terms)
+        } else {
+            self.transcript.append_scalar(b"t_x", &t_x);
+
+            let t_x_blinding = proof_shares.iter().map(|ps| ps.t_x_blinding).sum();
+            (t_x_blinding, Vec::new())
+        };
 
-        self.transcript.append_scalar(b"t_x", &t_x);
         self.transcript
             .append_scalar(b"t_x_blinding", &t_x_blinding);
         self.transcript.append_scalar(b"e_blinding", &e_blinding);
 
         // Get a challenge value to combine statements for the IPP
-        let w = self.transcript.challenge_scalar(b"w");
-        let Q = w * self.pc_gens.B;
+        let w: C::Scalar = self.transcript.challenge_scalar(b"w");
+        let Q = self.pc_gens.B * w;
 
-        let G_factors: Vec<Scalar> = iter::repeat(Scalar::one()).take(self.n * self.m).collect();
-        let H_factors: Vec<Scalar> = util::exp_iter(self.bit_challenge.y.invert())
+        let G_factors: Vec<C::Scalar> = iter::repeat(C::Scalar::one())
+            .take(self.n * self.m)
+            .collect();
+        let H_factors: Vec<C::Scalar> = util::exp_iter(self.bit_challenge.y.invert())
             .take(self.n * self.m)
             .collect();
 
-        let l_vec: Vec<Scalar> = proof_shares
+        let l_vec: Vec<C::Scalar> = proof_shares
             .iter()
             .flat_map(|ps| ps.l_vec.clone().into_iter())
             .collect();
-        let r_vec: Vec<Scalar> = proof_shares
+        let r_vec: Vec<C::Scalar> = proof_shares
             .iter()
             .flat_map(|ps| ps.r_vec.clone().into_iter())
             .collect();
@@ -280,15 +382,24 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
             r_vec,
         );
 
+        // The T_1_j, T_2_j of proofs with per-party value generators
+        // are kept in the `value_gen_terms` instead of their sums.
+        let (T_1, T_2) = if self.value_gens.is_some() {
+            (C::Compressed::identity(), C::Compressed::identity())
+        } else {
+            (self.T_1.compress(), self.T_2.compress())
+        };
+
         Ok(RangeProof {
             A: self.A.compress(),
             S: self.S.compress(),
-            T_1: self.T_1.compress(),
-            T_2: self.T_2.compress(),
+            T_1,
+            T_2,
             t_x,
             t_x_blinding,
             e_blinding,
             ipp_proof,
+            value_gen_terms,
         })
     }
 
@@ -299,7 +410,7 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
     /// This is a convenience wrapper around receive_shares_with_rng
     ///
     #[cfg(feature = "std")]
-    pub fn receive_shares(self, proof_shares: &[ProofShare]) -> Result<RangeProof, MPCError> {
+    pub fn receive_shares(self, proof_shares: &[ProofShare<C>]) -> Result<RangeProof<C>, MPCError> {
         self.receive_shares_with_rng(proof_shares, &mut thread_rng())
     }
 
@@ -316,29 +427,51 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
     /// performing local aggregation,
     /// [`receive_trusted_shares`](DealerAwaitingProofShares::receive_trusted_shares)
     /// saves time by skipping verification of the aggregated proof.
-    pub fn receive_shares_with_rng<T: RngCore + CryptoRng>(
+    pub fn receive_shares_with_rng<R: RngCore + CryptoRng>(
         mut self,
-        proof_shares: &[ProofShare],
-        rng: &mut T,
-    ) -> Result<RangeProof, MPCError> {
+        proof_shares: &[ProofShare<C>],
+        rng: &mut R,
+    ) -> Result<RangeProof<C>, MPCError> {
         let proof = self.assemble_shares(proof_shares)?;
 
         let Vs: Vec<_> = self.bit_commitments.iter().map(|vc| vc.V_j).collect();
 
         // See comment in `Dealer::new` for why we use `initial_transcript`
         let transcript = &mut self.initial_transcript;
-        if proof
-            .verify_multiple_with_rng(self.bp_gens, self.pc_gens, transcript, &Vs, self.n, rng)
-            .is_ok()
-        {
+        let result = match self.value_gens {
+            Some(ref value_gens) => proof.verify_multiple_with_value_gens_with_rng(
+                self.bp_gens,
+                self.pc_gens,
+                transcript,
+                &Vs,
+                ValueGens {
+                    generators: value_gens,
+                    n: self.n,
+                },
+                rng,
+            ),
+            None => proof.verify_multiple_with_rng(
+                self.bp_gens,
+                self.pc_gens,
+                transcript,
+                &Vs,
+                self.n,
+                rng,
+            ),
+        };
+        if result.is_ok() {
             Ok(proof)
         } else {
             // Proof verification failed. Now audit the parties:
             let mut bad_shares = Vec::new();
             for j in 0..self.m {
+                let pc_gens = match self.value_gens {
+                    Some(ref value_gens) => self.pc_gens.with_value_generator(value_gens[j]),
+                    None => *self.pc_gens,
+                };
                 match proof_shares[j].audit_share(
                     &self.bp_gens,
-                    &self.pc_gens,
+                    &pc_gens,
                     j,
                     &self.bit_commitments[j],
                     &self.bit_challenge,
@@ -369,8 +502,8 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
     /// detects which party(ies) submitted malformed shares.
     pub fn receive_trusted_shares(
         mut self,
-        proof_shares: &[ProofShare],
-    ) -> Result<RangeProof, MPCError> {
+        proof_shares: &[ProofShare<C>],
+    ) -> Result<RangeProof<C>, MPCError> {
         self.assemble_shares(proof_shares)
     }
 }
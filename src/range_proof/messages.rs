@@ -8,56 +8,61 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 use core::iter;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
-use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::ristretto::RistrettoPoint;
 
+use crate::curve::{CompressedPoint, CurvePoint, CurveScalar};
 use crate::generators::{BulletproofGens, PedersenGens};
 
 /// A commitment to the bits of a party's value.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
-pub struct BitCommitment {
-    pub(super) V_j: CompressedRistretto,
-    pub(super) A_j: RistrettoPoint,
-    pub(super) S_j: RistrettoPoint,
+#[serde(bound = "")]
+pub struct BitCommitment<C: CurvePoint = RistrettoPoint> {
+    pub(super) V_j: C::Compressed,
+    pub(super) A_j: C,
+    pub(super) S_j: C,
 }
 
 /// Challenge values derived from all parties' [`BitCommitment`]s.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
-pub struct BitChallenge {
-    pub(super) y: Scalar,
-    pub(super) z: Scalar,
+#[serde(bound = "")]
+pub struct BitChallenge<C: CurvePoint = RistrettoPoint> {
+    pub(super) y: C::Scalar,
+    pub(super) z: C::Scalar,
 }
 
 /// A commitment to a party's polynomial coefficents.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
-pub struct PolyCommitment {
-    pub(super) T_1_j: RistrettoPoint,
-    pub(super) T_2_j: RistrettoPoint,
+#[serde(bound = "")]
+pub struct PolyCommitment<C: CurvePoint = RistrettoPoint> {
+    pub(super) T_1_j: C,
+    pub(super) T_2_j: C,
 }
 
 /// Challenge values derived from all parties' [`PolyCommitment`]s.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
-pub struct PolyChallenge {
-    pub(super) x: Scalar,
+#[serde(bound = "")]
+pub struct PolyChallenge<C: CurvePoint = RistrettoPoint> {
+    pub(super) x: C::Scalar,
 }
 
 /// A party's proof share, ready for aggregation into the final
 /// [`RangeProof`](::RangeProof).
 #[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct ProofShare {
-    pub(super) t_x: Scalar,
-    pub(super) t_x_blinding: Scalar,
-    pub(super) e_blinding: Scalar,
-    pub(super) l_vec: Vec<Scalar>,
-    pub(super) r_vec: Vec<Scalar>,
+#[serde(bound = "")]
+pub struct ProofShare<C: CurvePoint = RistrettoPoint> {
+    pub(super) t_x: C::Scalar,
+    pub(super) t_x_blinding: C::Scalar,
+    pub(super) e_blinding: C::Scalar,
+    pub(super) l_vec: Vec<C::Scalar>,
+    pub(super) r_vec: Vec<C::Scalar>,
 }
 
-impl ProofShare {
+impl<C: CurvePoint> ProofShare<C> {
     /// Checks consistency of all sizes in the proof share and returns the size of the l/r vector.
     pub(super) fn check_size(
         &self,
         expected_n: usize,
-        bp_gens: &BulletproofGens,
+        bp_gens: &BulletproofGens<C>,
         j: usize,
     ) -> Result<(), ()> {
         if self.l_vec.len() != expected_n {
@@ -83,16 +88,14 @@ impl ProofShare {
     /// malformed.
     pub(super) fn audit_share(
         &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
         j: usize,
-        bit_commitment: &BitCommitment,
-        bit_challenge: &BitChallenge,
-        poly_commitment: &PolyCommitment,
-        poly_challenge: &PolyChallenge,
+        bit_commitment: &BitCommitment<C>,
+        bit_challenge: &BitChallenge<C>,
+        poly_commitment: &PolyCommitment<C>,
+        poly_challenge: &PolyChallenge<C>,
     ) -> Result<(), ()> {
-        use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
-
         use crate::inner_product_proof::inner_product;
         use crate::util;
 
@@ -100,14 +103,14 @@ impl ProofShare {
 
         self.check_size(n, bp_gens, j)?;
 
-        let (y, z) = (&bit_challenge.y, &bit_challenge.z);
-        let x = &poly_challenge.x;
+        let (y, z) = (bit_challenge.y, bit_challenge.z);
+        let x = poly_challenge.x;
 
         // Precompute some variables
         let zz = z * z;
         let minus_z = -z;
-        let z_j = util::scalar_exp_vartime(z, j as u64); // z^j
-        let y_jn = util::scalar_exp_vartime(y, (j * n) as u64); // y^(j*n)
+        let z_j = util::scalar_exp_vartime(&z, j as u64); // z^j
+        let y_jn = util::scalar_exp_vartime(&y, (j * n) as u64); // y^(j*n)
         let y_jn_inv = y_jn.invert(); // y^(-j*n)
         let y_inv = y.invert(); // y^(-1)
 
@@ -115,19 +118,19 @@ impl ProofShare {
             return Err(());
         }
 
-        let g = self.l_vec.iter().map(|l_i| minus_z - l_i);
+        let g = self.l_vec.iter().map(|l_i| minus_z - *l_i);
         let h = self
             .r_vec
             .iter()
-            .zip(util::exp_iter(Scalar::from(2u64)))
+            .zip(util::exp_iter(C::Scalar::from(2u64)))
             .zip(util::exp_iter(y_inv))
             .map(|((r_i, exp_2), exp_y_inv)| {
-                z + exp_y_inv * y_jn_inv * (-r_i) + exp_y_inv * y_jn_inv * (zz * z_j * exp_2)
+                z + exp_y_inv * y_jn_inv * (-*r_i) + exp_y_inv * y_jn_inv * (zz * z_j * exp_2)
             });
 
-        let P_check = RistrettoPoint::vartime_multiscalar_mul(
-            iter::once(Scalar::one())
-                .chain(iter::once(*x))
+        let P_check = C::vartime_multiscalar_mul(
+            iter::once(C::Scalar::one())
+                .chain(iter::once(x))
                 .chain(iter::once(-self.e_blinding))
                 .chain(g)
                 .chain(h),
@@ -144,11 +147,11 @@ impl ProofShare {
         let V_j = bit_commitment.V_j.decompress().ok_or(())?;
 
         let sum_of_powers_y = util::sum_of_powers(&y, n);
-        let sum_of_powers_2 = util::sum_of_powers(&Scalar::from(2u64), n);
+        let sum_of_powers_2 = util::sum_of_powers(&C::Scalar::from(2u64), n);
         let delta = (z - zz) * sum_of_powers_y * y_jn - z * zz * sum_of_powers_2 * z_j;
-        let t_check = RistrettoPoint::vartime_multiscalar_mul(
+        let t_check = C::vartime_multiscalar_mul(
             iter::once(zz * z_j)
-                .chain(iter::once(*x))
+                .chain(iter::once(x))
                 .chain(iter::once(x * x))
                 .chain(iter::once(delta - self.t_x))
                 .chain(iter::once(-self.t_x_blinding)),
@@ -7,19 +7,21 @@ extern crate rand;
 
 #[cfg(feature = "std")]
 use self::rand::thread_rng;
+use alloc::vec;
 use alloc::vec::Vec;
 
 use core::iter;
 
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
-use merlin::Transcript;
+use core::marker::PhantomData;
 
+use curve25519_dalek::ristretto::RistrettoPoint;
+
+use crate::batch_verifier::BatchVerifier;
+use crate::curve::{CompressedPoint, CurvePoint, CurveScalar};
 use crate::errors::ProofError;
-use crate::generators::{BulletproofGens, PedersenGens};
+use crate::generators::{BulletproofGens, PedersenBases, PedersenGens, PedersenGensTable};
 use crate::inner_product_proof::InnerProductProof;
-use crate::transcript::TranscriptProtocol;
+use crate::transcript::{ProofTranscript, TranscriptProtocol};
 use crate::util;
 
 use rand_core::{CryptoRng, RngCore};
@@ -55,27 +57,107 @@ pub mod party;
 /// protocol locally.  That API is exposed in the [`aggregation`](::range_proof_mpc)
 /// module and can be used to perform online aggregation between
 /// parties without revealing secret values to each other.
+///
+/// # Value generators
+///
+/// By default, all of the values are committed with the value generator
+/// `pc_gens.B`.  The `*_with_value_gens` functions instead take a
+/// [`ValueGens`] with a value generator per commitment, such as the
+/// generators of different assets from [`PedersenGens::asset_generator`]
+/// or blinded asset tags, so that a single aggregated proof can cover
+/// commitments to amounts of different assets.  Such proofs carry commitments to each party's
+/// \\(t_1, t_2\\) coefficients and each party's \\(t(x)\\), and only verify
+/// with the same value generators.
+///
+/// # Other groups
+///
+/// Proofs are generic over the group `C` of the commitments, which
+/// defaults to `ristretto255`.  Proofs over other groups are created and
+/// verified in the same way, with the generators of that group from
+/// [`PedersenGens::standard`] and [`BulletproofGens::generate`], and are
+/// deserialized with [`RangeProof::decode`].
 #[derive(Clone, Debug)]
-pub struct RangeProof {
+pub struct RangeProof<C: CurvePoint = RistrettoPoint> {
     /// Commitment to the bits of the value
-    A: CompressedRistretto,
+    A: C::Compressed,
     /// Commitment to the blinding factors
-    S: CompressedRistretto,
+    S: C::Compressed,
     /// Commitment to the \\(t_1\\) coefficient of \\( t(x) \\)
-    T_1: CompressedRistretto,
+    T_1: C::Compressed,
     /// Commitment to the \\(t_2\\) coefficient of \\( t(x) \\)
-    T_2: CompressedRistretto,
+    T_2: C::Compressed,
     /// Evaluation of the polynomial \\(t(x)\\) at the challenge point \\(x\\)
-    t_x: Scalar,
+    t_x: C::Scalar,
     /// Blinding factor for the synthetic commitment to \\(t(x)\\)
-    t_x_blinding: Scalar,
+    t_x_blinding: C::Scalar,
     /// Blinding factor for the synthetic commitment to the inner-product arguments
-    e_blinding: Scalar,
+    e_blinding: C::Scalar,
     /// Proof data for the inner-product argument.
-    ipp_proof: InnerProductProof,
+    ipp_proof: InnerProductProof<C>,
+    /// The terms of each party for proofs with per-commitment value
+    /// generators, which are empty for proofs using `pc_gens.B`.
+    value_gen_terms: Vec<ValueGenTerms<C>>,
 }
 
-impl RangeProof {
+/// The terms of a proof with per-commitment value generators which are
+/// specific to one party, and are checked against its value generator.
+#[derive(Clone, Debug)]
+struct ValueGenTerms<C: CurvePoint> {
+    /// Commitment to the \\(t_1\\) coefficient of the party's \\( t(x) \\)
+    T_1: C::Compressed,
+    /// Commitment to the \\(t_2\\) coefficient of the party's \\( t(x) \\)
+    T_2: C::Compressed,
+    /// Evaluation of the party's polynomial \\(t(x)\\) at the challenge point \\(x\\)
+    t_x: C::Scalar,
+}
+
+/// The per-commitment value generators of a range proof, together with
+/// the bitsize `n` of the values.
+///
+/// The value generators, their number and `n` make up the domain separator
+/// of the proof's transcript, so a proof only verifies with the same
+/// `ValueGens` it was created with.
+#[derive(Copy, Clone, Debug)]
+pub struct ValueGens<'a, C: CurvePoint = RistrettoPoint> {
+    /// The value generator of each commitment, in place of `pc_gens.B`
+    pub generators: &'a [C],
+    /// The bitsize of the values
+    pub n: usize,
+}
+
+/// The value generators and bitsize that a proof is created or verified with.
+#[derive(Copy, Clone)]
+enum Bases<'a, C: CurvePoint> {
+    /// All values are committed with `pc_gens.B`, with the given bitsize.
+    Default(usize),
+    /// Each value is committed with its own value generator.
+    ValueGens(ValueGens<'a, C>),
+}
+
+impl<'a, C: CurvePoint> Bases<'a, C> {
+    fn n(&self) -> usize {
+        match self {
+            Bases::Default(n) => *n,
+            Bases::ValueGens(value_gens) => value_gens.n,
+        }
+    }
+
+    fn value_gens(&self) -> Option<&'a [C]> {
+        match self {
+            Bases::Default(_) => None,
+            Bases::ValueGens(value_gens) => Some(value_gens.generators),
+        }
+    }
+}
+
+/// Tag byte at the start of serialized proofs with per-commitment
+/// value generators.
+const VALUE_GENS_FORMAT_TAG: u8 = 1;
+/// Size of the tag byte and the number of values of serialized proofs
+/// with per-commitment value generators.
+const VALUE_GENS_HEADER_SIZE: usize = 1 + 8;
+
+impl<C: CurvePoint> RangeProof<C> {
     /// Create a rangeproof for a given pair of value `v` and
     /// blinding scalar `v_blinding`.
     /// This is a convenience wrapper around [`RangeProof::prove_multiple`].
@@ -133,14 +215,14 @@ impl RangeProof {
     /// # }
     /// ```
     pub fn prove_single_with_rng<T: RngCore + CryptoRng>(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
         v: u64,
-        v_blinding: &Scalar,
+        v_blinding: &C::Scalar,
         n: usize,
         rng: &mut T,
-    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+    ) -> Result<(RangeProof<C>, C::Compressed), ProofError> {
         let (p, Vs) = RangeProof::prove_multiple_with_rng(
             bp_gens,
             pc_gens,
@@ -159,13 +241,13 @@ impl RangeProof {
     /// passing in a threadsafe RNG.
     #[cfg(feature = "std")]
     pub fn prove_single(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
         v: u64,
-        v_blinding: &Scalar,
+        v_blinding: &C::Scalar,
         n: usize,
-    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+    ) -> Result<(RangeProof<C>, C::Compressed), ProofError> {
         RangeProof::prove_single_with_rng(
             bp_gens,
             pc_gens,
@@ -232,27 +314,381 @@ impl RangeProof {
     /// # }
     /// ```
     pub fn prove_multiple_with_rng<T: RngCore + CryptoRng>(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        values: &[u64],
+        blindings: &[C::Scalar],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof<C>, Vec<C::Compressed>), ProofError> {
+        RangeProof::prove_multiple_impl(
+            bp_gens,
+            pc_gens.into(),
+            transcript,
+            values,
+            blindings,
+            Bases::Default(n),
+            rng,
+        )
+    }
+
+    /// Create a rangeproof for a set of values.
+    /// This is a convenience wrapper around [`RangeProof::prove_multiple_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_multiple(
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
         values: &[u64],
-        blindings: &[Scalar],
+        blindings: &[C::Scalar],
         n: usize,
+    ) -> Result<(RangeProof<C>, Vec<C::Compressed>), ProofError> {
+        RangeProof::prove_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            values,
+            blindings,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Create a rangeproof for a set of values, where the `j`-th value
+    /// is committed with the value generator `value_gens.generators[j]` in
+    /// place of `pc_gens.B`.
+    ///
+    /// # Example
+    /// ```
+    /// extern crate rand;
+    /// use rand::thread_rng;
+    ///
+    /// extern crate curve25519_dalek;
+    /// use curve25519_dalek::scalar::Scalar;
+    ///
+    /// extern crate merlin;
+    /// use merlin::Transcript;
+    ///
+    /// extern crate bulletproofs;
+    /// use bulletproofs::{BulletproofGens, PedersenGens, RangeProof, ValueGens};
+    ///
+    /// # fn main() {
+    /// let pc_gens = PedersenGens::default();
+    /// let bp_gens = BulletproofGens::new(64, 2);
+    ///
+    /// // Two 32-bit outputs of different assets.
+    /// let value_gens = ValueGens {
+    ///     generators: &[
+    ///         PedersenGens::asset_generator(b"gold"),
+    ///         PedersenGens::asset_generator(b"silver"),
+    ///     ],
+    ///     n: 32,
+    /// };
+    /// let secrets = [4242344947u64, 3718732727u64];
+    /// let blindings: Vec<_> = (0..2).map(|_| Scalar::random(&mut thread_rng())).collect();
+    ///
+    /// let mut prover_transcript = Transcript::new(b"doctest example");
+    ///
+    /// // Create an aggregated 32-bit rangeproof and corresponding commitments.
+    /// let (proof, commitments) = RangeProof::prove_multiple_with_value_gens(
+    ///     &bp_gens,
+    ///     &pc_gens,
+    ///     &mut prover_transcript,
+    ///     &secrets,
+    ///     &blindings,
+    ///     value_gens,
+    /// ).expect("A real program could handle errors");
+    ///
+    /// // Verification requires a transcript with identical initial state:
+    /// let mut verifier_transcript = Transcript::new(b"doctest example");
+    /// assert!(
+    ///     proof
+    ///         .verify_multiple_with_value_gens(
+    ///             &bp_gens,
+    ///             &pc_gens,
+    ///             &mut verifier_transcript,
+    ///             &commitments,
+    ///             value_gens,
+    ///         )
+    ///         .is_ok()
+    /// );
+    /// # }
+    /// ```
+    pub fn prove_multiple_with_value_gens_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        values: &[u64],
+        blindings: &[C::Scalar],
+        value_gens: ValueGens<C>,
         rng: &mut T,
-    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+    ) -> Result<(RangeProof<C>, Vec<C::Compressed>), ProofError> {
+        if values.len() != value_gens.generators.len() {
+            return Err(ProofError::InvalidInputLength);
+        }
+
+        RangeProof::prove_multiple_impl(
+            bp_gens,
+            pc_gens.into(),
+            transcript,
+            values,
+            blindings,
+            Bases::ValueGens(value_gens),
+            rng,
+        )
+    }
+
+    /// Create a rangeproof for a set of values with per-commitment value generators.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::prove_multiple_with_value_gens_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_multiple_with_value_gens(
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        values: &[u64],
+        blindings: &[C::Scalar],
+        value_gens: ValueGens<C>,
+    ) -> Result<(RangeProof<C>, Vec<C::Compressed>), ProofError> {
+        RangeProof::prove_multiple_with_value_gens_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            values,
+            blindings,
+            value_gens,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Create a rangeproof for a given pair of value `v` and
+    /// blinding scalar `v_blinding`, committing with the precomputed
+    /// tables of `pc_table`.
+    ///
+    /// The proof is the same as that of [`RangeProof::prove_single_with_rng`]
+    /// with the bases `pc_table.gens()`.  Creating the tables costs about
+    /// as much as a few dozen commitments, so provers creating many proofs
+    /// should create the [`PedersenGensTable`] once and pass it to each
+    /// proof.
+    pub fn prove_single_with_table_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<C>,
+        pc_table: &PedersenGensTable<C>,
+        transcript: &mut impl ProofTranscript,
+        v: u64,
+        v_blinding: &C::Scalar,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof<C>, C::Compressed), ProofError> {
+        let (p, Vs) = RangeProof::prove_multiple_with_table_with_rng(
+            bp_gens,
+            pc_table,
+            transcript,
+            &[v],
+            &[*v_blinding],
+            n,
+            rng,
+        )?;
+        Ok((p, Vs[0]))
+    }
+
+    /// Create a rangeproof for a given pair of value `v` and
+    /// blinding scalar `v_blinding` with precomputed tables.
+    /// This is a convenience wrapper around [`RangeProof::prove_single_with_table_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_single_with_table(
+        bp_gens: &BulletproofGens<C>,
+        pc_table: &PedersenGensTable<C>,
+        transcript: &mut impl ProofTranscript,
+        v: u64,
+        v_blinding: &C::Scalar,
+        n: usize,
+    ) -> Result<(RangeProof<C>, C::Compressed), ProofError> {
+        RangeProof::prove_single_with_table_with_rng(
+            bp_gens,
+            pc_table,
+            transcript,
+            v,
+            v_blinding,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Create a rangeproof for a set of values, committing with the
+    /// precomputed tables of `pc_table`.
+    ///
+    /// The proof is the same as that of [`RangeProof::prove_multiple_with_rng`]
+    /// with the bases `pc_table.gens()`.
+    pub fn prove_multiple_with_table_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<C>,
+        pc_table: &PedersenGensTable<C>,
+        transcript: &mut impl ProofTranscript,
+        values: &[u64],
+        blindings: &[C::Scalar],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof<C>, Vec<C::Compressed>), ProofError> {
+        RangeProof::prove_multiple_impl(
+            bp_gens,
+            pc_table.into(),
+            transcript,
+            values,
+            blindings,
+            Bases::Default(n),
+            rng,
+        )
+    }
+
+    /// Create a rangeproof for a set of values with precomputed tables.
+    /// This is a convenience wrapper around [`RangeProof::prove_multiple_with_table_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_multiple_with_table(
+        bp_gens: &BulletproofGens<C>,
+        pc_table: &PedersenGensTable<C>,
+        transcript: &mut impl ProofTranscript,
+        values: &[u64],
+        blindings: &[C::Scalar],
+        n: usize,
+    ) -> Result<(RangeProof<C>, Vec<C::Compressed>), ProofError> {
+        RangeProof::prove_multiple_with_table_with_rng(
+            bp_gens,
+            pc_table,
+            transcript,
+            values,
+            blindings,
+            n,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Create a rangeproof for a set of values with per-commitment value
+    /// generators, committing with the blinding table of `pc_table`.
+    ///
+    /// The proof is the same as that of
+    /// [`RangeProof::prove_multiple_with_value_gens_with_rng`] with the
+    /// bases `pc_table.gens()`.  The tables of the value generators are
+    /// created for each proof, while the table of the blinding base is
+    /// shared by all of the values.
+    pub fn prove_multiple_with_value_gens_with_table_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<C>,
+        pc_table: &PedersenGensTable<C>,
+        transcript: &mut impl ProofTranscript,
+        values: &[u64],
+        blindings: &[C::Scalar],
+        value_gens: ValueGens<C>,
+        rng: &mut T,
+    ) -> Result<(RangeProof<C>, Vec<C::Compressed>), ProofError> {
+        if values.len() != value_gens.generators.len() {
+            return Err(ProofError::InvalidInputLength);
+        }
+
+        RangeProof::prove_multiple_impl(
+            bp_gens,
+            pc_table.into(),
+            transcript,
+            values,
+            blindings,
+            Bases::ValueGens(value_gens),
+            rng,
+        )
+    }
+
+    /// Create a rangeproof for a set of values with per-commitment value
+    /// generators and precomputed tables.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::prove_multiple_with_value_gens_with_table_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn prove_multiple_with_value_gens_with_table(
+        bp_gens: &BulletproofGens<C>,
+        pc_table: &PedersenGensTable<C>,
+        transcript: &mut impl ProofTranscript,
+        values: &[u64],
+        blindings: &[C::Scalar],
+        value_gens: ValueGens<C>,
+    ) -> Result<(RangeProof<C>, Vec<C::Compressed>), ProofError> {
+        RangeProof::prove_multiple_with_value_gens_with_table_with_rng(
+            bp_gens,
+            pc_table,
+            transcript,
+            values,
+            blindings,
+            value_gens,
+            &mut thread_rng(),
+        )
+    }
+
+    fn prove_multiple_impl<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens<C>,
+        pc_bases: PedersenBases<'_, C>,
+        transcript: &mut impl ProofTranscript,
+        values: &[u64],
+        blindings: &[C::Scalar],
+        bases: Bases<C>,
+        rng: &mut T,
+    ) -> Result<(RangeProof<C>, Vec<C::Compressed>), ProofError> {
         use self::dealer::*;
         use self::party::*;
 
+        let (n, value_gens) = (bases.n(), bases.value_gens());
+
         if values.len() != blindings.len() {
             return Err(ProofError::WrongNumBlindingFactors);
         }
 
-        let dealer = Dealer::new(bp_gens, pc_gens, transcript, n, values.len())?;
+        let pc_gens: &PedersenGens<C> = &pc_bases;
+
+        // Each party commits with its own value generator in place of
+        // pc_gens.B.  With precomputed tables, each party has a table for
+        // its value generator, and all parties share the table of
+        // pc_gens.B_blinding.
+        let value_bases: Vec<PedersenGens<C>> = value_gens
+            .unwrap_or_default()
+            .iter()
+            .map(|&B_j| pc_gens.with_value_generator(B_j))
+            .collect();
+        let value_tables: Vec<C::Table> = match pc_bases {
+            PedersenBases::Table(_) => value_bases
+                .iter()
+                .map(|gens| C::create_table(&gens.B))
+                .collect(),
+            _ => Vec::new(),
+        };
+        let (dealer, party_bases) = match (value_gens, pc_bases) {
+            (Some(value_gens), PedersenBases::Table(pc_table)) => (
+                Dealer::new_with_value_gens(bp_gens, pc_gens, transcript, n, value_gens.to_vec())?,
+                value_bases
+                    .iter()
+                    .zip(value_tables.iter())
+                    .map(|(&gens, B_table)| PedersenBases::ValueTable {
+                        gens,
+                        B_table,
+                        blinding_table: pc_table,
+                    })
+                    .collect(),
+            ),
+            (Some(value_gens), _) => (
+                Dealer::new_with_value_gens(bp_gens, pc_gens, transcript, n, value_gens.to_vec())?,
+                value_bases.iter().map(PedersenBases::from).collect(),
+            ),
+            (None, _) => (
+                Dealer::new(bp_gens, pc_gens, transcript, n, values.len())?,
+                vec![pc_bases],
+            ),
+        };
 
         let parties: Vec<_> = values
             .iter()
             .zip(blindings.iter())
-            .map(|(&v, &v_blinding)| Party::new(bp_gens, pc_gens, v, v_blinding, n))
+            .zip(party_bases.iter().cycle())
+            .map(|((&v, &v_blinding), &pc_bases)| {
+                Party::create(bp_gens, pc_bases, v, v_blinding, n)
+            })
             // Collect the iterator of Results into a Result<Vec>, then unwrap it
             .collect::<Result<Vec<_>, _>>()?;
 
@@ -287,38 +723,15 @@ impl RangeProof {
         Ok((proof, value_commitments))
     }
 
-    /// Create a rangeproof for a set of values.
-    /// This is a convenience wrapper around [`RangeProof::prove_multiple_with_rng`],
-    /// passing in a threadsafe RNG.
-    #[cfg(feature = "std")]
-    pub fn prove_multiple(
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
-        values: &[u64],
-        blindings: &[Scalar],
-        n: usize,
-    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
-        RangeProof::prove_multiple_with_rng(
-            bp_gens,
-            pc_gens,
-            transcript,
-            values,
-            blindings,
-            n,
-            &mut thread_rng(),
-        )
-    }
-
     /// Verifies a rangeproof for a given value commitment \\(V\\).
     ///
     /// This is a convenience wrapper around `verify_multiple` for the `m=1` case.
     pub fn verify_single_with_rng<T: RngCore + CryptoRng>(
         &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
-        V: &CompressedRistretto,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        V: &C::Compressed,
         n: usize,
         rng: &mut T,
     ) -> Result<(), ProofError> {
@@ -332,10 +745,10 @@ impl RangeProof {
     #[cfg(feature = "std")]
     pub fn verify_single(
         &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
-        V: &CompressedRistretto,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        V: &C::Compressed,
         n: usize,
     ) -> Result<(), ProofError> {
         self.verify_single_with_rng(bp_gens, pc_gens, transcript, V, n, &mut thread_rng())
@@ -344,13 +757,145 @@ impl RangeProof {
     /// Verifies an aggregated rangeproof for the given value commitments.
     pub fn verify_multiple_with_rng<T: RngCore + CryptoRng>(
         &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
-        value_commitments: &[CompressedRistretto],
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        value_commitments: &[C::Compressed],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let mut batch = BatchVerifier::new(rng);
+        self.batch_verify_multiple(
+            &mut batch,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+        )?;
+        batch.verify()
+    }
+
+    /// Appends the verification equation of a rangeproof for a given value
+    /// commitment \\(V\\) to the `batch`.
+    ///
+    /// This is a convenience wrapper around `batch_verify_multiple` for the `m=1` case.
+    pub fn batch_verify_single<T: RngCore + CryptoRng>(
+        &self,
+        batch: &mut BatchVerifier<T, C>,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        V: &C::Compressed,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.batch_verify_multiple(batch, bp_gens, pc_gens, transcript, &[*V], n)
+    }
+
+    /// Appends the verification equation of an aggregated rangeproof for
+    /// the given value commitments to the `batch`, which checks it together
+    /// with the equations of other proofs in [`BatchVerifier::verify`].
+    pub fn batch_verify_multiple<T: RngCore + CryptoRng>(
+        &self,
+        batch: &mut BatchVerifier<T, C>,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        value_commitments: &[C::Compressed],
         n: usize,
+    ) -> Result<(), ProofError> {
+        self.batch_verify_impl(
+            batch,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            Bases::Default(n),
+        )
+    }
+
+    /// Verifies an aggregated rangeproof for the given value commitments,
+    /// where the `j`-th commitment uses the value generator
+    /// `value_gens.generators[j]`.
+    pub fn verify_multiple_with_value_gens_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        value_commitments: &[C::Compressed],
+        value_gens: ValueGens<C>,
         rng: &mut T,
     ) -> Result<(), ProofError> {
+        let mut batch = BatchVerifier::new(rng);
+        self.batch_verify_multiple_with_value_gens(
+            &mut batch,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            value_gens,
+        )?;
+        batch.verify()
+    }
+
+    /// Verifies an aggregated rangeproof with per-commitment value generators.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::verify_multiple_with_value_gens_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(feature = "std")]
+    pub fn verify_multiple_with_value_gens(
+        &self,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        value_commitments: &[C::Compressed],
+        value_gens: ValueGens<C>,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_with_value_gens_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            value_gens,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Appends the verification equation of an aggregated rangeproof with
+    /// per-commitment value generators to the `batch`.
+    pub fn batch_verify_multiple_with_value_gens<T: RngCore + CryptoRng>(
+        &self,
+        batch: &mut BatchVerifier<T, C>,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        value_commitments: &[C::Compressed],
+        value_gens: ValueGens<C>,
+    ) -> Result<(), ProofError> {
+        if value_commitments.len() != value_gens.generators.len() {
+            return Err(ProofError::InvalidInputLength);
+        }
+
+        self.batch_verify_impl(
+            batch,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            Bases::ValueGens(value_gens),
+        )
+    }
+
+    fn batch_verify_impl<T: RngCore + CryptoRng>(
+        &self,
+        batch: &mut BatchVerifier<T, C>,
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        value_commitments: &[C::Compressed],
+        bases: Bases<C>,
+    ) -> Result<(), ProofError> {
+        let (n, value_gens) = (bases.n(), bases.value_gens());
         let m = value_commitments.len();
 
         // First, replay the "interactive" protocol using the proof
@@ -365,7 +910,24 @@ impl RangeProof {
             return Err(ProofError::InvalidGeneratorsLength);
         }
 
-        transcript.rangeproof_domain_sep(n as u64, m as u64);
+        // A proof only verifies with the kind of value generators it was created for.
+        match value_gens {
+            Some(value_gens) => {
+                if self.value_gen_terms.len() != m {
+                    return Err(ProofError::VerificationError);
+                }
+                transcript.rangeproof_value_gens_domain_sep(n as u64, m as u64);
+                for B_j in value_gens.iter() {
+                    transcript.append_point(b"B", &B_j.compress());
+                }
+            }
+            None => {
+                if !self.value_gen_terms.is_empty() {
+                    return Err(ProofError::VerificationError);
+                }
+                transcript.rangeproof_domain_sep(n as u64, m as u64);
+            }
+        }
 
         for V in value_commitments.iter() {
             // Allow the commitments to be zero (0 value, 0 blinding)
@@ -376,24 +938,39 @@ impl RangeProof {
         transcript.validate_and_append_point(b"A", &self.A)?;
         transcript.validate_and_append_point(b"S", &self.S)?;
 
-        let y = transcript.challenge_scalar(b"y");
-        let z = transcript.challenge_scalar(b"z");
+        let y: C::Scalar = transcript.challenge_scalar(b"y");
+        let z: C::Scalar = transcript.challenge_scalar(b"z");
         let zz = z * z;
         let minus_z = -z;
 
-        transcript.validate_and_append_point(b"T_1", &self.T_1)?;
-        transcript.validate_and_append_point(b"T_2", &self.T_2)?;
+        if value_gens.is_some() {
+            for terms in self.value_gen_terms.iter() {
+                transcript.validate_and_append_point(b"T_1", &terms.T_1)?;
+                transcript.validate_and_append_point(b"T_2", &terms.T_2)?;
+            }
+        } else {
+            transcript.validate_and_append_point(b"T_1", &self.T_1)?;
+            transcript.validate_and_append_point(b"T_2", &self.T_2)?;
+        }
 
-        let x = transcript.challenge_scalar(b"x");
+        let x: C::Scalar = transcript.challenge_scalar(b"x");
 
-        transcript.append_scalar(b"t_x", &self.t_x);
+        let rho = if value_gens.is_some() {
+            for terms in self.value_gen_terms.iter() {
+                transcript.append_scalar(b"t_x", &terms.t_x);
+            }
+            transcript.challenge_scalar(b"rho")
+        } else {
+            transcript.append_scalar(b"t_x", &self.t_x);
+            C::Scalar::one()
+        };
         transcript.append_scalar(b"t_x_blinding", &self.t_x_blinding);
         transcript.append_scalar(b"e_blinding", &self.e_blinding);
 
-        let w = transcript.challenge_scalar(b"w");
+        let w: C::Scalar = transcript.challenge_scalar(b"w");
 
         // Challenge value for batching statements to be verified
-        let c = Scalar::random(rng);
+        let c = C::Scalar::random(batch.rng());
 
         let (x_sq, x_inv_sq, s) = self.ipp_proof.verification_scalars(n * m, transcript)?;
         let s_inv = s.iter().rev();
@@ -403,52 +980,87 @@ impl RangeProof {
 
         // Construct concat_z_and_2, an iterator of the values of
         // z^0 * \vec(2)^n || z^1 * \vec(2)^n || ... || z^(m-1) * \vec(2)^n
-        let powers_of_2: Vec<Scalar> = util::exp_iter(Scalar::from(2u64)).take(n).collect();
-        let concat_z_and_2: Vec<Scalar> = util::exp_iter(z)
+        let powers_of_2: Vec<C::Scalar> = util::exp_iter(C::Scalar::from(2u64)).take(n).collect();
+        let concat_z_and_2: Vec<C::Scalar> = util::exp_iter(z)
             .take(m)
-            .flat_map(|exp_z| powers_of_2.iter().map(move |exp_2| exp_2 * exp_z))
+            .flat_map(|exp_z| powers_of_2.iter().map(move |exp_2| *exp_2 * exp_z))
             .collect();
 
-        let g = s.iter().map(|s_i| minus_z - a * s_i);
+        let g = s.iter().map(|s_i| minus_z - a * *s_i);
         let h = s_inv
             .zip(util::exp_iter(y.invert()))
             .zip(concat_z_and_2.iter())
-            .map(|((s_i_inv, exp_y_inv), z_and_2)| z + exp_y_inv * (zz * z_and_2 - b * s_i_inv));
-
-        let value_commitment_scalars = util::exp_iter(z).take(m).map(|z_exp| c * zz * z_exp);
-        let basepoint_scalar = w * (self.t_x - a * b) + c * (delta(n, m, &y, &z) - self.t_x);
+            .map(|((s_i_inv, exp_y_inv), z_and_2)| z + exp_y_inv * (zz * *z_and_2 - b * *s_i_inv));
+
+        // The check of t_x, which is either the aggregated check against
+        // pc_gens.B, or the checks of each party against its value
+        // generator, combined with powers of rho.
+        let mut t_check_scalars = Vec::with_capacity(4 * m + 2);
+        let mut t_check_points = Vec::with_capacity(4 * m + 2);
+        let mut basepoint_scalar = w * (self.t_x - a * b);
+        match value_gens {
+            Some(value_gens) => {
+                let sum_y = util::sum_of_powers(&y, n);
+                let sum_2 = util::sum_of_powers(&C::Scalar::from(2u64), n);
+                let y_n = util::scalar_exp_vartime(&y, n as u64);
+
+                let mut exp_rho = c; // c * rho^j
+                let mut exp_y_n = C::Scalar::one(); // y^(j*n)
+                let mut exp_z = zz; // z^(j+2)
+                for ((terms, V), B_j) in self
+                    .value_gen_terms
+                    .iter()
+                    .zip(value_commitments.iter())
+                    .zip(value_gens.iter())
+                {
+                    let delta_j = (z - zz) * sum_y * exp_y_n - z * exp_z * sum_2;
+                    t_check_scalars.push(exp_rho * exp_z);
+                    t_check_scalars.push(exp_rho * x);
+                    t_check_scalars.push(exp_rho * x * x);
+                    t_check_scalars.push(exp_rho * (delta_j - terms.t_x));
+                    t_check_points.push(V.decompress());
+                    t_check_points.push(terms.T_1.decompress());
+                    t_check_points.push(terms.T_2.decompress());
+                    t_check_points.push(Some(*B_j));
+
+                    exp_rho *= rho;
+                    exp_y_n *= y_n;
+                    exp_z *= z;
+                }
+            }
+            None => {
+                basepoint_scalar += c * (delta(n, m, &y, &z) - self.t_x);
+                t_check_scalars.push(c * x);
+                t_check_scalars.push(c * x * x);
+                t_check_points.push(self.T_1.decompress());
+                t_check_points.push(self.T_2.decompress());
+                for (z_exp, V) in util::exp_iter(z).zip(value_commitments.iter()) {
+                    t_check_scalars.push(c * zz * z_exp);
+                    t_check_points.push(V.decompress());
+                }
+            }
+        }
 
-        let mega_check = RistrettoPoint::optional_multiscalar_mul(
-            iter::once(Scalar::one())
+        batch.append(
+            iter::once(C::Scalar::one())
                 .chain(iter::once(x))
-                .chain(iter::once(c * x))
-                .chain(iter::once(c * x * x))
                 .chain(x_sq.iter().cloned())
                 .chain(x_inv_sq.iter().cloned())
                 .chain(iter::once(-self.e_blinding - c * self.t_x_blinding))
                 .chain(iter::once(basepoint_scalar))
                 .chain(g)
                 .chain(h)
-                .chain(value_commitment_scalars),
+                .chain(t_check_scalars),
             iter::once(self.A.decompress())
                 .chain(iter::once(self.S.decompress()))
-                .chain(iter::once(self.T_1.decompress()))
-                .chain(iter::once(self.T_2.decompress()))
                 .chain(self.ipp_proof.L_vec.iter().map(|L| L.decompress()))
                 .chain(self.ipp_proof.R_vec.iter().map(|R| R.decompress()))
                 .chain(iter::once(Some(pc_gens.B_blinding)))
                 .chain(iter::once(Some(pc_gens.B)))
                 .chain(bp_gens.G(n, m).map(|&x| Some(x)))
                 .chain(bp_gens.H(n, m).map(|&x| Some(x)))
-                .chain(value_commitments.iter().map(|V| V.decompress())),
+                .chain(t_check_points),
         )
-        .ok_or_else(|| ProofError::VerificationError)?;
-
-        if mega_check.is_identity() {
-            Ok(())
-        } else {
-            Err(ProofError::VerificationError)
-        }
     }
 
     /// Verifies an aggregated rangeproof for the given value commitments.
@@ -457,10 +1069,10 @@ impl RangeProof {
     #[cfg(feature = "std")]
     pub fn verify_multiple(
         &self,
-        bp_gens: &BulletproofGens,
-        pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
-        value_commitments: &[CompressedRistretto],
+        bp_gens: &BulletproofGens<C>,
+        pc_gens: &PedersenGens<C>,
+        transcript: &mut impl ProofTranscript,
+        value_commitments: &[C::Compressed],
         n: usize,
     ) -> Result<(), ProofError> {
         self.verify_multiple_with_rng(
@@ -480,50 +1092,109 @@ impl RangeProof {
     ///
     /// The layout of the range proof encoding is:
     ///
-    /// * four compressed Ristretto points \\(A,S,T_1,T_2\\),
+    /// * four compressed points \\(A,S,T_1,T_2\\),
     /// * three scalars \\(t_x, \tilde{t}_x, \tilde{e}\\),
-    /// * \\(n\\) pairs of compressed Ristretto points \\(L_0,R_0\dots,L_{n-1},R_{n-1}\\),
+    /// * \\(n\\) pairs of compressed points \\(L_0,R_0\dots,L_{n-1},R_{n-1}\\),
     /// * two scalars \\(a, b\\).
+    ///
+    /// Proofs with per-commitment value generators for \\(m\\) values are
+    /// serialized as a tag byte, followed by \\(m\\) as an 8-byte little-endian
+    /// integer and \\(2 \lg n + 3m + 6\\) 32-byte elements:
+    ///
+    /// * two compressed points \\(A,S\\),
+    /// * \\(m\\) pairs of compressed points \\(T_{1,j},T_{2,j}\\),
+    /// * \\(m\\) scalars \\(t_{x,j}\\),
+    /// * two scalars \\(\tilde{t}_x, \tilde{e}\\),
+    /// * the inner-product proof, as above.
+    ///
+    /// Scalars are 32 bytes and compressed points are 32 bytes for
+    /// `ristretto255`.  For other groups, the points take
+    /// `C::Compressed::SIZE` bytes each instead.
     pub fn to_bytes(&self) -> Vec<u8> {
+        if !self.value_gen_terms.is_empty() {
+            return self.value_gens_to_bytes();
+        }
+
         // 7 elements: points A, S, T1, T2, scalars tx, tx_bl, e_bl.
-        let mut buf = Vec::with_capacity(7 * 32 + self.ipp_proof.serialized_size());
+        let mut buf =
+            Vec::with_capacity(4 * C::Compressed::SIZE + 3 * 32 + self.ipp_proof.serialized_size());
         buf.extend_from_slice(self.A.as_bytes());
         buf.extend_from_slice(self.S.as_bytes());
         buf.extend_from_slice(self.T_1.as_bytes());
         buf.extend_from_slice(self.T_2.as_bytes());
-        buf.extend_from_slice(self.t_x.as_bytes());
-        buf.extend_from_slice(self.t_x_blinding.as_bytes());
-        buf.extend_from_slice(self.e_blinding.as_bytes());
+        buf.extend_from_slice(&self.t_x.to_bytes());
+        buf.extend_from_slice(&self.t_x_blinding.to_bytes());
+        buf.extend_from_slice(&self.e_blinding.to_bytes());
         buf.extend(self.ipp_proof.to_bytes_iter());
         buf
     }
 
-    /// Deserializes the proof from a byte slice.
+    fn value_gens_to_bytes(&self) -> Vec<u8> {
+        let m = self.value_gen_terms.len();
+        // 4 + 3m elements: points A, S, T1_j, T2_j, scalars tx_j, tx_bl, e_bl.
+        let mut buf = Vec::with_capacity(
+            VALUE_GENS_HEADER_SIZE
+                + (2 + 2 * m) * C::Compressed::SIZE
+                + (2 + m) * 32
+                + self.ipp_proof.serialized_size(),
+        );
+        buf.push(VALUE_GENS_FORMAT_TAG);
+        buf.extend_from_slice(&(m as u64).to_le_bytes());
+        buf.extend_from_slice(self.A.as_bytes());
+        buf.extend_from_slice(self.S.as_bytes());
+        for terms in self.value_gen_terms.iter() {
+            buf.extend_from_slice(terms.T_1.as_bytes());
+            buf.extend_from_slice(terms.T_2.as_bytes());
+        }
+        for terms in self.value_gen_terms.iter() {
+            buf.extend_from_slice(&terms.t_x.to_bytes());
+        }
+        buf.extend_from_slice(&self.t_x_blinding.to_bytes());
+        buf.extend_from_slice(&self.e_blinding.to_bytes());
+        buf.extend(self.ipp_proof.to_bytes_iter());
+        buf
+    }
+
+    /// Deserializes a proof over the group `C` from a byte slice, in the
+    /// layout of [`RangeProof::to_bytes`].
     ///
     /// Returns an error if the byte slice cannot be parsed into a `RangeProof`.
-    pub fn from_bytes(slice: &[u8]) -> Result<RangeProof, ProofError> {
-        if slice.len() % 32 != 0 {
-            return Err(ProofError::FormatError);
-        }
-        if slice.len() < 7 * 32 {
-            return Err(ProofError::FormatError);
+    pub fn decode(slice: &[u8]) -> Result<RangeProof<C>, ProofError> {
+        // Without value generators, a proof has 4 + 2k points and five
+        // 32-byte scalars.  Proofs with value generators have an even
+        // number of points and 32-byte scalars after their 9-byte header,
+        // so their length is odd and never has this form.
+        let point_size = C::Compressed::SIZE;
+        let scalars_size = 5 * 32;
+        if slice.len() < 4 * point_size + scalars_size
+            || (slice.len() - scalars_size) % (2 * point_size) != 0
+        {
+            if slice.len() <= VALUE_GENS_HEADER_SIZE {
+                return Err(ProofError::FormatError);
+            }
+            return RangeProof::value_gens_decode(slice);
         }
 
         use crate::util::read32;
 
-        let A = CompressedRistretto(read32(&slice[0 * 32..]));
-        let S = CompressedRistretto(read32(&slice[1 * 32..]));
-        let T_1 = CompressedRistretto(read32(&slice[2 * 32..]));
-        let T_2 = CompressedRistretto(read32(&slice[3 * 32..]));
+        let read_point = |i: usize| {
+            C::Compressed::from_slice(&slice[i * point_size..(i + 1) * point_size])
+                .ok_or(ProofError::FormatError)
+        };
+        let A = read_point(0)?;
+        let S = read_point(1)?;
+        let T_1 = read_point(2)?;
+        let T_2 = read_point(3)?;
 
-        let t_x = Scalar::from_canonical_bytes(read32(&slice[4 * 32..]))
+        let pos = 4 * point_size;
+        let t_x = C::Scalar::from_canonical_bytes(read32(&slice[pos..]))
             .ok_or(ProofError::FormatError)?;
-        let t_x_blinding = Scalar::from_canonical_bytes(read32(&slice[5 * 32..]))
+        let t_x_blinding = C::Scalar::from_canonical_bytes(read32(&slice[pos + 32..]))
             .ok_or(ProofError::FormatError)?;
-        let e_blinding = Scalar::from_canonical_bytes(read32(&slice[6 * 32..]))
+        let e_blinding = C::Scalar::from_canonical_bytes(read32(&slice[pos + 2 * 32..]))
             .ok_or(ProofError::FormatError)?;
 
-        let ipp_proof = InnerProductProof::from_bytes(&slice[7 * 32..])?;
+        let ipp_proof = InnerProductProof::decode(&slice[pos + 3 * 32..])?;
 
         Ok(RangeProof {
             A,
@@ -534,11 +1205,94 @@ impl RangeProof {
             t_x_blinding,
             e_blinding,
             ipp_proof,
+            value_gen_terms: Vec::new(),
+        })
+    }
+
+    fn value_gens_decode(slice: &[u8]) -> Result<RangeProof<C>, ProofError> {
+        if slice[0] != VALUE_GENS_FORMAT_TAG {
+            return Err(ProofError::FormatError);
+        }
+
+        let point_size = C::Compressed::SIZE;
+        let mut m_bytes = [0u8; 8];
+        m_bytes.copy_from_slice(&slice[1..VALUE_GENS_HEADER_SIZE]);
+        let m = u64::from_le_bytes(m_bytes);
+        let mut slice = &slice[VALUE_GENS_HEADER_SIZE..];
+
+        // Bound m by the length first, so that the number of elements
+        // cannot overflow.
+        if m == 0 || m > (slice.len() / (2 * point_size + 32)) as u64 {
+            return Err(ProofError::FormatError);
+        }
+        let m = m as usize;
+        if slice.len() < (2 + 2 * m) * point_size + (2 + m) * 32 {
+            return Err(ProofError::FormatError);
+        }
+
+        // These macros take care of counting bytes in the slice
+        macro_rules! read32 {
+            () => {{
+                let tmp = util::read32(slice);
+                slice = &slice[32..];
+                tmp
+            }};
+        }
+        macro_rules! read_point {
+            () => {{
+                let tmp = C::Compressed::from_slice(&slice[..point_size])
+                    .ok_or(ProofError::FormatError)?;
+                slice = &slice[point_size..];
+                tmp
+            }};
+        }
+
+        let A = read_point!();
+        let S = read_point!();
+        let mut T_vec = Vec::with_capacity(m);
+        for _ in 0..m {
+            let T_1 = read_point!();
+            let T_2 = read_point!();
+            T_vec.push((T_1, T_2));
+        }
+        let mut value_gen_terms = Vec::with_capacity(m);
+        for (T_1, T_2) in T_vec {
+            let t_x = C::Scalar::from_canonical_bytes(read32!()).ok_or(ProofError::FormatError)?;
+            value_gen_terms.push(ValueGenTerms { T_1, T_2, t_x });
+        }
+        let t_x_blinding =
+            C::Scalar::from_canonical_bytes(read32!()).ok_or(ProofError::FormatError)?;
+        let e_blinding =
+            C::Scalar::from_canonical_bytes(read32!()).ok_or(ProofError::FormatError)?;
+
+        let ipp_proof = InnerProductProof::decode(slice)?;
+
+        Ok(RangeProof {
+            A,
+            S,
+            T_1: C::Compressed::identity(),
+            T_2: C::Compressed::identity(),
+            t_x: value_gen_terms.iter().map(|terms| terms.t_x).sum(),
+            t_x_blinding,
+            e_blinding,
+            ipp_proof,
+            value_gen_terms,
         })
     }
 }
 
-impl Serialize for RangeProof {
+impl RangeProof {
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a `RangeProof`.
+    ///
+    /// Use [`RangeProof::decode`] for proofs over other groups.
+    pub fn from_bytes(slice: &[u8]) -> Result<RangeProof, ProofError> {
+        RangeProof::decode(slice)
+    }
+}
+
+impl<C: CurvePoint> Serialize for RangeProof<C> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
@@ -547,36 +1301,36 @@ impl Serialize for RangeProof {
     }
 }
 
-impl<'de> Deserialize<'de> for RangeProof {
+impl<'de, C: CurvePoint> Deserialize<'de> for RangeProof<C> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct RangeProofVisitor;
+        struct RangeProofVisitor<C>(PhantomData<C>);
 
-        impl<'de> Visitor<'de> for RangeProofVisitor {
-            type Value = RangeProof;
+        impl<'de, C: CurvePoint> Visitor<'de> for RangeProofVisitor<C> {
+            type Value = RangeProof<C>;
 
             fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
                 formatter.write_str("a valid RangeProof")
             }
 
-            fn visit_bytes<E>(self, v: &[u8]) -> Result<RangeProof, E>
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<RangeProof<C>, E>
             where
                 E: serde::de::Error,
             {
                 // Using Error::custom requires T: Display, which our error
                 // type only implements when it implements std::error::Error.
                 #[cfg(feature = "std")]
-                return RangeProof::from_bytes(v).map_err(serde::de::Error::custom);
+                return RangeProof::decode(v).map_err(serde::de::Error::custom);
                 // In no-std contexts, drop the error message.
                 #[cfg(not(feature = "std"))]
-                return RangeProof::from_bytes(v)
+                return RangeProof::decode(v)
                     .map_err(|_| serde::de::Error::custom("deserialization error"));
             }
         }
 
-        deserializer.deserialize_bytes(RangeProofVisitor)
+        deserializer.deserialize_bytes(RangeProofVisitor(PhantomData))
     }
 }
 
@@ -584,18 +1338,21 @@ impl<'de> Deserialize<'de> for RangeProof {
 /// \\[
 /// \delta(y,z) = (z - z^{2}) \langle \mathbf{1}, {\mathbf{y}}^{n \cdot m} \rangle - \sum_{j=0}^{m-1} z^{j+3} \cdot \langle \mathbf{1}, {\mathbf{2}}^{n \cdot m} \rangle
 /// \\]
-fn delta(n: usize, m: usize, y: &Scalar, z: &Scalar) -> Scalar {
+fn delta<S: CurveScalar>(n: usize, m: usize, y: &S, z: &S) -> S {
     let sum_y = util::sum_of_powers(y, n * m);
-    let sum_2 = util::sum_of_powers(&Scalar::from(2u64), n);
+    let sum_2 = util::sum_of_powers(&S::from(2u64), n);
     let sum_z = util::sum_of_powers(z, m);
 
-    (z - z * z) * sum_y - z * z * z * sum_2 * sum_z
+    (*z - *z * *z) * sum_y - *z * *z * *z * sum_2 * sum_z
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use curve25519_dalek::scalar::Scalar;
+    use merlin::Transcript;
+
     use crate::generators::PedersenGens;
 
     #[test]
@@ -723,6 +1480,316 @@ mod tests {
         singleparty_create_and_verify_helper(64, 8);
     }
 
+    #[test]
+    fn create_and_verify_with_domain_gens() {
+        let pc_gens = PedersenGens::from_label(b"RangeProofDomainTest");
+        let bp_gens = BulletproofGens::with_domain(b"RangeProofDomainTest", 32, 2);
+        let mut rng = rand::thread_rng();
+
+        let blindings = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+        let (proof, value_commitments) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"AggregatedRangeProofTest"),
+            &[1037, 578],
+            &blindings,
+            32,
+        )
+        .unwrap();
+
+        assert!(proof
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"AggregatedRangeProofTest"),
+                &value_commitments,
+                32
+            )
+            .is_ok());
+
+        // The proof does not verify against the generators of another domain.
+        assert!(proof
+            .verify_multiple(
+                &BulletproofGens::new(32, 2),
+                &pc_gens,
+                &mut Transcript::new(b"AggregatedRangeProofTest"),
+                &value_commitments,
+                32
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn create_and_verify_with_value_gens() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 4);
+        let mut rng = rand::thread_rng();
+
+        // Outputs of two assets, one of them behind a blinded asset tag.
+        let gold = PedersenGens::asset_generator(b"gold");
+        let silver = PedersenGens::asset_generator(b"silver");
+        let silver_tag = pc_gens.blinded_asset_tag(b"silver", Scalar::random(&mut rng));
+        let value_gens = [gold, silver, gold, silver_tag];
+
+        let values = [1037, 578, 0, u32::max_value() as u64];
+        let blindings: Vec<_> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+        let (proof, value_commitments) = RangeProof::prove_multiple_with_value_gens(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"AggregatedRangeProofTest"),
+            &values,
+            &blindings,
+            ValueGens {
+                generators: &value_gens,
+                n: 32,
+            },
+        )
+        .unwrap();
+
+        // Each commitment uses its own value generator.
+        for j in 0..4 {
+            assert_eq!(
+                value_commitments[j],
+                pc_gens
+                    .with_value_generator(value_gens[j])
+                    .commit(values[j].into(), blindings[j])
+                    .compress()
+            );
+        }
+
+        let verify = |proof: &RangeProof, value_gens: &[RistrettoPoint]| {
+            proof.verify_multiple_with_value_gens(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"AggregatedRangeProofTest"),
+                &value_commitments,
+                ValueGens {
+                    generators: value_gens,
+                    n: 32,
+                },
+            )
+        };
+
+        assert!(verify(&proof, &value_gens).is_ok());
+
+        let proof_bytes = proof.to_bytes();
+        assert_eq!(proof_bytes.len(), 1 + 8 + (2 * 7 + 3 * 4 + 6) * 32);
+        assert!(verify(&RangeProof::from_bytes(&proof_bytes).unwrap(), &value_gens).is_ok());
+        let proof_bincode = bincode::serialize(&proof).unwrap();
+        assert!(verify(&bincode::deserialize(&proof_bincode).unwrap(), &value_gens).is_ok());
+
+        // Swapping the assets of two outputs makes the proof fail.
+        assert!(verify(&proof, &[silver, gold, gold, silver_tag]).is_err());
+        assert!(verify(&proof, &[gold, silver, gold, silver]).is_err());
+        assert_eq!(
+            verify(&proof, &value_gens[..2]),
+            Err(ProofError::InvalidInputLength)
+        );
+
+        // The proof does not verify as a proof using pc_gens.B, and
+        // vice versa.
+        assert!(proof
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"AggregatedRangeProofTest"),
+                &value_commitments,
+                32
+            )
+            .is_err());
+        let (plain_proof, plain_commitments) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"AggregatedRangeProofTest"),
+            &values,
+            &blindings,
+            32,
+        )
+        .unwrap();
+        assert!(plain_proof
+            .verify_multiple_with_value_gens(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"AggregatedRangeProofTest"),
+                &plain_commitments,
+                ValueGens {
+                    generators: &[pc_gens.B; 4],
+                    n: 32,
+                },
+            )
+            .is_err());
+
+        // Malformed headers are rejected.
+        let mut bad_bytes = proof_bytes.clone();
+        bad_bytes[0] = 2;
+        assert!(RangeProof::from_bytes(&bad_bytes).is_err());
+        let mut bad_bytes = proof_bytes.clone();
+        bad_bytes[1..9].copy_from_slice(&u64::max_value().to_le_bytes());
+        assert!(RangeProof::from_bytes(&bad_bytes).is_err());
+    }
+
+    #[test]
+    fn create_with_shared_table() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaChaRng;
+
+        let pc_gens = PedersenGens::default();
+        let pc_table = PedersenGensTable::new(&pc_gens);
+        let bp_gens = BulletproofGens::new(32, 2);
+        let mut rng = rand::thread_rng();
+
+        let values = [1037, 578];
+        let blindings: Vec<_> = (0..2).map(|_| Scalar::random(&mut rng)).collect();
+        let value_gens = [
+            PedersenGens::asset_generator(b"gold"),
+            PedersenGens::asset_generator(b"silver"),
+        ];
+
+        // The proofs with the table equal those without it.
+        let seed = [7u8; 32];
+        let plain = RangeProof::prove_multiple_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"SharedTableTest"),
+            &values,
+            &blindings,
+            32,
+            &mut ChaChaRng::from_seed(seed),
+        )
+        .unwrap();
+        let with_table = RangeProof::prove_multiple_with_table_with_rng(
+            &bp_gens,
+            &pc_table,
+            &mut Transcript::new(b"SharedTableTest"),
+            &values,
+            &blindings,
+            32,
+            &mut ChaChaRng::from_seed(seed),
+        )
+        .unwrap();
+        assert_eq!(plain.0.to_bytes(), with_table.0.to_bytes());
+        assert_eq!(plain.1, with_table.1);
+
+        let plain = RangeProof::prove_multiple_with_value_gens_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"SharedTableTest"),
+            &values,
+            &blindings,
+            ValueGens {
+                generators: &value_gens,
+                n: 32,
+            },
+            &mut ChaChaRng::from_seed(seed),
+        )
+        .unwrap();
+        let with_table = RangeProof::prove_multiple_with_value_gens_with_table_with_rng(
+            &bp_gens,
+            &pc_table,
+            &mut Transcript::new(b"SharedTableTest"),
+            &values,
+            &blindings,
+            ValueGens {
+                generators: &value_gens,
+                n: 32,
+            },
+            &mut ChaChaRng::from_seed(seed),
+        )
+        .unwrap();
+        assert_eq!(plain.0.to_bytes(), with_table.0.to_bytes());
+        assert_eq!(plain.1, with_table.1);
+
+        // One table serves many proofs.
+        for &v in &values {
+            let (proof, V) = RangeProof::prove_single_with_table(
+                &bp_gens,
+                &pc_table,
+                &mut Transcript::new(b"SharedTableTest"),
+                v,
+                &blindings[0],
+                32,
+            )
+            .unwrap();
+            assert!(proof
+                .verify_single(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut Transcript::new(b"SharedTableTest"),
+                    &V,
+                    32
+                )
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn detect_wrong_value_generator_during_aggregation() {
+        use self::dealer::*;
+        use self::party::*;
+
+        use crate::errors::MPCError;
+
+        let m = 2;
+        let n = 32;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, m);
+        let gold_gens = pc_gens.for_asset(b"gold");
+        let silver_gens = pc_gens.for_asset(b"silver");
+        let value_gens = vec![gold_gens.B, silver_gens.B];
+
+        let mut rng = rand::thread_rng();
+
+        let mut prove = |party1_gens: &PedersenGens| {
+            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+            let dealer = Dealer::new_with_value_gens(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                n,
+                value_gens.clone(),
+            )
+            .unwrap();
+
+            // Party 0 commits to gold, party 1 is supposed to commit to silver.
+            let party0 =
+                Party::new(&bp_gens, &gold_gens, 1037, Scalar::random(&mut rng), n).unwrap();
+            let party1 =
+                Party::new(&bp_gens, party1_gens, 578, Scalar::random(&mut rng), n).unwrap();
+
+            let (party0, bit_com0) = party0.assign_position(0).unwrap();
+            let (party1, bit_com1) = party1.assign_position(1).unwrap();
+            let (dealer, bit_challenge) = dealer
+                .receive_bit_commitments(vec![bit_com0, bit_com1])
+                .unwrap();
+
+            let (party0, poly_com0) = party0.apply_challenge(&bit_challenge);
+            let (party1, poly_com1) = party1.apply_challenge(&bit_challenge);
+            let (dealer, poly_challenge) = dealer
+                .receive_poly_commitments(vec![poly_com0, poly_com1])
+                .unwrap();
+
+            let share0 = party0.apply_challenge(&poly_challenge).unwrap();
+            let share1 = party1.apply_challenge(&poly_challenge).unwrap();
+            dealer.receive_shares(&[share0, share1])
+        };
+
+        assert!(prove(&silver_gens).is_ok());
+
+        // A party committing to gold in place of silver is detected.
+        match prove(&gold_gens) {
+            Err(MPCError::MalformedProofShares { bad_shares }) => {
+                assert_eq!(bad_shares, vec![1]);
+            }
+            Err(_) => {
+                panic!("Got wrong error type from malformed shares");
+            }
+            Ok(_) => {
+                panic!("The proof was malformed, but it was not detected");
+            }
+        }
+    }
+
     #[test]
     fn detect_dishonest_party_during_aggregation() {
         use self::dealer::*;
@@ -838,4 +1905,44 @@ mod tests {
 
         assert!(maybe_share0.unwrap_err() == MPCError::MaliciousDealer);
     }
+
+    #[test]
+    fn create_and_verify_with_keccak256_transcript() {
+        use crate::transcript::Keccak256Transcript;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 2);
+        let mut rng = rand::thread_rng();
+
+        let values = [7, u32::max_value() as u64];
+        let blindings = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+        let (proof, value_commitments) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut Keccak256Transcript::new(b"AggregatedRangeProofTest"),
+            &values,
+            &blindings,
+            32,
+        )
+        .unwrap();
+
+        assert!(proof
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Keccak256Transcript::new(b"AggregatedRangeProofTest"),
+                &value_commitments,
+                32,
+            )
+            .is_ok());
+        assert!(proof
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"AggregatedRangeProofTest"),
+                &value_commitments,
+                32,
+            )
+            .is_err());
+    }
 }
@@ -15,13 +15,12 @@ extern crate alloc;
 use alloc::vec::Vec;
 use clear_on_drop::clear::Clear;
 use core::iter;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
-use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::MultiscalarMul;
+use curve25519_dalek::ristretto::RistrettoPoint;
 use rand_core::{CryptoRng, RngCore};
 
+use crate::curve::{CurvePoint, CurveScalar};
 use crate::errors::MPCError;
-use crate::generators::{BulletproofGens, PedersenGens};
+use crate::generators::{BulletproofGens, PedersenBases, PedersenGens, PedersenGensTable};
 use crate::util;
 
 #[cfg(feature = "std")]
@@ -34,13 +33,44 @@ pub struct Party {}
 
 impl Party {
     /// Constructs a `PartyAwaitingPosition` with the given rangeproof parameters.
-    pub fn new<'a>(
-        bp_gens: &'a BulletproofGens,
-        pc_gens: &'a PedersenGens,
+    ///
+    /// The party commits to its value and to its polynomial coefficients
+    /// with the value generator `pc_gens.B`.  In a proof coordinated by
+    /// [`Dealer::new_with_value_gens`](super::dealer::Dealer::new_with_value_gens),
+    /// each party passes the bases for its own value generator, for instance
+    /// from [`PedersenGens::for_asset`], with the dealer's blinding base.
+    pub fn new<'a, C: CurvePoint>(
+        bp_gens: &'a BulletproofGens<C>,
+        pc_gens: &'a PedersenGens<C>,
         v: u64,
-        v_blinding: Scalar,
+        v_blinding: C::Scalar,
         n: usize,
-    ) -> Result<PartyAwaitingPosition<'a>, MPCError> {
+    ) -> Result<PartyAwaitingPosition<'a, C>, MPCError> {
+        Party::create(bp_gens, pc_gens.into(), v, v_blinding, n)
+    }
+
+    /// Constructs a `PartyAwaitingPosition` with the given rangeproof
+    /// parameters, which commits with the precomputed tables of `pc_table`.
+    ///
+    /// The commitments are the same as those of a party created by
+    /// [`Party::new`] with the bases `pc_table.gens()`.
+    pub fn new_with_table<'a, C: CurvePoint>(
+        bp_gens: &'a BulletproofGens<C>,
+        pc_table: &'a PedersenGensTable<C>,
+        v: u64,
+        v_blinding: C::Scalar,
+        n: usize,
+    ) -> Result<PartyAwaitingPosition<'a, C>, MPCError> {
+        Party::create(bp_gens, pc_table.into(), v, v_blinding, n)
+    }
+
+    pub(crate) fn create<'a, C: CurvePoint>(
+        bp_gens: &'a BulletproofGens<C>,
+        pc_gens: PedersenBases<'a, C>,
+        v: u64,
+        v_blinding: C::Scalar,
+        n: usize,
+    ) -> Result<PartyAwaitingPosition<'a, C>, MPCError> {
         if !(n == 8 || n == 16 || n == 32 || n == 64) {
             return Err(MPCError::InvalidBitsize);
         }
@@ -62,23 +92,23 @@ impl Party {
 }
 
 /// A party waiting for the dealer to assign their position in the aggregation.
-pub struct PartyAwaitingPosition<'a> {
-    bp_gens: &'a BulletproofGens,
-    pc_gens: &'a PedersenGens,
+pub struct PartyAwaitingPosition<'a, C: CurvePoint = RistrettoPoint> {
+    bp_gens: &'a BulletproofGens<C>,
+    pc_gens: PedersenBases<'a, C>,
     n: usize,
     v: u64,
-    v_blinding: Scalar,
-    V: CompressedRistretto,
+    v_blinding: C::Scalar,
+    V: C::Compressed,
 }
 
-impl<'a> PartyAwaitingPosition<'a> {
+impl<'a, C: CurvePoint> PartyAwaitingPosition<'a, C> {
     /// Assigns a position in the aggregated proof to this party,
     /// allowing the party to commit to the bits of their value.
     #[cfg(feature = "std")]
     pub fn assign_position(
         self,
         j: usize,
-    ) -> Result<(PartyAwaitingBitChallenge<'a>, BitCommitment), MPCError> {
+    ) -> Result<(PartyAwaitingBitChallenge<'a, C>, BitCommitment<C>), MPCError> {
         self.assign_position_with_rng(j, &mut thread_rng())
     }
 
@@ -88,35 +118,35 @@ impl<'a> PartyAwaitingPosition<'a> {
         self,
         j: usize,
         rng: &mut T,
-    ) -> Result<(PartyAwaitingBitChallenge<'a>, BitCommitment), MPCError> {
+    ) -> Result<(PartyAwaitingBitChallenge<'a, C>, BitCommitment<C>), MPCError> {
         if self.bp_gens.party_capacity <= j {
             return Err(MPCError::InvalidGeneratorsLength);
         }
 
         let bp_share = self.bp_gens.share(j);
 
-        let a_blinding = Scalar::random(rng);
+        let a_blinding = C::Scalar::random(rng);
         // Compute A = <a_L, G> + <a_R, H> + a_blinding * B_blinding
         let mut A = self.pc_gens.B_blinding * a_blinding;
 
-        use subtle::{Choice, ConditionallySelectable};
+        use subtle::Choice;
         let mut i = 0;
         for (G_i, H_i) in bp_share.G(self.n).zip(bp_share.H(self.n)) {
             // If v_i = 0, we add a_L[i] * G[i] + a_R[i] * H[i] = - H[i]
             // If v_i = 1, we add a_L[i] * G[i] + a_R[i] * H[i] =   G[i]
             let v_i = Choice::from(((self.v >> i) & 1) as u8);
-            let mut point = -H_i;
+            let mut point = -*H_i;
             point.conditional_assign(G_i, v_i);
             A += point;
             i += 1;
         }
 
-        let s_blinding = Scalar::random(rng);
-        let s_L: Vec<Scalar> = (0..self.n).map(|_| Scalar::random(rng)).collect();
-        let s_R: Vec<Scalar> = (0..self.n).map(|_| Scalar::random(rng)).collect();
+        let s_blinding = C::Scalar::random(rng);
+        let s_L: Vec<C::Scalar> = (0..self.n).map(|_| C::Scalar::random(rng)).collect();
+        let s_R: Vec<C::Scalar> = (0..self.n).map(|_| C::Scalar::random(rng)).collect();
 
         // Compute S = <s_L, G> + <s_R, H> + s_blinding * B_blinding
-        let S = RistrettoPoint::multiscalar_mul(
+        let S = C::multiscalar_mul(
             iter::once(&s_blinding).chain(s_L.iter()).chain(s_R.iter()),
             iter::once(&self.pc_gens.B_blinding)
                 .chain(bp_share.G(self.n))
@@ -145,7 +175,7 @@ impl<'a> PartyAwaitingPosition<'a> {
 }
 
 /// Overwrite secrets with null bytes when they go out of scope.
-impl<'a> Drop for PartyAwaitingPosition<'a> {
+impl<'a, C: CurvePoint> Drop for PartyAwaitingPosition<'a, C> {
     fn drop(&mut self) {
         self.v.clear();
         self.v_blinding.clear();
@@ -154,26 +184,26 @@ impl<'a> Drop for PartyAwaitingPosition<'a> {
 
 /// A party which has committed to the bits of its value
 /// and is waiting for the aggregated value challenge from the dealer.
-pub struct PartyAwaitingBitChallenge<'a> {
+pub struct PartyAwaitingBitChallenge<'a, C: CurvePoint = RistrettoPoint> {
     n: usize, // bitsize of the range
     v: u64,
-    v_blinding: Scalar,
+    v_blinding: C::Scalar,
     j: usize,
-    pc_gens: &'a PedersenGens,
-    a_blinding: Scalar,
-    s_blinding: Scalar,
-    s_L: Vec<Scalar>,
-    s_R: Vec<Scalar>,
+    pc_gens: PedersenBases<'a, C>,
+    a_blinding: C::Scalar,
+    s_blinding: C::Scalar,
+    s_L: Vec<C::Scalar>,
+    s_R: Vec<C::Scalar>,
 }
 
-impl<'a> PartyAwaitingBitChallenge<'a> {
+impl<'a, C: CurvePoint> PartyAwaitingBitChallenge<'a, C> {
     /// Receive a [`BitChallenge`] from the dealer and use it to
     /// compute commitments to the party's polynomial coefficients.
     #[cfg(feature = "std")]
     pub fn apply_challenge(
         self,
-        vc: &BitChallenge,
-    ) -> (PartyAwaitingPolyChallenge, PolyCommitment) {
+        vc: &BitChallenge<C>,
+    ) -> (PartyAwaitingPolyChallenge<C>, PolyCommitment<C>) {
         self.apply_challenge_with_rng(vc, &mut thread_rng())
     }
 
@@ -181,23 +211,23 @@ impl<'a> PartyAwaitingBitChallenge<'a> {
     /// compute commitments to the party's polynomial coefficients.
     pub fn apply_challenge_with_rng<T: RngCore + CryptoRng>(
         self,
-        vc: &BitChallenge,
+        vc: &BitChallenge<C>,
         rng: &mut T,
-    ) -> (PartyAwaitingPolyChallenge, PolyCommitment) {
+    ) -> (PartyAwaitingPolyChallenge<C>, PolyCommitment<C>) {
         let n = self.n;
         let offset_y = util::scalar_exp_vartime(&vc.y, (self.j * n) as u64);
         let offset_z = util::scalar_exp_vartime(&vc.z, self.j as u64);
 
         // Calculate t by calculating vectors l0, l1, r0, r1 and multiplying
-        let mut l_poly = util::VecPoly1::zero(n);
-        let mut r_poly = util::VecPoly1::zero(n);
+        let mut l_poly = util::VecPoly1::<C::Scalar>::zero(n);
+        let mut r_poly = util::VecPoly1::<C::Scalar>::zero(n);
 
         let offset_zz = vc.z * vc.z * offset_z;
         let mut exp_y = offset_y; // start at y^j
-        let mut exp_2 = Scalar::one(); // start at 2^0 = 1
+        let mut exp_2 = C::Scalar::one(); // start at 2^0 = 1
         for i in 0..n {
-            let a_L_i = Scalar::from((self.v >> i) & 1);
-            let a_R_i = a_L_i - Scalar::one();
+            let a_L_i = C::Scalar::from((self.v >> i) & 1);
+            let a_R_i = a_L_i - C::Scalar::one();
 
             l_poly.0[i] = a_L_i - vc.z;
             l_poly.1[i] = self.s_L[i];
@@ -211,8 +241,8 @@ impl<'a> PartyAwaitingBitChallenge<'a> {
         let t_poly = l_poly.inner_product(&r_poly);
 
         // Generate x by committing to T_1, T_2 (line 49-54)
-        let t_1_blinding = Scalar::random(rng);
-        let t_2_blinding = Scalar::random(rng);
+        let t_1_blinding = C::Scalar::random(rng);
+        let t_2_blinding = C::Scalar::random(rng);
         let T_1 = self.pc_gens.commit(t_poly.1, t_1_blinding);
         let T_2 = self.pc_gens.commit(t_poly.2, t_2_blinding);
 
@@ -238,7 +268,7 @@ impl<'a> PartyAwaitingBitChallenge<'a> {
 }
 
 /// Overwrite secrets with null bytes when they go out of scope.
-impl<'a> Drop for PartyAwaitingBitChallenge<'a> {
+impl<'a, C: CurvePoint> Drop for PartyAwaitingBitChallenge<'a, C> {
     fn drop(&mut self) {
         self.v.clear();
         self.v_blinding.clear();
@@ -261,25 +291,25 @@ impl<'a> Drop for PartyAwaitingBitChallenge<'a> {
 
 /// A party which has committed to their polynomial coefficents
 /// and is waiting for the polynomial challenge from the dealer.
-pub struct PartyAwaitingPolyChallenge {
-    offset_zz: Scalar,
-    l_poly: util::VecPoly1,
-    r_poly: util::VecPoly1,
-    t_poly: util::Poly2,
-    v_blinding: Scalar,
-    a_blinding: Scalar,
-    s_blinding: Scalar,
-    t_1_blinding: Scalar,
-    t_2_blinding: Scalar,
+pub struct PartyAwaitingPolyChallenge<C: CurvePoint = RistrettoPoint> {
+    offset_zz: C::Scalar,
+    l_poly: util::VecPoly1<C::Scalar>,
+    r_poly: util::VecPoly1<C::Scalar>,
+    t_poly: util::Poly2<C::Scalar>,
+    v_blinding: C::Scalar,
+    a_blinding: C::Scalar,
+    s_blinding: C::Scalar,
+    t_1_blinding: C::Scalar,
+    t_2_blinding: C::Scalar,
 }
 
-impl PartyAwaitingPolyChallenge {
+impl<C: CurvePoint> PartyAwaitingPolyChallenge<C> {
     /// Receive a [`PolyChallenge`] from the dealer and compute the
     /// party's proof share.
-    pub fn apply_challenge(self, pc: &PolyChallenge) -> Result<ProofShare, MPCError> {
+    pub fn apply_challenge(self, pc: &PolyChallenge<C>) -> Result<ProofShare<C>, MPCError> {
         // Prevent a malicious dealer from annihilating the blinding
         // factors by supplying a zero challenge.
-        if pc.x == Scalar::zero() {
+        if pc.x == C::Scalar::zero() {
             return Err(MPCError::MaliciousDealer);
         }
 
@@ -291,7 +321,7 @@ impl PartyAwaitingPolyChallenge {
 
         let t_x = self.t_poly.eval(pc.x);
         let t_x_blinding = t_blinding_poly.eval(pc.x);
-        let e_blinding = self.a_blinding + self.s_blinding * &pc.x;
+        let e_blinding = self.a_blinding + self.s_blinding * pc.x;
         let l_vec = self.l_poly.eval(pc.x);
         let r_vec = self.r_poly.eval(pc.x);
 
@@ -306,7 +336,7 @@ impl PartyAwaitingPolyChallenge {
 }
 
 /// Overwrite secrets with null bytes when they go out of scope.
-impl Drop for PartyAwaitingPolyChallenge {
+impl<C: CurvePoint> Drop for PartyAwaitingPolyChallenge<C> {
     fn drop(&mut self) {
         self.v_blinding.clear();
         self.a_blinding.clear();
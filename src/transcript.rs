@@ -1,18 +1,450 @@
-//! Defines a `TranscriptProtocol` trait for using a Merlin transcript.
+//! Defines the `ProofTranscript` trait for the Fiat-Shamir transcripts
+//! of the proofs, with implementations for a Merlin transcript and a
+//! Keccak-256 transcript, a `RecordingTranscript` wrapper for debugging
+//! diverging transcripts, and a `TranscriptProtocol` trait for using
+//! them.
 
-use curve25519_dalek::ristretto::CompressedRistretto;
-use curve25519_dalek::scalar::Scalar;
-use merlin::Transcript;
+extern crate alloc;
 
+use alloc::vec::Vec;
+use core::fmt;
+use digest::Digest;
+use merlin::{Transcript, TranscriptRng};
+use rand_core::{impls, CryptoRng, Error, RngCore};
+use sha3::Keccak256;
+
+use crate::curve::{CompressedPoint, CurveScalar};
 use crate::errors::ProofError;
 
+/// A Fiat-Shamir transcript, from which the proofs derive their challenges.
+///
+/// The proofs of this crate are generic over the transcript, so that
+/// they can be verified where a transcript over another hash function is
+/// cheaper, such as in smart contracts.  The prover and the verifier must
+/// use the same implementation, with identical initial states.
+///
+/// The default transcript is a [`merlin::Transcript`], and
+/// [`Keccak256Transcript`] is provided as an alternative.
+pub trait ProofTranscript: Clone {
+    /// The RNG returned by [`ProofTranscript::witness_rng`].
+    type Rng: RngCore + CryptoRng;
+
+    /// Appends a prover's `message` to the transcript, with a `label`
+    /// for domain separation.
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]);
+
+    /// Appends `x` to the transcript, encoded as 8 little-endian bytes.
+    fn append_u64(&mut self, label: &'static [u8], x: u64) {
+        self.append_message(label, &x.to_le_bytes());
+    }
+
+    /// Fills `dest` with challenge bytes, determined by the transcript and
+    /// the `label`, and binds the transcript to the challenge.
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]);
+
+    /// Returns an RNG for the prover's blinding factors, seeded by the
+    /// transcript, the prover's `witness` data and randomness from `rng`,
+    /// without modifying the transcript.
+    fn witness_rng<R: RngCore + CryptoRng>(
+        &self,
+        witness: &[(&'static [u8], &[u8])],
+        rng: &mut R,
+    ) -> Self::Rng;
+}
+
+impl ProofTranscript for Transcript {
+    type Rng = TranscriptRng;
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        Transcript::append_message(self, label, message);
+    }
+
+    fn append_u64(&mut self, label: &'static [u8], x: u64) {
+        Transcript::append_u64(self, label, x);
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        Transcript::challenge_bytes(self, label, dest);
+    }
+
+    fn witness_rng<R: RngCore + CryptoRng>(
+        &self,
+        witness: &[(&'static [u8], &[u8])],
+        rng: &mut R,
+    ) -> TranscriptRng {
+        let mut builder = self.build_rng();
+        for &(label, bytes) in witness {
+            builder = builder.rekey_with_witness_bytes(label, bytes);
+        }
+        builder.finalize(rng)
+    }
+}
+
+/// A transcript over the Keccak-256 hash function, as used by the EVM.
+///
+/// # Specification
+///
+/// The transcript state is a 32-byte string \\(s\\), and \\(H\\) denotes
+/// Keccak-256.  Labels, messages and lengths are framed with
+/// \\(\mathrm{len}(x)\\), the length of \\(x\\) as 4 little-endian bytes.
+///
+/// * `new(label)` sets \\(s = H(\mathtt{"Keccak256Transcript\ v1"} \\| \mathrm{len}(label) \\| label)\\);
+/// * `append_message(label, message)` sets
+///   \\(s = H(\mathtt{0x00} \\| s \\| \mathrm{len}(label) \\| label \\| \mathrm{len}(message) \\| message)\\);
+/// * `challenge_bytes(label, dest)` for \\(L\\) bytes sets
+///   \\(s = H(\mathtt{0x01} \\| s \\| \mathrm{len}(label) \\| label \\| \mathrm{LE32}(L))\\),
+///   then fills `dest` with the first \\(L\\) bytes of
+///   \\(H(\mathtt{0x02} \\| s \\| \mathrm{LE32}(0)) \\| H(\mathtt{0x02} \\| s \\| \mathrm{LE32}(1)) \\| \dots\\).
+///
+/// Challenge scalars are 64 challenge bytes reduced modulo the group
+/// order, read as an integer in the byte order of the scalars, and
+/// `u64`s are appended as 8 little-endian bytes, as for the Merlin
+/// transcript.
+///
+/// The RNG for blinding factors hashes the state, the witness data and
+/// 32 bytes from the external RNG into a seed \\(k\\) with the prefix
+/// \\(\mathtt{0x03}\\), and outputs the blocks \\(H(\mathtt{0x04} \\| k \\| \mathrm{LE64}(i))\\).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Keccak256Transcript {
+    state: [u8; 32],
+}
+
+impl Keccak256Transcript {
+    /// Creates a transcript with the given domain separation `label`.
+    pub fn new(label: &'static [u8]) -> Self {
+        let mut hash = Keccak256::new();
+        hash.input(b"Keccak256Transcript v1");
+        hash.input((label.len() as u32).to_le_bytes());
+        hash.input(label);
+
+        let mut state = [0u8; 32];
+        state.copy_from_slice(&hash.result());
+        Keccak256Transcript { state }
+    }
+
+    /// Returns the 32-byte state \\(s\\) of the transcript.
+    pub fn state(&self) -> [u8; 32] {
+        self.state
+    }
+
+    /// Hashes the prefix, the state and the framed `label` into a new state,
+    /// leaving the hash open for the rest of the input.
+    fn begin(&self, prefix: u8, label: &'static [u8]) -> Keccak256 {
+        let mut hash = Keccak256::new();
+        hash.input([prefix]);
+        hash.input(self.state);
+        hash.input((label.len() as u32).to_le_bytes());
+        hash.input(label);
+        hash
+    }
+}
+
+impl ProofTranscript for Keccak256Transcript {
+    type Rng = Keccak256TranscriptRng;
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        let mut hash = self.begin(0x00, label);
+        hash.input((message.len() as u32).to_le_bytes());
+        hash.input(message);
+        self.state.copy_from_slice(&hash.result());
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        let mut hash = self.begin(0x01, label);
+        hash.input((dest.len() as u32).to_le_bytes());
+        self.state.copy_from_slice(&hash.result());
+
+        for (i, chunk) in dest.chunks_mut(32).enumerate() {
+            let mut hash = Keccak256::new();
+            hash.input([0x02]);
+            hash.input(self.state);
+            hash.input((i as u32).to_le_bytes());
+            chunk.copy_from_slice(&hash.result()[..chunk.len()]);
+        }
+    }
+
+    fn witness_rng<R: RngCore + CryptoRng>(
+        &self,
+        witness: &[(&'static [u8], &[u8])],
+        rng: &mut R,
+    ) -> Keccak256TranscriptRng {
+        let mut hash = Keccak256::new();
+        hash.input([0x03]);
+        hash.input(self.state);
+        for &(label, bytes) in witness {
+            hash.input((label.len() as u32).to_le_bytes());
+            hash.input(label);
+            hash.input((bytes.len() as u32).to_le_bytes());
+            hash.input(bytes);
+        }
+        let mut random_bytes = [0u8; 32];
+        rng.fill_bytes(&mut random_bytes);
+        hash.input(random_bytes);
+
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&hash.result());
+        Keccak256TranscriptRng { seed, counter: 0 }
+    }
+}
+
+/// The RNG of a [`Keccak256Transcript`] for the prover's blinding factors.
+pub struct Keccak256TranscriptRng {
+    seed: [u8; 32],
+    counter: u64,
+}
+
+impl RngCore for Keccak256TranscriptRng {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(32) {
+            let mut hash = Keccak256::new();
+            hash.input([0x04]);
+            hash.input(self.seed);
+            hash.input(self.counter.to_le_bytes());
+            chunk.copy_from_slice(&hash.result()[..chunk.len()]);
+            self.counter += 1;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for Keccak256TranscriptRng {}
+
+/// An operation on a transcript, as recorded by a [`RecordingTranscript`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptOp {
+    /// A `message` appended with the given `label`.
+    Append {
+        label: &'static [u8],
+        message: Vec<u8>,
+    },
+    /// The `bytes` of a challenge drawn with the given `label`.
+    Challenge {
+        label: &'static [u8],
+        bytes: Vec<u8>,
+    },
+}
+
+impl TranscriptOp {
+    /// The label of the operation.
+    pub fn label(&self) -> &'static [u8] {
+        match self {
+            TranscriptOp::Append { label, .. } | TranscriptOp::Challenge { label, .. } => label,
+        }
+    }
+}
+
+/// Writes a label as text when it is UTF-8, and as bytes otherwise.
+fn fmt_label(f: &mut fmt::Formatter, label: &[u8]) -> fmt::Result {
+    match core::str::from_utf8(label) {
+        Ok(label) => write!(f, "{:?}", label),
+        Err(_) => write!(f, "{:?}", label),
+    }
+}
+
+fn fmt_hex(f: &mut fmt::Formatter, bytes: &[u8]) -> fmt::Result {
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for TranscriptOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (op, label, bytes) = match self {
+            TranscriptOp::Append { label, message } => ("append", label, message),
+            TranscriptOp::Challenge { label, bytes } => ("challenge", label, bytes),
+        };
+        write!(f, "{} ", op)?;
+        fmt_label(f, label)?;
+        write!(f, ": ")?;
+        fmt_hex(f, bytes)
+    }
+}
+
+/// A transcript that records every message appended to and every
+/// challenge drawn from an inner transcript, for finding where the
+/// prover's and the verifier's transcripts diverge.
+///
+/// The proofs are generic over the transcript, so a proof can be
+/// created and verified with recording transcripts, and the logs of both
+/// sides compared with [`first_divergence`]:
+///
+/// ```
+/// # use bulletproofs::{first_divergence, BulletproofGens, PedersenGens, RangeProof, RecordingTranscript};
+/// # use curve25519_dalek::scalar::Scalar;
+/// # use merlin::Transcript;
+/// let pc_gens = PedersenGens::default();
+/// let bp_gens = BulletproofGens::new(64, 1);
+/// let blinding = Scalar::from(5u64);
+///
+/// let mut prover_transcript = RecordingTranscript::new(Transcript::new(b"doctest example"));
+/// let (proof, committed_value) = RangeProof::prove_single(
+///     &bp_gens, &pc_gens, &mut prover_transcript, 1037578891, &blinding, 32,
+/// ).unwrap();
+///
+/// // The verifier uses the wrong domain separation label.
+/// let mut verifier_transcript = RecordingTranscript::new(Transcript::new(b"doctest exampel"));
+/// assert!(proof
+///     .verify_single(&bp_gens, &pc_gens, &mut verifier_transcript, &committed_value, 32)
+///     .is_err());
+///
+/// let divergence = first_divergence(prover_transcript.log(), verifier_transcript.log()).unwrap();
+/// println!("{}", divergence);
+///
+/// // The first challenge is the first operation that differs.
+/// assert_eq!(divergence.prover.unwrap().label(), b"y");
+/// ```
+///
+/// Both transcripts start from their inner transcript, so divergences
+/// in the initial state, such as a different label, show up at the first
+/// challenge.  The RNGs of the prover are not recorded.
+#[derive(Clone, Debug)]
+pub struct RecordingTranscript<T: ProofTranscript = Transcript> {
+    inner: T,
+    log: Vec<TranscriptOp>,
+}
+
+impl<T: ProofTranscript> RecordingTranscript<T> {
+    /// Wraps the `inner` transcript with an empty log.
+    pub fn new(inner: T) -> Self {
+        RecordingTranscript {
+            inner,
+            log: Vec::new(),
+        }
+    }
+
+    /// Returns the operations recorded so far, in order.
+    pub fn log(&self) -> &[TranscriptOp] {
+        &self.log
+    }
+
+    /// Returns the inner transcript and the recorded operations.
+    pub fn into_parts(self) -> (T, Vec<TranscriptOp>) {
+        (self.inner, self.log)
+    }
+}
+
+impl<T: ProofTranscript> ProofTranscript for RecordingTranscript<T> {
+    type Rng = T::Rng;
+
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.inner.append_message(label, message);
+        self.log.push(TranscriptOp::Append {
+            label,
+            message: message.to_vec(),
+        });
+    }
+
+    fn append_u64(&mut self, label: &'static [u8], x: u64) {
+        self.inner.append_u64(label, x);
+        self.log.push(TranscriptOp::Append {
+            label,
+            message: x.to_le_bytes().to_vec(),
+        });
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.inner.challenge_bytes(label, dest);
+        self.log.push(TranscriptOp::Challenge {
+            label,
+            bytes: dest.to_vec(),
+        });
+    }
+
+    fn witness_rng<R: RngCore + CryptoRng>(
+        &self,
+        witness: &[(&'static [u8], &[u8])],
+        rng: &mut R,
+    ) -> T::Rng {
+        self.inner.witness_rng(witness, rng)
+    }
+}
+
+/// The first operation at which two transcript logs differ, as found by
+/// [`first_divergence`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptDivergence {
+    /// The index of the operation in both logs.
+    pub index: usize,
+    /// The operation of the prover, or `None` if its log ended.
+    pub prover: Option<TranscriptOp>,
+    /// The operation of the verifier, or `None` if its log ended.
+    pub verifier: Option<TranscriptOp>,
+}
+
+impl fmt::Display for TranscriptDivergence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "transcripts diverge at operation {}:", self.index)?;
+        match &self.prover {
+            Some(op) => writeln!(f, "  prover:   {}", op)?,
+            None => writeln!(f, "  prover:   (end of log)")?,
+        }
+        match &self.verifier {
+            Some(op) => write!(f, "  verifier: {}", op),
+            None => write!(f, "  verifier: (end of log)"),
+        }
+    }
+}
+
+/// Compares the logs of a prover's and a verifier's
+/// [`RecordingTranscript`]s, and returns the first operation at which
+/// they differ, or `None` if the logs are identical.
+///
+/// Operations differ if they are of a different kind, or have a
+/// different label, message or challenge.  If one log is a prefix of
+/// the other, the divergence is at the end of the shorter log.
+pub fn first_divergence(
+    prover: &[TranscriptOp],
+    verifier: &[TranscriptOp],
+) -> Option<TranscriptDivergence> {
+    let index = prover
+        .iter()
+        .zip(verifier)
+        .position(|(p, v)| p != v)
+        .or_else(|| {
+            if prover.len() != verifier.len() {
+                Some(prover.len().min(verifier.len()))
+            } else {
+                None
+            }
+        })?;
+
+    Some(TranscriptDivergence {
+        index,
+        prover: prover.get(index).cloned(),
+        verifier: verifier.get(index).cloned(),
+    })
+}
+
 pub trait TranscriptProtocol {
     /// Append a domain separator for an `n`-bit, `m`-party range proof.
     fn rangeproof_domain_sep(&mut self, n: u64, m: u64);
 
+    /// Append a domain separator for an `n`-bit, `m`-party range proof
+    /// with a value generator per commitment.
+    fn rangeproof_value_gens_domain_sep(&mut self, n: u64, m: u64);
+
     /// Append a domain separator for a length-`n` inner product proof.
     fn innerproduct_domain_sep(&mut self, n: u64);
 
+    /// Append a domain separator for a length-`n` weighted inner product proof.
+    fn weighted_innerproduct_domain_sep(&mut self, n: u64);
+
+    /// Append a domain separator for `k` aggregated length-`n` linear proofs.
+    fn aggregated_linearproof_domain_sep(&mut self, n: u64, k: u64);
+
     /// Append a domain separator for a constraint system.
     fn r1cs_domain_sep(&mut self);
 
@@ -23,35 +455,52 @@ pub trait TranscriptProtocol {
     fn r1cs_2phase_domain_sep(&mut self);
 
     /// Append a `scalar` with the given `label`.
-    fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar);
+    fn append_scalar<S: CurveScalar>(&mut self, label: &'static [u8], scalar: &S);
 
     /// Append a `point` with the given `label`.
-    fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto);
+    fn append_point<P: CompressedPoint>(&mut self, label: &'static [u8], point: &P);
 
     /// Check that a point is not the identity, then append it to the
     /// transcript.  Otherwise, return an error.
-    fn validate_and_append_point(
+    fn validate_and_append_point<P: CompressedPoint>(
         &mut self,
         label: &'static [u8],
-        point: &CompressedRistretto,
+        point: &P,
     ) -> Result<(), ProofError>;
 
     /// Compute a `label`ed challenge variable.
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
+    fn challenge_scalar<S: CurveScalar>(&mut self, label: &'static [u8]) -> S;
 }
 
-impl TranscriptProtocol for Transcript {
+impl<T: ProofTranscript> TranscriptProtocol for T {
     fn rangeproof_domain_sep(&mut self, n: u64, m: u64) {
         self.append_message(b"dom-sep", b"rangeproof v1");
         self.append_u64(b"n", n);
         self.append_u64(b"m", m);
     }
 
+    fn rangeproof_value_gens_domain_sep(&mut self, n: u64, m: u64) {
+        self.append_message(b"dom-sep", b"rangeproof with value generators v1");
+        self.append_u64(b"n", n);
+        self.append_u64(b"m", m);
+    }
+
     fn innerproduct_domain_sep(&mut self, n: u64) {
         self.append_message(b"dom-sep", b"ipp v1");
         self.append_u64(b"n", n);
     }
 
+    fn weighted_innerproduct_domain_sep(&mut self, n: u64) {
+        self.append_message(b"dom-sep", b"wipp v1");
+        self.append_u64(b"n", n);
+    }
+
+    fn aggregated_linearproof_domain_sep(&mut self, n: u64, k: u64) {
+        self.append_message(b"dom-sep", b"aggregated linear proof v1");
+        self.append_u64(b"n", n);
+        self.append_u64(b"k", k);
+    }
+
     fn r1cs_domain_sep(&mut self) {
         self.append_message(b"dom-sep", b"r1cs v1");
     }
@@ -64,21 +513,19 @@ impl TranscriptProtocol for Transcript {
         self.append_message(b"dom-sep", b"r1cs-2phase");
     }
 
-    fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
-        self.append_message(label, scalar.as_bytes());
+    fn append_scalar<S: CurveScalar>(&mut self, label: &'static [u8], scalar: &S) {
+        self.append_message(label, &scalar.to_bytes());
     }
 
-    fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto) {
+    fn append_point<P: CompressedPoint>(&mut self, label: &'static [u8], point: &P) {
         self.append_message(label, point.as_bytes());
     }
 
-    fn validate_and_append_point(
+    fn validate_and_append_point<P: CompressedPoint>(
         &mut self,
         label: &'static [u8],
-        point: &CompressedRistretto,
+        point: &P,
     ) -> Result<(), ProofError> {
-        use curve25519_dalek::traits::IsIdentity;
-
         if point.is_identity() {
             Err(ProofError::VerificationError)
         } else {
@@ -86,10 +533,149 @@ impl TranscriptProtocol for Transcript {
         }
     }
 
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+    fn challenge_scalar<S: CurveScalar>(&mut self, label: &'static [u8]) -> S {
         let mut buf = [0u8; 64];
         self.challenge_bytes(label, &mut buf);
 
-        Scalar::from_bytes_mod_order_wide(&buf)
+        S::from_bytes_mod_order_wide(&buf)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use curve25519_dalek::scalar::Scalar;
+
+    #[test]
+    fn keccak256_is_the_evm_hash() {
+        // Keccak-256 of the empty string, as returned by the EVM's SHA3 opcode.
+        assert_eq!(
+            hex::encode(Keccak256::digest(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn keccak256_transcript_vectors() {
+        let mut transcript = Keccak256Transcript::new(b"test transcript");
+        transcript.append_message(b"message", b"some data");
+        transcript.append_u64(b"n", 64);
+
+        let mut challenge = [0u8; 40];
+        transcript.challenge_bytes(b"challenge", &mut challenge);
+        assert_eq!(
+            hex::encode(&challenge[..]),
+            "cb925ff87b888b920697bc0e14d0890df4d5e758cec6cef72d65cf52b0d27253c357e3fd925dc982"
+        );
+
+        let x: Scalar = transcript.challenge_scalar(b"x");
+        assert_eq!(
+            hex::encode(x.as_bytes()),
+            "f21643e11d32bb040cf386801c61dcb9e9a30c689062b4928ee4b9af4d4ad30b"
+        );
+    }
+
+    #[test]
+    fn keccak256_transcript_follows_specification() {
+        fn hash(parts: &[&[u8]]) -> [u8; 32] {
+            let mut hash = Keccak256::new();
+            for part in parts {
+                hash.input(part);
+            }
+            let mut out = [0u8; 32];
+            out.copy_from_slice(&hash.result());
+            out
+        }
+
+        let s = hash(&[b"Keccak256Transcript v1", &[4, 0, 0, 0], b"test"]);
+        let s = hash(&[&[0x00], &s, &[3, 0, 0, 0], b"msg", &[2, 0, 0, 0], b"hi"]);
+        let s = hash(&[&[0x01], &s, &[2, 0, 0, 0], b"ch", &[40, 0, 0, 0]]);
+        let mut expected = hash(&[&[0x02], &s, &[0, 0, 0, 0]]).to_vec();
+        expected.extend_from_slice(&hash(&[&[0x02], &s, &[1, 0, 0, 0]])[..8]);
+
+        let mut transcript = Keccak256Transcript::new(b"test");
+        transcript.append_message(b"msg", b"hi");
+        let mut challenge = [0u8; 40];
+        transcript.challenge_bytes(b"ch", &mut challenge);
+
+        assert_eq!(&challenge[..], &expected[..]);
+        assert_eq!(transcript.state, s);
+    }
+
+    #[test]
+    fn transcript_backends_disagree() {
+        let mut merlin = Transcript::new(b"test");
+        let mut keccak = Keccak256Transcript::new(b"test");
+        merlin.append_message(b"msg", b"hi");
+        keccak.append_message(b"msg", b"hi");
+
+        assert_ne!(
+            merlin.challenge_scalar::<Scalar>(b"x"),
+            keccak.challenge_scalar(b"x")
+        );
+    }
+
+    #[test]
+    fn recording_transcript_matches_inner_transcript() {
+        let mut plain = Transcript::new(b"test");
+        plain.append_message(b"msg", b"hi");
+        plain.append_u64(b"n", 8);
+
+        let mut recording = RecordingTranscript::new(Transcript::new(b"test"));
+        recording.append_message(b"msg", b"hi");
+        recording.append_u64(b"n", 8);
+
+        let x: Scalar = recording.challenge_scalar(b"x");
+        assert_eq!(plain.challenge_scalar::<Scalar>(b"x"), x);
+
+        let log = recording.log();
+        assert_eq!(log.len(), 3);
+        assert_eq!(
+            log[0],
+            TranscriptOp::Append {
+                label: b"msg",
+                message: b"hi".to_vec(),
+            }
+        );
+        assert_eq!(
+            log[1],
+            TranscriptOp::Append {
+                label: b"n",
+                message: 8u64.to_le_bytes().to_vec(),
+            }
+        );
+        assert_eq!(log[2].label(), b"x");
+        assert_eq!(format!("{}", log[0]), "append \"msg\": 6869");
+    }
+
+    #[test]
+    fn first_divergence_of_logs() {
+        let mut prover = RecordingTranscript::new(Keccak256Transcript::new(b"test"));
+        let mut verifier = prover.clone();
+
+        prover.r1cs_domain_sep();
+        prover.append_message(b"V", b"a");
+        prover.append_message(b"V", b"b");
+        let _: Scalar = prover.challenge_scalar(b"y");
+
+        // The verifier misses the domain separator.
+        verifier.append_message(b"V", b"a");
+        verifier.append_message(b"V", b"b");
+        let _: Scalar = verifier.challenge_scalar(b"y");
+
+        let divergence = first_divergence(prover.log(), verifier.log()).unwrap();
+        assert_eq!(divergence.index, 0);
+        assert_eq!(divergence.prover.unwrap().label(), b"dom-sep");
+        assert_eq!(divergence.verifier.unwrap().label(), b"V");
+
+        // A log that is a prefix of the other diverges at its end.
+        let (_, log) = prover.into_parts();
+        let divergence = first_divergence(&log[..2], &log).unwrap();
+        assert_eq!(divergence.index, 2);
+        assert_eq!(divergence.prover, None);
+        assert_eq!(divergence.verifier, Some(log[2].clone()));
+
+        assert_eq!(first_divergence(&log, &log), None);
     }
 }
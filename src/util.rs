@@ -8,48 +8,44 @@ use alloc::vec::Vec;
 use clear_on_drop::clear::Clear;
 use curve25519_dalek::scalar::Scalar;
 
+use crate::curve::CurveScalar;
 use crate::inner_product_proof::inner_product;
 
 /// Represents a degree-1 vector polynomial \\(\mathbf{a} + \mathbf{b} \cdot x\\).
-pub struct VecPoly1(pub Vec<Scalar>, pub Vec<Scalar>);
+pub struct VecPoly1<S: CurveScalar = Scalar>(pub Vec<S>, pub Vec<S>);
 
 /// Represents a degree-3 vector polynomial
 /// \\(\mathbf{a} + \mathbf{b} \cdot x + \mathbf{c} \cdot x^2 + \mathbf{d} \cdot x^3 \\).
 #[cfg(feature = "yoloproofs")]
-pub struct VecPoly3(
-    pub Vec<Scalar>,
-    pub Vec<Scalar>,
-    pub Vec<Scalar>,
-    pub Vec<Scalar>,
-);
+pub struct VecPoly3<S: CurveScalar = Scalar>(pub Vec<S>, pub Vec<S>, pub Vec<S>, pub Vec<S>);
 
 /// Represents a degree-2 scalar polynomial \\(a + b \cdot x + c \cdot x^2\\)
-pub struct Poly2(pub Scalar, pub Scalar, pub Scalar);
+pub struct Poly2<S: CurveScalar = Scalar>(pub S, pub S, pub S);
 
 /// Represents a degree-6 scalar polynomial, without the zeroth degree
 /// \\(a \cdot x + b \cdot x^2 + c \cdot x^3 + d \cdot x^4 + e \cdot x^5 + f \cdot x^6\\)
 #[cfg(feature = "yoloproofs")]
-pub struct Poly6 {
-    pub t1: Scalar,
-    pub t2: Scalar,
-    pub t3: Scalar,
-    pub t4: Scalar,
-    pub t5: Scalar,
-    pub t6: Scalar,
+pub struct Poly6<S: CurveScalar = Scalar> {
+    pub t1: S,
+    pub t2: S,
+    pub t3: S,
+    pub t4: S,
+    pub t5: S,
+    pub t6: S,
 }
 
 /// Provides an iterator over the powers of a `Scalar`.
 ///
 /// This struct is created by the `exp_iter` function.
-pub struct ScalarExp {
-    x: Scalar,
-    next_exp_x: Scalar,
+pub struct ScalarExp<S: CurveScalar = Scalar> {
+    x: S,
+    next_exp_x: S,
 }
 
-impl Iterator for ScalarExp {
-    type Item = Scalar;
+impl<S: CurveScalar> Iterator for ScalarExp<S> {
+    type Item = S;
 
-    fn next(&mut self) -> Option<Scalar> {
+    fn next(&mut self) -> Option<S> {
         let exp_x = self.next_exp_x;
         self.next_exp_x *= self.x;
         Some(exp_x)
@@ -61,29 +57,29 @@ impl Iterator for ScalarExp {
 }
 
 /// Return an iterator of the powers of `x`.
-pub fn exp_iter(x: Scalar) -> ScalarExp {
-    let next_exp_x = Scalar::one();
+pub fn exp_iter<S: CurveScalar>(x: S) -> ScalarExp<S> {
+    let next_exp_x = S::one();
     ScalarExp { x, next_exp_x }
 }
 
-pub fn add_vec(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
+pub fn add_vec<S: CurveScalar>(a: &[S], b: &[S]) -> Vec<S> {
     if a.len() != b.len() {
         // throw some error
         //println!("lengths of vectors don't match for vector addition");
     }
-    let mut out = vec![Scalar::zero(); b.len()];
+    let mut out = vec![S::zero(); b.len()];
     for i in 0..a.len() {
         out[i] = a[i] + b[i];
     }
     out
 }
 
-impl VecPoly1 {
+impl<S: CurveScalar> VecPoly1<S> {
     pub fn zero(n: usize) -> Self {
-        VecPoly1(vec![Scalar::zero(); n], vec![Scalar::zero(); n])
+        VecPoly1(vec![S::zero(); n], vec![S::zero(); n])
     }
 
-    pub fn inner_product(&self, rhs: &VecPoly1) -> Poly2 {
+    pub fn inner_product(&self, rhs: &VecPoly1<S>) -> Poly2<S> {
         // Uses Karatsuba's method
         let l = self;
         let r = rhs;
@@ -99,9 +95,9 @@ impl VecPoly1 {
         Poly2(t0, t1, t2)
     }
 
-    pub fn eval(&self, x: Scalar) -> Vec<Scalar> {
+    pub fn eval(&self, x: S) -> Vec<S> {
         let n = self.0.len();
-        let mut out = vec![Scalar::zero(); n];
+        let mut out = vec![S::zero(); n];
         for i in 0..n {
             out[i] = self.0[i] + self.1[i] * x;
         }
@@ -110,13 +106,13 @@ impl VecPoly1 {
 }
 
 #[cfg(feature = "yoloproofs")]
-impl VecPoly3 {
+impl<S: CurveScalar> VecPoly3<S> {
     pub fn zero(n: usize) -> Self {
         VecPoly3(
-            vec![Scalar::zero(); n],
-            vec![Scalar::zero(); n],
-            vec![Scalar::zero(); n],
-            vec![Scalar::zero(); n],
+            vec![S::zero(); n],
+            vec![S::zero(); n],
+            vec![S::zero(); n],
+            vec![S::zero(); n],
         )
     }
 
@@ -124,7 +120,7 @@ impl VecPoly3 {
     /// - `lhs.0` is zero;
     /// - `rhs.2` is zero;
     /// This is the case in the constraint system proof.
-    pub fn special_inner_product(lhs: &Self, rhs: &Self) -> Poly6 {
+    pub fn special_inner_product(lhs: &Self, rhs: &Self) -> Poly6<S> {
         // TODO: make checks that l_poly.0 and r_poly.2 are zero.
 
         let t1 = inner_product(&lhs.1, &rhs.0);
@@ -144,9 +140,9 @@ impl VecPoly3 {
         }
     }
 
-    pub fn eval(&self, x: Scalar) -> Vec<Scalar> {
+    pub fn eval(&self, x: S) -> Vec<S> {
         let n = self.0.len();
-        let mut out = vec![Scalar::zero(); n];
+        let mut out = vec![S::zero(); n];
         for i in 0..n {
             out[i] = self.0[i] + x * (self.1[i] + x * (self.2[i] + x * self.3[i]));
         }
@@ -154,20 +150,20 @@ impl VecPoly3 {
     }
 }
 
-impl Poly2 {
-    pub fn eval(&self, x: Scalar) -> Scalar {
+impl<S: CurveScalar> Poly2<S> {
+    pub fn eval(&self, x: S) -> S {
         self.0 + x * (self.1 + x * self.2)
     }
 }
 
 #[cfg(feature = "yoloproofs")]
-impl Poly6 {
-    pub fn eval(&self, x: Scalar) -> Scalar {
+impl<S: CurveScalar> Poly6<S> {
+    pub fn eval(&self, x: S) -> S {
         x * (self.t1 + x * (self.t2 + x * (self.t3 + x * (self.t4 + x * (self.t5 + x * self.t6)))))
     }
 }
 
-impl Drop for VecPoly1 {
+impl<S: CurveScalar> Drop for VecPoly1<S> {
     fn drop(&mut self) {
         for e in self.0.iter_mut() {
             e.clear();
@@ -178,7 +174,7 @@ impl Drop for VecPoly1 {
     }
 }
 
-impl Drop for Poly2 {
+impl<S: CurveScalar> Drop for Poly2<S> {
     fn drop(&mut self) {
         self.0.clear();
         self.1.clear();
@@ -187,7 +183,7 @@ impl Drop for Poly2 {
 }
 
 #[cfg(feature = "yoloproofs")]
-impl Drop for VecPoly3 {
+impl<S: CurveScalar> Drop for VecPoly3<S> {
     fn drop(&mut self) {
         for e in self.0.iter_mut() {
             e.clear();
@@ -205,7 +201,7 @@ impl Drop for VecPoly3 {
 }
 
 #[cfg(feature = "yoloproofs")]
-impl Drop for Poly6 {
+impl<S: CurveScalar> Drop for Poly6<S> {
     fn drop(&mut self) {
         self.t1.clear();
         self.t2.clear();
@@ -219,8 +215,8 @@ impl Drop for Poly6 {
 /// Raises `x` to the power `n` using binary exponentiation,
 /// with (1 to 2)*lg(n) scalar multiplications.
 /// TODO: a consttime version of this would be awfully similar to a Montgomery ladder.
-pub fn scalar_exp_vartime(x: &Scalar, mut n: u64) -> Scalar {
-    let mut result = Scalar::one();
+pub fn scalar_exp_vartime<S: CurveScalar>(x: &S, mut n: u64) -> S {
+    let mut result = S::one();
     let mut aux = *x; // x, x^2, x^4, x^8, ...
     while n > 0 {
         let bit = n & 1;
@@ -237,15 +233,15 @@ pub fn scalar_exp_vartime(x: &Scalar, mut n: u64) -> Scalar {
 /// If `n` is a power of 2, it uses the efficient algorithm with `2*lg n` multiplications and additions.
 /// If `n` is not a power of 2, it uses the slow algorithm with `n` multiplications and additions.
 /// In the Bulletproofs case, all calls to `sum_of_powers` should have `n` as a power of 2.
-pub fn sum_of_powers(x: &Scalar, n: usize) -> Scalar {
+pub fn sum_of_powers<S: CurveScalar>(x: &S, n: usize) -> S {
     if !n.is_power_of_two() {
         return sum_of_powers_slow(x, n);
     }
     if n == 0 || n == 1 {
-        return Scalar::from(n as u64);
+        return S::from(n as u64);
     }
     let mut m = n;
-    let mut result = Scalar::one() + x;
+    let mut result = S::one() + *x;
     let mut factor = *x;
     while m > 2 {
         factor = factor * factor;
@@ -256,10 +252,51 @@ pub fn sum_of_powers(x: &Scalar, n: usize) -> Scalar {
 }
 
 // takes the sum of all of the powers of x, up to n
-fn sum_of_powers_slow(x: &Scalar, n: usize) -> Scalar {
+fn sum_of_powers_slow<S: CurveScalar>(x: &S, n: usize) -> S {
     exp_iter(*x).take(n).sum()
 }
 
+/// Returns the number of folding rounds that reduce a length-`n` vector
+/// to a single element, halving it (rounded up) in each round.
+pub fn folding_rounds(n: usize) -> usize {
+    n.next_power_of_two().trailing_zeros() as usize
+}
+
+/// Computes, for each index of a length-`n` vector, the product of the
+/// factors that the folding rounds of an inner-product argument apply to it.
+///
+/// In round `j`, a vector of length \\(m\\) is split into a left and right
+/// half of length \\(\lfloor m/2 \rfloor\\), whose elements are multiplied by
+/// `left[j]` and `right[j]` respectively.  If \\(m\\) is odd, the unpaired
+/// last element is carried into the next round with factor one.
+pub fn folding_products<S: CurveScalar>(n: usize, left: &[S], right: &[S]) -> Vec<S> {
+    let rounds = folding_rounds(n);
+    assert_eq!(left.len(), rounds);
+    assert_eq!(right.len(), rounds);
+
+    let mut lengths = Vec::with_capacity(rounds);
+    let mut m = n;
+    for _ in 0..rounds {
+        lengths.push(m);
+        m = (m + 1) / 2;
+    }
+
+    // Unfold the products from the last round back to the first.
+    let mut products = vec![S::one()];
+    for j in (0..rounds).rev() {
+        let m = lengths[j];
+        let k = m / 2;
+        let mut unfolded = Vec::with_capacity(m);
+        unfolded.extend(products[..k].iter().map(|&p| p * left[j]));
+        unfolded.extend(products[..k].iter().map(|&p| p * right[j]));
+        if m % 2 == 1 {
+            unfolded.push(products[k]);
+        }
+        products = unfolded;
+    }
+    products
+}
+
 /// Given `data` with `len >= 32`, return the first 32 bytes.
 pub fn read32(data: &[u8]) -> [u8; 32] {
     let mut buf32 = [0u8; 32];
@@ -350,6 +387,23 @@ mod tests {
         assert_eq!(sum_of_powers_slow(&x, 6), Scalar::from(111111u64));
     }
 
+    #[test]
+    fn test_folding_products() {
+        let l: Vec<_> = (1..3u64).map(Scalar::from).collect();
+        let r: Vec<_> = (3..5u64).map(Scalar::from).collect();
+
+        // Round 0 folds [0] with [1] and carries [2]; round 1 folds the result.
+        assert_eq!(
+            folding_products(3, &l, &r),
+            vec![l[0] * l[1], r[0] * l[1], r[1]]
+        );
+        assert_eq!(
+            folding_products(4, &l, &r),
+            vec![l[0] * l[1], l[0] * r[1], r[0] * l[1], r[0] * r[1]]
+        );
+        assert_eq!(folding_products::<Scalar>(1, &[], &[]), vec![Scalar::one()]);
+    }
+
     #[test]
     fn vec_of_scalars_clear_on_drop() {
         let mut v = vec![Scalar::from(24u64), Scalar::from(42u64)];
@@ -0,0 +1,280 @@
+//! The `vector_commitment` module contains API for committing to
+//! vectors of scalars with Pedersen commitments.
+
+#![allow(non_snake_case)]
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::iter;
+use core::ops::{Add, Sub};
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::MultiscalarMul;
+
+use crate::errors::ProofError;
+use crate::generators::{GeneratorsChain, PedersenGens};
+
+/// Represents the bases for vector Pedersen commitments
+/// \\(C = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{b}, \mathbf{H} \rangle
+/// + v \cdot B + r \cdot \tilde{B}\\).
+///
+/// The vectors \\(\mathbf{G}\\) and \\(\mathbf{H}\\) are produced by
+/// applying SHAKE256 to a domain separation label, in the same way as
+/// the generators of [`BulletproofGens`](crate::BulletproofGens), and
+/// \\(B\\), \\(\tilde{B}\\) are the bases of `pc_gens`.
+///
+/// Commitments to a single vector \\(\mathbf{a}\\) with a value
+/// \\(v = \langle \mathbf{a}, \mathbf{b} \rangle\\) are the commitments of a
+/// [`LinearProof`](crate::LinearProof), and unblinded commitments to a
+/// pair of vectors with \\(v = \langle \mathbf{a}, \mathbf{b} \rangle\\)
+/// are the commitments of an [`InnerProductProof`](crate::InnerProductProof).
+#[derive(Clone)]
+pub struct VectorPedersenGens {
+    /// The maximum length of the committed vectors.
+    pub gens_capacity: usize,
+    /// Bases for the committed value and the blinding factor.
+    pub pc_gens: PedersenGens,
+    /// Domain separation label of the generators.
+    label: Vec<u8>,
+    /// Precomputed \\(\mathbf G\\) generators.
+    G_vec: Vec<RistrettoPoint>,
+    /// Precomputed \\(\mathbf H\\) generators.
+    H_vec: Vec<RistrettoPoint>,
+}
+
+impl VectorPedersenGens {
+    /// Create a new `VectorPedersenGens` object with the default
+    /// label, for vectors of up to `gens_capacity` elements.
+    pub fn new(gens_capacity: usize) -> Self {
+        VectorPedersenGens::with_label(b"VectorPedersenGens", gens_capacity)
+    }
+
+    /// Create a new `VectorPedersenGens` object for vectors of up to
+    /// `gens_capacity` elements, whose generators are determined by `label`.
+    ///
+    /// Generators with different labels are independent of each other and
+    /// of the generators of `BulletproofGens`.
+    pub fn with_label(label: &[u8], gens_capacity: usize) -> Self {
+        let mut gens = VectorPedersenGens {
+            gens_capacity: 0,
+            pc_gens: PedersenGens::default(),
+            label: label.to_vec(),
+            G_vec: Vec::new(),
+            H_vec: Vec::new(),
+        };
+        gens.increase_capacity(gens_capacity);
+        gens
+    }
+
+    /// Increases the generators' capacity to the amount specified.
+    /// If less than or equal to the current capacity, does nothing.
+    pub fn increase_capacity(&mut self, new_capacity: usize) {
+        if self.gens_capacity >= new_capacity {
+            return;
+        }
+
+        let mut label = Vec::with_capacity(self.label.len() + 16);
+        label.extend_from_slice(b"VectorPedersenG");
+        label.extend_from_slice(&self.label);
+        self.G_vec.extend(
+            &mut GeneratorsChain::<RistrettoPoint>::new(&label)
+                .fast_forward(self.gens_capacity)
+                .take(new_capacity - self.gens_capacity),
+        );
+
+        label[14] = b'H';
+        self.H_vec.extend(
+            &mut GeneratorsChain::<RistrettoPoint>::new(&label)
+                .fast_forward(self.gens_capacity)
+                .take(new_capacity - self.gens_capacity),
+        );
+        self.gens_capacity = new_capacity;
+    }
+
+    /// Return an iterator over the \\(\mathbf G\\) generators with given size `n`.
+    pub fn G(&self, n: usize) -> impl Iterator<Item = &RistrettoPoint> {
+        self.G_vec.iter().take(n)
+    }
+
+    /// Return an iterator over the \\(\mathbf H\\) generators with given size `n`.
+    pub fn H(&self, n: usize) -> impl Iterator<Item = &RistrettoPoint> {
+        self.H_vec.iter().take(n)
+    }
+
+    /// Creates a commitment
+    /// \\(C = \langle \mathbf{a}, \mathbf{G} \rangle + v \cdot B + r \cdot \tilde{B}\\)
+    /// to the vector `a`, the value `value` and the blinding factor `blinding`.
+    ///
+    /// Returns an error if `a` is longer than the capacity of the generators.
+    pub fn commit(
+        &self,
+        a: &[Scalar],
+        value: Scalar,
+        blinding: Scalar,
+    ) -> Result<VectorCommitment, ProofError> {
+        self.commit_pair(a, &[], value, blinding)
+    }
+
+    /// Creates a commitment
+    /// \\(C = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{b}, \mathbf{H} \rangle
+    /// + v \cdot B + r \cdot \tilde{B}\\)
+    /// to the vectors `a` and `b`, the value `value` and the blinding factor `blinding`.
+    ///
+    /// Returns an error if `a` or `b` is longer than the capacity of the generators.
+    pub fn commit_pair(
+        &self,
+        a: &[Scalar],
+        b: &[Scalar],
+        value: Scalar,
+        blinding: Scalar,
+    ) -> Result<VectorCommitment, ProofError> {
+        if a.len() > self.gens_capacity || b.len() > self.gens_capacity {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
+        Ok(VectorCommitment(RistrettoPoint::multiscalar_mul(
+            a.iter()
+                .chain(b.iter())
+                .chain(iter::once(&value))
+                .chain(iter::once(&blinding)),
+            self.G(a.len())
+                .chain(self.H(b.len()))
+                .chain(iter::once(&self.pc_gens.B))
+                .chain(iter::once(&self.pc_gens.B_blinding)),
+        )))
+    }
+
+    /// Checks that `commitment` opens to the vector `a`, the value `value`
+    /// and the blinding factor `blinding`.
+    pub fn verify_opening(
+        &self,
+        commitment: &VectorCommitment,
+        a: &[Scalar],
+        value: Scalar,
+        blinding: Scalar,
+    ) -> Result<(), ProofError> {
+        if self.commit(a, value, blinding)? == *commitment {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+}
+
+/// A vector Pedersen commitment, created by [`VectorPedersenGens::commit`]
+/// or [`VectorPedersenGens::commit_pair`].
+///
+/// Commitments are additively homomorphic: the sum of two commitments
+/// opens to the sums of their vectors, values and blinding factors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VectorCommitment(RistrettoPoint);
+
+impl VectorCommitment {
+    /// Returns the commitment as a Ristretto point.
+    pub fn as_point(&self) -> &RistrettoPoint {
+        &self.0
+    }
+
+    /// Compresses the commitment, for use in a transcript or proof verification.
+    pub fn compress(&self) -> CompressedRistretto {
+        self.0.compress()
+    }
+
+    /// Decompresses a commitment, returning `None` if the point is invalid.
+    pub fn decompress(commitment: &CompressedRistretto) -> Option<VectorCommitment> {
+        commitment.decompress().map(VectorCommitment)
+    }
+}
+
+impl From<RistrettoPoint> for VectorCommitment {
+    fn from(point: RistrettoPoint) -> Self {
+        VectorCommitment(point)
+    }
+}
+
+impl Add for VectorCommitment {
+    type Output = VectorCommitment;
+
+    fn add(self, rhs: VectorCommitment) -> VectorCommitment {
+        VectorCommitment(self.0 + rhs.0)
+    }
+}
+
+impl Sub for VectorCommitment {
+    type Output = VectorCommitment;
+
+    fn sub(self, rhs: VectorCommitment) -> VectorCommitment {
+        VectorCommitment(self.0 - rhs.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::generators::BulletproofGens;
+
+    #[test]
+    fn resizing_small_gens_matches_creating_bigger_gens() {
+        let gens = VectorPedersenGens::new(64);
+
+        let mut gens_resized = VectorPedersenGens::new(32);
+        gens_resized.increase_capacity(64);
+
+        assert_eq!(
+            gens.G(64).collect::<Vec<_>>(),
+            gens_resized.G(64).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            gens.H(64).collect::<Vec<_>>(),
+            gens_resized.H(64).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn labels_give_independent_generators() {
+        let gens = VectorPedersenGens::new(4);
+        let other_gens = VectorPedersenGens::with_label(b"other", 4);
+        let bp_gens = BulletproofGens::new(4, 1);
+
+        let G: Vec<_> = gens.G(4).collect();
+        assert_ne!(G, other_gens.G(4).collect::<Vec<_>>());
+        assert_ne!(G, gens.H(4).collect::<Vec<_>>());
+        assert_ne!(G, bp_gens.share(0).G(4).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn commitments_are_homomorphic() {
+        let gens = VectorPedersenGens::new(8);
+        let mut rng = rand::thread_rng();
+
+        let a: Vec<_> = (0..8).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..5).map(|_| Scalar::random(&mut rng)).collect();
+        let (v_a, v_b) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+        let (r_a, r_b) = (Scalar::random(&mut rng), Scalar::random(&mut rng));
+
+        let C_a = gens.commit(&a, v_a, r_a).unwrap();
+        let C_b = gens.commit(&b, v_b, r_b).unwrap();
+        assert!(gens.verify_opening(&C_a, &a, v_a, r_a).is_ok());
+        assert!(gens.verify_opening(&C_a, &a, v_a, r_b).is_err());
+
+        // The shorter vector is padded with zeros.
+        let sum: Vec<_> = (0..8)
+            .map(|i| a[i] + b.get(i).cloned().unwrap_or_else(Scalar::zero))
+            .collect();
+        assert!(gens
+            .verify_opening(&(C_a + C_b), &sum, v_a + v_b, r_a + r_b)
+            .is_ok());
+        assert!(gens
+            .verify_opening(&(C_a + C_b - C_b), &a, v_a, r_a)
+            .is_ok());
+
+        assert_eq!(
+            gens.commit(&[Scalar::one(); 9], v_a, r_a),
+            Err(ProofError::InvalidGeneratorsLength)
+        );
+    }
+}
@@ -0,0 +1,584 @@
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+use core::iter;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+use rand_core::{CryptoRng, RngCore};
+
+use crate::errors::ProofError;
+use crate::transcript::{ProofTranscript, TranscriptProtocol};
+use crate::util;
+
+/// The challenges \\([e\_{j}^{2}]\\), \\([e\_{j}^{-2}]\\), the scalars \\([s\_{i}]\\)
+/// and the final challenge \\(e\\) of a weighted inner-product proof.
+type VerificationScalars = (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Scalar);
+
+/// The generators of a [`WeightedInnerProductProof`].
+#[derive(Clone, Debug)]
+pub struct WippGens {
+    /// Generator for the weighted inner product
+    pub Q: RistrettoPoint,
+    /// Generator for the blinding factor
+    pub B_blinding: RistrettoPoint,
+    /// Generators for \\(\mathbf{a}\\)
+    pub G_vec: Vec<RistrettoPoint>,
+    /// Generators for \\(\mathbf{b}\\)
+    pub H_vec: Vec<RistrettoPoint>,
+}
+
+/// A zero-knowledge weighted inner-product proof.
+/// Protocol: Section 3 of [Bulletproofs+](https://eprint.iacr.org/2020/735.pdf)
+///
+/// Prove knowledge of vectors \\(\mathbf{a}, \mathbf{b}\\) and a blinding
+/// factor \\(\alpha\\) such that
+/// \\[
+///    P = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{b}, \mathbf{H} \rangle
+///      + (\mathbf{a} \odot\_y \mathbf{b}) \cdot Q + \alpha \cdot B\_{blinding},
+/// \\]
+/// where \\(\mathbf{a} \odot\_y \mathbf{b} = \sum\_{i=0}^{n-1} a\_i \cdot b\_i \cdot y^{i+1}\\)
+/// is the weighted inner product computed by [`weighted_inner_product`].
+#[derive(Clone, Debug)]
+pub struct WeightedInnerProductProof {
+    pub(crate) L_vec: Vec<CompressedRistretto>,
+    pub(crate) R_vec: Vec<CompressedRistretto>,
+    /// Commitment to the masks of the base case
+    pub(crate) A: CompressedRistretto,
+    /// Commitment to the weighted product of the masks
+    pub(crate) B: CompressedRistretto,
+    /// r', the masked base case `a`
+    pub(crate) r: Scalar,
+    /// s', the masked base case `b`
+    pub(crate) s: Scalar,
+    /// delta', the masked blinding factor
+    pub(crate) delta: Scalar,
+}
+
+impl WeightedInnerProductProof {
+    /// Create a weighted inner-product proof for the commitment
+    /// \\(P = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{b}, \mathbf{H} \rangle
+    /// + (\mathbf{a} \odot\_y \mathbf{b}) \cdot Q + \alpha \cdot B\_{blinding}\\).
+    ///
+    /// The weight `y` is expected to be a challenge of the parent protocol,
+    /// and the `transcript` is passed in as a parameter so that the
+    /// challenges depend on the *entire* transcript (including parent
+    /// protocols).
+    ///
+    /// The lengths of the vectors must all be the same, and must all be a power of 2.
+    pub fn create<T: RngCore + CryptoRng>(
+        transcript: &mut impl ProofTranscript,
+        rng: &mut T,
+        // Weight of the inner product
+        y: &Scalar,
+        gens: WippGens,
+        mut a_vec: Vec<Scalar>,
+        mut b_vec: Vec<Scalar>,
+        // Blinding factor of P
+        mut alpha: Scalar,
+    ) -> Result<WeightedInnerProductProof, ProofError> {
+        let WippGens {
+            Q,
+            B_blinding,
+            mut G_vec,
+            mut H_vec,
+        } = gens;
+        let mut n = a_vec.len();
+        // All of the input vectors must have the same length.
+        if G_vec.len() != n || H_vec.len() != n {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+        if b_vec.len() != n {
+            return Err(ProofError::InvalidInputLength);
+        }
+        // All of the input vectors must have a length that is a power of two.
+        if !n.is_power_of_two() {
+            return Err(ProofError::InvalidInputLength);
+        }
+
+        transcript.weighted_innerproduct_domain_sep(n as u64);
+
+        // Create slices G, H, a, b backed by their respective
+        // vectors.  This lets us reslice as we compress the lengths
+        // of the vectors in the main loop below.
+        let mut G = &mut G_vec[..];
+        let mut H = &mut H_vec[..];
+        let mut a = &mut a_vec[..];
+        let mut b = &mut b_vec[..];
+
+        let lg_n = n.next_power_of_two().trailing_zeros() as usize;
+        let mut L_vec = Vec::with_capacity(lg_n);
+        let mut R_vec = Vec::with_capacity(lg_n);
+
+        while n != 1 {
+            n /= 2;
+            let (a_L, a_R) = a.split_at_mut(n);
+            let (b_L, b_R) = b.split_at_mut(n);
+            let (G_L, G_R) = G.split_at_mut(n);
+            let (H_L, H_R) = H.split_at_mut(n);
+
+            let y_n = util::scalar_exp_vartime(y, n as u64);
+            let y_n_inv = y_n.invert();
+
+            let c_L = weighted_inner_product(a_L, b_R, y);
+            let c_R = y_n * weighted_inner_product(a_R, b_L, y);
+
+            let d_L = Scalar::random(rng);
+            let d_R = Scalar::random(rng);
+
+            // L = <a_L * y^{-n}, G_R> + <b_R, H_L> + c_L * Q + d_L * B_blinding
+            let L = RistrettoPoint::vartime_multiscalar_mul(
+                a_L.iter()
+                    .map(|a_L_i| a_L_i * y_n_inv)
+                    .chain(b_R.iter().cloned())
+                    .chain(iter::once(c_L))
+                    .chain(iter::once(d_L)),
+                G_R.iter()
+                    .chain(H_L.iter())
+                    .chain(iter::once(&Q))
+                    .chain(iter::once(&B_blinding)),
+            )
+            .compress();
+
+            // R = <a_R * y^n, G_L> + <b_L, H_R> + c_R * Q + d_R * B_blinding
+            let R = RistrettoPoint::vartime_multiscalar_mul(
+                a_R.iter()
+                    .map(|a_R_i| a_R_i * y_n)
+                    .chain(b_L.iter().cloned())
+                    .chain(iter::once(c_R))
+                    .chain(iter::once(d_R)),
+                G_L.iter()
+                    .chain(H_R.iter())
+                    .chain(iter::once(&Q))
+                    .chain(iter::once(&B_blinding)),
+            )
+            .compress();
+
+            L_vec.push(L);
+            R_vec.push(R);
+
+            transcript.append_point(b"L", &L);
+            transcript.append_point(b"R", &R);
+
+            let e: Scalar = transcript.challenge_scalar(b"e");
+            let e_inv = e.invert();
+
+            for i in 0..n {
+                a_L[i] = a_L[i] * e + a_R[i] * y_n * e_inv;
+                b_L[i] = b_L[i] * e_inv + b_R[i] * e;
+                G_L[i] = RistrettoPoint::vartime_multiscalar_mul(
+                    &[e_inv, e * y_n_inv],
+                    &[G_L[i], G_R[i]],
+                );
+                H_L[i] = RistrettoPoint::vartime_multiscalar_mul(&[e, e_inv], &[H_L[i], H_R[i]]);
+            }
+
+            alpha = d_L * e * e + alpha + d_R * e_inv * e_inv;
+
+            a = a_L;
+            b = b_L;
+            G = G_L;
+            H = H_L;
+        }
+
+        let r = Scalar::random(rng);
+        let s = Scalar::random(rng);
+        let delta = Scalar::random(rng);
+        let eta = Scalar::random(rng);
+
+        // A = r * G + s * H + y * (r * b + s * a) * Q + delta * B_blinding
+        let A = RistrettoPoint::vartime_multiscalar_mul(
+            &[r, s, y * (r * b[0] + s * a[0]), delta],
+            &[G[0], H[0], Q, B_blinding],
+        )
+        .compress();
+
+        // B = y * r * s * Q + eta * B_blinding
+        let B =
+            RistrettoPoint::vartime_multiscalar_mul(&[y * r * s, eta], &[Q, B_blinding]).compress();
+
+        transcript.append_point(b"A", &A);
+        transcript.append_point(b"B", &B);
+
+        let e: Scalar = transcript.challenge_scalar(b"e");
+
+        Ok(WeightedInnerProductProof {
+            L_vec,
+            R_vec,
+            A,
+            B,
+            r: r + a[0] * e,
+            s: s + b[0] * e,
+            delta: eta + delta * e + alpha * e * e,
+        })
+    }
+
+    /// Computes the vectors of verification scalars \\([e\_{j}^{2}]\\), \\([e\_{j}^{-2}]\\)
+    /// and \\([s\_{i}]\\), and the final challenge \\(e\\), for combined multiscalar
+    /// multiplication in a parent protocol.
+    ///
+    /// The folded generators are
+    /// \\(G' = \sum\_i (s\_i \cdot y^{-i}) \cdot G\_i\\) and
+    /// \\(H' = \sum\_i s\_{n-1-i} \cdot H\_i\\), and the proof is valid if
+    /// \\[
+    ///    e^2 \cdot \Big(P + \sum\_j (e\_j^2 \cdot L\_j + e\_j^{-2} \cdot R\_j)\Big) + e \cdot A + B
+    ///    = (r' e) \cdot G' + (s' e) \cdot H' + (r' y s') \cdot Q + \delta' \cdot B\_{blinding}.
+    /// \\]
+    ///
+    /// The verifier must provide the input length \\(n\\) explicitly to avoid unbounded allocation.
+    pub fn verification_scalars(
+        &self,
+        n: usize,
+        transcript: &mut impl ProofTranscript,
+    ) -> Result<VerificationScalars, ProofError> {
+        let lg_n = self.L_vec.len();
+        if lg_n >= 32 {
+            // 4 billion multiplications should be enough for anyone
+            // and this check prevents overflow in 1<<lg_n below.
+            return Err(ProofError::VerificationError);
+        }
+        if n != (1 << lg_n) {
+            return Err(ProofError::VerificationError);
+        }
+
+        transcript.weighted_innerproduct_domain_sep(n as u64);
+
+        // 1. Recompute e_k,...,e_1 and e based on the proof transcript
+
+        let mut challenges = Vec::with_capacity(lg_n);
+        for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
+            transcript.validate_and_append_point(b"L", L)?;
+            transcript.validate_and_append_point(b"R", R)?;
+            challenges.push(transcript.challenge_scalar(b"e"));
+        }
+
+        transcript.validate_and_append_point(b"A", &self.A)?;
+        transcript.validate_and_append_point(b"B", &self.B)?;
+        let e: Scalar = transcript.challenge_scalar(b"e");
+
+        // 2. Compute 1/(e_k...e_1) and 1/e_k, ..., 1/e_1
+
+        let mut challenges_inv = challenges.clone();
+        let allinv = Scalar::batch_invert(&mut challenges_inv);
+
+        // 3. Compute e_j^2 and (1/e_j)^2
+
+        for i in 0..lg_n {
+            challenges[i] = challenges[i] * challenges[i];
+            challenges_inv[i] = challenges_inv[i] * challenges_inv[i];
+        }
+        let challenges_sq = challenges;
+        let challenges_inv_sq = challenges_inv;
+
+        // 4. Compute s values inductively.
+
+        let mut s = Vec::with_capacity(n);
+        s.push(allinv);
+        for i in 1..n {
+            let lg_i = (32 - 1 - (i as u32).leading_zeros()) as usize;
+            let k = 1 << lg_i;
+            // The challenges are stored in "creation order" as [e_k,...,e_1],
+            // so e_{lg(i)+1} = is indexed by (lg_n-1) - lg_i
+            let e_lg_i_sq = challenges_sq[(lg_n - 1) - lg_i];
+            s.push(s[i - k] * e_lg_i_sq);
+        }
+
+        Ok((challenges_sq, challenges_inv_sq, s, e))
+    }
+
+    /// Verifies the proof for the commitment `P` with the weight `y`
+    /// and the generators `gens`.
+    ///
+    /// For efficiency, parent protocols should use `verification_scalars`
+    /// to combine this check with their own in a single multiscalar multiplication.
+    pub fn verify(
+        &self,
+        n: usize,
+        transcript: &mut impl ProofTranscript,
+        y: &Scalar,
+        P: &RistrettoPoint,
+        gens: &WippGens,
+    ) -> Result<(), ProofError> {
+        let (Q, B_blinding) = (&gens.Q, &gens.B_blinding);
+        let (G, H) = (&gens.G_vec, &gens.H_vec);
+        if G.len() != n || H.len() != n {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
+        let (e_sq, e_inv_sq, s, e) = self.verification_scalars(n, transcript)?;
+        let e_sq_final = e * e;
+
+        let y_inv = y.invert();
+        let g_scalars = s
+            .iter()
+            .zip(util::exp_iter(y_inv))
+            .map(|(s_i, y_inv_i)| -(self.r * e) * s_i * y_inv_i);
+
+        // 1/s[i] is s[!i], and !i runs from n-1 to 0 as i runs from 0 to n-1
+        let h_scalars = s.iter().rev().map(|s_i_inv| -(self.s * e) * s_i_inv);
+
+        let l_scalars = e_sq.iter().map(|e_j_sq| e_sq_final * e_j_sq);
+        let r_scalars = e_inv_sq.iter().map(|e_j_inv_sq| e_sq_final * e_j_inv_sq);
+
+        let points = self
+            .L_vec
+            .iter()
+            .chain(self.R_vec.iter())
+            .chain(iter::once(&self.A))
+            .chain(iter::once(&self.B))
+            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let check = RistrettoPoint::vartime_multiscalar_mul(
+            iter::once(e_sq_final)
+                .chain(l_scalars)
+                .chain(r_scalars)
+                .chain(iter::once(e))
+                .chain(iter::once(Scalar::one()))
+                .chain(g_scalars)
+                .chain(h_scalars)
+                .chain(iter::once(-(self.r * y * self.s)))
+                .chain(iter::once(-self.delta)),
+            iter::once(P)
+                .chain(points.iter())
+                .chain(G.iter())
+                .chain(H.iter())
+                .chain(iter::once(Q))
+                .chain(iter::once(B_blinding)),
+        );
+
+        if check.is_identity() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Returns the size in bytes required to serialize the weighted
+    /// inner product proof.
+    ///
+    /// For vectors of length `n` the proof size is
+    /// \\(32 \cdot (2\lg n+5)\\) bytes.
+    pub fn serialized_size(&self) -> usize {
+        (self.L_vec.len() * 2 + 5) * 32
+    }
+
+    /// Serializes the proof into a byte array of \\(2n+5\\) 32-byte elements.
+    /// The layout of the weighted inner product proof is:
+    /// * \\(n\\) pairs of compressed Ristretto points \\(L_0, R_0 \dots, L_{n-1}, R_{n-1}\\),
+    /// * two compressed Ristretto points \\(A, B\\),
+    /// * three scalars \\(r', s', \delta'\\).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        for (l, r) in self.L_vec.iter().zip(self.R_vec.iter()) {
+            buf.extend_from_slice(l.as_bytes());
+            buf.extend_from_slice(r.as_bytes());
+        }
+        buf.extend_from_slice(self.A.as_bytes());
+        buf.extend_from_slice(self.B.as_bytes());
+        buf.extend_from_slice(self.r.as_bytes());
+        buf.extend_from_slice(self.s.as_bytes());
+        buf.extend_from_slice(self.delta.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    /// Returns an error in the following cases:
+    /// * the slice does not have \\(2n+5\\) 32-byte elements,
+    /// * \\(n\\) is larger or equal to 32 (proof is too big),
+    /// * any of 3 scalars are not canonical scalars modulo Ristretto group order.
+    pub fn from_bytes(slice: &[u8]) -> Result<WeightedInnerProductProof, ProofError> {
+        let b = slice.len();
+        if b % 32 != 0 {
+            return Err(ProofError::FormatError);
+        }
+        let num_elements = b / 32;
+        if num_elements < 5 {
+            return Err(ProofError::FormatError);
+        }
+        if (num_elements - 5) % 2 != 0 {
+            return Err(ProofError::FormatError);
+        }
+        let lg_n = (num_elements - 5) / 2;
+        if lg_n >= 32 {
+            return Err(ProofError::FormatError);
+        }
+
+        use crate::util::read32;
+
+        let mut L_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
+        let mut R_vec: Vec<CompressedRistretto> = Vec::with_capacity(lg_n);
+        for i in 0..lg_n {
+            let pos = 2 * i * 32;
+            L_vec.push(CompressedRistretto(read32(&slice[pos..])));
+            R_vec.push(CompressedRistretto(read32(&slice[pos + 32..])));
+        }
+
+        let pos = 2 * lg_n * 32;
+        let A = CompressedRistretto(read32(&slice[pos..]));
+        let B = CompressedRistretto(read32(&slice[pos + 32..]));
+        let r = Scalar::from_canonical_bytes(read32(&slice[pos + 64..]))
+            .ok_or(ProofError::FormatError)?;
+        let s = Scalar::from_canonical_bytes(read32(&slice[pos + 96..]))
+            .ok_or(ProofError::FormatError)?;
+        let delta = Scalar::from_canonical_bytes(read32(&slice[pos + 128..]))
+            .ok_or(ProofError::FormatError)?;
+
+        Ok(WeightedInnerProductProof {
+            L_vec,
+            R_vec,
+            A,
+            B,
+            r,
+            s,
+            delta,
+        })
+    }
+}
+
+/// Computes a weighted inner product of two vectors
+/// \\[
+///    \mathbf{a} \odot\_y \mathbf{b} = \sum\_{i=0}^{n-1} a\_i \cdot b\_i \cdot y^{i+1}.
+/// \\]
+/// Panics if the lengths of \\(\mathbf{a}\\) and \\(\mathbf{b}\\) are not equal.
+pub fn weighted_inner_product(a: &[Scalar], b: &[Scalar], y: &Scalar) -> Scalar {
+    if a.len() != b.len() {
+        panic!("weighted_inner_product(a,b,y): lengths of vectors do not match");
+    }
+    let mut out = Scalar::zero();
+    let mut y_i = *y;
+    for i in 0..a.len() {
+        out += a[i] * b[i] * y_i;
+        y_i *= y;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use merlin::Transcript;
+
+    use crate::generators::{BulletproofGens, PedersenGens};
+
+    fn test_helper(n: usize) {
+        let mut rng = rand::thread_rng();
+
+        let bp_gens = BulletproofGens::new(n, 1);
+        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+        let H: Vec<RistrettoPoint> = bp_gens.share(0).H(n).cloned().collect();
+
+        let pc_gens = PedersenGens::default();
+        let Q = pc_gens.B;
+        let B_blinding = pc_gens.B_blinding;
+        let gens = WippGens {
+            Q,
+            B_blinding,
+            G_vec: G.clone(),
+            H_vec: H.clone(),
+        };
+
+        // y would be a challenge of the parent protocol, so we pick a random one.
+        let y = Scalar::random(&mut rng);
+
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let alpha = Scalar::random(&mut rng);
+        let c = weighted_inner_product(&a, &b, &y);
+
+        // P = <a, G> + <b, H> + (a ⊙_y b) * Q + alpha * B_blinding
+        let P = RistrettoPoint::vartime_multiscalar_mul(
+            a.iter()
+                .chain(b.iter())
+                .chain(iter::once(&c))
+                .chain(iter::once(&alpha)),
+            G.iter()
+                .chain(H.iter())
+                .chain(iter::once(&Q))
+                .chain(iter::once(&B_blinding)),
+        );
+
+        let mut prover_transcript = Transcript::new(b"wipptest");
+        let proof = WeightedInnerProductProof::create(
+            &mut prover_transcript,
+            &mut rng,
+            &y,
+            gens.clone(),
+            a,
+            b,
+            alpha,
+        )
+        .unwrap();
+
+        let mut verifier_transcript = Transcript::new(b"wipptest");
+        assert!(proof
+            .verify(n, &mut verifier_transcript, &y, &P, &gens)
+            .is_ok());
+
+        // A different weight must not verify
+        let mut verifier_transcript = Transcript::new(b"wipptest");
+        assert!(proof
+            .verify(n, &mut verifier_transcript, &(y + Scalar::one()), &P, &gens)
+            .is_err());
+
+        // Test serialization and deserialization
+        let serialized_proof = proof.to_bytes();
+        assert_eq!(proof.serialized_size(), serialized_proof.len());
+
+        let proof = WeightedInnerProductProof::from_bytes(&serialized_proof).unwrap();
+        let mut verifier_transcript = Transcript::new(b"wipptest");
+        assert!(proof
+            .verify(n, &mut verifier_transcript, &y, &P, &gens)
+            .is_ok());
+    }
+
+    #[test]
+    fn make_wipp_1() {
+        test_helper(1);
+    }
+
+    #[test]
+    fn make_wipp_2() {
+        test_helper(2);
+    }
+
+    #[test]
+    fn make_wipp_4() {
+        test_helper(4);
+    }
+
+    #[test]
+    fn make_wipp_32() {
+        test_helper(32);
+    }
+
+    #[test]
+    fn make_wipp_64() {
+        test_helper(64);
+    }
+
+    #[test]
+    fn test_weighted_inner_product() {
+        let a = vec![
+            Scalar::from(1u64),
+            Scalar::from(2u64),
+            Scalar::from(3u64),
+            Scalar::from(4u64),
+        ];
+        let b = vec![
+            Scalar::from(2u64),
+            Scalar::from(3u64),
+            Scalar::from(4u64),
+            Scalar::from(5u64),
+        ];
+        // 2*2 + 6*4 + 12*8 + 20*16 = 444
+        assert_eq!(
+            Scalar::from(444u64),
+            weighted_inner_product(&a, &b, &Scalar::from(2u64))
+        );
+    }
+}
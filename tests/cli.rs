@@ -0,0 +1,212 @@
+#![allow(non_snake_case)]
+//! Tests of the `bulletproofs` command-line tool.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+use curve25519_dalek::scalar::Scalar;
+use serde_json::Value;
+
+use bulletproofs::PedersenGens;
+
+fn bulletproofs(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_bulletproofs"))
+        .args(args)
+        .output()
+        .expect("the tool should run")
+}
+
+fn stdout(output: &Output) -> String {
+    assert!(
+        output.status.success(),
+        "the command failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// A file in the temporary directory, removed when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str, contents: &str) -> TempFile {
+        let path =
+            env::temp_dir().join(format!("bulletproofs-cli-{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        TempFile(path)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn commit_with_blinding() {
+    let blinding = Scalar::from(7u64);
+    let output = bulletproofs(&[
+        "commit",
+        "42",
+        "--blinding",
+        &hex::encode(blinding.as_bytes()),
+    ]);
+    let json: Value = serde_json::from_str(&stdout(&output)).unwrap();
+
+    let V = PedersenGens::default().commit(Scalar::from(42u64), blinding);
+    assert_eq!(json["commitment"], hex::encode(V.compress().as_bytes()));
+}
+
+#[test]
+fn prove_and_verify() {
+    let input = TempFile::new("prove-input.json", r#"{"values": [5, 200]}"#);
+    let proof = stdout(&bulletproofs(&["prove", input.path(), "--bits", "8"]));
+    let proof_file = TempFile::new("prove-output.json", &proof);
+
+    assert_eq!(
+        stdout(&bulletproofs(&["verify", proof_file.path()])),
+        "ok\n"
+    );
+
+    // The transcript label must match.
+    let output = bulletproofs(&["verify", proof_file.path(), "--label", "other"]);
+    assert!(!output.status.success());
+
+    // The proof and commitments can also be passed as hex.
+    let json: Value = serde_json::from_str(&proof).unwrap();
+    let proof = json["proof"].as_str().unwrap();
+    let commitments: Vec<&str> = json["commitments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|V| V.as_str().unwrap())
+        .collect();
+    let args = [
+        "verify",
+        "--proof",
+        proof,
+        "--commitment",
+        commitments[0],
+        "--commitment",
+        commitments[1],
+        "--bits",
+        "8",
+    ];
+    assert_eq!(stdout(&bulletproofs(&args)), "ok\n");
+
+    // Swapping the commitments breaks the proof.
+    let mut args = args;
+    args.swap(4, 6);
+    assert!(!bulletproofs(&args).status.success());
+}
+
+#[test]
+fn invalid_parameters() {
+    let input = TempFile::new("invalid-input.json", r#"{"values": [5, 200, 7]}"#);
+    let output = bulletproofs(&["prove", input.path(), "--bits", "8"]);
+    assert!(!output.status.success());
+
+    let input = TempFile::new("invalid-bits-input.json", r#"{"values": [5, 200]}"#);
+    let output = bulletproofs(&["prove", input.path(), "--bits", "12"]);
+    assert!(!output.status.success());
+
+    let proof = stdout(&bulletproofs(&["prove", input.path(), "--bits", "8"]));
+    let mut json: Value = serde_json::from_str(&proof).unwrap();
+
+    // The bit size is checked before the generators are created.
+    let args = [
+        "verify",
+        "--proof",
+        json["proof"].as_str().unwrap(),
+        "--commitment",
+        json["commitments"][0].as_str().unwrap(),
+        "--commitment",
+        json["commitments"][1].as_str().unwrap(),
+        "--bits",
+        "4000000000",
+    ];
+    let output = bulletproofs(&args);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid bit size"));
+
+    json["bits"] = 4000000000u64.into();
+    let proof_file = TempFile::new("invalid-bits-output.json", &json.to_string());
+    let output = bulletproofs(&["verify", proof_file.path()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("invalid bit size"));
+
+    // The number of commitments is bounded before the generators are created.
+    let mut args = vec!["verify", "--proof", json["proof"].as_str().unwrap()];
+    let commitment = json["commitments"][0].as_str().unwrap();
+    for _ in 0..128 {
+        args.extend_from_slice(&["--commitment", commitment]);
+    }
+    let output = bulletproofs(&args);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("must be at most 64"));
+}
+
+#[test]
+fn prove_from_hex_openings() {
+    let blinding = Scalar::from(11u64);
+    let opening = format!(
+        "{}{}",
+        hex::encode(1000u64.to_le_bytes()),
+        hex::encode(blinding.as_bytes())
+    );
+    let input = TempFile::new("prove-input.hex", &opening);
+    let proof = stdout(&bulletproofs(&["prove", input.path(), "--bits", "16"]));
+    let json: Value = serde_json::from_str(&proof).unwrap();
+
+    let V = PedersenGens::default().commit(Scalar::from(1000u64), blinding);
+    assert_eq!(json["commitments"][0], hex::encode(V.compress().as_bytes()));
+
+    let proof_file = TempFile::new("prove-hex-output.json", &proof);
+    assert_eq!(
+        stdout(&bulletproofs(&["verify", proof_file.path()])),
+        "ok\n"
+    );
+}
+
+#[test]
+fn inspect_proofs() {
+    let corpus = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/vectors/kat-v1.json");
+    let corpus: Value = serde_json::from_str(&fs::read_to_string(corpus).unwrap()).unwrap();
+    let vector = |name: &str| {
+        corpus["vectors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|v| v["name"] == name)
+            .unwrap()["proof"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+
+    let range_proof = vector("range_proof_aggregated_8x2");
+    let output = stdout(&bulletproofs(&["inspect", "range", &range_proof]));
+    assert!(output.starts_with("RangeProof, 544 bytes\n"));
+    assert!(output.contains(&format!("  A              {}\n", &range_proof[..64])));
+    assert!(output.contains("  ipp_proof      4 rounds\n"));
+
+    let r1cs_proof = vector("r1cs_proof_shuffle");
+    let r1cs_file = TempFile::new("inspect-r1cs.hex", &r1cs_proof);
+    let output = stdout(&bulletproofs(&["inspect", "r1cs", r1cs_file.path()]));
+    assert!(output.starts_with("R1CSProof, "));
+    assert!(output.contains("  two phases\n"));
+    assert!(output.contains("  S2             "));
+
+    // Malformed proofs are rejected.
+    let output = bulletproofs(&["inspect", "range", &range_proof[2..]]);
+    assert!(!output.status.success());
+    let output = bulletproofs(&["inspect", "r1cs", "00ff"]);
+    assert!(!output.status.success());
+}
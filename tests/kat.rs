@@ -0,0 +1,613 @@
+#![allow(non_snake_case)]
+//! Known-answer tests for the proof encodings and transcripts.
+//!
+//! The corpus in `tests/vectors/kat-v1.json` records, for every vector,
+//! the inputs, the generators, the challenges drawn from the transcript
+//! and the bytes of the proof.  The proofs are created deterministically,
+//! with a `ChaChaRng` seeded by the `rng_seed` of the vector, so any change
+//! to an encoding or to the transcript makes `kat_corpus_is_up_to_date` fail.
+//!
+//! After an intentional change, bump the version of the corpus and
+//! regenerate it with
+//!
+//! ```text
+//! BULLETPROOFS_UPDATE_KAT=1 cargo test --test kat
+//! ```
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+
+use merlin::Transcript;
+
+use serde_json::{json, Value};
+
+use bulletproofs::r1cs::{
+    ConstraintSystem, Prover, R1CSError, R1CSProof, RandomizableConstraintSystem,
+    RandomizedConstraintSystem, Variable, Verifier,
+};
+use bulletproofs::{
+    BulletproofGens, InnerProductProof, LinearProof, PedersenGens, RangeProof, RecordingTranscript,
+    TranscriptOp, VectorCommitment, VectorPedersenGens,
+};
+
+/// The version of the corpus, which is part of its file name.
+const KAT_VERSION: u64 = 1;
+
+/// Set to regenerate the corpus instead of checking it.
+const UPDATE_ENV: &str = "BULLETPROOFS_UPDATE_KAT";
+
+fn corpus_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("vectors")
+        .join(format!("kat-v{}.json", KAT_VERSION))
+}
+
+fn load_corpus() -> Value {
+    let json = fs::read_to_string(corpus_path()).expect("the corpus should be readable");
+    serde_json::from_str(&json).expect("the corpus should be valid JSON")
+}
+
+// Encoding helpers
+
+fn scalar_hex(s: &Scalar) -> String {
+    hex::encode(s.as_bytes())
+}
+
+fn point_hex(P: &RistrettoPoint) -> String {
+    hex::encode(P.compress().as_bytes())
+}
+
+fn pedersen_json(pc_gens: &PedersenGens) -> Value {
+    json!({
+        "B": point_hex(&pc_gens.B),
+        "B_blinding": point_hex(&pc_gens.B_blinding),
+    })
+}
+
+fn vector_gens_json(gens: &VectorPedersenGens, n: usize) -> Value {
+    json!({
+        "pedersen": pedersen_json(&gens.pc_gens),
+        "G": gens.G(n).map(point_hex).collect::<Vec<_>>(),
+        "H": gens.H(n).map(point_hex).collect::<Vec<_>>(),
+    })
+}
+
+/// The generators \(G\) and \(H\) of each party, rather than the
+/// serialization of `BulletproofGens`, so that the corpus does not depend
+/// on its format.
+fn bulletproof_gens_json(bp_gens: &BulletproofGens) -> Value {
+    let parties: Vec<_> = (0..bp_gens.party_capacity)
+        .map(|j| bp_gens.share(j))
+        .collect();
+    json!({
+        "G": parties
+            .iter()
+            .map(|share| share.G(bp_gens.gens_capacity).map(point_hex).collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+        "H": parties
+            .iter()
+            .map(|share| share.H(bp_gens.gens_capacity).map(point_hex).collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+    })
+}
+
+/// The challenges drawn from a transcript, in order.
+fn challenges_json(log: &[TranscriptOp]) -> Value {
+    log.iter()
+        .filter_map(|op| match op {
+            TranscriptOp::Challenge { label, bytes } => Some(json!({
+                "label": String::from_utf8_lossy(label),
+                "bytes": hex::encode(bytes),
+            })),
+            TranscriptOp::Append { .. } => None,
+        })
+        .collect()
+}
+
+// Decoding helpers
+
+fn bytes_of(v: &Value) -> Vec<u8> {
+    hex::decode(v.as_str().expect("a hex string")).expect("valid hex")
+}
+
+fn scalar_of(v: &Value) -> Scalar {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(&bytes_of(v));
+    Scalar::from_canonical_bytes(bytes).expect("a canonical scalar")
+}
+
+fn compressed_of(v: &Value) -> CompressedRistretto {
+    CompressedRistretto::from_slice(&bytes_of(v))
+}
+
+fn point_of(v: &Value) -> RistrettoPoint {
+    compressed_of(v).decompress().expect("a valid point")
+}
+
+/// Creates the generators of the vector with `BulletproofGens::new`, and
+/// checks that they are those of the vector.
+fn bulletproof_gens_of(v: &Value) -> BulletproofGens {
+    let G = v["G"].as_array().expect("an array of parties");
+    let party_capacity = G.len();
+    let gens_capacity = G[0].as_array().expect("an array of points").len();
+    let bp_gens = BulletproofGens::new(gens_capacity, party_capacity);
+    assert_eq!(&bulletproof_gens_json(&bp_gens), v);
+    bp_gens
+}
+
+fn u64_of(v: &Value) -> u64 {
+    v.as_u64().expect("an integer")
+}
+
+fn seed_of(vector: &Value) -> [u8; 32] {
+    let mut seed = [0u8; 32];
+    seed.copy_from_slice(&bytes_of(&vector["rng_seed"]));
+    seed
+}
+
+/// The label of the transcript of every proof system.
+fn transcript_label(proof_system: &str) -> &'static [u8] {
+    match proof_system {
+        "range_proof" => b"bulletproofs-kat range_proof",
+        "inner_product_proof" => b"bulletproofs-kat inner_product_proof",
+        "linear_proof" => b"bulletproofs-kat linear_proof",
+        "r1cs_proof" => b"bulletproofs-kat r1cs_proof",
+        _ => panic!("unknown proof system {}", proof_system),
+    }
+}
+
+fn label_of(vector: &Value) -> &'static [u8] {
+    let label = transcript_label(vector["proof_system"].as_str().unwrap());
+    assert_eq!(
+        vector["transcript_label"].as_str().unwrap().as_bytes(),
+        label
+    );
+    label
+}
+
+/// Checks that the commitments of the vector open to its values and blindings.
+fn commitments_open(vector: &Value, pc_gens: &PedersenGens) -> bool {
+    let values = vector["inputs"]["values"].as_array().unwrap();
+    let blindings = vector["inputs"]["blindings"].as_array().unwrap();
+    let commitments = vector["commitments"].as_array().unwrap();
+    values.len() == commitments.len()
+        && values
+            .iter()
+            .zip(blindings.iter())
+            .zip(commitments.iter())
+            .all(|((v, r), V)| pc_gens.commit(Scalar::from(u64_of(v)), scalar_of(r)) == point_of(V))
+}
+
+// Range proofs
+
+fn range_proof_vector(name: &str, seed: [u8; 32], values: &[u64], n: usize) -> Value {
+    let label = transcript_label("range_proof");
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, values.len());
+
+    let mut rng = ChaChaRng::from_seed(seed);
+    let blindings: Vec<Scalar> = values.iter().map(|_| Scalar::random(&mut rng)).collect();
+
+    let mut transcript = RecordingTranscript::new(Transcript::new(label));
+    let (proof, commitments) = RangeProof::prove_multiple_with_rng(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        values,
+        &blindings,
+        n,
+        &mut rng,
+    )
+    .unwrap();
+
+    json!({
+        "name": name,
+        "proof_system": "range_proof",
+        "transcript_label": String::from_utf8_lossy(label),
+        "rng_seed": hex::encode(seed),
+        "generators": {
+            "pedersen": pedersen_json(&pc_gens),
+            "bulletproofs": bulletproof_gens_json(&bp_gens),
+        },
+        "inputs": {
+            "n": n,
+            "values": values,
+            "blindings": blindings.iter().map(scalar_hex).collect::<Vec<_>>(),
+        },
+        "commitments": commitments.iter().map(|V| hex::encode(V.as_bytes())).collect::<Vec<_>>(),
+        "challenges": challenges_json(transcript.log()),
+        "proof": hex::encode(proof.to_bytes()),
+    })
+}
+
+fn verify_range_proof_vector(vector: &Value) -> bool {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = bulletproof_gens_of(&vector["generators"]["bulletproofs"]);
+    let n = u64_of(&vector["inputs"]["n"]) as usize;
+    let commitments: Vec<CompressedRistretto> = vector["commitments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(compressed_of)
+        .collect();
+
+    if !commitments_open(vector, &pc_gens) {
+        return false;
+    }
+
+    let proof = RangeProof::from_bytes(&bytes_of(&vector["proof"])).unwrap();
+    let mut transcript = Transcript::new(label_of(vector));
+    proof
+        .verify_multiple_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &commitments,
+            n,
+            &mut ChaChaRng::from_seed(seed_of(vector)),
+        )
+        .is_ok()
+}
+
+// Inner-product proofs
+
+fn inner_product_proof_vector(name: &str, a: &[u64], b: &[u64]) -> Value {
+    let label = transcript_label("inner_product_proof");
+    let n = a.len();
+    let gens = VectorPedersenGens::new(n);
+    let a_vec: Vec<Scalar> = a.iter().map(|x| Scalar::from(*x)).collect();
+    let b_vec: Vec<Scalar> = b.iter().map(|x| Scalar::from(*x)).collect();
+
+    let mut transcript = RecordingTranscript::new(Transcript::new(label));
+    let (proof, P) =
+        InnerProductProof::prove_committed(&mut transcript, &gens, a_vec, b_vec).unwrap();
+
+    json!({
+        "name": name,
+        "proof_system": "inner_product_proof",
+        "transcript_label": String::from_utf8_lossy(label),
+        "generators": vector_gens_json(&gens, n),
+        "inputs": {
+            "a": a,
+            "b": b,
+        },
+        "commitments": [point_hex(P.as_point())],
+        "challenges": challenges_json(transcript.log()),
+        "proof": hex::encode(proof.to_bytes()),
+    })
+}
+
+fn verify_inner_product_proof_vector(vector: &Value) -> bool {
+    let n = vector["inputs"]["a"].as_array().unwrap().len();
+    let gens = VectorPedersenGens::new(n);
+    let P = VectorCommitment::from(point_of(&vector["commitments"][0]));
+
+    let proof = InnerProductProof::from_bytes(&bytes_of(&vector["proof"])).unwrap();
+    let mut transcript = Transcript::new(label_of(vector));
+    proof
+        .verify_committed(n, &mut transcript, &gens, &P)
+        .is_ok()
+}
+
+// Linear proofs
+
+fn linear_proof_vector(name: &str, seed: [u8; 32], a: &[u64], b: &[u64]) -> Value {
+    let label = transcript_label("linear_proof");
+    let n = a.len();
+    let gens = VectorPedersenGens::new(n);
+    let a_vec: Vec<Scalar> = a.iter().map(|x| Scalar::from(*x)).collect();
+    let b_vec: Vec<Scalar> = b.iter().map(|x| Scalar::from(*x)).collect();
+
+    let mut rng = ChaChaRng::from_seed(seed);
+    let blinding = Scalar::random(&mut rng);
+
+    let mut transcript = RecordingTranscript::new(Transcript::new(label));
+    let (proof, C) =
+        LinearProof::prove_committed(&mut transcript, &mut rng, &gens, a_vec, blinding, b_vec)
+            .unwrap();
+
+    json!({
+        "name": name,
+        "proof_system": "linear_proof",
+        "transcript_label": String::from_utf8_lossy(label),
+        "rng_seed": hex::encode(seed),
+        "generators": vector_gens_json(&gens, n),
+        "inputs": {
+            "a": a,
+            "b": b,
+            "blinding": scalar_hex(&blinding),
+        },
+        "commitments": [point_hex(C.as_point())],
+        "challenges": challenges_json(transcript.log()),
+        "proof": hex::encode(proof.to_bytes()),
+    })
+}
+
+fn verify_linear_proof_vector(vector: &Value) -> bool {
+    let b_vec: Vec<Scalar> = vector["inputs"]["b"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|x| Scalar::from(u64_of(x)))
+        .collect();
+    let gens = VectorPedersenGens::new(b_vec.len());
+    let C = VectorCommitment::from(point_of(&vector["commitments"][0]));
+
+    let proof = LinearProof::from_bytes(&bytes_of(&vector["proof"])).unwrap();
+    let mut transcript = Transcript::new(label_of(vector));
+    proof
+        .verify_committed(&mut transcript, &gens, &C, b_vec)
+        .is_ok()
+}
+
+// Constraint system proofs
+
+/// Constrains the committed `x` and `y` to be permutations of each other,
+/// with a randomized phase so that both proof layouts are covered.
+fn shuffle_gadget<CS: RandomizableConstraintSystem>(
+    cs: &mut CS,
+    x: Vec<Variable>,
+    y: Vec<Variable>,
+) -> Result<(), R1CSError> {
+    cs.specify_randomized_constraints(move |cs| {
+        let z = cs.challenge_scalar(b"shuffle challenge");
+        let (_, _, x_prod) = cs.multiply(x[0] - z, x[1] - z);
+        let (_, _, y_prod) = cs.multiply(y[0] - z, y[1] - z);
+        cs.constrain(x_prod - y_prod);
+        Ok(())
+    })
+}
+
+/// Constrains the committed `a`, `b` and `c` to satisfy `a * b = c`.
+fn multiply_gadget<CS: ConstraintSystem>(cs: &mut CS, vars: &[Variable]) {
+    let (_, _, o) = cs.multiply(vars[0].into(), vars[1].into());
+    cs.constrain(o - vars[2]);
+}
+
+/// Applies the gadget named by `gadget` to the committed variables.
+fn r1cs_gadget<CS: RandomizableConstraintSystem>(
+    cs: &mut CS,
+    gadget: &str,
+    vars: Vec<Variable>,
+) -> Result<(), R1CSError> {
+    match gadget {
+        "multiply" => {
+            multiply_gadget(cs, &vars);
+            Ok(())
+        }
+        "shuffle" => {
+            let y = vars[2..].to_vec();
+            let x = vars[..2].to_vec();
+            shuffle_gadget(cs, x, y)
+        }
+        _ => panic!("unknown gadget {}", gadget),
+    }
+}
+
+fn r1cs_proof_vector(name: &str, seed: [u8; 32], gadget: &str, values: &[u64]) -> Value {
+    let label = transcript_label("r1cs_proof");
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+
+    let mut rng = ChaChaRng::from_seed(seed);
+    let blindings: Vec<Scalar> = values.iter().map(|_| Scalar::random(&mut rng)).collect();
+
+    let mut transcript = RecordingTranscript::new(Transcript::new(label));
+    let mut prover = Prover::new(&pc_gens, &mut transcript);
+    let (commitments, vars): (Vec<_>, Vec<_>) = values
+        .iter()
+        .zip(blindings.iter())
+        .map(|(v, r)| prover.commit(Scalar::from(*v), *r))
+        .unzip();
+    r1cs_gadget(&mut prover, gadget, vars).unwrap();
+    let proof = prover.prove_with_rng(&bp_gens, &mut rng).unwrap();
+
+    json!({
+        "name": name,
+        "proof_system": "r1cs_proof",
+        "transcript_label": String::from_utf8_lossy(label),
+        "rng_seed": hex::encode(seed),
+        "generators": {
+            "pedersen": pedersen_json(&pc_gens),
+            "bulletproofs": bulletproof_gens_json(&bp_gens),
+        },
+        "inputs": {
+            "gadget": gadget,
+            "values": values,
+            "blindings": blindings.iter().map(scalar_hex).collect::<Vec<_>>(),
+        },
+        "commitments": commitments.iter().map(|V| hex::encode(V.as_bytes())).collect::<Vec<_>>(),
+        "challenges": challenges_json(transcript.log()),
+        "proof": hex::encode(proof.to_bytes()),
+    })
+}
+
+fn verify_r1cs_proof_vector(vector: &Value) -> bool {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = bulletproof_gens_of(&vector["generators"]["bulletproofs"]);
+
+    if !commitments_open(vector, &pc_gens) {
+        return false;
+    }
+
+    let proof = R1CSProof::from_bytes(&bytes_of(&vector["proof"])).unwrap();
+    let mut transcript = Transcript::new(label_of(vector));
+    let mut verifier: Verifier = Verifier::new(&mut transcript);
+    let vars: Vec<Variable> = vector["commitments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|V| verifier.commit(compressed_of(V)))
+        .collect();
+    let gadget = vector["inputs"]["gadget"].as_str().unwrap();
+    if r1cs_gadget(&mut verifier, gadget, vars).is_err() {
+        return false;
+    }
+    verifier
+        .verify_with_rng(
+            &proof,
+            &pc_gens,
+            &bp_gens,
+            &mut ChaChaRng::from_seed(seed_of(vector)),
+        )
+        .is_ok()
+}
+
+// The corpus
+
+fn generate_corpus() -> Value {
+    json!({
+        "version": KAT_VERSION,
+        "group": "ristretto255",
+        "transcript": "merlin",
+        "rng": "ChaCha20 from rand_chacha 0.2, seeded with rng_seed",
+        "vectors": [
+            range_proof_vector("range_proof_single_8", [1u8; 32], &[200], 8),
+            range_proof_vector("range_proof_aggregated_8x2", [2u8; 32], &[3, 255], 8),
+            range_proof_vector("range_proof_single_32", [3u8; 32], &[1037578891], 32),
+            inner_product_proof_vector("inner_product_proof_4", &[1, 2, 3, 4], &[5, 6, 7, 8]),
+            inner_product_proof_vector("inner_product_proof_3", &[9, 10, 11], &[12, 13, 14]),
+            linear_proof_vector("linear_proof_4", [4u8; 32], &[1, 2, 3, 4], &[5, 6, 7, 8]),
+            r1cs_proof_vector("r1cs_proof_multiply", [5u8; 32], "multiply", &[3, 5, 15]),
+            r1cs_proof_vector("r1cs_proof_shuffle", [6u8; 32], "shuffle", &[3, 7, 7, 3]),
+        ],
+    })
+}
+
+/// Returns the path to the first difference between `expected` and `actual`.
+fn first_difference(path: String, expected: &Value, actual: &Value) -> Option<String> {
+    match (expected, actual) {
+        (Value::Object(e), Value::Object(a)) => {
+            for key in e.keys().chain(a.keys()) {
+                let (e_k, a_k) = (e.get(key), a.get(key));
+                match (e_k, a_k) {
+                    (Some(e_k), Some(a_k)) => {
+                        let path = format!("{}.{}", path, key);
+                        if let Some(diff) = first_difference(path, e_k, a_k) {
+                            return Some(diff);
+                        }
+                    }
+                    _ => return Some(format!("{}.{}", path, key)),
+                }
+            }
+            None
+        }
+        (Value::Array(e), Value::Array(a)) => {
+            for (i, (e_i, a_i)) in e.iter().zip(a.iter()).enumerate() {
+                if let Some(diff) = first_difference(format!("{}[{}]", path, i), e_i, a_i) {
+                    return Some(diff);
+                }
+            }
+            if e.len() != a.len() {
+                return Some(format!("{}.len()", path));
+            }
+            None
+        }
+        _ if expected == actual => None,
+        _ => Some(path),
+    }
+}
+
+#[test]
+fn kat_generation_is_deterministic() {
+    assert_eq!(generate_corpus(), generate_corpus());
+}
+
+#[test]
+fn kat_corpus_is_up_to_date() {
+    let corpus = generate_corpus();
+
+    if env::var_os(UPDATE_ENV).is_some() {
+        let path = corpus_path();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let json = serde_json::to_string_pretty(&corpus).unwrap();
+        fs::write(&path, json + "\n").unwrap();
+        return;
+    }
+
+    if let Some(diff) = first_difference("corpus".into(), &load_corpus(), &corpus) {
+        panic!(
+            "the proofs differ from the known answers at `{}`; if the change \
+             is intentional, bump KAT_VERSION and set {} to regenerate the corpus",
+            diff, UPDATE_ENV
+        );
+    }
+}
+
+#[test]
+fn kat_corpus_proofs_verify() {
+    let corpus = load_corpus();
+    assert_eq!(u64_of(&corpus["version"]), KAT_VERSION);
+
+    for vector in corpus["vectors"].as_array().unwrap() {
+        let verified = match vector["proof_system"].as_str().unwrap() {
+            "range_proof" => verify_range_proof_vector(vector),
+            "inner_product_proof" => verify_inner_product_proof_vector(vector),
+            "linear_proof" => verify_linear_proof_vector(vector),
+            "r1cs_proof" => verify_r1cs_proof_vector(vector),
+            system => panic!("unknown proof system {}", system),
+        };
+        assert!(verified, "{} does not verify", vector["name"]);
+    }
+}
+
+#[test]
+fn kat_corpus_rejects_tampered_proofs() {
+    let corpus = load_corpus();
+
+    for vector in corpus["vectors"].as_array().unwrap() {
+        let mut tampered = vector.clone();
+        let mut proof = bytes_of(&vector["proof"]);
+        // Flip a bit of the last scalar, which every encoding ends with.
+        let last = proof.len() - 32;
+        proof[last] ^= 1;
+        tampered["proof"] = Value::String(hex::encode(proof));
+
+        let verified = match vector["proof_system"].as_str().unwrap() {
+            "range_proof" => verify_range_proof_vector(&tampered),
+            "inner_product_proof" => verify_inner_product_proof_vector(&tampered),
+            "linear_proof" => verify_linear_proof_vector(&tampered),
+            "r1cs_proof" => verify_r1cs_proof_vector(&tampered),
+            system => panic!("unknown proof system {}", system),
+        };
+        assert!(!verified, "tampered {} verifies", vector["name"]);
+    }
+}
+
+#[test]
+fn kat_verifier_challenges_match() {
+    let corpus = load_corpus();
+    let vector = &corpus["vectors"][0];
+
+    let mut transcript = RecordingTranscript::new(Transcript::new(label_of(vector)));
+    let pc_gens = PedersenGens::default();
+    let bp_gens = bulletproof_gens_of(&vector["generators"]["bulletproofs"]);
+    let commitments: Vec<CompressedRistretto> = vector["commitments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(compressed_of)
+        .collect();
+    let proof = RangeProof::from_bytes(&bytes_of(&vector["proof"])).unwrap();
+    proof
+        .verify_multiple_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &commitments,
+            u64_of(&vector["inputs"]["n"]) as usize,
+            &mut ChaChaRng::from_seed(seed_of(vector)),
+        )
+        .unwrap();
+
+    // The verifier draws the same challenges as the prover did.
+    assert_eq!(challenges_json(transcript.log()), vector["challenges"]);
+}
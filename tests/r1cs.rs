@@ -4,14 +4,20 @@ extern crate bulletproofs;
 extern crate curve25519_dalek;
 extern crate merlin;
 extern crate rand;
+extern crate rand_chacha;
 
 use bulletproofs::r1cs::*;
-use bulletproofs::{BulletproofGens, PedersenGens};
+use bulletproofs::{
+    first_divergence, BatchVerifier, BulletproofGens, CurvePoint, CurveScalar, Keccak256Transcript,
+    PedersenGens, PedersenGensTable, RangeProof, RecordingTranscript,
+};
 use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
 
 // Shuffle gadget (documented in markdown file)
 
@@ -349,6 +355,194 @@ fn example_gadget_roundtrip_serialization_helper(
     example_gadget_verify(&pc_gens, &bp_gens, c2, proof, commitments)
 }
 
+#[test]
+fn example_gadget_domain_test() {
+    let pc_gens = PedersenGens::from_label(b"R1CSExampleGadget");
+    let bp_gens = BulletproofGens::with_domain(b"R1CSExampleGadget", 128, 1);
+
+    let (proof, commitments) = example_gadget_proof(&pc_gens, &bp_gens, 3, 4, 6, 1, 40, 9).unwrap();
+    assert!(
+        example_gadget_verify(&pc_gens, &bp_gens, 9, proof.clone(), commitments.clone()).is_ok()
+    );
+
+    // The proof does not verify against the generators of another domain.
+    let other_bp_gens = BulletproofGens::new(128, 1);
+    assert!(example_gadget_verify(&pc_gens, &other_bp_gens, 9, proof, commitments).is_err());
+}
+
+#[test]
+fn example_gadget_table_test() {
+    let pc_gens = PedersenGens::default();
+    let pc_table = PedersenGensTable::new(&pc_gens);
+    let bp_gens = BulletproofGens::new(128, 1);
+
+    let mut transcript = Transcript::new(b"R1CSExampleGadget");
+    let mut prover = Prover::new_with_table(&pc_table, &mut transcript);
+
+    let (commitments, vars): (Vec<_>, Vec<_>) = [3u64, 4, 6, 1, 40]
+        .iter()
+        .map(|x| prover.commit(Scalar::from(*x), Scalar::random(&mut thread_rng())))
+        .unzip();
+    example_gadget(
+        &mut prover,
+        vars[0].into(),
+        vars[1].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(9u64).into(),
+    );
+    let proof = prover.prove(&bp_gens).unwrap();
+
+    // The proof verifies with the bases of the tables.
+    assert!(example_gadget_verify(&pc_gens, &bp_gens, 9, proof, commitments).is_ok());
+}
+
+#[test]
+fn example_gadget_keccak256_transcript_test() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+
+    let mut transcript = Keccak256Transcript::new(b"R1CSExampleGadget");
+    let mut prover = Prover::new(&pc_gens, &mut transcript);
+
+    let (commitments, vars): (Vec<_>, Vec<_>) = [3u64, 4, 6, 1, 40]
+        .iter()
+        .map(|x| prover.commit(Scalar::from(*x), Scalar::random(&mut thread_rng())))
+        .unzip();
+    example_gadget(
+        &mut prover,
+        vars[0].into(),
+        vars[1].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(9u64).into(),
+    );
+    let proof = prover.prove(&bp_gens).unwrap();
+
+    let mut transcript = Keccak256Transcript::new(b"R1CSExampleGadget");
+    let mut verifier = Verifier::new(&mut transcript);
+    let vars: Vec<_> = commitments.iter().map(|V| verifier.commit(*V)).collect();
+    example_gadget(
+        &mut verifier,
+        vars[0].into(),
+        vars[1].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(9u64).into(),
+    );
+    assert!(verifier.verify(&proof, &pc_gens, &bp_gens).is_ok());
+
+    // The challenges of a Merlin transcript differ.
+    assert!(example_gadget_verify(&pc_gens, &bp_gens, 9, proof, commitments).is_err());
+}
+
+#[test]
+fn example_gadget_transcript_divergence_test() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+
+    let mut prover_transcript = RecordingTranscript::new(Transcript::new(b"R1CSExampleGadget"));
+    let mut prover = Prover::new(&pc_gens, &mut prover_transcript);
+    let (commitments, vars): (Vec<_>, Vec<_>) = [3u64, 4, 6, 1, 40]
+        .iter()
+        .map(|x| prover.commit(Scalar::from(*x), Scalar::random(&mut thread_rng())))
+        .unzip();
+    example_gadget(
+        &mut prover,
+        vars[0].into(),
+        vars[1].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(9u64).into(),
+    );
+    let proof = prover.prove(&bp_gens).unwrap();
+
+    // The verifier commits the first two variables in the wrong order.
+    let mut verifier_transcript = RecordingTranscript::new(Transcript::new(b"R1CSExampleGadget"));
+    let mut verifier = Verifier::new(&mut verifier_transcript);
+    let order = [1, 0, 2, 3, 4];
+    let vars: Vec<_> = order
+        .iter()
+        .map(|&i| verifier.commit(commitments[i]))
+        .collect();
+    example_gadget(
+        &mut verifier,
+        vars[1].into(),
+        vars[0].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(9u64).into(),
+    );
+    assert!(verifier.verify(&proof, &pc_gens, &bp_gens).is_err());
+
+    // The logs diverge at the first commitment, after the domain separator.
+    let divergence = first_divergence(prover_transcript.log(), verifier_transcript.log()).unwrap();
+    assert_eq!(divergence.index, 1);
+    assert_eq!(divergence.prover.unwrap().label(), b"V");
+    assert_eq!(divergence.verifier.unwrap().label(), b"V");
+}
+
+fn example_gadget_seeded_proof(seed: [u8; 32]) -> (R1CSProof, Vec<CompressedRistretto>) {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+    let mut rng = ChaChaRng::from_seed(seed);
+
+    let mut transcript = Transcript::new(b"R1CSExampleGadget");
+    let mut prover = Prover::new(&pc_gens, &mut transcript);
+    let (commitments, vars): (Vec<_>, Vec<_>) = [3u64, 4, 6, 1, 40]
+        .iter()
+        .map(|x| prover.commit(Scalar::from(*x), Scalar::random(&mut rng)))
+        .unzip();
+    example_gadget(
+        &mut prover,
+        vars[0].into(),
+        vars[1].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(9u64).into(),
+    );
+    let proof = prover.prove_with_rng(&bp_gens, &mut rng).unwrap();
+
+    (proof, commitments)
+}
+
+#[test]
+fn example_gadget_deterministic_test() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+
+    // The same seed gives the same proof, byte for byte.
+    let (proof, commitments) = example_gadget_seeded_proof([5u8; 32]);
+    let (again, _) = example_gadget_seeded_proof([5u8; 32]);
+    assert_eq!(proof.to_bytes(), again.to_bytes());
+
+    let (other, _) = example_gadget_seeded_proof([6u8; 32]);
+    assert_ne!(proof.to_bytes(), other.to_bytes());
+
+    let mut transcript = Transcript::new(b"R1CSExampleGadget");
+    let mut verifier = Verifier::new(&mut transcript);
+    let vars: Vec<_> = commitments.iter().map(|V| verifier.commit(*V)).collect();
+    example_gadget(
+        &mut verifier,
+        vars[0].into(),
+        vars[1].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(9u64).into(),
+    );
+    let mut rng = ChaChaRng::from_seed([7u8; 32]);
+    assert!(verifier
+        .verify_with_rng(&proof, &pc_gens, &bp_gens, &mut rng)
+        .is_ok());
+}
+
 #[test]
 fn example_gadget_test() {
     // (3 + 4) * (6 + 1) = (40 + 9)
@@ -365,6 +559,80 @@ fn example_gadget_serialization_test() {
     assert!(example_gadget_roundtrip_serialization_helper(3, 4, 6, 1, 40, 10).is_err());
 }
 
+// Verifier logic, deferring the final check to a batch
+fn example_gadget_batch_verify<T: rand::RngCore + rand::CryptoRng>(
+    batch: &mut BatchVerifier<T>,
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    c2: u64,
+    proof: &R1CSProof,
+    commitments: &[CompressedRistretto],
+) -> Result<(), R1CSError> {
+    let mut transcript = Transcript::new(b"R1CSExampleGadget");
+    let mut verifier = Verifier::new(&mut transcript);
+    let vars: Vec<_> = commitments.iter().map(|V| verifier.commit(*V)).collect();
+
+    example_gadget(
+        &mut verifier,
+        vars[0].into(),
+        vars[1].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(c2).into(),
+    );
+
+    verifier.batch_verify(batch, proof, pc_gens, bp_gens)
+}
+
+#[test]
+fn example_gadget_batch_test() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+
+    // (3 + 4) * (6 + 1) = (40 + 9) and (1 + 2) * (3 + 4) = (20 + 1)
+    let (proof1, commitments1) =
+        example_gadget_proof(&pc_gens, &bp_gens, 3, 4, 6, 1, 40, 9).unwrap();
+    let (proof2, commitments2) =
+        example_gadget_proof(&pc_gens, &bp_gens, 1, 2, 3, 4, 20, 1).unwrap();
+
+    let (range_proof, V) = RangeProof::prove_single(
+        &bp_gens,
+        &pc_gens,
+        &mut Transcript::new(b"R1CSExampleRangeProof"),
+        1037,
+        &Scalar::random(&mut thread_rng()),
+        32,
+    )
+    .unwrap();
+
+    let batch_helper = |c2_of_proof2: u64| -> Result<(), R1CSError> {
+        let mut batch = BatchVerifier::new(thread_rng());
+        example_gadget_batch_verify(&mut batch, &pc_gens, &bp_gens, 9, &proof1, &commitments1)?;
+        example_gadget_batch_verify(
+            &mut batch,
+            &pc_gens,
+            &bp_gens,
+            c2_of_proof2,
+            &proof2,
+            &commitments2,
+        )?;
+        range_proof.batch_verify_single(
+            &mut batch,
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"R1CSExampleRangeProof"),
+            &V,
+            32,
+        )?;
+        Ok(batch.verify()?)
+    };
+
+    assert!(batch_helper(1).is_ok());
+    // (1 + 2) * (3 + 4) != (20 + 2)
+    assert!(batch_helper(2).is_err());
+}
+
 // Range Proof gadget
 
 /// Enforces that the quantity of v is in the range [0, 2^n).
@@ -453,3 +721,84 @@ fn range_proof_helper(v_val: u64, n: usize) -> Result<(), R1CSError> {
     // Verifier verifies proof
     Ok(verifier.verify(&proof, &pc_gens, &bp_gens)?)
 }
+
+// Range Proof gadget over any group
+
+/// Enforces that the quantity of v is in the range [0, 2^n), with
+/// coefficients in the scalar field `S` of any group.
+fn generic_range_proof<S: CurveScalar, CS: ConstraintSystem<S>>(
+    cs: &mut CS,
+    mut v: LinearCombination<S>,
+    v_assignment: Option<u64>,
+    n: usize,
+) -> Result<(), R1CSError> {
+    let mut exp_2 = S::one();
+    for i in 0..n {
+        let (a, b, o) = cs.allocate_multiplier(v_assignment.map(|q| {
+            let bit: u64 = (q >> i) & 1;
+            (S::from(1 - bit), S::from(bit))
+        }))?;
+
+        // Enforce a * b = 0 and a = 1 - b.
+        cs.constrain(o.into());
+        cs.constrain(LinearCombination::from(a) + b - Variable::One());
+
+        v = v - LinearCombination::from(b) * exp_2;
+        exp_2 = exp_2 + exp_2;
+    }
+
+    cs.constrain(v);
+
+    Ok(())
+}
+
+fn generic_range_proof_helper<C: CurvePoint>(v_val: u64, n: usize) -> Result<(), R1CSError> {
+    let pc_gens = PedersenGens::<C>::standard();
+    let bp_gens = BulletproofGens::<C>::generate(&[], 128, 1);
+
+    let (proof, commitment) = {
+        let mut prover_transcript = Transcript::new(b"GenericRangeProofTest");
+        let mut prover = Prover::new(&pc_gens, &mut prover_transcript);
+
+        let (com, var) = prover.commit(v_val.into(), C::Scalar::random(&mut thread_rng()));
+        generic_range_proof(&mut prover, var.into(), Some(v_val), n)?;
+
+        (prover.prove(&bp_gens)?, com)
+    };
+
+    // The proof survives a roundtrip through its encoding.
+    let proof = R1CSProof::<C>::decode(&proof.to_bytes())?;
+
+    let mut verifier_transcript = Transcript::new(b"GenericRangeProofTest");
+    let mut verifier = Verifier::<_, C>::new(&mut verifier_transcript);
+    let var = verifier.commit(commitment);
+    generic_range_proof(&mut verifier, var.into(), None, n)?;
+
+    verifier.verify(&proof, &pc_gens, &bp_gens)
+}
+
+#[test]
+fn generic_range_proof_gadget_ristretto() {
+    use curve25519_dalek::ristretto::RistrettoPoint;
+
+    assert!(generic_range_proof_helper::<RistrettoPoint>(1000, 10).is_ok());
+    assert!(generic_range_proof_helper::<RistrettoPoint>(1024, 10).is_err());
+}
+
+#[cfg(feature = "secp256k1")]
+#[test]
+fn generic_range_proof_gadget_secp256k1() {
+    use bulletproofs::Secp256k1Point;
+
+    assert!(generic_range_proof_helper::<Secp256k1Point>(1000, 10).is_ok());
+    assert!(generic_range_proof_helper::<Secp256k1Point>(1024, 10).is_err());
+}
+
+#[cfg(feature = "evm")]
+#[test]
+fn generic_range_proof_gadget_bn254() {
+    use bulletproofs::Bn254Point;
+
+    assert!(generic_range_proof_helper::<Bn254Point>(1000, 10).is_ok());
+    assert!(generic_range_proof_helper::<Bn254Point>(1024, 10).is_err());
+}
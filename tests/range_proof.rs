@@ -137,3 +137,52 @@ fn generate_test_vectors() {
 
     panic!();
 }
+
+#[cfg(feature = "secp256k1")]
+#[test]
+fn secp256k1_aggregated_proof() {
+    use bulletproofs::{CompressedPoint, CurveScalar, Secp256k1Point, Secp256k1Scalar};
+
+    let mut rng = ChaChaRng::from_seed([7u8; 32]);
+    let pc_gens = PedersenGens::<Secp256k1Point>::standard();
+    let bp_gens = BulletproofGens::<Secp256k1Point>::generate(&[], 32, 4);
+
+    let values = [7u64, 1 << 20, 0, u32::max_value() as u64];
+    let blindings: Vec<_> = (0..4).map(|_| Secp256k1Scalar::random(&mut rng)).collect();
+
+    let (proof, commitments) = RangeProof::prove_multiple_with_rng(
+        &bp_gens,
+        &pc_gens,
+        &mut Transcript::new(b"Secp256k1RangeProofTest"),
+        &values,
+        &blindings,
+        32,
+        &mut rng,
+    )
+    .unwrap();
+    assert!(commitments.iter().all(|com| com.as_bytes().len() == 33));
+
+    let proof = RangeProof::<Secp256k1Point>::decode(&proof.to_bytes()).unwrap();
+    assert!(proof
+        .verify_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"Secp256k1RangeProofTest"),
+            &commitments,
+            32,
+        )
+        .is_ok());
+
+    // The proof does not verify the commitments in another order.
+    let mut swapped = commitments.clone();
+    swapped.swap(0, 1);
+    assert!(proof
+        .verify_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"Secp256k1RangeProofTest"),
+            &swapped,
+            32,
+        )
+        .is_err());
+}